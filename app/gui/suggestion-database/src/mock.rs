@@ -440,40 +440,43 @@ macro_rules! doc_section_mark {
 #[macro_export]
 macro_rules! doc_section {
     (@ $tag:ident, $body:expr) => {
-        $crate::mock::enso_doc_parser::DocSection::Tag {
+        $crate::mock::enso_doc_parser::DocSection::<String>::Tag {
             tag:  $crate::mock::enso_doc_parser::Tag::$tag,
             body: $body.into(),
         }
     };
     ($(- $body:expr);*) => {
-        $crate::mock::enso_doc_parser::DocSection::List { items: vec![
+        $crate::mock::enso_doc_parser::DocSection::<String>::List { items: vec![
             $($body.into()),*
         ]}
     };
     ($(- $name:expr, $desc:expr);*) => {
-        $crate::mock::enso_doc_parser::DocSection::Arguments { args: vec![
+        $crate::mock::enso_doc_parser::DocSection::<String>::Arguments { args: vec![
             $($crate::mock::enso_doc_parser::Argument { name: $name.into(), description: $desc.into() }),*
         ]}
     };
     ($mark:tt $body:expr) => {
-        $crate::mock::enso_doc_parser::DocSection::Marked {
+        $crate::mock::enso_doc_parser::DocSection::<String>::Marked {
             mark:   $crate::doc_section_mark!($mark),
             header: None,
             body:   $body.into(),
         }
     };
     ($mark:tt $header:expr, $body:expr) => {
-        $crate::mock::enso_doc_parser::DocSection::Marked {
+        $crate::mock::enso_doc_parser::DocSection::<String>::Marked {
             mark:   $crate::doc_section_mark!($mark),
             header: Some($header.into()),
             body:   $body.into(),
         }
     };
     ($paragraph:expr) => {
-        $crate::mock::enso_doc_parser::DocSection::Paragraph { body: $paragraph.into() }
+        $crate::mock::enso_doc_parser::DocSection::<String>::Paragraph { body: $paragraph.into() }
     };
     ($key:expr => $body:expr) => {
-        $crate::mock::enso_doc_parser::DocSection::Keyed { key: $key.into(), body: $body.into() }
+        $crate::mock::enso_doc_parser::DocSection::<String>::Keyed {
+            key:  $key.into(),
+            body: $body.into(),
+        }
     };
 }
 
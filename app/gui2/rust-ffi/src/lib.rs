@@ -23,7 +23,7 @@ pub fn parse_doc_to_json(docs: &str) -> String {
 #[wasm_bindgen]
 pub fn parse(code: &str) -> Vec<u8> {
     let ast = PARSER.with(|parser| parser.run(code));
-    enso_parser::format::serialize(&ast).expect("Failed to serialize AST to binary format")
+    enso_parser::format::encode(&ast)
 }
 
 #[wasm_bindgen(start)]
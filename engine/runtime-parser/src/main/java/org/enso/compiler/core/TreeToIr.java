@@ -1541,7 +1541,7 @@ final class TreeToIr {
       );
     } catch (SyntaxException err) {
       if (err.where instanceof Invalid invalid) {
-        return err.toError(invalidImportReason(invalid.getError()));
+        return err.toError(invalidImportReason(invalid.getError().getMessage()));
       } else {
         return err.toError(invalidImportReason(null));
       }
@@ -1613,7 +1613,7 @@ final class TreeToIr {
         );
     } catch (SyntaxException err) {
       if (err.where instanceof Invalid invalid) {
-        return err.toError(invalidExportReason(invalid.getError()));
+        return err.toError(invalidExportReason(invalid.getError().getMessage()));
       } else {
         return err.toError(invalidExportReason(null));
       }
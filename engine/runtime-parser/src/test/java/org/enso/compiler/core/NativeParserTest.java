@@ -0,0 +1,59 @@
+package org.enso.compiler.core;
+
+import static org.junit.Assert.assertEquals;
+import static org.junit.Assert.assertTrue;
+
+import java.util.List;
+import org.enso.syntax2.Diagnostic;
+import org.enso.syntax2.Parser;
+import org.junit.AfterClass;
+import org.junit.BeforeClass;
+import org.junit.Test;
+
+public class NativeParserTest {
+  private static Parser parser;
+
+  @BeforeClass
+  public static void initParser() {
+    parser = Parser.create();
+  }
+
+  @AfterClass
+  public static void closeParser() {
+    if (parser != null) {
+      parser.close();
+    }
+  }
+
+  @Test
+  public void testParseBatch() {
+    var inputs = List.of("main = 42", "foo = (1 +\nx = ]", "", "a = 'ą' + (");
+    var results = parser.parseBatch(inputs);
+    assertEquals(inputs.size(), results.size());
+    for (int i = 0; i < inputs.size(); i++) {
+      assertEquals(inputs.get(i), results.get(i).tree().codeRepr());
+    }
+    assertTrue(results.get(0).diagnostics().isEmpty());
+    var errors = results.get(1).diagnostics();
+    assertEquals(2, errors.size());
+    assertEquals("E003", errors.get(0).code());
+    assertEquals(6, errors.get(0).start());
+    var fix = errors.get(0).fixes().get(0).edits().get(0);
+    assertEquals(new Diagnostic.Edit(10, 10, ")"), fix);
+    // Locations are in UTF-16 code units.
+    var unclosed = results.get(3).diagnostics().get(0);
+    assertEquals(10, unclosed.start());
+    assertEquals(11, unclosed.end());
+  }
+
+  @Test
+  public void testParseIncremental() {
+    var versions = List.of("a = 1\nb = 2\nc = 3", "a = 1\nb = (2\nc = 3", "a = 1\nb = 2\nc = 3 ą");
+    for (var code : versions) {
+      var result = parser.parseIncremental(code);
+      assertEquals(code, result.tree().codeRepr());
+    }
+    var result = parser.parseIncremental("x = (");
+    assertEquals(1, result.diagnostics().size());
+  }
+}
@@ -15,7 +15,7 @@ use unrolled_linked_list::UnrolledLinkedList;
 
 /// Version of the slot. An even version (0, 2, 4, ...) means that the slot is occupied. An odd
 /// version (1, 3, 5, ...) means that the slot is free.
-#[derive(Clone, Copy, Debug, Display, Default, Deref, PartialEq, Eq, Zeroable)]
+#[derive(Clone, Copy, Debug, Display, Default, Deref, PartialEq, Eq, Hash, Zeroable)]
 #[repr(transparent)]
 struct Version(usize);
 
@@ -55,7 +55,8 @@ impl Version {
     Default(bound = ""),
     Debug(bound = ""),
     PartialEq(bound = ""),
-    Eq(bound = "")
+    Eq(bound = ""),
+    Hash(bound = "")
 )]
 pub struct VersionedIndex<Kind = ()> {
     index:   usize,
@@ -1,15 +1,15 @@
 //! This module contains implementation of loops mainly used for per-frame callbacks firing.
 
 use crate::prelude::*;
-use crate::system::web::traits::*;
 use enso_callback::traits::*;
 
-use crate::system::web;
 use crate::types::unit2::Duration;
 
 use enso_callback as callback;
+use frp::clock;
+use frp::clock::Clock;
+use frp::clock::TimerHandle;
 use frp::microtasks::TickPhases;
-use web::Closure;
 
 
 
@@ -64,6 +64,9 @@ pub trait RawOnFrameCallback = FnMut(Duration) + 'static;
 /// Binding to JS `requestAnimationFrame` function. This is not exported publicly because it should
 /// not be used directly as it does not give us control over the animation loop evaluation order.
 /// Use [`Loop`] instead.
+///
+/// The frames are requested from the [`clock::current`] clock at the time of the loop creation,
+/// which allows driving the loop with a [`clock::ManualClock`] in tests.
 #[derive(CloneRef, Derivative)]
 #[derivative(Clone(bound = ""))]
 #[derivative(Debug(bound = ""))]
@@ -76,15 +79,9 @@ where OnFrame: RawOnFrameCallback
 {
     /// Create and start a new animation loop.
     fn new(on_frame: OnFrame) -> Self {
-        let data = Rc::new(RefCell::new(JsLoopData::new(on_frame)));
-        let weak_data = Rc::downgrade(&data);
-        let js_on_frame =
-            move |time: f64| weak_data.upgrade().for_each(|t| t.borrow_mut().run(time));
-        data.borrow_mut().js_on_frame = Some(Closure::new(js_on_frame));
-        let js_on_frame_handle_id = web::window.request_animation_frame_with_closure_or_panic(
-            data.borrow_mut().js_on_frame.as_ref().unwrap(),
-        );
-        data.borrow_mut().js_on_frame_handle_id = js_on_frame_handle_id;
+        let clock = clock::current();
+        let data = Rc::new_cyclic(|weak| RefCell::new(JsLoopData::new(on_frame, clock, weak)));
+        data.borrow_mut().request_frame();
         Self { data }
     }
 }
@@ -94,34 +91,39 @@ where OnFrame: RawOnFrameCallback
 #[derivative(Debug(bound = ""))]
 struct JsLoopData<OnFrame> {
     #[derivative(Debug = "ignore")]
-    on_frame:              OnFrame,
-    js_on_frame:           Option<Closure<dyn FnMut(f64)>>,
-    js_on_frame_handle_id: i32,
+    on_frame:     OnFrame,
+    clock:        Rc<dyn Clock>,
+    weak:         Weak<RefCell<JsLoopData<OnFrame>>>,
+    frame_handle: Option<TimerHandle>,
 }
 
-impl<OnFrame> JsLoopData<OnFrame> {
+impl<OnFrame> JsLoopData<OnFrame>
+where OnFrame: RawOnFrameCallback
+{
     /// Constructor.
-    fn new(on_frame: OnFrame) -> Self {
-        let js_on_frame = default();
-        let js_on_frame_handle_id = default();
-        Self { on_frame, js_on_frame, js_on_frame_handle_id }
+    fn new(
+        on_frame: OnFrame,
+        clock: Rc<dyn Clock>,
+        weak: &Weak<RefCell<JsLoopData<OnFrame>>>,
+    ) -> Self {
+        let weak = weak.clone();
+        let frame_handle = default();
+        Self { on_frame, clock, weak, frame_handle }
     }
 
-    // FIXME: We are converting `f64` to `f32` here which is a mistake. We should revert to `f64`
-    //        for a better time precision.
-    fn run(&mut self, current_time_ms: f64)
-    where OnFrame: FnMut(Duration) {
-        let on_frame = &mut self.on_frame;
-        self.js_on_frame_handle_id = self.js_on_frame.as_ref().map_or(default(), |js_on_frame| {
-            on_frame((current_time_ms as f32).ms());
-            web::window.request_animation_frame_with_closure_or_panic(js_on_frame)
-        })
+    fn request_frame(&mut self) {
+        let weak = self.weak.clone();
+        let on_frame = move |time: std::time::Duration| {
+            weak.upgrade().for_each(|t| t.borrow_mut().run(time.as_secs_f64() * 1000.0))
+        };
+        self.frame_handle = Some(self.clock.request_animation_frame(Box::new(on_frame)));
     }
-}
 
-impl<OnFrame> Drop for JsLoopData<OnFrame> {
-    fn drop(&mut self) {
-        web::window.cancel_animation_frame_or_warn(self.js_on_frame_handle_id);
+    // FIXME: We are converting `f64` to `f32` here which is a mistake. We should revert to `f64`
+    //        for a better time precision.
+    fn run(&mut self, current_time_ms: f64) {
+        (self.on_frame)((current_time_ms as f32).ms());
+        self.request_frame();
     }
 }
 
@@ -512,3 +514,36 @@ pub mod test_utils {
         });
     }
 }
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use frp::clock::ManualClock;
+
+    #[test]
+    fn loop_driven_by_manual_clock() {
+        let clock = ManualClock::new();
+        let _guard = clock::install(clock.clone_ref());
+        let frames = Rc::new(RefCell::new(Vec::new()));
+        let frames_log = frames.clone_ref();
+        let _loop = Loop::new_before_animations(move |time: TimeInfo| {
+            frames_log.borrow_mut().push(time.since_animation_loop_started)
+        });
+        clock.advance(clock.frame_period() / 2);
+        assert!(frames.borrow().is_empty());
+        clock.advance_frame();
+        assert_eq!(frames.borrow().len(), 1);
+        clock.advance_frame();
+        clock.advance_frame();
+        let frames = frames.borrow();
+        assert_eq!(frames.len(), 3);
+        assert!(frames[0] < frames[1] && frames[1] < frames[2]);
+    }
+}
@@ -0,0 +1,491 @@
+//! Static analysis of FRP networks.
+//!
+//! The analysis inspects the connections between nodes of a [`Network`] and reports common
+//! mistakes which are hard to spot by reading the network definition:
+//! - Event cycles without a node that can stop the propagation (like `gate` or `filter`). Any event
+//!   entering such a cycle is emitted infinitely (until the recursion limit is hit).
+//! - Nodes whose output is never used, neither as an event input nor as a watched behavior. Nodes
+//!   defined with a name starting with an underscore (like the ones created by `eval`) are
+//!   considered intentionally unused.
+//! - Samplers which are never sampled.
+//! - `gate` and `switch` nodes whose control input never receives an event. Such a control input
+//!   always has its default value. Events can be emitted from outside of the network to sources and
+//!   to `any_mut` nodes without inputs (which are commonly used as inputs of FRP endpoints), so
+//!   controls driven by such nodes are not reported.
+//!
+//! The event connections are recorded by the nodes themselves when they are connected, while the
+//! control inputs are taken from the links registered with [`Network::register_link`]. The
+//! analysis runs automatically in debug builds only, see [`Network::debug_check_well_formed`].
+//!
+//! ```text
+//! frp::new_network! { network
+//!     def trigger = source::<()>();
+//!     def enabled = trigger.map(|_| true);
+//!     def passed  = trigger.gate(&enabled);
+//! }
+//! network.assert_well_formed(); // Panics, as `passed` is unused.
+//! ```
+//!
+//! [`Network`]: crate::network::Network
+//! [`Network::register_link`]: crate::network::Network::register_link
+//! [`Network::debug_check_well_formed`]: crate::network::Network::debug_check_well_formed
+
+use crate::prelude::*;
+
+use crate::node::*;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// Kinds of nodes which can stop the propagation of an event. Cycles containing such a node are
+/// not reported, as they are commonly used to implement recursive logic.
+const FILTERING_KINDS: &[Label] = &[
+    "Gate",
+    "GateNot",
+    "BufferedGate",
+    "Filter",
+    "FilterMap",
+    "Unwrap",
+    "Iter",
+    "Batch",
+    "BatchUnique",
+    "Debounce",
+];
+
+/// Kinds of nodes which are event sinks by design, and thus are allowed to have unused outputs.
+const SINK_KINDS: &[Label] = &["Trace", "TraceIf", "Profile"];
+
+/// Kinds of nodes which can be emitted to from outside of the network.
+const ROOT_KINDS: &[Label] = &["Source"];
+
+/// Kinds of nodes which can be emitted to from outside of the network if they don't have any event
+/// inputs. For example, the `any_mut` nodes are used as inputs of FRP endpoints, and emitted to
+/// directly, like in `Timeout::restart`.
+const INPUT_LESS_ROOT_KINDS: &[Label] = &["Any", "AnyData_"];
+
+const SAMPLER_KIND: Label = "Sampler";
+
+
+
+// ================
+// === NodeInfo ===
+// ================
+
+/// Connection information of a single network node, the input of the analysis.
+#[derive(Clone, Debug)]
+#[allow(missing_docs)]
+pub struct NodeInfo {
+    pub id:                 Id,
+    pub label:              Label,
+    /// The node type, as reported by [`crate::stream::HasOutputTypeLabel`].
+    pub kind:               Label,
+    /// Ids of nodes this node receives events from. They can belong to other networks.
+    pub event_inputs:       Vec<Id>,
+    /// Ids of nodes controlling this node, like the condition of a `gate`.
+    pub controls:           Vec<Id>,
+    pub event_target_count: usize,
+    pub watch_count:        usize,
+}
+
+impl NodeInfo {
+    fn name(&self) -> NodeName {
+        NodeName { id: self.id, label: self.label, kind: self.kind }
+    }
+
+    /// The name the node was defined with in the `frp::new_network!` macro. The macro labels nodes
+    /// as `module::path::name:line`.
+    fn definition_name(&self) -> &str {
+        let name = self.label.rsplit("::").next().unwrap_or(self.label);
+        name.split(':').next().unwrap_or(name)
+    }
+}
+
+
+// === NodeName ===
+
+/// Identification of a node in the analysis [`Report`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub struct NodeName {
+    pub id:    Id,
+    pub label: Label,
+    pub kind:  Label,
+}
+
+impl Display for NodeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} '{}'", self.kind, self.label)
+    }
+}
+
+
+
+// ==============
+// === Report ===
+// ==============
+
+/// A problem found by the analysis.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum Problem {
+    /// The nodes form an event cycle which does not contain any filtering node.
+    Cycle { nodes: Vec<NodeName> },
+    /// The output of the node is neither an event input of, nor watched by, any other node.
+    UnusedOutput { node: NodeName },
+    /// The sampler is not watched by any other node.
+    UnsampledSampler { node: NodeName },
+    /// The control input of the node never receives an event.
+    UndrivenControl { node: NodeName, control: NodeName },
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cycle { nodes } => {
+                let nodes = nodes.iter().map(|node| node.to_string()).join(" -> ");
+                write!(f, "Event cycle without a filtering node: {nodes}.")
+            }
+            Self::UnusedOutput { node } => write!(f, "Output of {node} is never used."),
+            Self::UnsampledSampler { node } => write!(f, "{node} is never sampled."),
+            Self::UndrivenControl { node, control } =>
+                write!(f, "{node} is controlled by {control}, which never receives an event."),
+        }
+    }
+}
+
+/// Result of the network analysis.
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    /// All found problems.
+    pub problems: Vec<Problem>,
+}
+
+impl Report {
+    /// Check whether no problems were found.
+    pub fn is_empty(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Only the problems describing event cycles.
+    pub fn cycles(&self) -> impl Iterator<Item = &Problem> {
+        self.problems.iter().filter(|problem| matches!(problem, Problem::Cycle { .. }))
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "No problems found.");
+        }
+        for (index, problem) in self.problems.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "- {problem}")?;
+        }
+        Ok(())
+    }
+}
+
+
+
+// ================
+// === Analysis ===
+// ================
+
+/// Analyze the nodes of a network. See the module docs to learn more.
+pub fn analyze(nodes: &[NodeInfo]) -> Report {
+    let graph = Graph::new(nodes);
+    let mut problems = vec![];
+    problems.extend(graph.cycles());
+    for node in nodes {
+        let is_sampler = node.kind == SAMPLER_KIND;
+        let is_sink = SINK_KINDS.contains(&node.kind);
+        let is_ignored = node.definition_name().starts_with('_');
+        let is_used = node.event_target_count > 0 || node.watch_count > 0;
+        if is_sampler && node.watch_count <= 1 {
+            // Every sampler watches itself, so that its value is cached.
+            problems.push(Problem::UnsampledSampler { node: node.name() });
+        } else if !is_sampler && !is_sink && !is_ignored && !is_used {
+            problems.push(Problem::UnusedOutput { node: node.name() });
+        }
+    }
+    problems.extend(graph.undriven_controls());
+    Report { problems }
+}
+
+
+// === Graph ===
+
+/// The event connections between nodes of a single network.
+#[derive(Debug)]
+struct Graph<'a> {
+    nodes:   &'a [NodeInfo],
+    index:   HashMap<Id, usize>,
+    targets: Vec<Vec<usize>>,
+}
+
+impl<'a> Graph<'a> {
+    fn new(nodes: &'a [NodeInfo]) -> Self {
+        let index: HashMap<Id, usize> =
+            nodes.iter().enumerate().map(|(ix, node)| (node.id, ix)).collect();
+        let mut targets = vec![vec![]; nodes.len()];
+        for (ix, node) in nodes.iter().enumerate() {
+            for source in &node.event_inputs {
+                if let Some(&source_ix) = index.get(source) {
+                    targets[source_ix].push(ix);
+                }
+            }
+        }
+        Self { nodes, index, targets }
+    }
+
+    /// Event cycles without filtering nodes. They are found as strongly connected components using
+    /// an iterative version of Tarjan's algorithm, so deep networks can't overflow the stack.
+    fn cycles(&self) -> Vec<Problem> {
+        let count = self.nodes.len();
+        let mut order: Vec<Option<usize>> = vec![None; count];
+        let mut low_link = vec![0; count];
+        let mut on_stack = vec![false; count];
+        let mut stack = vec![];
+        let mut next_order = 0;
+        let mut cycles = vec![];
+        for root in 0..count {
+            if order[root].is_some() {
+                continue;
+            }
+            let mut work = vec![(root, 0)];
+            while let Some((ix, edge)) = work.pop() {
+                if edge == 0 {
+                    order[ix] = Some(next_order);
+                    low_link[ix] = next_order;
+                    next_order += 1;
+                    stack.push(ix);
+                    on_stack[ix] = true;
+                }
+                if let Some(&target) = self.targets[ix].get(edge) {
+                    work.push((ix, edge + 1));
+                    match order[target] {
+                        None => work.push((target, 0)),
+                        Some(target_order) if on_stack[target] =>
+                            low_link[ix] = low_link[ix].min(target_order),
+                        Some(_) => {}
+                    }
+                    continue;
+                }
+                if let Some(&(parent, _)) = work.last() {
+                    low_link[parent] = low_link[parent].min(low_link[ix]);
+                }
+                if Some(low_link[ix]) == order[ix] {
+                    let mut component = vec![];
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == ix {
+                            break;
+                        }
+                    }
+                    let is_loop = component.len() > 1 || self.targets[ix].contains(&ix);
+                    if is_loop {
+                        cycles.push(component);
+                    }
+                }
+            }
+        }
+        cycles
+            .into_iter()
+            .filter(|component| {
+                !component.iter().any(|&ix| FILTERING_KINDS.contains(&self.nodes[ix].kind))
+            })
+            .map(|mut component| {
+                component.sort_unstable();
+                let nodes = component.into_iter().map(|ix| self.nodes[ix].name()).collect();
+                Problem::Cycle { nodes }
+            })
+            .collect()
+    }
+
+    /// Control inputs which never receive events. A node is considered driven if it can receive
+    /// an event from a node which can be emitted to from outside of the network (see
+    /// [`ROOT_KINDS`] and [`INPUT_LESS_ROOT_KINDS`]), or from a node outside of this network.
+    fn undriven_controls(&self) -> Vec<Problem> {
+        let mut driven = vec![false; self.nodes.len()];
+        let mut queue: Vec<usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| {
+                let is_input_less_root =
+                    node.event_inputs.is_empty() && INPUT_LESS_ROOT_KINDS.contains(&node.kind);
+                let is_root = ROOT_KINDS.contains(&node.kind) || is_input_less_root;
+                is_root || node.event_inputs.iter().any(|id| !self.index.contains_key(id))
+            })
+            .map(|(ix, _)| ix)
+            .collect();
+        while let Some(ix) = queue.pop() {
+            if !driven[ix] {
+                driven[ix] = true;
+                queue.extend(self.targets[ix].iter().filter(|&&target| !driven[target]));
+            }
+        }
+        let mut problems = vec![];
+        for node in self.nodes {
+            for control in &node.controls {
+                if let Some(&control_ix) = self.index.get(control) {
+                    if !driven[control_ix] {
+                        let control = self.nodes[control_ix].name();
+                        problems.push(Problem::UndrivenControl { node: node.name(), control });
+                    }
+                }
+            }
+        }
+        problems
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate as frp;
+
+    fn kinds(problem: &Problem) -> Vec<Label> {
+        match problem {
+            Problem::Cycle { nodes } => nodes.iter().map(|node| node.kind).collect(),
+            Problem::UnusedOutput { node } | Problem::UnsampledSampler { node } => vec![node.kind],
+            Problem::UndrivenControl { node, control } => vec![node.kind, control.kind],
+        }
+    }
+
+    #[test]
+    fn well_formed_network() {
+        frp::new_network! { network
+            def source = source::<i32>();
+            def enabled = source.map(|t| *t > 0);
+            def passed = source.gate(&enabled);
+            def value = passed.sampler();
+            def _sum = source.map2(&value, |a, b| a + b);
+            trace passed;
+        }
+        network.assert_well_formed();
+    }
+
+    #[test]
+    fn unfiltered_cycles_are_reported() {
+        frp::new_network! { network
+            def source = source::<()>();
+            def looped = any_mut::<()>();
+            looped <+ source;
+            def mapped = looped.map(|_| ());
+            looped <+ mapped;
+        }
+        let report = network.analyze();
+        assert_eq!(report.problems.len(), 1, "{report}");
+        assert_eq!(kinds(&report.problems[0]), vec!["Any", "Map"]);
+        assert_eq!(report.cycles().count(), 1);
+    }
+
+    #[test]
+    fn filtered_cycles_are_not_reported() {
+        frp::new_network! { network
+            def source = source::<i32>();
+            def looped = any_mut::<i32>();
+            looped <+ source;
+            def decreased = looped.filter_map(|t| (*t > 0).as_some(t - 1));
+            looped <+ decreased;
+        }
+        network.assert_well_formed();
+    }
+
+    #[test]
+    fn unused_outputs_are_reported() {
+        frp::new_network! { network
+            def source = source::<i32>();
+            def unused = source.map(|t| t + 1);
+            def _ignored = source.map(|t| t + 2);
+        }
+        let report = network.analyze();
+        assert_eq!(report.problems.len(), 1, "{report}");
+        let is_unused = |node: &NodeName| node.id == unused.id();
+        assert!(
+            matches!(&report.problems[..], [Problem::UnusedOutput { node }] if is_unused(node))
+        );
+    }
+
+    #[test]
+    fn unsampled_samplers_are_reported() {
+        frp::new_network! { network
+            def source = source::<i32>();
+            def value = source.sampler();
+        }
+        let report = network.analyze();
+        assert_eq!(report.problems.len(), 1, "{report}");
+        let is_value = |node: &NodeName| node.id == value.id();
+        assert!(
+            matches!(&report.problems[..], [Problem::UnsampledSampler { node }] if is_value(node))
+        );
+    }
+
+    #[test]
+    fn undriven_controls_are_reported() {
+        frp::new_network! { network
+            def source = source::<i32>();
+            def enabled = any_mut::<bool>();
+            def disabled = enabled.filter(|t| !*t);
+            def enabling = disabled.constant(true);
+            enabled <+ enabling;
+            def _passed = source.gate(&enabled);
+            def _switched = enabled.switch(&source, &source);
+        }
+        let report = network.analyze();
+        let kinds = report.problems.iter().map(kinds).collect_vec();
+        assert_eq!(kinds, vec![vec!["Gate", "Any"], vec!["AllWith3", "Any"]], "{report}");
+    }
+
+    #[test]
+    fn controls_emitted_to_from_outside_are_not_reported() {
+        frp::new_network! { network
+            def source = source::<i32>();
+            def enabled = any_mut::<bool>();
+            def trigger = any_mut_();
+            def triggered = trigger.constant(true);
+            def _passed = source.gate(&enabled);
+            def _switched = enabled.switch(&source, &source);
+            def _passed_on_trigger = source.gate(&triggered);
+        }
+        network.assert_well_formed();
+    }
+
+    #[test]
+    fn controls_driven_from_other_networks_are_not_reported() {
+        frp::new_network! { other_network
+            def condition = source::<bool>();
+        }
+        frp::new_network! { network
+            def source = source::<i32>();
+            def enabled = any_mut::<bool>();
+            enabled <+ condition;
+            def _passed = source.gate(&enabled);
+        }
+        network.assert_well_formed();
+        drop(other_network);
+    }
+
+    #[test]
+    #[should_panic(expected = "is never sampled")]
+    fn assert_well_formed_panics_with_report() {
+        frp::new_network! { network
+            def source = source::<i32>();
+            def _value = source.sampler();
+        }
+        network.assert_well_formed();
+    }
+}
@@ -0,0 +1,533 @@
+//! An injectable source of time used by timers, the microtask scheduler, and animation loops.
+//!
+//! By default, all time-related functionality is driven by the browser event loop through the
+//! [`WebClock`]. In tests, a [`ManualClock`] can be installed instead. It never advances on its own
+//! and fires the scheduled callbacks only when it is explicitly advanced, which allows testing
+//! timers, debouncing and animations natively, without a browser and without waiting for the real
+//! time to pass:
+//!
+//! ```text
+//! let clock = ManualClock::new();
+//! let _guard = clock::install(clock.clone_ref());
+//! let timeout = Timeout::new(&network);
+//! timeout.restart.emit(300);
+//! clock.advance(Duration::from_millis(300));
+//! ```
+//!
+//! Timers and animation loops use the clock that was current when they were created, while the
+//! microtask scheduler uses the clock that is current when a task is scheduled.
+
+use crate::prelude::*;
+use enso_web::traits::*;
+
+use enso_web::window;
+use enso_web::Closure;
+use enso_web::JsEventHandler;
+use enso_web::JsValue;
+use enso_web::Promise;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The animation frame period of the [`ManualClock`], matching a 60 FPS display.
+const DEFAULT_FRAME_PERIOD: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// The minimal period of intervals scheduled with the [`ManualClock`]. Shorter periods are clamped
+/// to this value, so advancing the clock always terminates.
+const MIN_INTERVAL_PERIOD: Duration = Duration::from_millis(1);
+
+
+
+// ===================
+// === TimerHandle ===
+// ===================
+
+/// A handle of a callback scheduled with a [`Clock`]. The callback is cancelled when the handle is
+/// dropped, unless it was [`forgotten`](Self::forget).
+#[must_use]
+pub struct TimerHandle {
+    cancel: Option<Box<dyn FnOnce()>>,
+}
+
+impl TimerHandle {
+    /// Constructor. The `cancel` function will be called when the handle is dropped.
+    pub fn new(cancel: impl FnOnce() + 'static) -> Self {
+        Self { cancel: Some(Box::new(cancel)) }
+    }
+
+    /// Drop the handle without cancelling the scheduled callback.
+    pub fn forget(mut self) {
+        self.cancel = None;
+    }
+}
+
+impl Drop for TimerHandle {
+    fn drop(&mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            cancel();
+        }
+    }
+}
+
+impl Debug for TimerHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TimerHandle")
+    }
+}
+
+
+
+// =============
+// === Clock ===
+// =============
+
+/// A source of time and a scheduler of time-based callbacks. See the module docs to learn more.
+pub trait Clock: Debug {
+    /// The time elapsed since the clock origin.
+    fn now(&self) -> Duration;
+
+    /// Call the function once, after the provided delay.
+    fn set_timeout(&self, delay: Duration, f: Box<dyn FnOnce()>) -> TimerHandle;
+
+    /// Call the function repeatedly, every `period`, until the handle is dropped.
+    fn set_interval(&self, period: Duration, f: Box<dyn FnMut()>) -> TimerHandle;
+
+    /// Call the function before the next frame is painted. The function is provided with the frame
+    /// start time, measured from the clock origin.
+    fn request_animation_frame(&self, f: Box<dyn FnOnce(Duration)>) -> TimerHandle;
+
+    /// Call the function after the current task is done, but before the next task starts.
+    fn queue_microtask(&self, f: Box<dyn FnOnce()>);
+}
+
+
+// === Current Clock ===
+
+thread_local! {
+    static CURRENT_CLOCK: RefCell<Rc<dyn Clock>> = RefCell::new(Rc::new(WebClock::new()));
+}
+
+/// The clock currently installed in this thread. Unless [`install`] was used, it is the
+/// [`WebClock`].
+pub fn current() -> Rc<dyn Clock> {
+    CURRENT_CLOCK.with(|clock| clock.borrow().clone())
+}
+
+/// Install the clock as the current one in this thread. The previous clock is restored when the
+/// returned guard is dropped.
+pub fn install(clock: impl Clock + 'static) -> ClockGuard {
+    let previous = CURRENT_CLOCK.with(|current| current.replace(Rc::new(clock)));
+    ClockGuard { previous: Some(previous) }
+}
+
+/// Guard returned by [`install`]. Restores the previously installed clock when dropped.
+#[must_use]
+#[derive(Debug)]
+pub struct ClockGuard {
+    previous: Option<Rc<dyn Clock>>,
+}
+
+impl Drop for ClockGuard {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            CURRENT_CLOCK.with(|current| drop(current.replace(previous)));
+        }
+    }
+}
+
+
+
+// ================
+// === WebClock ===
+// ================
+
+/// A [`Clock`] driven by the browser event loop. It uses `performance.now`, `setTimeout`,
+/// `setInterval`, `requestAnimationFrame`, and resolved promises under the hood.
+#[derive(Clone, CloneRef)]
+pub struct WebClock {
+    data: Rc<WebClockData>,
+}
+
+struct WebClockData {
+    resolved_promise:       Promise,
+    microtasks:             RefCell<Vec<Box<dyn FnOnce()>>>,
+    run_microtasks_closure: JsEventHandler,
+}
+
+impl WebClock {
+    /// Constructor.
+    pub fn new() -> Self {
+        let data = Rc::new_cyclic(|weak: &Weak<WebClockData>| {
+            let resolved_promise = Promise::resolve(&JsValue::NULL);
+            let microtasks = default();
+            let run_microtasks_closure = Closure::new(f!([weak] (_: JsValue) {
+                if let Some(data) = weak.upgrade() {
+                    let microtasks = data.microtasks.take();
+                    microtasks.into_iter().for_each(|f| f());
+                }
+            }));
+            WebClockData { resolved_promise, microtasks, run_microtasks_closure }
+        });
+        Self { data }
+    }
+}
+
+impl Default for WebClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for WebClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "WebClock")
+    }
+}
+
+impl Clock for WebClock {
+    fn now(&self) -> Duration {
+        window.performance().map_or(default(), |performance| from_ms(performance.now()))
+    }
+
+    fn set_timeout(&self, delay: Duration, f: Box<dyn FnOnce()>) -> TimerHandle {
+        let mut f = Some(f);
+        let closure: Closure<dyn FnMut()> = Closure::new(move || f.take().for_each(|f| f()));
+        let js_func = closure.as_js_function();
+        let result =
+            window.set_timeout_with_callback_and_timeout_and_arguments_0(js_func, ms(delay));
+        let id = result.expect("setTimeout should never fail when callback is a function.");
+        TimerHandle::new(move || {
+            window.clear_timeout_with_handle(id);
+            drop(closure);
+        })
+    }
+
+    fn set_interval(&self, period: Duration, f: Box<dyn FnMut()>) -> TimerHandle {
+        let closure: Closure<dyn FnMut()> = Closure::new(f);
+        let js_func = closure.as_js_function();
+        let result =
+            window.set_interval_with_callback_and_timeout_and_arguments_0(js_func, ms(period));
+        let id = result.expect("setInterval should never fail when callback is a function.");
+        TimerHandle::new(move || {
+            window.clear_interval_with_handle(id);
+            drop(closure);
+        })
+    }
+
+    fn request_animation_frame(&self, f: Box<dyn FnOnce(Duration)>) -> TimerHandle {
+        let mut f = Some(f);
+        let closure: Closure<dyn FnMut(f64)> =
+            Closure::new(move |time: f64| f.take().for_each(|f| f(from_ms(time))));
+        let id = window.request_animation_frame_with_closure_or_panic(&closure);
+        TimerHandle::new(move || {
+            window.cancel_animation_frame_or_warn(id);
+            drop(closure);
+        })
+    }
+
+    fn queue_microtask(&self, f: Box<dyn FnOnce()>) {
+        let mut microtasks = self.data.microtasks.borrow_mut();
+        if microtasks.is_empty() {
+            // Result left unused on purpose. We only care about the closure being run in the next
+            // microtask, which is a guaranteed side effect of providing it to [`Promise::then`]
+            // method on already resolved promise.
+            let _ = self.data.resolved_promise.then(&self.data.run_microtasks_closure);
+        }
+        microtasks.push(f);
+    }
+}
+
+/// Convert the duration to integer milliseconds, as expected by the browser timer APIs.
+fn ms(duration: Duration) -> i32 {
+    duration.as_millis().try_into().unwrap_or(i32::MAX)
+}
+
+/// Convert the time in milliseconds, as provided by the browser APIs, to a duration.
+fn from_ms(ms: f64) -> Duration {
+    Duration::from_secs_f64(ms.max(0.0) / 1000.0)
+}
+
+
+
+// ===================
+// === ManualClock ===
+// ===================
+
+/// A [`Clock`] which time passes only when it is explicitly advanced. Scheduled callbacks are fired
+/// in time order while the clock is being advanced, and the microtask queue is emptied after each
+/// of them. Animation frames are started every [`ManualClock::frame_period`].
+#[derive(Clone, CloneRef, Debug, Default)]
+pub struct ManualClock {
+    data: Rc<ManualClockData>,
+}
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+struct ManualClockData {
+    time:          Cell<Duration>,
+    frame_period:  Duration,
+    next_timer_id: Cell<usize>,
+    #[derivative(Debug = "ignore")]
+    timers:        RefCell<Vec<ScheduledTimer>>,
+    #[derivative(Debug = "ignore")]
+    microtasks:    RefCell<VecDeque<Box<dyn FnOnce()>>>,
+    /// The id of the interval being currently run and whether it was cancelled while running.
+    running_timer: Cell<Option<(usize, bool)>>,
+}
+
+impl Default for ManualClockData {
+    fn default() -> Self {
+        Self {
+            time:          default(),
+            frame_period:  DEFAULT_FRAME_PERIOD,
+            next_timer_id: default(),
+            timers:        default(),
+            microtasks:    default(),
+            running_timer: default(),
+        }
+    }
+}
+
+struct ScheduledTimer {
+    id:       usize,
+    time:     Duration,
+    callback: TimerCallback,
+}
+
+enum TimerCallback {
+    Timeout(Box<dyn FnOnce()>),
+    Interval(Duration, Box<dyn FnMut()>),
+    AnimationFrame(Box<dyn FnOnce(Duration)>),
+}
+
+impl ManualClock {
+    /// Constructor. The clock starts at zero time.
+    pub fn new() -> Self {
+        default()
+    }
+
+    /// The time between subsequent animation frames.
+    pub fn frame_period(&self) -> Duration {
+        self.data.frame_period
+    }
+
+    /// Advance the time, firing all callbacks scheduled up to the new time in their time order.
+    /// Callbacks scheduled while advancing are fired as well if they are due before the new time.
+    pub fn advance(&self, by: Duration) {
+        let target = self.data.time.get() + by;
+        self.run_microtasks();
+        while let Some(timer) = self.data.take_next_timer(target) {
+            self.data.time.set(timer.time);
+            self.data.run_timer(timer);
+            self.run_microtasks();
+        }
+        self.data.time.set(target);
+    }
+
+    /// Advance the time to the start of the next animation frame.
+    pub fn advance_frame(&self) {
+        let next_frame = self.data.next_frame_time();
+        self.advance(next_frame - self.data.time.get());
+    }
+
+    /// Run all queued microtasks, including the ones queued by them, without advancing the time.
+    pub fn run_microtasks(&self) {
+        loop {
+            // The queue must not be borrowed while the task runs, as it may queue further tasks.
+            let task = self.data.microtasks.borrow_mut().pop_front();
+            let Some(task) = task else { break };
+            task();
+        }
+    }
+
+    fn schedule(&self, time: Duration, callback: TimerCallback) -> TimerHandle {
+        let id = self.data.next_timer_id.get();
+        self.data.next_timer_id.set(id + 1);
+        self.data.timers.borrow_mut().push(ScheduledTimer { id, time, callback });
+        let data = Rc::downgrade(&self.data);
+        TimerHandle::new(move || data.upgrade().for_each(|data| data.cancel(id)))
+    }
+}
+
+impl ManualClockData {
+    fn next_frame_time(&self) -> Duration {
+        let period = self.frame_period.as_nanos();
+        let frame = self.time.get().as_nanos() / period + 1;
+        Duration::from_nanos((frame * period) as u64)
+    }
+
+    fn take_next_timer(&self, max_time: Duration) -> Option<ScheduledTimer> {
+        let mut timers = self.timers.borrow_mut();
+        let due = timers.iter().enumerate().filter(|(_, timer)| timer.time <= max_time);
+        let next = due.min_by_key(|(_, timer)| (timer.time, timer.id)).map(|(index, _)| index);
+        next.map(|index| timers.remove(index))
+    }
+
+    fn run_timer(&self, timer: ScheduledTimer) {
+        let ScheduledTimer { id, time, callback } = timer;
+        match callback {
+            TimerCallback::Timeout(f) => f(),
+            TimerCallback::AnimationFrame(f) => f(time),
+            TimerCallback::Interval(period, mut f) => {
+                self.running_timer.set(Some((id, false)));
+                f();
+                let cancelled = self.running_timer.take().map_or(false, |(_, cancelled)| cancelled);
+                if !cancelled {
+                    let callback = TimerCallback::Interval(period, f);
+                    let time = time + period;
+                    self.timers.borrow_mut().push(ScheduledTimer { id, time, callback });
+                }
+            }
+        }
+    }
+
+    fn cancel(&self, id: usize) {
+        if self.running_timer.get().map(|(running_id, _)| running_id) == Some(id) {
+            self.running_timer.set(Some((id, true)));
+        }
+        self.timers.borrow_mut().retain(|timer| timer.id != id);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.data.time.get()
+    }
+
+    fn set_timeout(&self, delay: Duration, f: Box<dyn FnOnce()>) -> TimerHandle {
+        self.schedule(self.now() + delay, TimerCallback::Timeout(f))
+    }
+
+    fn set_interval(&self, period: Duration, f: Box<dyn FnMut()>) -> TimerHandle {
+        let period = period.max(MIN_INTERVAL_PERIOD);
+        self.schedule(self.now() + period, TimerCallback::Interval(period, f))
+    }
+
+    fn request_animation_frame(&self, f: Box<dyn FnOnce(Duration)>) -> TimerHandle {
+        self.schedule(self.data.next_frame_time(), TimerCallback::AnimationFrame(f))
+    }
+
+    fn queue_microtask(&self, f: Box<dyn FnOnce()>) {
+        self.data.microtasks.borrow_mut().push_back(f);
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timers_fire_in_time_order() {
+        let clock = ManualClock::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let _t1 =
+            clock.set_timeout(Duration::from_millis(20), Box::new(f!(log.borrow_mut().push(20))));
+        let _t2 =
+            clock.set_timeout(Duration::from_millis(10), Box::new(f!(log.borrow_mut().push(10))));
+        let _t3 =
+            clock.set_timeout(Duration::from_millis(30), Box::new(f!(log.borrow_mut().push(30))));
+        clock.advance(Duration::from_millis(25));
+        assert_eq!(*log.borrow(), [10, 20]);
+        assert_eq!(clock.now(), Duration::from_millis(25));
+        clock.advance(Duration::from_millis(5));
+        assert_eq!(*log.borrow(), [10, 20, 30]);
+    }
+
+    #[test]
+    fn dropped_timers_do_not_fire() {
+        let clock = ManualClock::new();
+        let fired = Rc::new(Cell::new(false));
+        drop(clock.set_timeout(Duration::from_millis(10), Box::new(f!(fired.set(true)))));
+        clock.advance(Duration::from_millis(100));
+        assert!(!fired.get());
+    }
+
+    #[test]
+    fn interval_fires_until_cancelled() {
+        let clock = ManualClock::new();
+        let count = Rc::new(Cell::new(0));
+        let handle =
+            clock.set_interval(Duration::from_millis(10), Box::new(f!(count.set(count.get() + 1))));
+        clock.advance(Duration::from_millis(35));
+        assert_eq!(count.get(), 3);
+        drop(handle);
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(count.get(), 3);
+    }
+
+    #[test]
+    fn interval_cancelled_from_its_callback() {
+        let clock = ManualClock::new();
+        let count = Rc::new(Cell::new(0));
+        let handle: Rc<RefCell<Option<TimerHandle>>> = default();
+        let callback = f!([count, handle] () {
+            count.set(count.get() + 1);
+            handle.borrow_mut().take();
+        });
+        *handle.borrow_mut() =
+            Some(clock.set_interval(Duration::from_millis(10), Box::new(callback)));
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    fn microtasks_run_after_each_timer() {
+        let clock = ManualClock::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let timer_callback = f!([clock, log] () {
+            log.borrow_mut().push("timer");
+            clock.queue_microtask(Box::new(f!(log.borrow_mut().push("microtask"))));
+        });
+        let _t1 = clock.set_timeout(Duration::from_millis(10), Box::new(timer_callback.clone()));
+        let _t2 = clock.set_timeout(Duration::from_millis(20), Box::new(timer_callback));
+        clock.advance(Duration::from_millis(20));
+        assert_eq!(*log.borrow(), ["timer", "microtask", "timer", "microtask"]);
+    }
+
+    #[test]
+    fn microtasks_queued_by_microtasks_run() {
+        let clock = ManualClock::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        clock.queue_microtask(Box::new(f!([clock, log] () {
+            log.borrow_mut().push(1);
+            clock.queue_microtask(Box::new(f!(log.borrow_mut().push(2))));
+        })));
+        clock.run_microtasks();
+        assert_eq!(*log.borrow(), [1, 2]);
+    }
+
+    #[test]
+    fn animation_frames_start_at_frame_boundaries() {
+        let clock = ManualClock::new();
+        let frame_time = Rc::new(Cell::new(None));
+        let frame_time_cell = frame_time.clone_ref();
+        let _frame = clock.request_animation_frame(Box::new(move |t| frame_time_cell.set(Some(t))));
+        clock.advance(Duration::from_millis(5));
+        assert_eq!(frame_time.get(), None);
+        clock.advance_frame();
+        assert_eq!(frame_time.get(), Some(clock.frame_period()));
+        assert_eq!(clock.now(), clock.frame_period());
+    }
+
+    #[test]
+    fn installed_clock_is_current_until_guard_dropped() {
+        let clock = ManualClock::new();
+        clock.advance(Duration::from_secs(1));
+        {
+            let _guard = install(clock.clone_ref());
+            assert_eq!(current().now(), Duration::from_secs(1));
+        }
+        assert_eq!(format!("{:?}", current()), "WebClock");
+    }
+}
@@ -52,6 +52,11 @@ impl Counter {
         Rc::weak_count(&self.count) == 0
     }
 
+    /// The number of live watches.
+    pub fn count(&self) -> usize {
+        Rc::weak_count(&self.count)
+    }
+
     /// Creates a new watch and returns a handle.
     pub fn new_watch(&self) -> Handle {
         Handle::new(self)
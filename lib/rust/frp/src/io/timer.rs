@@ -9,3 +9,112 @@ mod timeout;
 pub use delayed_interval::*;
 pub use interval::*;
 pub use timeout::*;
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    use crate as frp;
+    use frp::clock;
+    use frp::clock::ManualClock;
+    use std::time::Duration;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn timeout_expires_after_delay() {
+        let clock = ManualClock::new();
+        let _guard = clock::install(clock.clone_ref());
+        let network = frp::Network::new("test");
+        let timeout = Timeout::new(&network);
+        frp::extend! { network
+            expired_count <- timeout.on_expired.count().sampler();
+            is_running <- timeout.is_running.sampler();
+        }
+        timeout.restart.emit(300);
+        assert!(is_running.value());
+        clock.advance(ms(299));
+        assert_eq!(expired_count.value(), 0);
+        clock.advance(ms(1));
+        assert_eq!(expired_count.value(), 1);
+        assert!(!is_running.value());
+
+        timeout.restart.emit(100);
+        clock.advance(ms(50));
+        timeout.restart.emit(100);
+        clock.advance(ms(50));
+        assert_eq!(expired_count.value(), 1);
+        timeout.cancel.emit(());
+        clock.advance(ms(1000));
+        assert_eq!(expired_count.value(), 1);
+    }
+
+    #[test]
+    fn interval_fires_periodically() {
+        let clock = ManualClock::new();
+        let _guard = clock::install(clock.clone_ref());
+        let network = frp::Network::new("test");
+        let interval = Interval::new(&network);
+        frp::extend! { network
+            interval_count <- interval.on_interval.count().sampler();
+        }
+        interval.restart.emit(100);
+        clock.advance(ms(350));
+        assert_eq!(interval_count.value(), 3);
+        interval.restart.emit(10);
+        clock.advance(ms(50));
+        assert_eq!(interval_count.value(), 8);
+        interval.stop.emit(());
+        clock.advance(ms(1000));
+        assert_eq!(interval_count.value(), 8);
+    }
+
+    #[test]
+    fn delayed_interval_fires_after_delay() {
+        let clock = ManualClock::new();
+        let _guard = clock::install(clock.clone_ref());
+        let network = frp::Network::new("test");
+        let timer = DelayedInterval::new(&network);
+        frp::extend! { network
+            trigger_count <- timer.on_trigger.count().sampler();
+        }
+        timer.restart.emit(DelayedIntervalConfig::new(500, 100));
+        clock.advance(ms(499));
+        assert_eq!(trigger_count.value(), 0);
+        clock.advance(ms(1));
+        assert_eq!(trigger_count.value(), 1);
+        clock.advance(ms(200));
+        assert_eq!(trigger_count.value(), 3);
+        timer.stop.emit(());
+        clock.advance(ms(1000));
+        assert_eq!(trigger_count.value(), 3);
+    }
+
+    #[test]
+    fn debounce_emits_after_current_task() {
+        let clock = ManualClock::new();
+        let _guard = clock::install(clock.clone_ref());
+        frp::new_network! { network
+            def source = source::<usize>();
+            def debounced = source.debounce();
+            def debounced_count = debounced.count();
+            def debounced_value = debounced.sampler();
+            def debounced_count = debounced_count.sampler();
+        }
+        source.emit(1);
+        source.emit(2);
+        assert_eq!(debounced_count.value(), 0);
+        clock.advance(default());
+        assert_eq!(debounced_count.value(), 1);
+        assert_eq!(debounced_value.value(), 2);
+    }
+}
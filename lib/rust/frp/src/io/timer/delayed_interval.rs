@@ -4,6 +4,8 @@ use crate::prelude::*;
 
 use crate as frp;
 
+use frp::clock;
+use frp::clock::Clock;
 use frp::io::timer::Interval;
 use frp::io::timer::Timeout;
 
@@ -29,10 +31,10 @@ use frp::io::timer::Timeout;
 /// in `stop`:        -------------x-----------------------x---x----
 /// out `on_trigger`: ------x-x-x-x--------xxxxx----x--x--x---------
 ///
-/// The timer is based on `setTimeout` and `setInterval` browser APIs. That means there is no
-/// guarantee about when exactly the events will be emitted. In practice, the initial delay might be
-/// slightly longer and there might be some jitter in periodic triggers. This effect will be more
-/// pronounced when the browser event loop is very busy.
+/// The timer is driven by a [`Clock`], by default based on `setTimeout` and `setInterval` browser
+/// APIs. That means there is no guarantee about when exactly the events will be emitted. In
+/// practice, the initial delay might be slightly longer and there might be some jitter in periodic
+/// triggers. This effect will be more pronounced when the browser event loop is very busy.
 #[derive(Clone, CloneRef, Debug)]
 pub struct DelayedInterval {
     delay_timer:    Timeout,
@@ -49,10 +51,15 @@ pub struct DelayedInterval {
 }
 
 impl DelayedInterval {
-    /// Constructor. Timer is initially not active.
+    /// Constructor. Timer is initially not active. It is driven by the [`clock::current`] clock.
     pub fn new(network: &frp::Network) -> Self {
-        let delay_timer = Timeout::new(network);
-        let interval_timer = Interval::new(network);
+        Self::new_with_clock(network, clock::current())
+    }
+
+    /// Constructor. Timer is initially not active.
+    pub fn new_with_clock(network: &frp::Network, clock: Rc<dyn Clock>) -> Self {
+        let delay_timer = Timeout::new_with_clock(network, clock.clone());
+        let interval_timer = Interval::new_with_clock(network, clock);
 
         frp::extend! { network
             restart <- any_mut::<DelayedIntervalConfig>();
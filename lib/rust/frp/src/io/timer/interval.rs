@@ -4,8 +4,10 @@ use crate::prelude::*;
 
 use crate as frp;
 
-use enso_web::window;
-use enso_web::Closure;
+use frp::clock;
+use frp::clock::Clock;
+use frp::clock::TimerHandle;
+use std::time::Duration;
 
 
 // ==============
@@ -20,9 +22,6 @@ pub use enso_web::traits::*;
 // === Interval ===
 // ================
 
-/// Closure type alias for use in `setInterval` call.
-type TimerClosure = Closure<dyn FnMut()>;
-
 /// Periodic timer.
 ///
 /// The timer can be started or stopped at any time using `restart` and `stop` inputs. After it is
@@ -33,9 +32,9 @@ type TimerClosure = Closure<dyn FnMut()>;
 /// in `stop`:         -----------------------------x-----x-------
 /// out `on_interval`: -----x-----x-------x--x--x--x--------------
 ///
-/// The timer is based on `setInterval` browser API. That means there is no guarantee about the
-/// exact time the events will be emitted. The true period between events will approach provided
-/// value over time.
+/// The timer is driven by a [`Clock`], by default based on `setInterval` browser API. That means
+/// there is no guarantee about the exact time the events will be emitted. The true period between
+/// events will approach provided value over time.
 #[derive(Clone, CloneRef, Debug)]
 pub struct Interval {
     /// Starts the timer with provided period value, specified in integer milliseconds. If the
@@ -49,14 +48,19 @@ pub struct Interval {
 }
 
 impl Interval {
-    /// Constructor. Timer is initially not started.
+    /// Constructor. Timer is initially not started. It is driven by the [`clock::current`] clock.
     pub fn new(network: &frp::Network) -> Self {
+        Self::new_with_clock(network, clock::current())
+    }
+
+    /// Constructor. Timer is initially not started.
+    pub fn new_with_clock(network: &frp::Network, clock: Rc<dyn Clock>) -> Self {
         frp::extend! { network
             on_interval <- any_mut();
         }
 
-        let closure: TimerClosure = Closure::new(f!(on_interval.emit(())));
-        let raw_interval = Rc::new(RawInterval::new(closure));
+        let callback = Rc::new(f!(on_interval.emit(())));
+        let raw_interval = Rc::new(RawInterval::new(clock, callback));
 
         frp::extend! { network
             restart <- any_mut::<i32>();
@@ -74,37 +78,28 @@ impl Interval {
 // === RawInterval ===
 // ===================
 
-#[derive(Debug)]
+#[derive(Derivative)]
+#[derivative(Debug)]
 struct RawInterval {
-    closure:      TimerClosure,
-    timer_handle: RefCell<Option<i32>>,
+    clock:        Rc<dyn Clock>,
+    #[derivative(Debug = "ignore")]
+    callback:     Rc<dyn Fn()>,
+    timer_handle: RefCell<Option<TimerHandle>>,
 }
 
 impl RawInterval {
-    fn new(closure: TimerClosure) -> Self {
-        Self { closure, timer_handle: default() }
+    fn new(clock: Rc<dyn Clock>, callback: Rc<dyn Fn()>) -> Self {
+        Self { clock, callback, timer_handle: default() }
     }
 
     fn restart(&self, time: i32) {
-        let js_func = self.closure.as_js_function();
-        let result = window.set_interval_with_callback_and_timeout_and_arguments_0(js_func, time);
-        let handle = result.expect("setInterval should never fail when callback is a function.");
-        self.set_timer_handle(Some(handle));
+        let callback = self.callback.clone();
+        let period = Duration::from_millis(time.max(0) as u64);
+        let handle = self.clock.set_interval(period, Box::new(move || callback()));
+        drop(self.timer_handle.replace(Some(handle)));
     }
 
     fn stop(&self) {
-        self.set_timer_handle(None);
-    }
-
-    fn set_timer_handle(&self, handle: Option<i32>) {
-        if let Some(old_handle) = self.timer_handle.replace(handle) {
-            window.clear_interval_with_handle(old_handle);
-        }
-    }
-}
-
-impl Drop for RawInterval {
-    fn drop(&mut self) {
-        self.stop();
+        drop(self.timer_handle.take());
     }
 }
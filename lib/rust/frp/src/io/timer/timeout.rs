@@ -4,8 +4,10 @@ use crate::prelude::*;
 
 use crate as frp;
 
-use enso_web::window;
-use enso_web::Closure;
+use frp::clock;
+use frp::clock::Clock;
+use frp::clock::TimerHandle;
+use std::time::Duration;
 
 
 // ==============
@@ -20,9 +22,6 @@ pub use enso_web::traits::*;
 // === Timeout ===
 // ================
 
-/// Closure type alias for use in `setTimeout` call.
-type TimerClosure = Closure<dyn FnMut()>;
-
 /// One-shot timer.
 ///
 /// The timer can be started or cancelled at any time using `restart` and `cancel` inputs. After it
@@ -35,10 +34,10 @@ type TimerClosure = Closure<dyn FnMut()>;
 /// in `cancel`:      ----------------------------x----x---
 /// out `on_expired`: -----x--------------x----------------
 ///
-/// The timer is based on `setTimeout` browser API. That means there is no guarantee about the
-/// exact time the event will be emitted. It might be delayed if the browser event loop is busy. If
-/// you need to fire events periodically, prefer [`Interval`] timer in order to avoid unpredictable
-/// event trigger rate.
+/// The timer is driven by a [`Clock`], by default based on `setTimeout` browser API. That means
+/// there is no guarantee about the exact time the event will be emitted. It might be delayed if the
+/// browser event loop is busy. If you need to fire events periodically, prefer [`Interval`] timer
+/// in order to avoid unpredictable event trigger rate.
 #[derive(Clone, CloneRef, Debug)]
 pub struct Timeout {
     /// Starts the timer immediately with provided timeout value, specified in integer
@@ -55,14 +54,19 @@ pub struct Timeout {
 }
 
 impl Timeout {
-    /// Constructor. Timer is initially not started.
+    /// Constructor. Timer is initially not started. It is driven by the [`clock::current`] clock.
     pub fn new(network: &frp::Network) -> Self {
+        Self::new_with_clock(network, clock::current())
+    }
+
+    /// Constructor. Timer is initially not started.
+    pub fn new_with_clock(network: &frp::Network, clock: Rc<dyn Clock>) -> Self {
         frp::extend! { network
             on_expired <- any_mut();
         }
 
-        let closure: TimerClosure = Closure::new(f!(on_expired.emit(())));
-        let raw_timeout = Rc::new(RawTimeout::new(closure));
+        let callback = Rc::new(f!(on_expired.emit(())));
+        let raw_timeout = Rc::new(RawTimeout::new(clock, callback));
 
         frp::extend! { network
             restart <- any_mut::<i32>();
@@ -84,37 +88,28 @@ impl Timeout {
 // === RawTimeout ===
 // ==================
 
-#[derive(Debug)]
+#[derive(Derivative)]
+#[derivative(Debug)]
 struct RawTimeout {
-    closure:      TimerClosure,
-    timer_handle: RefCell<Option<i32>>,
+    clock:        Rc<dyn Clock>,
+    #[derivative(Debug = "ignore")]
+    callback:     Rc<dyn Fn()>,
+    timer_handle: RefCell<Option<TimerHandle>>,
 }
 
 impl RawTimeout {
-    fn new(closure: TimerClosure) -> Self {
-        Self { closure, timer_handle: default() }
+    fn new(clock: Rc<dyn Clock>, callback: Rc<dyn Fn()>) -> Self {
+        Self { clock, callback, timer_handle: default() }
     }
 
     fn restart(&self, time: i32) {
-        let js_func = self.closure.as_js_function();
-        let result = window.set_timeout_with_callback_and_timeout_and_arguments_0(js_func, time);
-        let handle = result.expect("setTimeout should never fail when callback is a function.");
-        self.set_timer_handle(Some(handle));
+        let callback = self.callback.clone();
+        let delay = Duration::from_millis(time.max(0) as u64);
+        let handle = self.clock.set_timeout(delay, Box::new(move || callback()));
+        drop(self.timer_handle.replace(Some(handle)));
     }
 
     fn cancel(&self) {
-        self.set_timer_handle(None);
-    }
-
-    fn set_timer_handle(&self, handle: Option<i32>) {
-        if let Some(old_handle) = self.timer_handle.replace(handle) {
-            window.clear_timeout_with_handle(old_handle);
-        }
-    }
-}
-
-impl Drop for RawTimeout {
-    fn drop(&mut self) {
-        self.cancel();
+        drop(self.timer_handle.take());
     }
 }
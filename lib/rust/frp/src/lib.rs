@@ -166,7 +166,9 @@
 #![feature(downcast_unchecked)]
 #![recursion_limit = "512"]
 
+pub mod analysis;
 pub mod any_data;
+pub mod clock;
 pub mod data;
 pub mod debug;
 pub mod fan;
@@ -177,6 +179,7 @@ pub mod microtasks;
 pub mod network;
 pub mod node;
 pub mod nodes;
+pub mod recorder;
 pub mod stream;
 
 pub use network::*;
@@ -96,6 +96,8 @@ macro_rules! _new_network {
     ($trace:ident $network:ident $($ts:tt)*) => {
         let $network = $crate::Network::new(stringify!($network));
         $crate::_extend! { $trace $network $($ts)* }
+        #[cfg(debug_assertions)]
+        $network.debug_check_well_formed();
     };
 }
 
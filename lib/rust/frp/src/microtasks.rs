@@ -58,6 +58,9 @@
 //! tasks spawn another set of tasks, the whole process will be repeated, all within the same
 //! event loop task.
 //!
+//! The microtasks are queued with the [`clock::current`] clock. By default it uses the JavaScript
+//! event loop, while in tests a [`clock::ManualClock`] can be used to run them explicitly.
+//!
 //! Since microtasks can themselves enqueue more microtasks, and the event loop continues processing
 //! microtasks until the queue is empty, there's a real risk of getting the event loop endlessly
 //! processing microtasks. In order to prevent completely blocking rendering, the scheduler has a
@@ -77,15 +80,12 @@
 use crate::prelude::*;
 use enso_callback::traits::*;
 
+use crate::clock;
+use crate::clock::TimerHandle;
 use enso_callback as callback;
 use enso_generics::Cons;
 use enso_generics::Nil;
 use enso_generics::PushLastField;
-use enso_web::traits::WindowOps;
-use enso_web::Closure;
-use enso_web::JsEventHandler;
-use enso_web::JsValue;
-use enso_web::Promise;
 
 
 
@@ -156,31 +156,13 @@ struct Scheduler {
 
 impl Scheduler {
     fn new() -> Self {
-        let data = Rc::new_cyclic(|weak: &Weak<SchedulerData>| {
-            let resolved_promise = Promise::resolve(&JsValue::NULL);
-            let callbacks = default();
-            let late_callbacks = default();
-            let schedule_depth = default();
-            let is_scheduled = default();
-            let run_all_closure = Closure::new(f!([weak] (_: JsValue) {
-                if let Some(data) = weak.upgrade() {
-                    data.run_all();
-                }
-            }));
-            let schedule_closure = Closure::new(f!([weak] (_: f64) {
-                if let Some(data) = weak.upgrade() {
-                    data.schedule_task();
-                }
-            }));
-            SchedulerData {
-                is_scheduled,
-                callbacks,
-                late_callbacks,
-                schedule_depth,
-                resolved_promise,
-                run_all_closure,
-                schedule_closure,
-            }
+        let data = Rc::new_cyclic(|weak: &Weak<SchedulerData>| SchedulerData {
+            weak:             weak.clone(),
+            is_scheduled:     default(),
+            callbacks:        default(),
+            late_callbacks:   default(),
+            schedule_depth:   default(),
+            past_limit_frame: default(),
         });
         Self { data }
     }
@@ -214,28 +196,37 @@ fn profile(f: impl FnOnce() + 'static) -> impl FnOnce() + 'static {
 }
 
 struct SchedulerData {
+    weak:             Weak<SchedulerData>,
     is_scheduled:     Cell<bool>,
     callbacks:        callback::registry::NoArgsOnce,
     late_callbacks:   callback::registry::NoArgsOnce,
     schedule_depth:   Cell<usize>,
-    resolved_promise: Promise,
-    run_all_closure:  JsEventHandler,
-    schedule_closure: JsEventHandler<f64>,
+    past_limit_frame: RefCell<Option<TimerHandle>>,
 }
 
 impl SchedulerData {
     #[profile(Task)]
     fn schedule_task(&self) {
         if !self.is_scheduled.replace(true) {
-            // Result left unused on purpose. We only care about `closure` being run in the next
-            // microtask, which is a guaranteed side effect of providing it to [`Promise::then`]
-            // method on already resolved promise.
-            let _ = self.resolved_promise.then(&self.run_all_closure);
+            let weak = self.weak.clone();
+            clock::current().queue_microtask(Box::new(move || {
+                if let Some(data) = weak.upgrade() {
+                    data.run_all();
+                }
+            }));
         }
     }
+
     fn schedule_task_past_limit(&self) {
         if !self.is_scheduled.replace(true) {
-            enso_web::window.request_animation_frame_with_closure_or_panic(&self.schedule_closure);
+            let weak = self.weak.clone();
+            let handle = clock::current().request_animation_frame(Box::new(move |_| {
+                if let Some(data) = weak.upgrade() {
+                    data.is_scheduled.set(false);
+                    data.schedule_task();
+                }
+            }));
+            drop(self.past_limit_frame.replace(Some(handle)));
         }
     }
 
@@ -541,4 +532,23 @@ mod tests {
             .schedule();
         collector.flush_and_assert_ordered(6);
     }
+
+    #[test]
+    fn nested_microtasks_with_manual_clock() {
+        let clock = clock::ManualClock::new();
+        let _guard = clock::install(clock.clone_ref());
+        let collector = Collector::default();
+        next_microtask(f!([collector] () {
+            collector.push(1);
+            next_microtask(f!([collector] () {
+                collector.push(2);
+                next_microtask_late(f!(collector.push(3))).forget();
+            }))
+            .forget();
+        }))
+        .forget();
+        assert!(collector.vec.borrow().is_empty());
+        clock.run_microtasks();
+        assert_eq!(collector.vec.take(), [1, 2, 3]);
+    }
 }
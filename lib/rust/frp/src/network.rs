@@ -4,7 +4,9 @@ use crate::node::*;
 use crate::prelude::*;
 
 use crate::debug;
+use crate::recorder;
 use crate::stream;
+use crate::stream::NodeConnections;
 use crate::stream::Stream;
 
 
@@ -40,8 +42,8 @@ pub struct WeakNetwork {
 }
 
 /// Network item.
-pub trait Item: HasId + HasLabel + stream::HasOutputTypeLabel {}
-impl<T> Item for T where T: HasId + HasLabel + stream::HasOutputTypeLabel {}
+pub trait Item: HasId + HasLabel + stream::HasOutputTypeLabel + NodeConnections {}
+impl<T> Item for T where T: HasId + HasLabel + stream::HasOutputTypeLabel + NodeConnections {}
 
 /// Internal data of `Network`.
 #[derive(Derivative)]
@@ -143,11 +145,68 @@ impl Network {
         self.data.nodes.borrow_mut().push(node);
     }
 
+    /// Number of nodes registered in this network.
+    pub fn node_count(&self) -> usize {
+        self.data.nodes.borrow().len()
+    }
+
+    /// Ids of all nodes registered in this network.
+    pub fn node_ids(&self) -> Vec<Id> {
+        self.data.nodes.borrow().iter().map(|node| node.id()).collect()
+    }
+
+    /// Start recording events emitted by nodes of this network. Only the last `capacity` events
+    /// are kept. See [`recorder`] module docs to learn more.
+    pub fn record_events(&self, capacity: usize) -> recorder::Recorder {
+        recorder::Recorder::new_for_network(self, capacity)
+    }
+
     /// Register a new link between nodes. Visualization purposes only.
     pub fn register_link(&self, target: Id, link: Link) {
         self.data.links.borrow_mut().insert(target, link);
     }
 
+    /// Statically analyze the connections between nodes of this network. See the
+    /// [`crate::analysis`] module docs to learn more.
+    pub fn analyze(&self) -> crate::analysis::Report {
+        let links = self.data.links.borrow();
+        let nodes = self.data.nodes.borrow();
+        let infos = nodes.iter().map(|node| {
+            let id = node.id();
+            let controls = links.get(&id).filter(|link| matches!(link.tp, LinkType::Behavior));
+            crate::analysis::NodeInfo {
+                id,
+                label: node.label(),
+                kind: node.output_type_label(),
+                event_inputs: node.event_inputs(),
+                controls: controls.map(|link| link.source).into_iter().collect(),
+                event_target_count: node.event_target_count(),
+                watch_count: node.watch_count(),
+            }
+        });
+        crate::analysis::analyze(&infos.collect_vec())
+    }
+
+    /// Panic if the analysis of this network reports any problems. To be used in tests.
+    #[track_caller]
+    pub fn assert_well_formed(&self) {
+        let report = self.analyze();
+        assert!(
+            report.is_empty(),
+            "FRP network '{}' is not well formed:\n{report}",
+            self.data.label
+        );
+    }
+
+    /// Warn about event cycles in this network. Called by the `new_network!` macro in debug builds,
+    /// once the network is defined. Other problems are not reported, as nodes of a freshly defined
+    /// network are often connected to other networks later.
+    pub fn debug_check_well_formed(&self) {
+        for cycle in self.analyze().cycles() {
+            warn!("FRP network '{}': {cycle}", self.data.label);
+        }
+    }
+
     /// Registers the provided bridge network as child of this network.
     pub fn register_bridge_network(&self, sub_network: &BridgeNetwork) {
         self.data.bridges.borrow_mut().push(sub_network.clone_ref())
@@ -139,7 +139,9 @@ impl Network {
     where
         T1: EventOutput,
         T2: EventOutput<Output = bool>, {
-        self.register(OwnedGate::new(label, event, behavior))
+        let out = self.register(OwnedGate::new(label, event, behavior));
+        self.register_link(out.id(), Link::behavior(behavior));
+        out
     }
 
     pub fn sampled_gate<T1, T2>(
@@ -179,7 +181,9 @@ impl Network {
     where
         T1: EventOutput,
         T2: EventOutput<Output = bool>, {
-        self.register(OwnedGateNot::new(label, event, behavior))
+        let out = self.register(OwnedGateNot::new(label, event, behavior));
+        self.register_link(out.id(), Link::behavior(behavior));
+        out
     }
 
     /// Passes the incoming event of the first stream only if the value of the second stream is
@@ -200,7 +204,9 @@ impl Network {
         T1: EventOutput,
         T2: EventOutput<Output = bool>,
     {
-        self.register(OwnedBufferedGate::new(label, event, behavior))
+        let out = self.register(OwnedBufferedGate::new(label, event, behavior));
+        self.register_link(out.id(), Link::behavior(behavior));
+        out
     }
 
     /// Passes the incoming event of the first stream only if the second stream has emitted an event
@@ -500,13 +506,10 @@ impl Network {
         T2: EventOutput<Output = T>,
         T3: EventOutput<Output = T>,
         T: Data, {
-        self.all_with3(
-            label,
-            check,
-            t2,
-            t3,
-            |check, t1, t2| if *check { t2.clone() } else { t1.clone() },
-        )
+        let switch = |check: &bool, t1: &T, t2: &T| if *check { t2.clone() } else { t1.clone() };
+        let out = self.all_with3(label, check, t2, t3, switch);
+        self.register_link(out.id(), Link::behavior(check));
+        out
     }
 
     /// On every `true` event from the first input, emit the second parameter. On every `false`
@@ -2742,6 +2745,10 @@ where
             false
         }
     }
+
+    fn on_connected(&self, source: Id) {
+        stream::WeakEventConsumer::<Output<T1>>::on_connected(&self.gate, source)
+    }
 }
 
 
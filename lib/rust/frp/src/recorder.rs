@@ -0,0 +1,563 @@
+//! Recording of events flowing through FRP networks, and replaying them for debugging.
+//!
+//! A [`Recorder`] stores every event emitted by FRP nodes in a ring buffer. Each recorded event
+//! contains the emitting node label, the time it was emitted at (as reported by the
+//! [`clock::current`] clock), the [`Debug`] representation of the value, the JSON representation
+//! of the value if its type implements [`Serialize`], and the id of the event that caused it (the
+//! event being propagated when the new one was emitted). This allows reconstructing the exact
+//! order in which events flowed through the network, which is invisible when looking at the static
+//! network structure only.
+//!
+//! Recorded events can be exported as a JSON [`Trace`]. A [`Replay`] re-feeds the root events of
+//! a trace (the ones emitted from outside of the FRP network, e.g. by user input handlers) into a
+//! fresh network and reports differences between the recorded events and the events replayed in
+//! that network.
+//!
+//! ```text
+//! let recorder = network.record_events(1000);
+//! // ... interact with the network ...
+//! let json = recorder.trace().to_json();
+//!
+//! let trace = Trace::from_json(&json)?;
+//! let diff = Replay::new(&network).source(mouse_position_label, &mouse_position).run(&trace);
+//! assert!(diff.is_empty(), "{diff}");
+//! ```
+
+use crate::prelude::*;
+
+use crate::clock;
+use crate::microtasks;
+use crate::network::Network;
+use crate::network::WeakNetwork;
+use crate::node::*;
+use crate::stream::EventEmitter;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+
+
+// ===================
+// === TracedEvent ===
+// ===================
+
+/// Unique identifier of a recorded event.
+pub type EventId = u64;
+
+/// A single event emitted by an FRP node.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TracedEvent {
+    /// Unique identifier of this event.
+    pub id:      EventId,
+    /// The event that was being propagated when this event was emitted. It is [`None`] for events
+    /// emitted from outside of the FRP network, e.g. by sources in user input handlers.
+    pub parent:  Option<EventId>,
+    /// The identifier of the emitting node. It is valid only during a single program run.
+    pub node:    usize,
+    /// The label of the emitting node.
+    pub label:   String,
+    /// The time the event was emitted at, in milliseconds.
+    pub time_ms: f64,
+    /// The [`Debug`] representation of the emitted value.
+    pub value:   String,
+    /// The JSON representation of the emitted value, if its type implements [`Serialize`].
+    pub json:    Option<serde_json::Value>,
+}
+
+impl TracedEvent {
+    /// Check whether the event was emitted by a node with the same label and had the same value.
+    pub fn matches(&self, other: &TracedEvent) -> bool {
+        self.label == other.label && self.value == other.value
+    }
+}
+
+impl Display for TracedEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.label, self.value)
+    }
+}
+
+
+// === TraceJson ===
+
+/// Conversion of emitted values to JSON. Values of types not implementing [`Serialize`] have no
+/// JSON representation.
+trait TraceJson {
+    fn trace_json(&self) -> Option<serde_json::Value>;
+}
+
+impl<T> TraceJson for T {
+    default fn trace_json(&self) -> Option<serde_json::Value> {
+        None
+    }
+}
+
+impl<T: Serialize> TraceJson for T {
+    fn trace_json(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(self).ok()
+    }
+}
+
+
+
+// ================
+// === Recorder ===
+// ================
+
+thread_local! {
+    static RECORDING: RecordingState = default();
+}
+
+/// The number of live recorders in all threads. Emitting an event checks it before accessing the
+/// thread-local [`RECORDING`] state, so that recording costs a single atomic load while no recorder
+/// is active.
+static ACTIVE_RECORDERS: AtomicUsize = AtomicUsize::new(0);
+
+/// The state shared by all recorders in the current thread.
+#[derive(Debug, Default)]
+struct RecordingState {
+    recorders:     RefCell<Vec<Rc<RecorderData>>>,
+    next_event_id: Cell<EventId>,
+    /// Stack of events being currently propagated. The top one is the parent of new events.
+    propagating:   RefCell<Vec<EventId>>,
+}
+
+/// Records events emitted by FRP nodes into a ring buffer. Recording stops when the recorder is
+/// dropped. See the module docs to learn more.
+#[derive(Debug)]
+pub struct Recorder {
+    data: Rc<RecorderData>,
+}
+
+#[derive(Debug)]
+struct RecorderData {
+    capacity: usize,
+    events:   RefCell<VecDeque<TracedEvent>>,
+    network:  Option<NetworkFilter>,
+}
+
+impl Recorder {
+    /// Start recording events emitted by nodes of all networks. Only the last `capacity` events
+    /// are kept.
+    pub fn new(capacity: usize) -> Self {
+        Self::new_with_filter(capacity, None)
+    }
+
+    /// Start recording events emitted by nodes of the provided network. Only the last `capacity`
+    /// events are kept.
+    pub fn new_for_network(network: &Network, capacity: usize) -> Self {
+        Self::new_with_filter(capacity, Some(NetworkFilter::new(network)))
+    }
+
+    fn new_with_filter(capacity: usize, network: Option<NetworkFilter>) -> Self {
+        let events = default();
+        let data = Rc::new(RecorderData { capacity, events, network });
+        RECORDING.with(|state| state.recorders.borrow_mut().push(data.clone()));
+        ACTIVE_RECORDERS.fetch_add(1, Ordering::Relaxed);
+        Self { data }
+    }
+
+    /// All recorded events, in the order they were emitted.
+    pub fn events(&self) -> Vec<TracedEvent> {
+        self.data.events.borrow().iter().cloned().collect()
+    }
+
+    /// The trace of all recorded events.
+    pub fn trace(&self) -> Trace {
+        Trace { events: self.events() }
+    }
+
+    /// Remove all recorded events.
+    pub fn clear(&self) {
+        self.data.events.borrow_mut().clear();
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        ACTIVE_RECORDERS.fetch_sub(1, Ordering::Relaxed);
+        let _ = RECORDING.try_with(|state| {
+            state.recorders.borrow_mut().retain(|data| !Rc::ptr_eq(data, &self.data))
+        });
+    }
+}
+
+impl RecorderData {
+    fn accepts(&self, node: Id) -> bool {
+        self.network.as_ref().map_or(true, |network| network.contains(node))
+    }
+
+    fn push(&self, event: TracedEvent) {
+        let mut events = self.events.borrow_mut();
+        if events.len() >= self.capacity {
+            events.pop_front();
+        }
+        if self.capacity > 0 {
+            events.push_back(event);
+        }
+    }
+}
+
+
+// === NetworkFilter ===
+
+/// Filter accepting nodes of a single network. The set of node ids is refreshed lazily, whenever
+/// new nodes are registered in the network.
+#[derive(Debug)]
+struct NetworkFilter {
+    network:    WeakNetwork,
+    node_ids:   RefCell<HashSet<Id>>,
+    node_count: Cell<usize>,
+}
+
+impl NetworkFilter {
+    fn new(network: &Network) -> Self {
+        let network = network.downgrade();
+        let node_ids = default();
+        let node_count = default();
+        Self { network, node_ids, node_count }
+    }
+
+    fn contains(&self, node: Id) -> bool {
+        let Some(network) = self.network.upgrade() else { return false };
+        if network.node_count() != self.node_count.get() {
+            *self.node_ids.borrow_mut() = network.node_ids().into_iter().collect();
+            self.node_count.set(network.node_count());
+        }
+        self.node_ids.borrow().contains(&node)
+    }
+}
+
+
+// === Recording ===
+
+/// Guard returned by [`record_event`]. As long as it is alive, the recorded event is the parent of
+/// all newly emitted events.
+#[derive(Debug)]
+pub(crate) struct PropagationScope {
+    _private: (),
+}
+
+impl Drop for PropagationScope {
+    fn drop(&mut self) {
+        let _ = RECORDING.try_with(|state| state.propagating.borrow_mut().pop());
+    }
+}
+
+/// Record the event emitted by a node, if any recorder is active. The returned guard should be
+/// kept alive while the event is being propagated.
+#[inline(always)]
+pub(crate) fn record_event<T: Data>(node: Id, label: Label, value: &T) -> Option<PropagationScope> {
+    if ACTIVE_RECORDERS.load(Ordering::Relaxed) == 0 {
+        return None;
+    }
+    RECORDING.try_with(|state| state.record(node, label, value)).ok().flatten()
+}
+
+impl RecordingState {
+    #[inline(always)]
+    fn record<T: Data>(&self, node: Id, label: Label, value: &T) -> Option<PropagationScope> {
+        if self.recorders.borrow().is_empty() {
+            None
+        } else {
+            Some(self.record_unchecked(node, label, value))
+        }
+    }
+
+    fn record_unchecked<T: Data>(&self, node: Id, label: Label, value: &T) -> PropagationScope {
+        let id = self.next_event_id.get();
+        self.next_event_id.set(id + 1);
+        let parent = self.propagating.borrow().last().copied();
+        let recorders = self.recorders.borrow().clone();
+        let accepting = recorders.iter().filter(|recorder| recorder.accepts(node));
+        let mut accepting = accepting.peekable();
+        if accepting.peek().is_some() {
+            let time_ms = clock::current().now().as_secs_f64() * 1000.0;
+            let event = TracedEvent {
+                id,
+                parent,
+                node: node.into(),
+                label: label.into(),
+                time_ms,
+                value: format!("{value:?}"),
+                json: value.trace_json(),
+            };
+            accepting.for_each(|recorder| recorder.push(event.clone()));
+        }
+        self.propagating.borrow_mut().push(id);
+        PropagationScope { _private: () }
+    }
+}
+
+
+
+// =============
+// === Trace ===
+// =============
+
+/// A sequence of recorded events, which can be exported to and imported from JSON.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Trace {
+    /// The recorded events, in the order they were emitted.
+    pub events: Vec<TracedEvent>,
+}
+
+impl Trace {
+    /// Export the trace to JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Trace serialization should never fail.")
+    }
+
+    /// Import the trace from JSON.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Events emitted from outside of the FRP network, e.g. by sources in user input handlers.
+    pub fn roots(&self) -> impl Iterator<Item = &TracedEvent> {
+        self.events.iter().filter(|event| event.parent.is_none())
+    }
+
+    /// Events directly caused by the provided event.
+    pub fn children(&self, id: EventId) -> impl Iterator<Item = &TracedEvent> {
+        self.events.iter().filter(move |event| event.parent == Some(id))
+    }
+}
+
+
+
+// ==============
+// === Replay ===
+// ==============
+
+/// Re-feeds the root events of a [`Trace`] into a network and diffs the events emitted by the
+/// network during the replay against the recorded ones. See the module docs to learn more.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct Replay {
+    network: WeakNetwork,
+    #[derivative(Debug = "ignore")]
+    sources: HashMap<String, Box<dyn Fn(&serde_json::Value) -> bool>>,
+}
+
+impl Replay {
+    /// Constructor. Only the events emitted by nodes of the provided network are compared with the
+    /// recorded ones.
+    pub fn new(network: &Network) -> Self {
+        Self { network: network.downgrade(), sources: default() }
+    }
+
+    /// Feed the recorded root events with the provided label into the `source` node. The values
+    /// are restored from their JSON representation, so their type has to implement
+    /// [`Deserialize`].
+    pub fn source<S>(mut self, label: impl Into<String>, source: &S) -> Self
+    where
+        S: EventEmitter + CloneRef + 'static,
+        Output<S>: DeserializeOwned, {
+        let source = source.clone_ref();
+        let feed = move |json: &serde_json::Value| {
+            let value = serde_json::from_value::<Output<S>>(json.clone());
+            value.map(|value| source.emit_event(&default(), &value)).is_ok()
+        };
+        self.sources.insert(label.into(), Box::new(feed));
+        self
+    }
+
+    /// Re-feed the root events of the trace into the registered sources and return the difference
+    /// between the recorded and the replayed events. Microtasks are flushed after each fed event.
+    /// Root events without a registered source or without a JSON representation are skipped. If
+    /// the network was dropped, no events are replayed.
+    pub fn run(&self, trace: &Trace) -> TraceDiff {
+        let Some(network) = self.network.upgrade_or_warn() else {
+            return TraceDiff::new(&trace.events, &[]);
+        };
+        let recorder = Recorder::new_for_network(&network, usize::MAX);
+        for event in trace.roots() {
+            let source = self.sources.get(&event.label);
+            let fed = source.zip(event.json.as_ref()).map_or(false, |(feed, json)| feed(json));
+            if !fed && source.is_some() {
+                warn!("Cannot restore the value of the recorded event {event}.");
+            }
+            microtasks::flush_microtasks();
+        }
+        TraceDiff::new(&trace.events, &recorder.events())
+    }
+}
+
+
+
+// =================
+// === TraceDiff ===
+// =================
+
+/// A difference between recorded and replayed events. Events are compared position by position,
+/// so an event emitted in a different order is reported as a mismatch.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TraceDiff {
+    /// All positions at which the events differ.
+    pub mismatches: Vec<Mismatch>,
+}
+
+/// Events differing at a given position of the compared traces.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mismatch {
+    /// The position of the events in the traces.
+    pub index:    usize,
+    /// The recorded event, if the recorded trace is long enough.
+    pub expected: Option<TracedEvent>,
+    /// The replayed event, if the replayed trace is long enough.
+    pub actual:   Option<TracedEvent>,
+}
+
+impl TraceDiff {
+    /// Compare the expected and actual events.
+    pub fn new(expected: &[TracedEvent], actual: &[TracedEvent]) -> Self {
+        let len = expected.len().max(actual.len());
+        let mismatches = (0..len)
+            .filter_map(|index| {
+                let expected = expected.get(index);
+                let actual = actual.get(index);
+                let matches = expected.zip(actual).map_or(false, |(e, a)| e.matches(a));
+                let expected = expected.cloned();
+                let actual = actual.cloned();
+                (!matches).then_some(Mismatch { index, expected, actual })
+            })
+            .collect();
+        Self { mismatches }
+    }
+
+    /// Check whether the compared events are the same.
+    pub fn is_empty(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl Display for TraceDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |event: &Option<TracedEvent>| {
+            event.as_ref().map_or("<no event>".to_string(), |event| event.to_string())
+        };
+        for mismatch in &self.mismatches {
+            let expected = describe(&mismatch.expected);
+            let actual = describe(&mismatch.actual);
+            writeln!(f, "#{}: expected {expected}, got {actual}", mismatch.index)?;
+        }
+        Ok(())
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate as frp;
+
+    #[test]
+    fn events_are_recorded_with_causality() {
+        frp::new_network! { network
+            def source = source::<i32>();
+            def doubled = source.map(|t| t * 2);
+        }
+        let recorder = network.record_events(100);
+        source.emit(1);
+        source.emit(2);
+        let events = recorder.events();
+        let values = events.iter().map(|e| e.value.as_str()).collect_vec();
+        assert_eq!(values, ["1", "2", "2", "4"]);
+        assert_eq!(events[0].parent, None);
+        assert_eq!(events[1].parent, Some(events[0].id));
+        assert_eq!(events[2].parent, None);
+        assert_eq!(events[3].parent, Some(events[2].id));
+        assert!(events[1].label.contains("doubled"));
+        assert_eq!(events[1].json, Some(serde_json::json!(2)));
+        drop(doubled);
+    }
+
+    #[test]
+    fn ring_buffer_keeps_latest_events() {
+        frp::new_network! { network
+            def source = source::<i32>();
+        }
+        let recorder = network.record_events(2);
+        (0..5).for_each(|i| source.emit(i));
+        let values = recorder.events().into_iter().map(|e| e.value).collect_vec();
+        assert_eq!(values, ["3", "4"]);
+    }
+
+    #[test]
+    fn other_networks_are_not_recorded() {
+        frp::new_network! { network1
+            def source1 = source::<i32>();
+        }
+        frp::new_network! { network2
+            def source2 = source::<i32>();
+        }
+        let recorder = network1.record_events(100);
+        source1.emit(1);
+        source2.emit(2);
+        assert_eq!(recorder.events().len(), 1);
+        drop(network2);
+    }
+
+    #[test]
+    fn recording_stops_when_recorder_dropped() {
+        frp::new_network! { network
+            def source = source::<i32>();
+        }
+        let recorder = Recorder::new(100);
+        source.emit(1);
+        let events = recorder.events();
+        drop(recorder);
+        source.emit(2);
+        assert_eq!(events.len(), 1);
+        drop(network);
+    }
+
+    fn sum_network() -> (Network, frp::Source<i32>, frp::Source<i32>) {
+        frp::new_network! { network
+            def a = source::<i32>();
+            def b = source::<i32>();
+            def _sum = all_with(&a, &b, |a, b| a + b);
+        }
+        (network, a, b)
+    }
+
+    #[test]
+    fn replay_reproduces_recorded_trace() {
+        let (network, a, b) = sum_network();
+        let recorder = network.record_events(100);
+        a.emit(1);
+        b.emit(2);
+        a.emit(3);
+        let json = recorder.trace().to_json();
+        drop(recorder);
+
+        let trace = Trace::from_json(&json).unwrap();
+        assert_eq!(trace.roots().count(), 3);
+        let (network2, a2, b2) = sum_network();
+        // Events of other networks observing the replayed one are not compared.
+        frp::new_network! { observer
+            def _observed = a2.map(|a| a + 1);
+        }
+        let a_label = trace.events[0].label.clone();
+        let b_label = trace.roots().nth(1).unwrap().label.clone();
+        let replay = Replay::new(&network2).source(a_label.clone(), &a2).source(b_label, &b2);
+        let diff = replay.run(&trace);
+        assert!(diff.is_empty(), "{diff}");
+        drop(observer);
+
+        let (network3, a3, _b3) = sum_network();
+        let diff = Replay::new(&network3).source(a_label, &a3).run(&trace);
+        assert!(!diff.is_empty());
+        assert_eq!(diff.mismatches[0].index, 2);
+    }
+}
@@ -5,6 +5,7 @@ use crate::node::*;
 use crate::prelude::*;
 
 use crate::data::watch;
+use crate::recorder;
 
 
 
@@ -112,6 +113,23 @@ impl<T> InputBehaviors for T {
 
 
 
+// =======================
+// === NodeConnections ===
+// =======================
+
+/// Information about the connections of a node. Used by the static network analysis, see the
+/// [`crate::analysis`] module.
+pub trait NodeConnections {
+    /// Ids of all nodes this node receives events from.
+    fn event_inputs(&self) -> Vec<Id>;
+    /// Number of live event targets of this node.
+    fn event_target_count(&self) -> usize;
+    /// Number of nodes watching the value of this node.
+    fn watch_count(&self) -> usize;
+}
+
+
+
 // ====================
 // === EventEmitter ===
 // ====================
@@ -151,6 +169,10 @@ pub trait WeakEventConsumer<T> {
     /// Callback for a new incoming event. Returns true if the event was consumed or false if it was
     /// not. Not consuming an event means that the event receiver was already dropped.
     fn on_event_if_exists(&self, stack: CallStack, value: &T) -> bool;
+
+    /// Callback for the consumer being registered as an event target of the node with the given
+    /// id. Used to collect the network topology, see the [`crate::analysis`] module.
+    fn on_connected(&self, _source: Id) {}
 }
 
 
@@ -250,6 +272,8 @@ pub struct NodeData<Out = ()> {
     value_cache:         RefCell<Out>,
     ongoing_evaluations: Cell<usize>,
     watch_counter:       watch::Counter,
+    /// Ids of nodes this node was registered as an event target of.
+    event_inputs:        RefCell<Vec<Id>>,
     label:               Label,
 }
 
@@ -267,6 +291,7 @@ impl<Out: Default> NodeData<Out> {
             value_cache,
             ongoing_evaluations: evaluations,
             watch_counter,
+            event_inputs: default(),
             label,
         }
     }
@@ -274,6 +299,13 @@ impl<Out: Default> NodeData<Out> {
     fn use_caching(&self) -> bool {
         !self.watch_counter.is_zero()
     }
+
+    /// The id of this node. It is the same as the id of [`Stream`] pointing to this data.
+    fn id(&self) -> Id {
+        let ptr: *const Self = self;
+        let raw = ptr as *const () as usize;
+        raw.into()
+    }
 }
 
 impl<Out: Data> HasOutput for NodeData<Out> {
@@ -290,6 +322,7 @@ impl<Out: Data> EventEmitter for NodeData<Out> {
             warn!("{}", backtrace())
         } else {
             self.ongoing_evaluations.set(self.ongoing_evaluations.get() + 1);
+            let _recorded = recorder::record_event(self.id(), self.label, value);
             if self.use_caching() {
                 *self.value_cache.borrow_mut() = value.clone();
             }
@@ -317,6 +350,7 @@ impl<Out: Data> EventEmitter for NodeData<Out> {
     }
 
     fn register_target(&self, target: EventInput<Out>) {
+        target.data.on_connected(self.id());
         if self.ongoing_evaluations.get() > 0 {
             self.new_targets.borrow_mut().push(target);
         } else {
@@ -630,6 +664,12 @@ where
             })
             .is_some()
     }
+
+    fn on_connected(&self, source: Id) {
+        if let Some(data) = self.stream.data.upgrade() {
+            data.event_inputs.borrow_mut().push(source);
+        }
+    }
 }
 
 
@@ -760,6 +800,27 @@ where Def: InputBehaviors
 }
 
 
+// === NodeConnections ===
+
+impl<Def: HasOutputStatic> NodeConnections for Node<Def> {
+    fn event_inputs(&self) -> Vec<Id> {
+        self.stream.data.event_inputs.borrow().clone()
+    }
+
+    fn event_target_count(&self) -> usize {
+        let data = &self.stream.data;
+        let is_live = |target: &&EventInput<Output<Def>>| !target.data.is_dropped();
+        let targets = data.targets.borrow().iter().filter(is_live).count();
+        let new_targets = data.new_targets.borrow().iter().filter(is_live).count();
+        targets + new_targets
+    }
+
+    fn watch_count(&self) -> usize {
+        self.stream.data.watch_counter.count()
+    }
+}
+
+
 // === Debug ===
 
 impl<Out> Debug for Stream<Out> {
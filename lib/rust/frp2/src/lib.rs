@@ -24,7 +24,7 @@
 //! # Events and Behaviors
 //! Every FRP implementation has its own evaluation logic. Some FRP implementations provide the user
 //! with an explicit distinction between "events" and "behaviors". "Events" are simple values passed
-//! trough the network, while "behaviors" are values that can be accessed at any time (like the
+//! through the network, while "behaviors" are values that can be accessed at any time (like the
 //! current mouse position). Such a distinction requires the user to explicitly specify where a
 //! behavior should be created, for example by creating a "hold" node, which on every incoming event
 //! clones its value and stores it in case someone would like to sample it. Such a distinction has
@@ -43,7 +43,8 @@
 //! - [`Listen`]: A node can have only one listen port. If a node has this port it can also have
 //!   zero or more [`Sample`] ports (but no [`ListenAndSample`] ports). In case an event is emitted
 //!   to this port, the node will sample all of its [`Sample`] ports, evaluate its expression, and
-//!   emit the output value.
+//!   emit the output value. The only exception are nodes merging streams, like `any`, which have
+//!   many listen ports and pass the incoming events through.
 //!
 //! - [`Sample`]: In contrast to listen ports, if an event is emitted to a sample port, the node
 //!   will not evaluate its expression. Sample ports are used only to sample the last emitted value
@@ -93,6 +94,14 @@
 //! ...
 //!
 //!
+//! # Transactional FRP evaluation order
+//! The runtime can be switched to an opt-in transactional mode with
+//! [`runtime::set_propagation_mode`]. In this mode, all events caused by a single source event are
+//! batched in a transaction and nodes are evaluated in topological order, each at most once per
+//! transaction. It eliminates the "diamond problem" at the cost of cloning every propagated value.
+//! See [`runtime::PropagationMode`] to learn more.
+//!
+//!
 //! # Reactive FRP evaluation order
 //! An alternative way to imperative FRP evaluation order is a reactive one. This library does NOT
 //! implement such a mode, but it is important to understand this difference, as we plan to
@@ -120,6 +129,22 @@
 //! at least one sample port, the data will be cloned before it is emitted, so the sample ports will
 //! be able to sample it on demand. If no sample ports are connected to the node output, the data
 //! will not be cloned.
+//!
+//!
+//! # Migration from `enso_frp`
+//! Most of the `enso_frp` nodes are available under the same names, taking the first input as
+//! `self` instead of a label. The notable differences are:
+//!
+//! - `any_mut` nodes pass through events arriving while they are already passing an event through,
+//!   just like in `enso_frp`. The nesting is limited to 100 events, after which events are dropped
+//!   with a warning. Recursive networks must stop the cycle themselves, for example with a filter.
+//! - `applyX` nodes are aliases of the `all_withX_` nodes.
+//! - The `profile` node takes the label of its profiler interval as an argument, since nodes are
+//!   not labelled.
+//! - The `all_mut`, `flat_map`, and `on_drop` nodes are not provided. The first one requires
+//!   sampling a dynamic number of inputs, which the node templates do not support. The other two
+//!   require nodes that outlive or create networks, and will be designed together with the network
+//!   lifetime rework.
 
 // === Features ===
 #![feature(allocator_api)]
@@ -223,6 +248,109 @@ mod tests {
         net2_results.assert_eq(&[3]);
     }
 
+    fn with_propagation_mode(mode: runtime::PropagationMode, f: impl FnOnce()) {
+        runtime::set_propagation_mode(mode);
+        f();
+        runtime::set_propagation_mode(default());
+    }
+
+    #[test]
+    fn test_eager_diamond() {
+        let net = Network_::new();
+        let src = net.source::<usize>();
+        let doubled = src.map_(|t| t * 2);
+        let (_, results) = net.debug_collect(src.all(doubled));
+        src.emit(&1);
+        results.assert_eq(&[(1, 2), (1, 2)]);
+    }
+
+    #[test]
+    fn test_transactional_diamond() {
+        with_propagation_mode(runtime::PropagationMode::Transactional, || {
+            let net = Network_::new();
+            let src = net.source::<usize>();
+            let doubled = src.map_(|t| t * 2);
+            let (_, results) = net.debug_collect(src.all(doubled));
+            src.emit(&1);
+            results.assert_eq(&[(1, 2)]);
+            src.emit(&2);
+            results.assert_eq(&[(1, 2), (2, 4)]);
+        });
+    }
+
+    #[test]
+    fn test_transactional_merge_fires_once() {
+        with_propagation_mode(runtime::PropagationMode::Transactional, || {
+            let net = Network_::new();
+            let src = net.source::<usize>();
+            let incremented = src.map_(|t| t + 1);
+            let (_, results) = net.debug_collect(src.any(incremented));
+            src.emit(&1);
+            results.assert_eq(&[2]);
+        });
+    }
+
+    #[test]
+    fn test_transactional_attach_reranks_nodes() {
+        with_propagation_mode(runtime::PropagationMode::Transactional, || {
+            let net = Network_::new();
+            let src = net.source::<usize>();
+            let merged = net.any_mut::<usize>();
+            let (_, results) = net.debug_collect(src.all(merged));
+            let doubled = src.map_(|t| t * 2);
+            merged.attach(doubled);
+            src.emit(&1);
+            results.assert_eq(&[(1, 2)]);
+        });
+    }
+
+    #[test]
+    fn test_transactional_cycle() {
+        with_propagation_mode(runtime::PropagationMode::Transactional, || {
+            let net = Network_::new();
+            let src = net.source::<usize>();
+            let counter = net.any_mut::<usize>();
+            counter.attach(src);
+            let decremented = counter.filter_map_(|t| t.checked_sub(1));
+            counter.attach(decremented);
+            let (_, results) = net.debug_collect(counter);
+            src.emit(&3);
+            results.assert_eq(&[3, 2, 1, 0]);
+        });
+    }
+
+    #[test]
+    fn test_transactional_after_eager_connections() {
+        let net = Network_::new();
+        let src = net.source::<usize>();
+        let doubled = src.map_(|t| t * 2);
+        let counter = net.any_mut::<usize>();
+        counter.attach(src);
+        let decremented = counter.filter_map_(|t| t.checked_sub(1));
+        counter.attach(decremented);
+        let (_, diamond_results) = net.debug_collect(src.all(doubled));
+        let (_, counter_results) = net.debug_collect(counter);
+        with_propagation_mode(runtime::PropagationMode::Transactional, || {
+            src.emit(&2);
+            diamond_results.assert_eq(&[(2, 4)]);
+            counter_results.assert_eq(&[2, 1, 0]);
+        });
+    }
+
+    #[test]
+    fn test_transactional_panic_recovery() {
+        with_propagation_mode(runtime::PropagationMode::Transactional, || {
+            let net = Network_::new();
+            let src = net.source::<usize>();
+            let checked = src.map_(|t| if *t == 0 { panic!("Zero is not allowed.") } else { *t });
+            let (_, results) = net.debug_collect(src.all(checked));
+            let emit_zero = std::panic::AssertUnwindSafe(|| src.emit(&0));
+            assert!(std::panic::catch_unwind(emit_zero).is_err());
+            src.emit(&1);
+            results.assert_eq(&[(1, 1)]);
+        });
+    }
+
     #[test]
     fn test2() {
         for _ in 0..10 {
@@ -356,6 +484,60 @@ mod benches {
         });
     }
 
+    fn diamond_network(net: &Network_) -> node::NodeInNetwork<(), nodes::Source<usize>> {
+        let src = net.source::<usize>();
+        let doubled = src.map_(|t| t * 2);
+        let _all = src.all_with_(doubled, map_fn);
+        src
+    }
+
+    // 4738568
+    #[bench]
+    fn bench_emit_frp_diamond(bencher: &mut Bencher) {
+        let net = Network_::new();
+        let src = diamond_network(&net);
+        bencher.iter(move || {
+            for i in 0..REPS {
+                src.emit(&i);
+            }
+        });
+    }
+
+    // 9693080
+    #[bench]
+    fn bench_emit_frp_diamond_transactional(bencher: &mut Bencher) {
+        runtime::set_propagation_mode(runtime::PropagationMode::Transactional);
+        let net = Network_::new();
+        let src = diamond_network(&net);
+        bencher.iter(move || {
+            for i in 0..REPS {
+                src.emit(&i);
+            }
+        });
+        runtime::set_propagation_mode(default());
+    }
+
+    // 23956651
+    #[bench]
+    fn bench_emit_frp_chain_pod_transactional(bencher: &mut Bencher) {
+        runtime::set_propagation_mode(runtime::PropagationMode::Transactional);
+        let net = Network_::new();
+        let n1 = net.source::<usize>();
+        let n2 = n1.map_(|t| t + 1);
+        let mut prev = n2;
+        for _ in 0..8 {
+            let next = prev.map_(|t| t + 1);
+            prev = next;
+        }
+
+        bencher.iter(move || {
+            for i in 0..REPS {
+                n1.emit(&i);
+            }
+        });
+        runtime::set_propagation_mode(default());
+    }
+
     // 1488912
     #[bench]
     fn bench_emit_non_pod_frp(bencher: &mut Bencher) {
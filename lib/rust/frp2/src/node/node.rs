@@ -2,6 +2,7 @@ use crate::prelude::*;
 
 use crate::data::Data;
 use crate::network::Network;
+use crate::node::input;
 use crate::node::template;
 use crate::node::template::Template;
 use crate::runtime::with_runtime;
@@ -76,6 +77,25 @@ impl<Model> Network<Model> {
         let id = with_runtime(|rt| rt.new_node(self.id, DefInfo::unlabelled(), f, init));
         TypedNode { _marker: ZST(), id }
     }
+
+    /// Constructor of a node listening to all of the provided inputs. Unlike nodes defined with
+    /// [`Template`], the node can have many [`input::Listen`] ports, and the [`f`] function is
+    /// called with the incoming event instead of the sampled input values.
+    ///
+    /// # Safety
+    /// The type safety is not guaranteed. You have to ensure that the events emitted by all inputs
+    /// have the type expected by the [`f`] function, and that it emits events of the `Output` type.
+    #[inline(always)]
+    #[allow(unsafe_code)]
+    pub(crate) unsafe fn new_listener_node_unchecked<Type, Output>(
+        &self,
+        inputs: &[input::Type],
+        f: impl EventConsumer,
+    ) -> NodeInNetwork<Model, TypedNode<Type, Output>> {
+        let node = self.new_node_with_init_unchecked(f, |_| {});
+        with_runtime(|rt| inputs.iter().for_each(|input| rt.connect(*input, node.id)));
+        NodeInNetwork::new(self, node)
+    }
 }
 
 
@@ -45,9 +45,9 @@ impl<'a, Output> EventContext<'a, Output> {
 }
 
 impl<'a, Output: Data> EventContext<'a, Output> {
-    /// Emit the output event.
+    /// Emit the output event. A node can emit any number of events while handling a single one.
     #[inline(always)]
-    pub(crate) fn emit(self, value: &Output) {
+    pub(crate) fn emit(&self, value: &Output) {
         self.runtime.unchecked_emit(self.node, value);
     }
 }
@@ -1,6 +1,7 @@
 //! FRP nodes definitions. They are the main user-facing API for creating FRP networks.
 
 use crate::prelude::*;
+use enso_generics::traits::*;
 
 use crate::data::Data;
 use crate::network::Model;
@@ -8,11 +9,19 @@ use crate::network::Network;
 use crate::node::input::Listen;
 use crate::node::input::ListenAndSample;
 use crate::node::input::Sample;
+use crate::node::input::Type;
 use crate::node::Node;
 use crate::node::NodeInNetwork;
 use crate::node::NodeOf;
 use crate::node::NodeWithDefaultOutput;
 use crate::node::TypedNode;
+use crate::runtime::with_runtime;
+use crate::runtime::NodeData;
+use crate::runtime::Runtime;
+
+use enso_frp::microtasks::next_microtask;
+use enso_frp::AnyData;
+use enso_generics::FieldAt;
 
 
 
@@ -105,7 +114,7 @@ pub struct SAMPLER;
 pub type Sampler<Output = ()> = TypedNode<SAMPLER, Output>;
 
 impl<'a, M: Model, N1: NodeWithDefaultOutput> NodeInNetwork<'a, M, N1> {
-    /// On every event, remember it, and pass it trough.
+    /// On every event, remember it, and pass it through.
     #[inline(never)]
     pub fn sampler(self) -> NodeInNetwork<'a, M, Stream<N1::Output>> {
         self.new_node((ListenAndSample(self),), move |event, _, t1| event.emit(t1))
@@ -116,7 +125,7 @@ impl<'a, M: Model, N1: NodeWithDefaultOutput> NodeInNetwork<'a, M, N1> {
 // === Trace ===
 
 impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
-    /// On every event, print it to console, and pass it trough.
+    /// On every event, print it to console, and pass it through.
     pub fn trace(self) -> NodeInNetwork<'a, M, Stream<N1::Output>> {
         self.new_node((Listen(self),), move |event, _, t0| {
             println!("TRACE: {t0:?}");
@@ -124,7 +133,7 @@ impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
         })
     }
 
-    /// On every event, print it to console if the `cond` input is `true`, and pass it trough.
+    /// On every event, print it to console if the `cond` input is `true`, and pass it through.
     pub fn trace_if(self, cond: impl NodeOf<bool>) -> NodeInNetwork<'a, M, Stream<N1::Output>> {
         self.new_node((Listen(self), Sample(cond)), move |event, _, src, cond| {
             if *cond {
@@ -136,6 +145,27 @@ impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
 }
 
 
+// === Profile ===
+
+impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
+    /// On every event, pass it through, measuring the propagation in a debug profiler interval
+    /// with the given label. In the transactional propagation mode, the listeners are evaluated
+    /// after the node, so only the scheduling of their evaluation is measured.
+    pub fn profile(self, label: &'static str) -> NodeInNetwork<'a, M, Stream<N1::Output>> {
+        self.new_node((Listen(self),), move |event, _, t0| {
+            use enso_frp::prelude::profiler;
+            use profiler::internal::StartState;
+            let label = profiler::internal::Label(label);
+            let parent = profiler::internal::EventId::implicit();
+            let now = Some(profiler::internal::Timestamp::now());
+            let profiler = profiler::Debug::start(parent, label, now, StartState::Active);
+            event.emit(t0);
+            profiler.finish();
+        })
+    }
+}
+
+
 // === DebugCollect ===
 
 /// A node that collects all the events it receives. Usd mainly for debug purposes.
@@ -155,7 +185,7 @@ impl<T> DebugCollectData<T> {
 }
 
 impl<M: Model> Network<M> {
-    /// On every event, remember it, and pass it trough.
+    /// On every event, remember it, and pass it through.
     pub fn debug_collect<T0: Data + Clone + 'static>(
         &self,
         source: impl Node<Output = T0>,
@@ -192,7 +222,7 @@ macro_rules! def_map_nodes {
 
     (# $name:ident $i:tt [$($ns:tt)*]) => { paste! {
         /// On every event on the first input, sample other inputs, evaluate the provided function,
-        /// and pass the result trough. The function contains mutable reference to the network
+        /// and pass the result through. The function contains mutable reference to the network
         /// model. If you don't need the model, use the `mapX_` family of functions instead.
         #[inline(never)]
         #[allow(non_snake_case)]
@@ -209,7 +239,7 @@ macro_rules! def_map_nodes {
         }
 
         /// On every event on the first input, sample other inputs, evaluate the provided function,
-        /// and pass the result trough. The function does not contain mutable reference to the
+        /// and pass the result through. The function does not contain mutable reference to the
         /// network model. If you need the model, use the `mapX` family of functions instead.
         #[inline(never)]
         #[allow(non_snake_case)]
@@ -231,4 +261,1259 @@ impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
     def_map_nodes![2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
 }
 
-// TODO: Allow more than one listen port - for example, `any` node uses this pattern.
+
+
+// ================
+// === Identity ===
+// ================
+
+impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
+    /// On every event, pass it through. This can be used to control the order of event handlers by
+    /// creating a separate node for the handlers that need to run after all the other ones.
+    pub fn identity(self) -> NodeInNetwork<'a, M, Stream<N1::Output>> {
+        self.new_node((Listen(self),), move |event, _, t1| event.emit(t1))
+    }
+}
+
+
+// === Constant ===
+
+impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
+    /// Replace the incoming event with the predefined value.
+    pub fn constant<T: Data>(self, value: T) -> NodeInNetwork<'a, M, Stream<T>> {
+        self.new_node((Listen(self),), move |event, _, _| event.emit(&value))
+    }
+
+    /// Replace the incoming event with `true`.
+    pub fn to_true(self) -> NodeInNetwork<'a, M, Stream<bool>> {
+        self.constant(true)
+    }
+
+    /// Replace the incoming event with `false`.
+    pub fn to_false(self) -> NodeInNetwork<'a, M, Stream<bool>> {
+        self.constant(false)
+    }
+
+    /// Repeat node listens for input events of type [`usize`] and emits events in number equal to
+    /// the input event value.
+    pub fn repeat(self) -> NodeInNetwork<'a, M, Stream>
+    where N1: NodeOf<usize> {
+        self.new_node((Listen(self),), move |event, _, t1| {
+            for _ in 0..*t1 {
+                event.emit(&());
+            }
+        })
+    }
+}
+
+
+// === Toggle ===
+
+impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
+    /// Emits `true`, `false`, `true`, `false`, ... on every incoming event. Initialized with
+    /// `false` value.
+    pub fn toggle(self) -> NodeInNetwork<'a, M, Stream<bool>> {
+        self.toggle_with(false)
+    }
+
+    /// Emits `false`, `true`, `false`, `true`, ... on every incoming event. Initialized with `true`
+    /// value.
+    pub fn toggle_true(self) -> NodeInNetwork<'a, M, Stream<bool>> {
+        self.toggle_with(true)
+    }
+
+    fn toggle_with(self, init: bool) -> NodeInNetwork<'a, M, Stream<bool>> {
+        let value = Cell::new(init);
+        // The initial value is visible to nodes sampling the toggle before its first event.
+        let init_cache = move |node: &mut NodeData| {
+            node.output_cache.replace(ZeroableOption::Some(Box::new(init)))
+        };
+        self.network.new_node_with_init(
+            (Listen(self),),
+            move |event, _, _| {
+                value.set(!value.get());
+                event.emit(&value.get());
+            },
+            init_cache,
+        )
+    }
+}
+
+
+// === Count ===
+
+impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
+    /// Count the incoming events.
+    pub fn count(self) -> NodeInNetwork<'a, M, Stream<usize>> {
+        let count = Cell::new(0);
+        self.new_node((Listen(self),), move |event, _, _| {
+            count.set(count.get() + 1);
+            event.emit(&count.get());
+        })
+    }
+}
+
+
+// === Previous ===
+
+impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
+    /// Remembers the value of the input stream and outputs the previously received one. The
+    /// default value is emitted on the first event.
+    pub fn previous(self) -> NodeInNetwork<'a, M, Stream<N1::Output>>
+    where N1::Output: Default + Clone {
+        let previous: RefCell<N1::Output> = default();
+        self.new_node((Listen(self),), move |event, _, t1| {
+            let value = previous.replace(t1.clone());
+            event.emit(&value);
+        })
+    }
+
+    /// Only if the input event has changed, emit the input event. This will hide multiple
+    /// consecutive events with the same value. The first event is compared with the default value.
+    pub fn on_change(self) -> NodeInNetwork<'a, M, Stream<N1::Output>>
+    where N1::Output: Default + Clone + PartialEq {
+        let previous: RefCell<N1::Output> = default();
+        self.new_node((Listen(self),), move |event, _, t1| {
+            let changed = *previous.borrow() != *t1;
+            if changed {
+                previous.replace(t1.clone());
+                event.emit(t1);
+            }
+        })
+    }
+}
+
+
+
+// ==============
+// === Filter ===
+// ==============
+
+impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
+    /// Pass the incoming event only if the predicate returns `true`. The predicate contains mutable
+    /// reference to the network model. If you don't need the model, use [`Self::filter_`] instead.
+    pub fn filter<P>(self, p: P) -> NodeInNetwork<'a, M, Stream<N1::Output>>
+    where P: 'static + Fn(&mut M, &N1::Output) -> bool {
+        self.new_node_with_model((Listen(self),), move |event, m, t1| {
+            if p(m, t1) {
+                event.emit(t1);
+            }
+        })
+    }
+
+    /// Pass the incoming event only if the predicate returns `true`. The predicate does not contain
+    /// mutable reference to the network model. If you need the model, use [`Self::filter`] instead.
+    pub fn filter_<P>(self, p: P) -> NodeInNetwork<'a, M, Stream<N1::Output>>
+    where P: 'static + Fn(&N1::Output) -> bool {
+        self.new_node((Listen(self),), move |event, _, t1| {
+            if p(t1) {
+                event.emit(t1);
+            }
+        })
+    }
+
+    /// Apply the function to the incoming event. If the result is [`Some`], pass its value on.
+    /// Otherwise, nothing happens. The function contains mutable reference to the network model.
+    /// If you don't need the model, use [`Self::filter_map_`] instead.
+    pub fn filter_map<F, Output>(self, f: F) -> NodeInNetwork<'a, M, Stream<Output>>
+    where
+        Output: Data,
+        F: 'static + Fn(&mut M, &N1::Output) -> Option<Output>, {
+        self.new_node_with_model((Listen(self),), move |event, m, t1| {
+            if let Some(value) = f(m, t1) {
+                event.emit(&value);
+            }
+        })
+    }
+
+    /// Apply the function to the incoming event. If the result is [`Some`], pass its value on.
+    /// Otherwise, nothing happens. The function does not contain mutable reference to the network
+    /// model. If you need the model, use [`Self::filter_map`] instead.
+    pub fn filter_map_<F, Output>(self, f: F) -> NodeInNetwork<'a, M, Stream<Output>>
+    where
+        Output: Data,
+        F: 'static + Fn(&N1::Output) -> Option<Output>, {
+        self.new_node((Listen(self),), move |event, _, t1| {
+            if let Some(value) = f(t1) {
+                event.emit(&value);
+            }
+        })
+    }
+}
+
+
+// === Option ===
+
+impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
+    /// Unwraps the value of incoming events and emits the unwrapped values. [`None`] values are
+    /// not passed on.
+    pub fn unwrap<T: Data>(self) -> NodeInNetwork<'a, M, Stream<T>>
+    where N1: NodeOf<Option<T>> {
+        self.new_node((Listen(self),), move |event, _, t1| {
+            if let Some(value) = t1 {
+                event.emit(value);
+            }
+        })
+    }
+
+    /// A shortcut for `.map_(|v| Some(v.clone()))`.
+    pub fn some(self) -> NodeInNetwork<'a, M, Stream<Option<N1::Output>>>
+    where N1::Output: Clone {
+        self.map_(|t1| Some(t1.clone()))
+    }
+
+    /// On every event, output whether its value is [`Some`].
+    pub fn is_some<T>(self) -> NodeInNetwork<'a, M, Stream<bool>>
+    where N1: NodeOf<Option<T>> {
+        self.map_(|t1| t1.is_some())
+    }
+
+    /// On every event, output whether its value is [`None`].
+    pub fn is_none<T>(self) -> NodeInNetwork<'a, M, Stream<bool>>
+    where N1: NodeOf<Option<T>> {
+        self.map_(|t1| t1.is_none())
+    }
+
+    /// A version of [`Self::map`] that operates on [`Some`] values. The provided function will
+    /// only be called when the incoming value is [`Some`]. The function contains mutable reference
+    /// to the network model. If you don't need the model, use [`Self::map_some_`] instead.
+    pub fn map_some<T, F, Output>(self, f: F) -> NodeInNetwork<'a, M, Stream<Option<Output>>>
+    where
+        N1: NodeOf<Option<T>>,
+        Option<Output>: Data,
+        F: 'static + Fn(&mut M, &T) -> Output, {
+        self.map(move |m, t1| t1.as_ref().map(|value| f(m, value)))
+    }
+
+    /// A version of [`Self::map_`] that operates on [`Some`] values. The provided function will
+    /// only be called when the incoming value is [`Some`]. The function does not contain mutable
+    /// reference to the network model. If you need the model, use [`Self::map_some`] instead.
+    pub fn map_some_<T, F, Output>(self, f: F) -> NodeInNetwork<'a, M, Stream<Option<Output>>>
+    where
+        N1: NodeOf<Option<T>>,
+        Option<Output>: Data,
+        F: 'static + Fn(&T) -> Output, {
+        self.map_(move |t1| t1.as_ref().map(&f))
+    }
+
+    /// Like [`Self::map_some`], but the provided function returns an optional value itself. The
+    /// function contains mutable reference to the network model. If you don't need the model, use
+    /// [`Self::and_then_`] instead.
+    pub fn and_then<T, F, Output>(self, f: F) -> NodeInNetwork<'a, M, Stream<Option<Output>>>
+    where
+        N1: NodeOf<Option<T>>,
+        Option<Output>: Data,
+        F: 'static + Fn(&mut M, &T) -> Option<Output>, {
+        self.map(move |m, t1| t1.as_ref().and_then(|value| f(m, value)))
+    }
+
+    /// Like [`Self::map_some_`], but the provided function returns an optional value itself. The
+    /// function does not contain mutable reference to the network model. If you need the model,
+    /// use [`Self::and_then`] instead.
+    pub fn and_then_<T, F, Output>(self, f: F) -> NodeInNetwork<'a, M, Stream<Option<Output>>>
+    where
+        N1: NodeOf<Option<T>>,
+        Option<Output>: Data,
+        F: 'static + Fn(&T) -> Option<Output>, {
+        self.map_(move |t1| t1.as_ref().and_then(&f))
+    }
+
+    /// Emit the value of the incoming event if it is [`Some`]. Otherwise, emit the value of the
+    /// second input. The output is emitted on every event of any of the inputs.
+    pub fn unwrap_or<T, N2>(self, n2: N2) -> NodeInNetwork<'a, M, Stream<T>>
+    where
+        N1: NodeOf<Option<T>>,
+        N2: NodeOf<T>,
+        T: Data + Default + Clone, {
+        self.all_with_(n2, |t1, t2| t1.as_ref().unwrap_or(t2).clone())
+    }
+}
+
+
+// === Conversions ===
+
+impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
+    /// Just like [`value.into()`] on the reference of the incoming value.
+    pub fn ref_into<T: Data>(self) -> NodeInNetwork<'a, M, Stream<T>>
+    where for<'t> &'t N1::Output: Into<T> {
+        self.map_(|t1| t1.into())
+    }
+
+    /// Just like [`value.clone().into()`] on the incoming value.
+    pub fn cloned_into<T: Data>(self) -> NodeInNetwork<'a, M, Stream<T>>
+    where N1::Output: Clone + Into<T> {
+        self.map_(|t1| t1.clone().into())
+    }
+
+    /// Just like [`Some(value.into())`] on the reference of the incoming value.
+    pub fn ref_into_some<T>(self) -> NodeInNetwork<'a, M, Stream<Option<T>>>
+    where
+        T: Data + Clone,
+        for<'t> &'t N1::Output: Into<T>, {
+        self.map_(|t1| Some(t1.into()))
+    }
+
+    /// Just like [`Some(value.clone().into())`] on the incoming value.
+    pub fn cloned_into_some<T>(self) -> NodeInNetwork<'a, M, Stream<Option<T>>>
+    where
+        T: Data + Clone,
+        N1::Output: Clone + Into<T>, {
+        self.map_(|t1| Some(t1.clone().into()))
+    }
+
+    /// Converts the incoming values to [`AnyData`] hiding their types. This can be used to create
+    /// FRP inputs accepting different types, not known at compile time.
+    pub fn any_data(self) -> NodeInNetwork<'a, M, Stream<AnyData>>
+    where N1::Output: Clone {
+        self.map_(|t1| AnyData::new(t1.clone()))
+    }
+}
+
+
+// === Fields ===
+
+macro_rules! def_field_nodes {
+    ($($i:tt),*) => { paste! { $(
+        /// Get the field with the given index of the incoming event.
+        pub fn [<_ $i>](self) -> NodeInNetwork<'a, M, Stream<FieldAt<$i, N1::Output>>>
+        where
+            N1::Output: enso_generics::[<GetFieldAt $i>],
+            FieldAt<$i, N1::Output>: Data + Sized, {
+            self.new_node((Listen(self),), move |event, _, t1| event.emit(t1.[<_ $i>]()))
+        }
+    )* }};
+}
+
+impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
+    def_field_nodes![0, 1, 2];
+}
+
+
+// === Iter ===
+
+impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
+    /// On every incoming event, iterate over its value and emit each element separately.
+    pub fn iter<T: Data>(self) -> NodeInNetwork<'a, M, Stream<T>>
+    where for<'t> &'t N1::Output: IntoIterator<Item = &'t T> {
+        self.new_node((Listen(self),), move |event, _, t1| {
+            for value in t1 {
+                event.emit(value);
+            }
+        })
+    }
+
+    /// Fold the incoming value using [`Monoid`] implementation.
+    pub fn fold<T>(self) -> NodeInNetwork<'a, M, Stream<T>>
+    where
+        T: Data + Monoid,
+        for<'t> &'t N1::Output: IntoIterator<Item = &'t T>, {
+        self.map_(|t1| t1.into_iter().fold(default(), |acc: T, value| acc.concat(value)))
+    }
+}
+
+
+
+// ============
+// === Gate ===
+// ============
+
+impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
+    /// On every event of the provided input, sample this node and emit its value. The incoming
+    /// event is dropped.
+    pub fn sample<N2: Node>(self, event: N2) -> NodeInNetwork<'a, M, Stream<N1::Output>>
+    where N1::Output: Default {
+        self.new_node((Listen(event), Sample(self)), move |event, _, _, t1| event.emit(t1))
+    }
+
+    /// Passes the incoming event only if the last value of the condition is `true`.
+    pub fn gate(self, cond: impl NodeOf<bool>) -> NodeInNetwork<'a, M, Stream<N1::Output>> {
+        self.new_node((Listen(self), Sample(cond)), move |event, _, t1, cond| {
+            if *cond {
+                event.emit(t1);
+            }
+        })
+    }
+
+    /// Passes the incoming event only if the last value of the condition is `false`.
+    pub fn gate_not(self, cond: impl NodeOf<bool>) -> NodeInNetwork<'a, M, Stream<N1::Output>> {
+        self.new_node((Listen(self), Sample(cond)), move |event, _, t1, cond| {
+            if !*cond {
+                event.emit(t1);
+            }
+        })
+    }
+
+    /// Like [`Self::gate`], but the last value of this node is also emitted every time the
+    /// condition becomes `true`.
+    pub fn sampled_gate(self, cond: impl NodeOf<bool>) -> NodeInNetwork<'a, M, Stream<N1::Output>>
+    where N1::Output: Default {
+        let on_open = NodeInNetwork::new(self.network, cond).on_true();
+        self.gate(cond).any(self.sample(on_open))
+    }
+
+    /// Like [`Self::gate_not`], but the last value of this node is also emitted every time the
+    /// condition becomes `false`.
+    pub fn sampled_gate_not(
+        self,
+        cond: impl NodeOf<bool>,
+    ) -> NodeInNetwork<'a, M, Stream<N1::Output>>
+    where
+        N1::Output: Default,
+    {
+        let on_open = NodeInNetwork::new(self.network, cond).on_false();
+        self.gate_not(cond).any(self.sample(on_open))
+    }
+
+    /// Passes the incoming event only if the last value of the condition is `true`. If the event is
+    /// received when the condition is `false`, it is buffered and emitted when the condition
+    /// becomes `true`. Only the last received event value is emitted next time the condition
+    /// becomes `true`. A single received event will be reemitted at most once.
+    ///
+    /// ```text
+    /// Condition: T---F---T-----F-------T---T---F---T--
+    /// Event:     --1--2-----3---4-5-6-----------------
+    /// Output:    --1-----2--3----------6--------------
+    /// ```
+    pub fn buffered_gate(
+        self,
+        cond: impl NodeOf<bool>,
+    ) -> NodeInNetwork<'a, M, Stream<N1::Output>>
+    where
+        N1::Output: Clone,
+    {
+        let buffer = Rc::new(RefCell::new(None));
+        let buffer_on_event = buffer.clone();
+        let on_event: NodeInNetwork<M, Stream<N1::Output>> =
+            self.new_node((Listen(self), Sample(cond)), move |event, _, t1, cond| {
+                if *cond {
+                    event.emit(t1);
+                } else {
+                    buffer_on_event.replace(Some(t1.clone()));
+                }
+            });
+        let on_open: NodeInNetwork<M, Stream<N1::Output>> =
+            self.new_node((Listen(cond),), move |event, _, cond| {
+                let value = if *cond { buffer.take() } else { None };
+                if let Some(value) = value {
+                    event.emit(&value);
+                }
+            });
+        on_event.any(on_open)
+    }
+
+    /// Passes the incoming event only if the second input has emitted an event since the last
+    /// event of this node.
+    ///
+    /// ```text
+    /// Event:  1---2---3-----4-------5---6---7---8--
+    /// Sync:   --|--------|---|---|-----------------
+    /// Output: ----2---------4-------5--------------
+    /// ```
+    pub fn sync_gate(self, sync: impl Node) -> NodeInNetwork<'a, M, Stream<N1::Output>> {
+        let can_emit = Rc::new(Cell::new(false));
+        let can_emit_on_sync = can_emit.clone();
+        let _on_sync: NodeInNetwork<M, Stream> =
+            self.new_node((Listen(sync),), move |_, _, _| can_emit_on_sync.set(true));
+        self.filter_(move |_| can_emit.take())
+    }
+}
+
+
+
+// =============
+// === Merge ===
+// =============
+
+/// For the input `f 2 3 4` it generates:
+/// ```text
+/// f! { 2 [N2] }
+/// f! { 3 [N2 N3] }
+/// f! { 4 [N2 N3 N4] }
+/// ```
+macro_rules! with_input_sequences {
+    ($f:ident $($is:literal)*) => {
+        with_input_sequences! { @ $f [] $($is)* }
+    };
+
+    (@ $f:ident [$($ns:tt)*] $i:tt $($is:tt)*) => { paste! {
+        $f! { $i [$($ns)* [<N $i>]] }
+        with_input_sequences! { @ $f [$($ns)* [<N $i>]] $($is)* }
+    }};
+
+    (@ $f:ident [$($ns:tt)*]) => {};
+}
+
+macro_rules! def_merge_nodes {
+    ($i:tt [$($ns:ident)*]) => { paste! {
+        /// Merge all inputs into a single output. Every event of any of the inputs is passed
+        /// through.
+        #[inline(never)]
+        #[allow(non_snake_case)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn [<any $i>] < $($ns,)* > (self, $($ns:$ns,)*)
+        -> NodeInNetwork<'a, M, Stream<N1::Output>>
+        where $($ns: NodeOf<N1::Output>,)* {
+            let inputs = [Type::from(Listen(self)), $(Type::from(Listen($ns)),)*];
+            // # Safety
+            // All inputs have the same output type as this node, and the events are passed as is.
+            #[allow(unsafe_code)]
+            unsafe {
+                self.network.new_listener_node_unchecked(&inputs,
+                    |rt: &Runtime, node: &NodeData, data: &dyn Data| rt.unchecked_emit(node, data)
+                )
+            }
+        }
+
+        /// Merge all inputs into a single output, dropping the incoming data. The inputs can have
+        /// different output types.
+        #[inline(never)]
+        #[allow(non_snake_case)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn [<any $i _>] < $($ns,)* > (self, $($ns:$ns,)*) -> NodeInNetwork<'a, M, Stream>
+        where $($ns: Node,)* {
+            let inputs = [Type::from(Listen(self)), $(Type::from(Listen($ns)),)*];
+            // # Safety
+            // The incoming data is dropped, and the node emits events of the unit type.
+            #[allow(unsafe_code)]
+            unsafe {
+                self.network.new_listener_node_unchecked(&inputs,
+                    |rt: &Runtime, node: &NodeData, _: &dyn Data| rt.unchecked_emit(node, &())
+                )
+            }
+        }
+
+        /// On every event of any of the inputs, sample all inputs, evaluate the provided function,
+        /// and pass the result through. The function contains mutable reference to the network
+        /// model. If you don't need the model, use the `all_withX_` family of functions instead.
+        #[inline(never)]
+        #[allow(non_snake_case)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn [<all_with $i>] < $($ns,)* F, Output>
+        (self, $($ns:$ns,)* f: F) -> NodeInNetwork<'a, M, Stream<Output>>
+        where
+            N1::Output: Default,
+            $($ns: NodeWithDefaultOutput,)*
+            Output: Data,
+            F: 'static + Fn(&mut M, &N1::Output, $(&$ns::Output,)*) -> Output, {
+            self.new_node_with_model((ListenAndSample(self), $(ListenAndSample($ns),)*),
+                move |event, m, t1, $($ns,)*| event.emit(&f(m, t1, $($ns,)*))
+            )
+        }
+
+        /// On every event of any of the inputs, sample all inputs, evaluate the provided function,
+        /// and pass the result through. The function does not contain mutable reference to the
+        /// network model. If you need the model, use the `all_withX` family of functions instead.
+        #[inline(never)]
+        #[allow(non_snake_case)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn [<all_with $i _>] < $($ns,)* F, Output>
+        (self, $($ns:$ns,)* f: F) -> NodeInNetwork<'a, M, Stream<Output>>
+        where
+            N1::Output: Default,
+            $($ns: NodeWithDefaultOutput,)*
+            Output: Data,
+            F: 'static + Fn(&N1::Output, $(&$ns::Output,)*) -> Output, {
+            self.new_node((ListenAndSample(self), $(ListenAndSample($ns),)*),
+                move |event, _, t1, $($ns,)*| event.emit(&f(t1, $($ns,)*))
+            )
+        }
+    }};
+}
+
+macro_rules! def_all_nodes {
+    ($i:tt [$($ns:ident)*]) => { paste! {
+        /// On every event of any of the inputs, sample all inputs and emit their values as a tuple.
+        #[inline(never)]
+        #[allow(non_snake_case)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn [<all $i>] < $($ns,)* > (self, $($ns:$ns,)*)
+        -> NodeInNetwork<'a, M, Stream<(N1::Output, $($ns::Output,)*)>>
+        where
+            N1::Output: Default + Clone,
+            $($ns: NodeWithDefaultOutput, $ns::Output: Clone,)* {
+            self.new_node((ListenAndSample(self), $(ListenAndSample($ns),)*),
+                move |event, _, t1, $($ns,)*| event.emit(&(t1.clone(), $($ns.clone(),)*))
+            )
+        }
+    }};
+}
+
+macro_rules! def_all_vec_nodes {
+    ($i:tt [$($ns:ident)*]) => { paste! {
+        /// On every event of any of the inputs, sample all inputs and emit their values as a
+        /// vector.
+        #[inline(never)]
+        #[allow(non_snake_case)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn [<all_vec $i>] < $($ns,)* > (self, $($ns:$ns,)*)
+        -> NodeInNetwork<'a, M, Stream<Vec<N1::Output>>>
+        where
+            N1::Output: Default + Clone,
+            $($ns: NodeOf<N1::Output>,)* {
+            self.new_node((ListenAndSample(self), $(ListenAndSample($ns),)*),
+                move |event, _, t1, $($ns,)*| event.emit(&vec![t1.clone(), $($ns.clone(),)*])
+            )
+        }
+    }};
+}
+
+macro_rules! def_apply_nodes {
+    ($i:tt [$($ns:ident)*]) => { paste! {
+        #[doc = "Alias for [`Self::all_with" $i "_`], kept for compatibility with `enso_frp`."]
+        #[inline(always)]
+        #[allow(non_snake_case)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn [<apply $i>] < $($ns,)* F, Output>
+        (self, $($ns:$ns,)* f: F) -> NodeInNetwork<'a, M, Stream<Output>>
+        where
+            N1::Output: Default,
+            $($ns: NodeWithDefaultOutput,)*
+            Output: Data,
+            F: 'static + Fn(&N1::Output, $(&$ns::Output,)*) -> Output, {
+            self.[<all_with $i _>]($($ns,)* f)
+        }
+    }};
+}
+
+// The number of inputs is limited by the number of [`ListenAndSample`] ports supported by the node
+// template, and for the `allX` nodes, by the size of tuples implementing [`Debug`]. The
+// `all_vecX` and `applyX` nodes are provided in the same range as in `enso_frp`.
+impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
+    with_input_sequences![def_merge_nodes 2 3 4 5 6 7 8 9 10 11 12 13 14 15];
+    with_input_sequences![def_all_nodes 2 3 4 5 6 7 8 9 10 11 12];
+    with_input_sequences![def_all_vec_nodes 2 3 4 5 6 7 8 9];
+    with_input_sequences![def_apply_nodes 2 3 4 5];
+
+    /// Merge both inputs into a single output. Every event of any of the inputs is passed through.
+    /// See [`Self::any3`] and others to merge more inputs.
+    pub fn any<N2>(self, n2: N2) -> NodeInNetwork<'a, M, Stream<N1::Output>>
+    where N2: NodeOf<N1::Output> {
+        self.any2(n2)
+    }
+
+    /// Merge both inputs into a single output, dropping the incoming data. The inputs can have
+    /// different output types. See [`Self::any3_`] and others to merge more inputs.
+    pub fn any_<N2: Node>(self, n2: N2) -> NodeInNetwork<'a, M, Stream> {
+        self.any2_(n2)
+    }
+
+    /// On every event of any of the inputs, sample both inputs and emit their values as a tuple.
+    /// See [`Self::all3`] and others to combine more inputs.
+    pub fn all<N2>(self, n2: N2) -> NodeInNetwork<'a, M, Stream<(N1::Output, N2::Output)>>
+    where
+        N1::Output: Default + Clone,
+        N2: NodeWithDefaultOutput,
+        N2::Output: Clone, {
+        self.all2(n2)
+    }
+
+    /// Specialization of [`Self::all_with2`] for two inputs.
+    pub fn all_with<N2, F, Output>(self, n2: N2, f: F) -> NodeInNetwork<'a, M, Stream<Output>>
+    where
+        N1::Output: Default,
+        N2: NodeWithDefaultOutput,
+        Output: Data,
+        F: 'static + Fn(&mut M, &N1::Output, &N2::Output) -> Output, {
+        self.all_with2(n2, f)
+    }
+
+    /// Specialization of [`Self::all_with2_`] for two inputs.
+    pub fn all_with_<N2, F, Output>(self, n2: N2, f: F) -> NodeInNetwork<'a, M, Stream<Output>>
+    where
+        N1::Output: Default,
+        N2: NodeWithDefaultOutput,
+        Output: Data,
+        F: 'static + Fn(&N1::Output, &N2::Output) -> Output, {
+        self.all_with2_(n2, f)
+    }
+}
+
+
+// === Any ===
+
+/// Marker type for the [`Any`] node.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ANY;
+
+/// Marker type for the [`Any_`] node.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ANY_;
+
+/// A node merging inputs attached after its creation. See [`Network::any_mut`] to learn more.
+pub type Any<Output = ()> = TypedNode<ANY, Output>;
+
+/// A node merging inputs of any type attached after its creation, dropping the incoming data. See
+/// [`Network::any_mut_`] to learn more.
+pub type Any_ = TypedNode<ANY_, ()>;
+
+/// The maximum number of nested events an [`Any`] node passes through. When a recursive network
+/// exceeds it, the event is dropped and a warning is reported. The limit is the same as the
+/// recursive evaluations limit of `enso_frp` nodes.
+const ANY_MUT_RECURSION_LIMIT: usize = 100;
+
+/// Pass the event through with the provided function, unless the recursion limit was exceeded.
+#[inline(always)]
+fn with_recursion_limit(depth: &Cell<usize>, f: impl FnOnce()) {
+    if depth.get() >= ANY_MUT_RECURSION_LIMIT {
+        warn!("The recursive evaluations limit of an `any_mut` node exceeded. Event dropped.");
+    } else {
+        depth.update(|d| d + 1);
+        f();
+        depth.update(|d| d - 1);
+    }
+}
+
+impl<M: Model> Network<M> {
+    /// Merges multiple inputs into a single output. The inputs are attached after the node is
+    /// created, with [`Any::attach`]. This allows creating recursive FRP networks. Just like in
+    /// `enso_frp`, events reaching this node while it is already passing an event through are
+    /// passed through as well. To prevent infinite loops, the number of nested events is limited
+    /// to 100. Events exceeding the limit are dropped with a warning.
+    pub fn any_mut<T: Data>(&self) -> NodeInNetwork<M, Any<T>> {
+        let depth = Cell::new(0);
+        // # Safety
+        // The attached inputs are checked to have the same output type as this node.
+        #[allow(unsafe_code)]
+        unsafe {
+            self.new_listener_node_unchecked(&[], move |rt: &Runtime, node: &NodeData, data| {
+                with_recursion_limit(&depth, || rt.unchecked_emit(node, data))
+            })
+        }
+    }
+
+    /// Like [`Self::any_mut`], but drops the incoming data. Inputs of any type can be attached.
+    pub fn any_mut_(&self) -> NodeInNetwork<M, Any_> {
+        let depth = Cell::new(0);
+        // # Safety
+        // The incoming data is dropped, and the node emits events of the unit type.
+        #[allow(unsafe_code)]
+        unsafe {
+            self.new_listener_node_unchecked(&[], move |rt: &Runtime, node: &NodeData, _| {
+                with_recursion_limit(&depth, || rt.unchecked_emit(node, &()))
+            })
+        }
+    }
+}
+
+impl<T: Data> Any<T> {
+    /// Pass all events of the given node through this node.
+    pub fn attach(&self, src: impl NodeOf<T>) {
+        with_runtime(|rt| rt.connect(Listen(src).into(), self.id));
+    }
+}
+
+impl Any_ {
+    /// Emit an event whenever the given node emits one.
+    pub fn attach(&self, src: impl Node) {
+        with_runtime(|rt| rt.connect(Listen(src).into(), self.id));
+    }
+}
+
+
+
+// ============
+// === Bool ===
+// ============
+
+impl<'a, M: Model, N1: NodeOf<bool>> NodeInNetwork<'a, M, N1> {
+    /// Whenever the input event is `true`, emit the output event.
+    pub fn on_true(self) -> NodeInNetwork<'a, M, Stream> {
+        self.new_node((Listen(self),), move |event, _, t1| {
+            if *t1 {
+                event.emit(&());
+            }
+        })
+    }
+
+    /// Whenever the input event is `false`, emit the output event.
+    pub fn on_false(self) -> NodeInNetwork<'a, M, Stream> {
+        self.new_node((Listen(self),), move |event, _, t1| {
+            if !*t1 {
+                event.emit(&());
+            }
+        })
+    }
+
+    /// On every input event, output its negation.
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> NodeInNetwork<'a, M, Stream<bool>> {
+        self.map_(|t1| !t1)
+    }
+
+    /// On every event of any of the inputs, sample both inputs and output their `or` value.
+    pub fn or(self, n2: impl NodeOf<bool>) -> NodeInNetwork<'a, M, Stream<bool>> {
+        self.all_with_(n2, |t1, t2| *t1 || *t2)
+    }
+
+    /// On every event of any of the inputs, sample both inputs and output their `and` value.
+    pub fn and(self, n2: impl NodeOf<bool>) -> NodeInNetwork<'a, M, Stream<bool>> {
+        self.all_with_(n2, |t1, t2| *t1 && *t2)
+    }
+
+    /// On every event of any of the inputs, sample both inputs and output `t1 && !t2`.
+    pub fn and_not(self, n2: impl NodeOf<bool>) -> NodeInNetwork<'a, M, Stream<bool>> {
+        self.all_with_(n2, |t1, t2| *t1 && !*t2)
+    }
+
+    /// Redirect the second or the third input to the output when the value of this node is `false`
+    /// or `true` respectively. The redirection is persistent: this node doesn't have to emit to
+    /// propagate the events of the other inputs. Moreover, when this node emits, an output event is
+    /// emitted with the updated value.
+    pub fn switch<N2, N3, T>(self, n2: N2, n3: N3) -> NodeInNetwork<'a, M, Stream<T>>
+    where
+        N2: NodeOf<T>,
+        N3: NodeOf<T>,
+        T: Data + Default + Clone, {
+        self.all_with3_(n2, n3, |t1, t2, t3| if *t1 { t3.clone() } else { t2.clone() })
+    }
+
+    /// On every `false` event, emit the first value. On every `true` event, emit the second one.
+    pub fn switch_constant<T: Data>(
+        self,
+        if_false: T,
+        if_true: T,
+    ) -> NodeInNetwork<'a, M, Stream<T>> {
+        self.new_node((Listen(self),), move |event, _, t1| {
+            event.emit(if *t1 { &if_true } else { &if_false })
+        })
+    }
+
+    /// On every `false` event, emit the default value. On every `true` event, emit the given one.
+    pub fn default_or<T: Data + Default>(self, value: T) -> NodeInNetwork<'a, M, Stream<T>> {
+        self.switch_constant(default(), value)
+    }
+
+    /// On every `true` event, emit the given value wrapped in [`Some`]. Otherwise, emit [`None`].
+    pub fn then_constant<T>(self, value: T) -> NodeInNetwork<'a, M, Stream<Option<T>>>
+    where Option<T>: Data {
+        self.switch_constant(None, Some(value))
+    }
+}
+
+impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
+    /// Replace the events of this node with `false` and events of the given node with `true`.
+    pub fn bool(self, n2: impl Node) -> NodeInNetwork<'a, M, Stream<bool>> {
+        let on_true = NodeInNetwork::new(self.network, n2).to_true();
+        self.to_false().any(on_true)
+    }
+}
+
+
+
+// ==================
+// === Microtasks ===
+// ==================
+
+impl<'a, M: Model, N1: Node> NodeInNetwork<'a, M, N1> {
+    /// Creates a node emitting the values passed to the `schedule` function in the next microtask.
+    /// The `schedule` function is called on every event of this node with its value, and returns
+    /// `true` if the microtask needs to be scheduled.
+    fn in_next_microtask<State, Output>(
+        self,
+        schedule: impl 'static + Fn(&RefCell<State>, &N1::Output) -> bool,
+        take: impl 'static + Fn(&RefCell<State>) -> Option<Output>,
+    ) -> NodeInNetwork<'a, M, Stream<Output>>
+    where
+        State: Default + 'static,
+        Output: Data,
+    {
+        let output: NodeInNetwork<M, Stream<Output>> = self.network.new_node((), |_, _| {});
+        let out = *output;
+        let state: Rc<RefCell<State>> = default();
+        let take = Rc::new(take);
+        let task = RefCell::new(None);
+        let _on_event: NodeInNetwork<M, Stream> =
+            self.new_node((Listen(self),), move |_, _, t1| {
+                if schedule(&state, t1) {
+                    let state = state.clone();
+                    let take = take.clone();
+                    task.replace(Some(next_microtask(move || {
+                        let exists = with_runtime(|rt| rt.node_exists(out.id));
+                        if let Some(value) = take(&state) && exists {
+                            out.emit(&value);
+                        }
+                    })));
+                }
+            });
+        output
+    }
+
+    /// Delay and deduplicate the incoming events. Once an event is received, it will be emitted
+    /// after the current program execution finishes, but before returning to the event loop. If
+    /// multiple events are received within the same microtask, only the last one will be emitted.
+    ///
+    /// Use [`Self::batch`] if you want to collect all emitted values within a microtask.
+    ///
+    /// ```text
+    /// Input:       ───────1──2─3───────────
+    /// Microtasks:  ── ▶──── ▶──── ▶──── ▶──
+    /// Output:      ─────────1─────3────────
+    /// ```
+    ///
+    /// Note: See documentation of [`enso_frp::microtasks`] module for more details about
+    /// microtasks.
+    pub fn debounce(self) -> NodeInNetwork<'a, M, Stream<N1::Output>>
+    where N1::Output: Clone {
+        self.in_next_microtask(
+            |next: &RefCell<Option<N1::Output>>, t1| next.replace(Some(t1.clone())).is_none(),
+            |next| next.take(),
+        )
+    }
+
+    /// Batch all incoming events emitted within a single microtask. The batch will be emitted
+    /// after the current program execution finishes, but before returning to the event loop. All
+    /// emitted batches are guaranteed to be non-empty.
+    ///
+    /// Use [`Self::debounce`] if you want to receive only the latest value emitted within a
+    /// microtask.
+    ///
+    /// ```text
+    /// Input:       ───────1────2─3────────────
+    /// Microtasks:  ── ▶───── ▶───── ▶───── ▶──
+    /// Output:      ──────────[1]────[2,3]─────
+    /// ```
+    pub fn batch(self) -> NodeInNetwork<'a, M, Stream<Vec<N1::Output>>>
+    where N1::Output: Clone {
+        self.in_next_microtask(
+            |batch: &RefCell<Vec<N1::Output>>, t1| {
+                let mut batch = batch.borrow_mut();
+                let schedule = batch.is_empty();
+                batch.push(t1.clone());
+                schedule
+            },
+            |batch| Some(batch.take()).filter(|batch| !batch.is_empty()),
+        )
+    }
+
+    /// Batch unique incoming events emitted within a single microtask. The batch will be emitted
+    /// after the current program execution finishes, but before returning to the event loop. All
+    /// emitted batches are guaranteed to be non-empty. Since the batch is emitted as a hash set,
+    /// the order of incoming events is not preserved.
+    ///
+    /// ```text
+    /// Input:       ───────1───3─2─3───────────
+    /// Microtasks:  ── ▶───── ▶───── ▶───── ▶──
+    /// Output:      ──────────{1}────{2,3}─────
+    /// ```
+    pub fn batch_unique(self) -> NodeInNetwork<'a, M, Stream<HashSet<N1::Output>>>
+    where N1::Output: Clone + Eq + Hash {
+        self.in_next_microtask(
+            |batch: &RefCell<HashSet<N1::Output>>, t1| {
+                let mut batch = batch.borrow_mut();
+                let schedule = batch.is_empty();
+                batch.insert(t1.clone());
+                schedule
+            },
+            |batch| Some(batch.take()).filter(|batch| !batch.is_empty()),
+        )
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::Network_;
+    use enso_frp::microtasks::flush_microtasks;
+
+    #[test]
+    fn test_toggle() {
+        let net = Network_::new();
+        let toggle_src = net.source_();
+        let map_src = net.source_();
+        let toggle = toggle_src.toggle();
+        let (_, results) = net.debug_collect(map_src.map2_(toggle, |_, value| *value));
+        let (_, toggled) = net.debug_collect(toggle);
+        toggle_src.emit(&());
+        map_src.emit(&());
+        toggle_src.emit(&());
+        map_src.emit(&());
+        results.assert_eq(&[true, false]);
+        toggled.assert_eq(&[true, false]);
+    }
+
+    #[test]
+    fn test_toggle_true() {
+        let net = Network_::new();
+        let toggle_src = net.source_();
+        let map_src = net.source_();
+        let toggle = toggle_src.toggle_true();
+        let (_, results) = net.debug_collect(map_src.map2_(toggle, |_, value| *value));
+        map_src.emit(&());
+        toggle_src.emit(&());
+        map_src.emit(&());
+        results.assert_eq(&[true, false]);
+    }
+
+    #[test]
+    fn test_profile() {
+        let net = Network_::new();
+        let src = net.source::<usize>();
+        let (_, results) = net.debug_collect(src.profile("test_profile"));
+        src.emit(&1);
+        src.emit(&2);
+        results.assert_eq(&[1, 2]);
+    }
+
+    #[test]
+    fn test_gate() {
+        let net = Network_::new();
+        let behavior = net.source::<bool>();
+        let some_event = net.source::<usize>();
+        let (_, gated) = net.debug_collect(some_event.gate(behavior));
+        let (_, gated_not) = net.debug_collect(some_event.gate_not(behavior));
+        for (i, value) in [false, true, true].iter().enumerate() {
+            behavior.emit(value);
+            some_event.emit(&i);
+        }
+        gated.assert_eq(&[1, 2]);
+        gated_not.assert_eq(&[0]);
+    }
+
+    #[test]
+    fn test_sampled_gate() {
+        let net = Network_::new();
+        let cond = net.source::<bool>();
+        let src = net.source::<usize>();
+        let (_, results) = net.debug_collect(src.sampled_gate(cond));
+        src.emit(&1);
+        cond.emit(&true);
+        src.emit(&2);
+        cond.emit(&false);
+        src.emit(&3);
+        results.assert_eq(&[1, 2]);
+    }
+
+    #[test]
+    fn test_buffered_gate() {
+        let net = Network_::new();
+        let cond = net.source::<bool>();
+        let src = net.source::<usize>();
+        let (_, results) = net.debug_collect(src.buffered_gate(cond));
+        cond.emit(&true);
+        src.emit(&1);
+        src.emit(&2);
+        cond.emit(&false);
+        src.emit(&3);
+        src.emit(&4);
+        cond.emit(&true);
+        cond.emit(&true);
+        results.assert_eq(&[1, 2, 4]);
+    }
+
+    #[test]
+    fn test_sync_gate() {
+        let net = Network_::new();
+        let sync = net.source_();
+        let src = net.source::<usize>();
+        let (_, results) = net.debug_collect(src.sync_gate(sync));
+        src.emit(&1);
+        sync.emit(&());
+        src.emit(&2);
+        src.emit(&3);
+        sync.emit(&());
+        sync.emit(&());
+        src.emit(&4);
+        results.assert_eq(&[2, 4]);
+    }
+
+    #[test]
+    fn test_filter() {
+        let net = Network_::new();
+        let src = net.source::<bool>();
+        let (_, filtered) = net.debug_collect(src.filter_(|value| *value));
+        let (_, filter_mapped) = net.debug_collect(src.filter_map_(|value| value.as_some(())));
+        for value in [false, true, true, false, false] {
+            src.emit(&value);
+        }
+        filtered.assert_eq(&[true, true]);
+        filter_mapped.assert_eq(&[(), ()]);
+    }
+
+    #[test]
+    fn test_filter_with_model() {
+        let net = Network::new_with_model(3);
+        let src = net.source::<usize>();
+        let filtered = src.filter(|limit, value| {
+            *limit += 1;
+            value < limit
+        });
+        let (_, results) = net.debug_collect(filtered);
+        for value in [5, 5, 5, 5] {
+            src.emit(&value);
+        }
+        results.assert_eq(&[5, 5]);
+    }
+
+    #[test]
+    fn test_any_and_all() {
+        let net = Network_::new();
+        let src1 = net.source::<usize>();
+        let src2 = net.source::<usize>();
+        let src3 = net.source::<String>();
+        let (_, any) = net.debug_collect(src1.any(src2));
+        let (_, any_) = net.debug_collect(src1.any3_(src2, src3));
+        let (_, all) = net.debug_collect(src1.all(src2));
+        let (_, all_with) =
+            net.debug_collect(src1.all_with3_(src2, src3, |a, b, c| a + b + c.len()));
+        src1.emit(&1);
+        src2.emit(&2);
+        src3.emit(&"abc".into());
+        src1.emit(&3);
+        any.assert_eq(&[1, 2, 3]);
+        any_.assert_eq(&[(), (), (), ()]);
+        all.assert_eq(&[(1, 0), (1, 2), (3, 2)]);
+        all_with.assert_eq(&[1, 3, 6, 8]);
+    }
+
+    #[test]
+    fn test_all_vec_and_apply() {
+        let net = Network_::new();
+        let src1 = net.source::<usize>();
+        let src2 = net.source::<usize>();
+        let src3 = net.source::<usize>();
+        let (_, all_vec) = net.debug_collect(src1.all_vec3(src2, src3));
+        let (_, apply) = net.debug_collect(src1.apply3(src2, src3, |a, b, c| a * b + c));
+        src1.emit(&1);
+        src2.emit(&2);
+        src3.emit(&3);
+        all_vec.assert_eq(&[vec![1, 0, 0], vec![1, 2, 0], vec![1, 2, 3]]);
+        apply.assert_eq(&[1, 2, 5]);
+    }
+
+    #[test]
+    fn test_any_mut_cycle() {
+        let net = Network_::new();
+        let src = net.source::<usize>();
+        let any = net.any_mut::<usize>();
+        any.attach(src);
+        let (_, results) = net.debug_collect(any);
+        let next = any.filter_(|value| *value < 3).map_(|value| value + 1);
+        let (_, next_results) = net.debug_collect(next);
+        any.attach(next);
+        src.emit(&1);
+        src.emit(&5);
+        results.assert_eq(&[1, 2, 3, 5]);
+        next_results.assert_eq(&[2, 3]);
+    }
+
+    #[test]
+    fn test_any_mut_recursion_limit() {
+        let net = Network_::new();
+        let src = net.source::<usize>();
+        let any = net.any_mut::<usize>();
+        any.attach(src);
+        let next = any.map_(|value| value + 1);
+        let (_, next_results) = net.debug_collect(next);
+        any.attach(next);
+        src.emit(&0);
+        let expected = (1..=ANY_MUT_RECURSION_LIMIT).collect::<Vec<_>>();
+        next_results.assert_eq(&expected);
+    }
+
+    #[test]
+    fn test_sample_and_previous() {
+        let net = Network_::new();
+        let behavior = net.source::<usize>();
+        let event = net.source_();
+        let (_, sampled) = net.debug_collect(behavior.sample(event));
+        let (_, previous) = net.debug_collect(behavior.previous());
+        event.emit(&());
+        behavior.emit(&1);
+        behavior.emit(&2);
+        event.emit(&());
+        sampled.assert_eq(&[0, 2]);
+        previous.assert_eq(&[0, 1]);
+    }
+
+    #[test]
+    fn test_on_change_and_count() {
+        let net = Network_::new();
+        let src = net.source::<usize>();
+        let (_, changes) = net.debug_collect(src.on_change());
+        let (_, count) = net.debug_collect(src.count());
+        for value in [0, 1, 1, 2, 1] {
+            src.emit(&value);
+        }
+        changes.assert_eq(&[1, 2, 1]);
+        count.assert_eq(&[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_iter_and_fold() {
+        let net = Network_::new();
+        let src = net.source::<Vec<Option<Vec<usize>>>>();
+        let (_, iter) = net.debug_collect(src.iter());
+        let (_, fold) = net.debug_collect(src.fold());
+        src.emit(&vec![Some(vec![1]), None, Some(vec![2, 3])]);
+        iter.assert_eq(&[Some(vec![1]), None, Some(vec![2, 3])]);
+        fold.assert_eq(&[Some(vec![1, 2, 3])]);
+    }
+
+    #[test]
+    fn test_fields_and_options() {
+        let net = Network_::new();
+        let src = net.source::<(usize, Option<usize>)>();
+        let field = src._1();
+        let (_, first) = net.debug_collect(src._0());
+        let (_, unwrapped) = net.debug_collect(field.unwrap());
+        let (_, mapped) = net.debug_collect(field.map_some_(|value| value * 2));
+        src.emit(&(1, None));
+        src.emit(&(2, Some(3)));
+        first.assert_eq(&[1, 2]);
+        unwrapped.assert_eq(&[3]);
+        mapped.assert_eq(&[None, Some(6)]);
+    }
+
+    #[test]
+    fn test_bool() {
+        let net = Network_::new();
+        let src1 = net.source::<bool>();
+        let src2 = net.source::<bool>();
+        let (_, and) = net.debug_collect(src1.and(src2));
+        let (_, on_true) = net.debug_collect(src1.on_true());
+        let (_, bool) = net.debug_collect(src1.bool(src2));
+        let (_, switch) = net.debug_collect(src1.switch(src1.constant(1), src2.constant(2)));
+        src1.emit(&true);
+        src2.emit(&true);
+        src1.emit(&false);
+        and.assert_eq(&[false, true, false]);
+        on_true.assert_eq(&[()]);
+        bool.assert_eq(&[false, true, false]);
+        // The condition emits to the switch both directly and through the constant node.
+        switch.assert_eq(&[0, 0, 2, 1, 1]);
+    }
+
+    #[test]
+    fn test_debounce_and_batch() {
+        let net = Network_::new();
+        let src = net.source::<usize>();
+        let (_, debounced) = net.debug_collect(src.debounce());
+        let (_, batched) = net.debug_collect(src.batch());
+        src.emit(&1);
+        src.emit(&2);
+        debounced.assert_eq(&[]);
+        flush_microtasks();
+        src.emit(&3);
+        flush_microtasks();
+        flush_microtasks();
+        debounced.assert_eq(&[2, 3]);
+        batched.assert_eq(&[vec![1, 2], vec![3]]);
+    }
+
+    #[test]
+    fn test_debounce_after_network_drop() {
+        let net = Network_::new();
+        let src = net.source::<usize>();
+        let (_, debounced) = net.debug_collect(src.debounce());
+        src.emit(&1);
+        drop(net);
+        flush_microtasks();
+        debounced.assert_eq(&[]);
+    }
+}
@@ -1,4 +1,6 @@
 //! The FRP runtime, a singleton containing all node and network data.
+//!
+//! The runtime can propagate events in one of two modes, see [`PropagationMode`] to learn more.
 
 use crate::prelude::*;
 use enso_generics::traits::*;
@@ -12,6 +14,7 @@ use crate::node::input;
 use enso_data_structures::unrolled_linked_list::UnrolledLinkedList;
 use enso_data_structures::unrolled_slot_map::UnrolledSlotMap;
 use enso_data_structures::unrolled_slot_map::VersionedIndex;
+use std::collections::BTreeMap;
 
 
 
@@ -27,9 +30,13 @@ pub(crate) trait EventConsumer = Fn(&Runtime, &NodeData, &dyn Data) + 'static;
 #[derive(Clone, Copy, Debug, Default, Zeroable)]
 #[allow(missing_docs)]
 pub struct Edge {
-    pub target:     NodeId,
+    pub target:      NodeId,
     /// Determines whether the edge is connected to a sample port.
-    pub is_sampler: bool,
+    pub is_sampler:  bool,
+    /// Determines whether the edge closes a cycle in the network. Such edges are ignored when
+    /// ranking nodes, see [`NodeData::rank`]. Not computed for edges created in the
+    /// [`PropagationMode::Eager`] mode until the next transaction begins.
+    pub is_feedback: bool,
 }
 
 
@@ -121,6 +128,14 @@ def_node! {
             /// [`ListenerAndSampler`] connections in [`Self::outputs`] and the count of all
             /// connections in [`Self::sampler_outputs`].
             sampler_count: Cell<usize>,
+            /// The topological rank of the node. Every node is ranked higher than all of its
+            /// inputs, except the ones connected with feedback edges. Used by the
+            /// [`PropagationMode::Transactional`] mode to evaluate nodes in topological order.
+            rank: Cell<usize>,
+            /// The last event scheduled for this node in the current transaction.
+            pending_event: ZeroOverheadRefCell<ZeroableOption<Box<dyn Data>>>,
+            /// The id of the last transaction this node was evaluated in.
+            evaluated_in: Cell<usize>,
         }
     }
 }
@@ -158,6 +173,104 @@ impl Clearable for NetworkData {
 
 
 
+// =======================
+// === PropagationMode ===
+// =======================
+
+/// Determines how events are propagated through FRP networks.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PropagationMode {
+    /// A node evaluates as soon as it receives an event, and its output events are propagated
+    /// depth-first. See the "Imperative FRP evaluation order" section of the crate docs. In
+    /// diamond-shaped networks, nodes can observe intermediate inconsistent states.
+    #[default]
+    Eager,
+    /// All events emitted in response to a single source event are batched in a transaction.
+    /// Within a transaction, nodes are evaluated in topological order, after all of their inputs
+    /// were updated, and every node is evaluated at most once. If a node receives many events,
+    /// only the last one is passed to it. If a node receives an event after it was already
+    /// evaluated (which is possible only in cyclic networks), the event is passed to it in a
+    /// follow-up transaction. Scheduling an event requires cloning it, so this mode is slower
+    /// than the [`Self::Eager`] one.
+    Transactional,
+}
+
+/// The propagation mode of the current thread's FRP runtime.
+pub fn propagation_mode() -> PropagationMode {
+    with_runtime(|rt| rt.propagation_mode.get())
+}
+
+/// Set the propagation mode of the current thread's FRP runtime. If changed during an event
+/// propagation, it will affect the next emitted source event.
+pub fn set_propagation_mode(mode: PropagationMode) {
+    with_runtime(|rt| rt.propagation_mode.set(mode))
+}
+
+
+
+// ===================
+// === Transaction ===
+// ===================
+
+/// The state of the [`PropagationMode::Transactional`] propagation.
+#[derive(Debug, Default)]
+struct Transaction {
+    is_running: Cell<bool>,
+    /// The id of the current (or the last, if none is running) transaction.
+    id:         Cell<usize>,
+    /// Nodes scheduled for evaluation, ordered by their rank. The second key element is a
+    /// sequence number that keeps the scheduling order of equally ranked nodes.
+    queue:      RefCell<BTreeMap<(usize, usize), NodeId>>,
+    next_seq:   Cell<usize>,
+    /// Events for nodes that were already evaluated in the current transaction. They will be
+    /// passed in the follow-up transaction.
+    deferred:   RefCell<Vec<(NodeId, Box<dyn Data>)>>,
+}
+
+impl Transaction {
+    #[inline(always)]
+    fn begin(&self) {
+        self.id.set(self.id.get() + 1);
+    }
+}
+
+
+
+// ========================
+// === TransactionGuard ===
+// ========================
+
+/// Marks the transaction as running for its lifetime. When dropped, discards all events that were
+/// not passed to nodes, which happens only if a node callback panicked. Thanks to that, the runtime
+/// stays usable after the panic.
+#[derive(Debug)]
+struct TransactionGuard<'a> {
+    runtime: &'a Runtime,
+}
+
+impl<'a> TransactionGuard<'a> {
+    fn new(runtime: &'a Runtime) -> Self {
+        runtime.transaction.is_running.set(true);
+        Self { runtime }
+    }
+}
+
+impl<'a> Drop for TransactionGuard<'a> {
+    fn drop(&mut self) {
+        let transaction = &self.runtime.transaction;
+        let queue = mem::take(&mut *transaction.queue.borrow_mut());
+        for node_id in queue.into_values() {
+            if let Some(node) = self.runtime.nodes.get(node_id) {
+                node.borrow().pending_event.replace(ZeroableOption::None);
+            }
+        }
+        transaction.deferred.borrow_mut().clear();
+        transaction.is_running.set(false);
+    }
+}
+
+
+
 // ===============
 // === Runtime ===
 // ===============
@@ -210,8 +323,14 @@ pub struct Runtime {
         NODE,
         prealloc::Zeroed,
     >,
-    metrics:  metrics::Metrics,
-    stack:    CallStack,
+    metrics:          metrics::Metrics,
+    stack:            CallStack,
+    propagation_mode: Cell<PropagationMode>,
+    transaction:      Transaction,
+    /// Node ranks are used only in the [`PropagationMode::Transactional`] mode, so they are not
+    /// updated when nodes are connected in the [`PropagationMode::Eager`] mode. Instead, they are
+    /// marked as outdated and recomputed before the next transaction begins.
+    ranks_outdated:   Cell<bool>,
 }
 
 impl Runtime {
@@ -242,6 +361,12 @@ impl Runtime {
         }
     }
 
+    /// Check whether the node exists, i.e. whether its network was not dropped.
+    #[inline(always)]
+    pub(crate) fn node_exists(&self, id: NodeId) -> bool {
+        self.nodes.exists(id)
+    }
+
     #[inline(always)]
     fn with_borrowed_node(&self, node_id: NodeId, f: impl FnOnce(&NodeData)) {
         if let Some(node) = self.nodes.get(node_id) {
@@ -281,7 +406,8 @@ impl Runtime {
     pub(crate) fn connect(&self, src_tp: input::Type, tgt_id: NodeId) {
         let src_id = src_tp.node_id();
         let is_sampler = src_tp.is_sampler();
-        let output_connection = Edge { target: tgt_id, is_sampler };
+        let is_feedback = self.rank_connection_if_needed(src_id, tgt_id);
+        let output_connection = Edge { target: tgt_id, is_sampler, is_feedback };
         self.with_borrowed_node(src_id, |src| {
             if is_sampler {
                 src.sampler_count.modify(|t| *t += 1);
@@ -294,6 +420,158 @@ impl Runtime {
         });
     }
 
+    #[inline(always)]
+    fn node_rank(&self, id: NodeId) -> Option<usize> {
+        self.nodes.get(id).map(|node| node.borrow().rank.get())
+    }
+
+    /// Update the ranks of nodes before connecting the source node to the target one if the
+    /// [`PropagationMode::Transactional`] mode is used. Otherwise, mark the ranks as outdated, so
+    /// that the eager mode does not pay for the ranking. Returns `true` if the connection would
+    /// close a cycle.
+    #[inline(always)]
+    fn rank_connection_if_needed(&self, src_id: NodeId, tgt_id: NodeId) -> bool {
+        let is_transactional = self.propagation_mode.get() == PropagationMode::Transactional;
+        if is_transactional && !self.ranks_outdated.get() {
+            self.rank_connection(src_id, tgt_id)
+        } else {
+            self.ranks_outdated.set(true);
+            false
+        }
+    }
+
+    /// Update the ranks of nodes before connecting the source node to the target one, so that the
+    /// target node and all nodes depending on it are ranked higher than the source node. Returns
+    /// `true` if the connection would close a cycle, in which case ranks are not changed.
+    fn rank_connection(&self, src_id: NodeId, tgt_id: NodeId) -> bool {
+        let ranks = (self.node_rank(src_id), self.node_rank(tgt_id));
+        let (Some(src_rank), Some(tgt_rank)) = ranks else { return false };
+        if tgt_rank > src_rank {
+            false
+        } else if self.reaches(tgt_id, src_id, src_rank) {
+            true
+        } else {
+            let mut to_raise = vec![(tgt_id, src_rank + 1)];
+            while let Some((id, rank)) = to_raise.pop() {
+                if let Some(node) = self.nodes.get(id) {
+                    let node = node.borrow();
+                    if node.rank.get() < rank {
+                        node.rank.set(rank);
+                        let outputs = node.outputs.borrow();
+                        let sampler_outputs = node.sampler_outputs.borrow();
+                        for edge in outputs.iter().chain(sampler_outputs.iter()) {
+                            if !edge.is_feedback {
+                                to_raise.push((edge.target, rank + 1));
+                            }
+                        }
+                    }
+                }
+            }
+            false
+        }
+    }
+
+    /// Check whether the target node can be reached from the source node through non-feedback
+    /// edges. Nodes ranked higher than `max_rank` are skipped, as they can't reach the target.
+    fn reaches(&self, src_id: NodeId, tgt_id: NodeId, max_rank: usize) -> bool {
+        let mut visited = HashSet::new();
+        let mut to_visit = vec![src_id];
+        while let Some(id) = to_visit.pop() {
+            if id == tgt_id {
+                return true;
+            }
+            if !visited.insert(id) {
+                continue;
+            }
+            if let Some(node) = self.nodes.get(id) {
+                let node = node.borrow();
+                let outputs = node.outputs.borrow();
+                let sampler_outputs = node.sampler_outputs.borrow();
+                for edge in outputs.iter().chain(sampler_outputs.iter()) {
+                    let in_range = || self.node_rank(edge.target).map_or(false, |r| r <= max_rank);
+                    if !edge.is_feedback && in_range() {
+                        to_visit.push(edge.target);
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// The targets of all outgoing edges of the given node. Each target is accompanied by the
+    /// location of its edge: whether it is stored in [`NodeData::sampler_outputs`], and its index.
+    fn output_edges(&self, id: NodeId) -> Vec<(bool, usize, Edge)> {
+        let mut edges = vec![];
+        if let Some(node) = self.nodes.get(id) {
+            let node = node.borrow();
+            let outputs = node.outputs.borrow();
+            let sampler_outputs = node.sampler_outputs.borrow();
+            edges.extend(outputs.iter().enumerate().map(|(index, edge)| (false, index, *edge)));
+            let sampler_edges = sampler_outputs.iter().enumerate();
+            edges.extend(sampler_edges.map(|(index, edge)| (true, index, *edge)));
+        }
+        edges
+    }
+
+    /// Rank all nodes from scratch. Edges closing cycles are found with a depth-first search and
+    /// marked as feedback edges. Then, nodes are ranked in the topological order of the remaining
+    /// edges.
+    #[inline(never)]
+    fn rank_all_nodes(&self) {
+        let mut node_ids = vec![];
+        for network in &self.networks {
+            node_ids.extend(network.borrow().nodes.iter().copied());
+        }
+        let mut visited = HashSet::new();
+        let mut on_stack = HashSet::new();
+        let mut post_order = vec![];
+        for root in node_ids {
+            if !self.nodes.exists(root) || !visited.insert(root) {
+                continue;
+            }
+            on_stack.insert(root);
+            let mut stack = vec![(root, self.output_edges(root).into_iter())];
+            while let Some((id, edges)) = stack.last_mut() {
+                let id = *id;
+                if let Some((is_sampler_output, index, edge)) = edges.next() {
+                    let target = edge.target;
+                    let is_feedback = on_stack.contains(&target);
+                    if edge.is_feedback != is_feedback {
+                        self.with_borrowed_node(id, |node| {
+                            let outputs = if is_sampler_output {
+                                &node.sampler_outputs
+                            } else {
+                                &node.outputs
+                            };
+                            outputs.borrow_mut()[index].is_feedback = is_feedback;
+                        });
+                    }
+                    if self.nodes.exists(target) && visited.insert(target) {
+                        on_stack.insert(target);
+                        stack.push((target, self.output_edges(target).into_iter()));
+                    }
+                } else {
+                    on_stack.remove(&id);
+                    post_order.push(id);
+                    stack.pop();
+                }
+            }
+        }
+        for &id in &post_order {
+            self.with_borrowed_node(id, |node| node.rank.set(0));
+        }
+        for &id in post_order.iter().rev() {
+            if let Some(rank) = self.node_rank(id) {
+                for (_, _, edge) in self.output_edges(id) {
+                    if let Some(target) = self.nodes.get(edge.target) && !edge.is_feedback {
+                        let target = target.borrow();
+                        target.rank.set(target.rank.get().max(rank + 1));
+                    }
+                }
+            }
+        }
+    }
+
     /// Emit the event to all listeners of the given node. The event type is not checked, so you
     /// have to guarantee that the type is correct.
     ///
@@ -312,6 +590,42 @@ impl Runtime {
     /// have to guarantee that the type is correct.
     #[inline(always)]
     pub(crate) fn unchecked_emit(&self, src_node: &NodeData, event: &dyn Data) {
+        let is_eager = self.propagation_mode.get() == PropagationMode::Eager;
+        if is_eager && !self.transaction.is_running.get() {
+            self.unchecked_emit_to_outputs(src_node, event, |_, tgt_node| {
+                tgt_node.on_event(self, event);
+            });
+        } else {
+            self.unchecked_emit_transactional(src_node, event);
+        }
+    }
+
+    /// Schedule all listeners of the given node for evaluation in the current transaction. If no
+    /// transaction is running, a new one is started. Kept out of line, so it does not slow down
+    /// the eager mode.
+    #[inline(never)]
+    fn unchecked_emit_transactional(&self, src_node: &NodeData, event: &dyn Data) {
+        let schedule_outputs = || {
+            self.unchecked_emit_to_outputs(src_node, event, |tgt_id, tgt_node| {
+                self.schedule(tgt_id, tgt_node, event.boxed_clone());
+            });
+        };
+        if self.transaction.is_running.get() {
+            schedule_outputs();
+        } else {
+            self.run_transaction(schedule_outputs);
+        }
+    }
+
+    /// Pass the event to all existing listeners of the given node with the provided function, and
+    /// remove the connections to dropped nodes.
+    #[inline(always)]
+    fn unchecked_emit_to_outputs(
+        &self,
+        src_node: &NodeData,
+        event: &dyn Data,
+        on_target: impl Fn(NodeId, &NodeData),
+    ) {
         // Clone the incoming data if there are any sampler outputs.
         if src_node.sampler_count.get() > 0 {
             src_node.output_cache.replace(ZeroableOption::Some(event.boxed_clone()));
@@ -322,7 +636,7 @@ impl Runtime {
             let mut cleanup_sampler_outputs = false;
             for &output in &*src_node.outputs.borrow() {
                 let target = self.nodes.get(output.target);
-                if target.map(|tgt_node| tgt_node.borrow().on_event(self, event)).is_none() {
+                if target.map(|tgt_node| on_target(output.target, &tgt_node.borrow())).is_none() {
                     cleanup_outputs = true;
                 }
             }
@@ -349,6 +663,58 @@ impl Runtime {
         });
     }
 
+    /// Run the provided function in a new transaction and evaluate all nodes scheduled by it. See
+    /// [`PropagationMode::Transactional`] to learn more.
+    fn run_transaction(&self, f: impl FnOnce()) {
+        if self.ranks_outdated.replace(false) {
+            self.rank_all_nodes();
+        }
+        let _guard = TransactionGuard::new(self);
+        self.transaction.begin();
+        f();
+        loop {
+            let next = self.transaction.queue.borrow_mut().pop_first();
+            if let Some((_, node_id)) = next {
+                self.evaluate_scheduled(node_id);
+            } else {
+                let deferred = mem::take(&mut *self.transaction.deferred.borrow_mut());
+                if deferred.is_empty() {
+                    break;
+                }
+                self.transaction.begin();
+                for (node_id, event) in deferred {
+                    if let Some(node) = self.nodes.get(node_id) {
+                        self.schedule(node_id, &node.borrow(), event);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Schedule the node for evaluation in the current transaction. If the node is already
+    /// scheduled, the previously scheduled event is replaced.
+    #[inline(always)]
+    fn schedule(&self, node_id: NodeId, node: &NodeData, event: Box<dyn Data>) {
+        if node.evaluated_in.get() == self.transaction.id.get() {
+            self.transaction.deferred.borrow_mut().push((node_id, event));
+        } else if node.pending_event.replace(ZeroableOption::Some(event)).is_none() {
+            let seq = self.transaction.next_seq.get();
+            self.transaction.next_seq.set(seq + 1);
+            self.transaction.queue.borrow_mut().insert((node.rank.get(), seq), node_id);
+        }
+    }
+
+    #[inline(always)]
+    fn evaluate_scheduled(&self, node_id: NodeId) {
+        if let Some(node) = self.nodes.get(node_id) {
+            let node = node.borrow();
+            if let ZeroableOption::Some(event) = node.pending_event.replace(ZeroableOption::None) {
+                node.evaluated_in.set(self.transaction.id.get());
+                node.on_event(self, &*event);
+            }
+        }
+    }
+
     /// Perform the provided function with the borrowed output cache of the given node. If the node
     /// did not store its cache, a default value will be used and an error will be emitted.
     ///
@@ -0,0 +1,231 @@
+//! Producing [JSON Schema](https://json-schema.org/) (draft 2020-12) descriptions of data based on
+//! reflection information.
+//!
+//! The schema describes the JSON representation `serde_json` produces for values of the type: This
+//! is the representation derived by `#[derive(Serialize)]` without any container attributes. The
+//! [`rust`] model is used, rather than the abstracted `meta` model, because reflection attributes
+//! such as `#[reflect(flatten)]` and `#[reflect(transparent)]` affect only abstracted models; they
+//! have no effect on the JSON representation.
+//!
+//! - A struct with named fields is an object with a property for each field.
+//! - A tuple struct is an array of its fields; a newtype struct is represented as its field, and a
+//!   unit struct as `null`.
+//! - An enum is externally tagged: A unit variant is represented as its name; any other variant is
+//!   an object with a single property, named after the variant, containing the variant's fields.
+//! - `Option`s are nullable; `Vec`s are arrays; `Result`s are objects with an `Ok` or `Err`
+//!   property.
+//! - A field with a customized serialization is described by its `#[reflect(as)]` type. A `char` is
+//!   described as a 32-bit code point: Reflection-based binary formats represent a `char` that way,
+//!   so a `char` field must have a serialization customized to match.
+//!
+//! Each struct or enum is defined in `$defs`, and referred to with `$ref`. Types are named after
+//! their Rust identifiers; if different instantiations of a generic type are present, their names
+//! are qualified with the type of their `#[reflect(subtype)]` field, or with a number.
+//!
+//! # Example
+//!
+//! ```
+//! # use enso_reflect::prelude::*;
+//! # use serde::Serialize;
+//! use enso_reflect::Reflect;
+//! use serde_json::json;
+//!
+//! #[derive(Serialize, Reflect)]
+//! struct Point {
+//!     x:     u32,
+//!     label: Option<String>,
+//! }
+//! let schema = enso_metamodel_json_schema::schema(Point::reflect());
+//! assert_eq!(schema["$ref"], "#/$defs/Point");
+//! let point = &schema["$defs"]["Point"];
+//! assert_eq!(point["required"], json!(["x", "label"]));
+//! let label = json!({ "anyOf": [{ "type": "string" }, { "type": "null" }] });
+//! assert_eq!(point["properties"]["label"], label);
+//! ```
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_metamodel::rust::*;
+
+use serde_json::json;
+use serde_json::Map;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The JSON Schema dialect of the generated schemas.
+pub const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+
+
+// ===========================
+// === Rust to JSON Schema ===
+// ===========================
+
+/// Produce a JSON Schema describing the JSON representation of the given type.
+pub fn schema(ty: TypeData) -> Value {
+    let mut schema = JsonSchema::default();
+    let root = schema.type_(ty);
+    let mut out = Map::new();
+    out.insert("$schema".to_owned(), DIALECT.into());
+    out.extend(root.as_object().unwrap().clone());
+    let defs = schema.defs.into_iter().map(|(id, def)| (schema.names[&id].clone(), def)).collect();
+    out.insert("$defs".to_owned(), Value::Object(defs));
+    Value::Object(out)
+}
+
+#[derive(Debug, Default)]
+struct JsonSchema {
+    /// The name of each struct or enum type's definition.
+    names: BTreeMap<TypeId, String>,
+    /// The definitions; a type's entry is present while its definition is being built, to support
+    /// recursive types.
+    defs:  BTreeMap<TypeId, Value>,
+    /// The names that have been assigned to definitions.
+    taken: BTreeSet<String>,
+}
+
+impl JsonSchema {
+    /// Return a schema for values of the type: A reference to its definition, if it is a struct or
+    /// enum; otherwise, the schema of the primitive.
+    fn type_(&mut self, ty: TypeData) -> Value {
+        let id = ty.id;
+        if let Data::Primitive(primitive) = &ty.data {
+            return self.primitive(primitive);
+        }
+        if !self.defs.contains_key(&id) {
+            let name = self.name(&ty);
+            self.names.insert(id, name);
+            self.defs.insert(id, Value::Null);
+            let def = match &ty.data {
+                Data::Struct(struct_) => self.fields(&struct_.fields),
+                Data::Enum(enum_) => self.enum_(enum_),
+                Data::Primitive(_) => unreachable!(),
+            };
+            self.defs.insert(id, def);
+        }
+        json!({ "$ref": format!("#/$defs/{}", self.names[&id]) })
+    }
+
+    /// Choose a unique name for a struct or enum type's definition.
+    fn name(&mut self, ty: &TypeData) -> String {
+        let mut name = ty.name.clone();
+        if self.taken.contains(&name) {
+            if let Data::Struct(Struct { fields: Fields::Named(fields), .. }) = &ty.data {
+                if let Some(field) = fields.iter().find(|field| field.subtype) {
+                    name.push_str(&field.type_.evaluate().name);
+                }
+            }
+        }
+        let base = name.clone();
+        let mut i = 1;
+        while self.taken.contains(&name) {
+            i += 1;
+            name = format!("{base}{i}");
+        }
+        self.taken.insert(name.clone());
+        name
+    }
+
+    fn fields(&mut self, fields: &Fields) -> Value {
+        match fields {
+            Fields::Named(fields) => {
+                let mut properties = Map::new();
+                let mut required = vec![];
+                for field in fields {
+                    let name = field.name.trim_start_matches("r#").to_owned();
+                    properties.insert(name.clone(), self.type_(field.type_.evaluate()));
+                    required.push(Value::String(name));
+                }
+                object(properties, required)
+            }
+            Fields::Unnamed(fields) if fields.len() == 1 => self.type_(fields[0].type_.evaluate()),
+            Fields::Unnamed(fields) => {
+                let items: Vec<_> =
+                    fields.iter().map(|field| self.type_(field.type_.evaluate())).collect();
+                let len = items.len();
+                json!({
+                    "type": "array",
+                    "prefixItems": items,
+                    "items": false,
+                    "minItems": len,
+                })
+            }
+            Fields::Unit => json!({ "type": "null" }),
+        }
+    }
+
+    fn enum_(&mut self, enum_: &Enum) -> Value {
+        let variants: Vec<_> = enum_
+            .variants
+            .iter()
+            .map(|variant| match &variant.fields {
+                Fields::Unit => json!({ "const": variant.ident }),
+                fields => {
+                    let fields = self.fields(fields);
+                    tagged(&variant.ident, fields)
+                }
+            })
+            .collect();
+        json!({ "oneOf": variants })
+    }
+
+    fn primitive(&mut self, primitive: &Primitive) -> Value {
+        match primitive {
+            Primitive::Bool => json!({ "type": "boolean" }),
+            Primitive::Usize => json!({ "type": "integer", "minimum": 0 }),
+            Primitive::U32 => json!({ "type": "integer", "minimum": 0, "maximum": u32::MAX }),
+            Primitive::I32 =>
+                json!({ "type": "integer", "minimum": i32::MIN, "maximum": i32::MAX }),
+            Primitive::Char => json!({ "type": "integer", "minimum": 0, "maximum": u32::MAX }),
+            Primitive::String => json!({ "type": "string" }),
+            Primitive::Vec(ty) => json!({ "type": "array", "items": self.type_(ty.evaluate()) }),
+            Primitive::Option(ty) =>
+                json!({ "anyOf": [self.type_(ty.evaluate()), { "type": "null" }] }),
+            Primitive::Result(ok, err) => {
+                let ok = tagged("Ok", self.type_(ok.evaluate()));
+                let err = tagged("Err", self.type_(err.evaluate()));
+                json!({ "oneOf": [ok, err] })
+            }
+        }
+    }
+}
+
+/// A closed object schema, in which all the given properties are required.
+fn object(properties: Map<String, Value>, required: Vec<Value>) -> Value {
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// The externally-tagged representation of a value.
+fn tagged(tag: &str, value: Value) -> Value {
+    let mut properties = Map::new();
+    properties.insert(tag.to_owned(), value);
+    object(properties, vec![tag.into()])
+}
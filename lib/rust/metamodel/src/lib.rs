@@ -9,6 +9,9 @@
 //! - [`java`]: A metamodel representing data models in the Java typesystem.
 //! - [`meta`]: An abstract metamodel, used to perform language-independent analysis of data models,
 //!   and as an intermediate when translating data models between language-specific metamodels.
+//! - [`python`]: Generation of Python dataclasses, with a decoder for a binary format.
+//! - [`typescript`]: Generation of TypeScript types that lazily deserialize a data model from a
+//!   binary format.
 //!
 //! Some accessory modules:
 //! - [`graphviz`]: Support for rendering graphical representations of data models with GraphViz.
@@ -27,6 +30,10 @@
 //! Other use cases supported include:
 //! - Analyze a data model's serialization to generate exhaustive test cases, using
 //!   [`meta::serialization::testcases`].
+//! - Generate TypeScript code implementing lazy deserialization of a data model, using
+//!   [`typescript::to_syntax`]; the test cases can be obtained in the format it reads with
+//!   [`meta::format::testcases`].
+//! - Generate Python dataclasses and a decoder for the same format, using [`python::implement`].
 //! - Produce graphs of type relationships, using [graphviz::Graph]`
 //!
 //! # Design
@@ -72,5 +79,9 @@ pub mod graphviz;
 #[cfg(feature = "java")]
 pub mod java;
 pub mod meta;
+#[cfg(feature = "python")]
+pub mod python;
 #[cfg(feature = "rust")]
 pub mod rust;
+#[cfg(feature = "typescript")]
+pub mod typescript;
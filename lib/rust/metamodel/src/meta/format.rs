@@ -0,0 +1,409 @@
+//! The lazily-deserializable binary format produced by `enso_parser::format`: layout analysis, and
+//! translation of test cases.
+//!
+//! # Format
+//!
+//! In order to support lazy deserialization, fields of each object are located at fixed offsets
+//! from the object; variable-sized data is stored out of band, with a reference (a 32-bit address)
+//! in the owning object identifying the location of the data:
+//! - Scalar primitives are stored inline, in little-endian byte order: A `Bool` is one byte; `U32`,
+//!   `I32`, and `Char` values are 4 bytes; `U64` and `I64` values are 8 bytes.
+//! - A `String` or `Sequence` is a reference to an object consisting of a 32-bit element count, and
+//!   the elements.
+//! - An `Option` is a 1-byte discriminant (0 or 1), followed by a reference to the value if it is
+//!   present.
+//! - A `Result` is a reference to an object consisting of a 32-bit discriminant, and the value.
+//! - A concrete type is stored inline, as the fields of its ancestors, followed by its own fields.
+//! - A type with [`Type::discriminants`] is a reference to an object consisting of a 32-bit
+//!   discriminant, the fields of the type (and of its ancestors), and the child's own fields.
+//!
+//! The data of a serialized value is followed by the value's inline representation, so a buffer
+//! containing a value of a type with discriminants ends with a reference to the root object.
+
+use crate::meta::*;
+
+use crate::meta::serialization::TestCases;
+use std::collections::BTreeMap;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The size of a reference to out-of-band data.
+pub const POINTER: usize = 4;
+
+/// The reference stored for an absent `Option` value.
+const NONE: u32 = 0xcdcdcdcd;
+
+
+
+// ==============
+// === Layout ===
+// ==============
+
+/// The sizes and field offsets of the types in a [`TypeGraph`].
+#[derive(Debug, Clone, Default)]
+pub struct Layout {
+    /// The size of each type, when it is stored inline in another object.
+    sizes:   BTreeMap<TypeId, usize>,
+    /// The size of the fields of each struct type, including the fields of its ancestors.
+    data:    BTreeMap<TypeId, usize>,
+    /// The offsets of the own fields of each struct type.
+    offsets: BTreeMap<TypeId, Vec<usize>>,
+}
+
+impl Layout {
+    /// Compute the layout of all types in the graph.
+    ///
+    /// Panics if the graph contains a type that cannot be represented in the format: A type with
+    /// discriminants must not have any fields following the child data, and a type with
+    /// discriminants nested in another such type must not inherit any fields.
+    pub fn new(graph: &TypeGraph) -> Self {
+        let mut layout = Self::default();
+        for (id, ty) in graph.types.iter() {
+            layout.size(graph, id);
+            if ty.discriminants.is_empty() {
+                continue;
+            }
+            let fields = ty.data.fields().unwrap_or_default();
+            let child_last = "Child data must follow all other fields in the lazy format.";
+            assert!(fields.is_empty() || ty.child_field == Some(fields.len()), "{child_last}");
+            if let Some(parent) = ty.parent {
+                let nested = "Nested abstract types cannot inherit fields in the lazy format.";
+                assert_eq!(layout.data_size(graph, parent), 0, "{nested}");
+            }
+        }
+        layout
+    }
+
+    /// Return the number of bytes the type occupies as a field of another object, or an element of
+    /// a sequence.
+    pub fn inline_size(&self, id: TypeId) -> usize {
+        self.sizes[&id]
+    }
+
+    /// Return the offsets of a struct type's own fields (not including inherited fields), relative
+    /// to the start of its data.
+    pub fn field_offsets(&self, id: TypeId) -> &[usize] {
+        &self.offsets[&id]
+    }
+
+    fn size(&mut self, graph: &TypeGraph, id: TypeId) -> usize {
+        if let Some(size) = self.sizes.get(&id) {
+            return *size;
+        }
+        let ty = &graph[id];
+        let size = match &ty.data {
+            Data::Primitive(Primitive::Bool) => 1,
+            Data::Primitive(Primitive::U32 | Primitive::I32 | Primitive::Char) => 4,
+            Data::Primitive(Primitive::U64 | Primitive::I64) => 8,
+            Data::Primitive(Primitive::Option(_)) => 1 + POINTER,
+            Data::Primitive(Primitive::String | Primitive::Sequence(_) | Primitive::Result(..)) =>
+                POINTER,
+            Data::Struct(_) if !ty.discriminants.is_empty() => {
+                self.data_size(graph, id);
+                POINTER
+            }
+            Data::Struct(_) => self.data_size(graph, id),
+        };
+        self.sizes.insert(id, size);
+        size
+    }
+
+    fn data_size(&mut self, graph: &TypeGraph, id: TypeId) -> usize {
+        if let Some(size) = self.data.get(&id) {
+            return *size;
+        }
+        let ty = &graph[id];
+        let mut offset = ty.parent.map_or(0, |parent| self.data_size(graph, parent));
+        let fields = ty.data.fields().unwrap_or_default();
+        let mut offsets = Vec::with_capacity(fields.len());
+        for field in fields {
+            offsets.push(offset);
+            offset += self.size(graph, field.type_);
+        }
+        self.offsets.insert(id, offsets);
+        self.data.insert(id, offset);
+        offset
+    }
+}
+
+
+
+// ==================
+// === Test Cases ===
+// ==================
+
+/// Generate test cases in this format, covering all types reachable from the root type. The cases
+/// are those produced by [`serialization::testcases`], translated with [`transcode`].
+pub fn testcases(graph: &TypeGraph, root: TypeId) -> TestCases {
+    let mut cases = serialization::testcases(graph, root);
+    for case in cases.accept.iter_mut().chain(&mut cases.reject) {
+        *case = transcode(graph, root, case);
+    }
+    cases
+}
+
+/// Translate a value of the root type from the `bincode` format (as read by the
+/// [`crate::java::bincode`] deserializers) to this format.
+///
+/// If the input contains an invalid discriminant, the output will contain the same invalid
+/// discriminant in the corresponding location, and input following it will not be translated.
+pub fn transcode(graph: &TypeGraph, root: TypeId, input: &[u8]) -> Vec<u8> {
+    let mut transcoder = Transcoder { graph, input, heap: Default::default() };
+    let mut root_data = vec![];
+    let _ = transcoder.value(root, &mut root_data);
+    let Transcoder { mut heap, .. } = transcoder;
+    heap.extend(root_data);
+    heap
+}
+
+/// Indicates that the input contained an invalid discriminant.
+#[derive(Debug, Copy, Clone)]
+struct Rejected;
+
+#[derive(Debug)]
+struct Transcoder<'g, 'i> {
+    graph: &'g TypeGraph,
+    input: &'i [u8],
+    /// Complete objects, located at their final addresses.
+    heap:  Vec<u8>,
+}
+
+impl<'g, 'i> Transcoder<'g, 'i> {
+    fn read<const N: usize>(&mut self) -> [u8; N] {
+        let (data, rest) = self.input.split_at(N);
+        self.input = rest;
+        data.try_into().unwrap()
+    }
+
+    fn read_u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.read())
+    }
+
+    fn read_u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.read())
+    }
+
+    /// Translate a value, writing its inline representation to `out`.
+    fn value(&mut self, id: TypeId, out: &mut Vec<u8>) -> Result<(), Rejected> {
+        let graph = self.graph;
+        let ty = &graph[id];
+        match &ty.data {
+            Data::Primitive(primitive) => self.primitive(*primitive, out),
+            Data::Struct(_) if !ty.discriminants.is_empty() =>
+                self.boxed(out, |this, object| this.variant(id, object)),
+            Data::Struct(_) => {
+                let mut hierarchy = graph.hierarchy(id);
+                hierarchy.reverse();
+                hierarchy.into_iter().try_for_each(|id| self.fields(id, out))
+            }
+        }
+    }
+
+    /// Translate the own fields of a struct type.
+    fn fields(&mut self, id: TypeId, out: &mut Vec<u8>) -> Result<(), Rejected> {
+        let graph = self.graph;
+        let fields = graph[id].data.fields().unwrap_or_default();
+        fields.iter().try_for_each(|field| self.value(field.type_, out))
+    }
+
+    /// Translate the object data of a type with discriminants.
+    fn variant(&mut self, id: TypeId, object: &mut Vec<u8>) -> Result<(), Rejected> {
+        let graph = self.graph;
+        let discriminant_offset = object.len();
+        object.extend([0; 4]);
+        let mut hierarchy = graph.hierarchy(id);
+        hierarchy.reverse();
+        hierarchy.into_iter().try_for_each(|id| self.fields(id, object))?;
+        let discriminant = self.read_u32();
+        object[discriminant_offset..][..4].copy_from_slice(&discriminant.to_le_bytes());
+        let child = graph[id].discriminants.get(&(discriminant as usize)).ok_or(Rejected)?;
+        match graph[*child].discriminants.is_empty() {
+            true => self.fields(*child, object),
+            false => self.value(*child, object),
+        }
+    }
+
+    fn primitive(&mut self, primitive: Primitive, out: &mut Vec<u8>) -> Result<(), Rejected> {
+        match primitive {
+            Primitive::Bool => out.extend(self.read::<1>()),
+            Primitive::U32 | Primitive::I32 | Primitive::Char => out.extend(self.read::<4>()),
+            Primitive::U64 | Primitive::I64 => out.extend(self.read::<8>()),
+            Primitive::String => {
+                let len = self.read_u64() as usize;
+                let (data, rest) = self.input.split_at(len);
+                self.input = rest;
+                self.boxed(out, |_, object| {
+                    object.extend((len as u32).to_le_bytes());
+                    object.extend(data);
+                    Ok(())
+                })?;
+            }
+            Primitive::Sequence(t0) => {
+                let len = self.read_u64();
+                self.boxed(out, |this, object| {
+                    object.extend((len as u32).to_le_bytes());
+                    (0..len).try_for_each(|_| this.value(t0, object))
+                })?;
+            }
+            Primitive::Option(t0) => {
+                let [discriminant] = self.read::<1>();
+                out.push(discriminant);
+                match discriminant {
+                    0 => out.extend(NONE.to_le_bytes()),
+                    1 => self.boxed(out, |this, object| this.value(t0, object))?,
+                    _ => {
+                        out.extend(0u32.to_le_bytes());
+                        return Err(Rejected);
+                    }
+                }
+            }
+            Primitive::Result(t0, t1) => self.boxed(out, |this, object| {
+                let discriminant = this.read_u32();
+                object.extend(discriminant.to_le_bytes());
+                match discriminant {
+                    0 => this.value(t0, object),
+                    1 => this.value(t1, object),
+                    _ => Err(Rejected),
+                }
+            })?,
+        }
+        Ok(())
+    }
+
+    /// Build an object with the given function, place it on the heap, and write a reference to it
+    /// to `out`. The reference is written even if the object is incomplete because the input was
+    /// rejected, so that the rejected data is reachable in the output.
+    fn boxed(
+        &mut self,
+        out: &mut Vec<u8>,
+        f: impl FnOnce(&mut Self, &mut Vec<u8>) -> Result<(), Rejected>,
+    ) -> Result<(), Rejected> {
+        let mut object = vec![];
+        let result = f(self, &mut object);
+        let address = u32::try_from(self.heap.len()).unwrap();
+        self.heap.extend(object);
+        out.extend(address.to_le_bytes());
+        result
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::collections::BTreeSet;
+
+    /// Build a graph equivalent to the Rust type:
+    /// ```text
+    /// enum Expr {
+    ///     Number { value: u32 },
+    ///     Call { name: String, args: Vec<Expr>, named: Option<u64> },
+    /// }
+    /// ```
+    fn expr_graph() -> (TypeGraph, TypeId) {
+        let mut graph = TypeGraph::new();
+        let mut primitive = |name: &str, primitive| {
+            let name = TypeName::from_pascal_case(name);
+            graph.types.insert(Type::new(name, Data::Primitive(primitive)))
+        };
+        let u32_ = primitive("U32", Primitive::U32);
+        let u64_ = primitive("U64", Primitive::U64);
+        let string = primitive("String", Primitive::String);
+        let option = primitive("OptionU64", Primitive::Option(u64_));
+        let expr = graph.types.unbound_key();
+        let expr_id = TypeId::from(&expr);
+        let args = graph.types.insert(Type::new(
+            TypeName::from_pascal_case("VecExpr"),
+            Data::Primitive(Primitive::Sequence(expr_id)),
+        ));
+        let field = |name: &str, type_| Field::named(FieldName::from_snake_case(name), type_);
+        let mut child = |name: &str, fields| {
+            let mut ty = Type::new(TypeName::from_pascal_case(name), Data::Struct(fields));
+            ty.parent = Some(expr_id);
+            graph.types.insert(ty)
+        };
+        let number = child("Number", vec![field("value", u32_)]);
+        let call_fields = vec![field("name", string), field("args", args), field("named", option)];
+        let call = child("Call", call_fields);
+        let mut expr_ty = Type::new(TypeName::from_pascal_case("Expr"), Data::Struct(vec![]));
+        expr_ty.abstract_ = true;
+        expr_ty.closed = true;
+        expr_ty.child_field = Some(0);
+        expr_ty.discriminants = [(0, number), (1, call)].into_iter().collect();
+        graph.types.bind(expr, expr_ty);
+        (graph, expr_id)
+    }
+
+    #[test]
+    fn layout() {
+        let (graph, expr) = expr_graph();
+        let layout = Layout::new(&graph);
+        assert_eq!(layout.inline_size(expr), POINTER);
+        let [number, call] = [0, 1].map(|i| graph[expr].discriminants[&i]);
+        assert_eq!(layout.field_offsets(number), &[0]);
+        assert_eq!(layout.field_offsets(call), &[0, 4, 8]);
+        assert_eq!(layout.inline_size(call), 13);
+    }
+
+    #[test]
+    fn transcoding() {
+        let (graph, expr) = expr_graph();
+        // `Call { name: "f", args: vec![Number { value: 7 }], named: None }`
+        let mut bincode = vec![];
+        bincode.extend(1u32.to_le_bytes());
+        bincode.extend(1u64.to_le_bytes());
+        bincode.push(b'f');
+        bincode.extend(1u64.to_le_bytes());
+        bincode.extend(0u32.to_le_bytes());
+        bincode.extend(7u32.to_le_bytes());
+        bincode.push(0);
+        let mut expected = vec![];
+        // 0: "f"
+        expected.extend(1u32.to_le_bytes());
+        expected.push(b'f');
+        // 5: Number { value: 7 }
+        expected.extend(0u32.to_le_bytes());
+        expected.extend(7u32.to_le_bytes());
+        // 13: [Number]
+        expected.extend(1u32.to_le_bytes());
+        expected.extend(5u32.to_le_bytes());
+        // 21: Call
+        expected.extend(1u32.to_le_bytes());
+        expected.extend(0u32.to_le_bytes());
+        expected.extend(13u32.to_le_bytes());
+        expected.push(0);
+        expected.extend(NONE.to_le_bytes());
+        // Root reference.
+        expected.extend(21u32.to_le_bytes());
+        assert_eq!(transcode(&graph, expr, &bincode), expected);
+        // An invalid discriminant is preserved, and the object containing it is still reachable.
+        let rejected = transcode(&graph, expr, &2u32.to_le_bytes());
+        assert_eq!(rejected, [2, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn testcases_cover_variants() {
+        let (graph, expr) = expr_graph();
+        let cases = testcases(&graph, expr);
+        let discriminants = |cases: &[Vec<u8>]| -> BTreeSet<u32> {
+            let root =
+                |case: &Vec<u8>| u32::from_le_bytes(case[case.len() - 4..].try_into().unwrap());
+            let discriminant = |case: &Vec<u8>, root: usize| {
+                u32::from_le_bytes(case[root..][..4].try_into().unwrap())
+            };
+            cases.iter().map(|case| discriminant(case, root(case) as usize)).collect()
+        };
+        assert_eq!(discriminants(&cases.accept), [0, 1].into_iter().collect());
+        assert!(discriminants(&cases.reject).contains(&2));
+    }
+}
@@ -10,6 +10,7 @@
 
 #[cfg(feature = "graphviz")]
 mod graphviz;
+pub mod format;
 pub mod serialization;
 pub mod transform;
 
@@ -0,0 +1,345 @@
+//! Generation of Python dataclasses, and a decoder for the binary format produced by
+//! `enso_parser::format` (see [`crate::meta::format`]).
+//!
+//! Like the [`crate::typescript`] backend, this backend generates code directly from the
+//! [`crate::meta`] representation. Unlike the TypeScript types, the Python types are decoded
+//! eagerly: Decoding a value produces a tree of plain dataclass instances, which can be inspected
+//! and compared with the usual Python tools.
+//!
+//! # Generated types
+//!
+//! Each struct type is implemented as a dataclass. The name of a class is the concatenation of the
+//! names of the type and its ancestors, so that the children of different types are distinguished
+//! (e.g. `TreeIdent` and `TokenIdent`). A child type's class is a subclass of its parent's class,
+//! and so inherits its fields; every class has a constructor accepting all of its fields,
+//! including inherited fields, in the order they are serialized.
+//!
+//! Primitives are represented by `bool`, `int`, and `str` (a `Char` is represented by its code
+//! point, as in the binary format); sequences by `list`; optional values by `None` or the value;
+//! and results by the generated `Ok` and `Err` classes.
+//!
+//! # Decoding
+//!
+//! The generated module defines a function `decode(type_, data)`, which decodes a value of the
+//! given class from a serialized buffer. If the data is not a valid encoding of a value of the
+//! type, it raises the generated `FormatError` exception.
+
+use crate::meta::*;
+
+use crate::meta::format::Layout;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// Python keywords; a field with one of these names is suffixed with an underscore.
+const KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// Definitions used by the generated code.
+const PRELUDE: &str = r#"from __future__ import annotations
+
+import struct
+from dataclasses import dataclass
+from typing import ClassVar, Generic, TypeVar
+
+T = TypeVar('T')
+E = TypeVar('E')
+
+_U32 = struct.Struct('<I')
+_I32 = struct.Struct('<i')
+_U64 = struct.Struct('<Q')
+_I64 = struct.Struct('<q')
+
+
+class FormatError(Exception):
+    """The data is not a valid encoding of a value of the type being decoded."""
+
+
+@dataclass
+class Ok(Generic[T]):
+    value: T
+
+
+@dataclass
+class Err(Generic[E]):
+    error: E
+
+
+def decode(type_, data):
+    """Decode a value of the given type from a buffer produced by `enso_parser::format`."""
+    data = memoryview(data)
+    try:
+        return type_._read(data, len(data) - type_._SIZE)
+    except (struct.error, IndexError, ValueError) as error:
+        raise FormatError(error) from error
+
+
+def _bool(data, address):
+    value = data[address]
+    if value > 1:
+        raise FormatError(f'Invalid bool: {value}.')
+    return value == 1
+
+
+def _u32(data, address):
+    return _U32.unpack_from(data, address)[0]
+
+
+def _i32(data, address):
+    return _I32.unpack_from(data, address)[0]
+
+
+def _u64(data, address):
+    return _U64.unpack_from(data, address)[0]
+
+
+def _i64(data, address):
+    return _I64.unpack_from(data, address)[0]
+
+
+def _object(data, address, size):
+    pointer = _u32(data, address)
+    if pointer + size > len(data):
+        raise FormatError(f'Object out of bounds: {pointer}.')
+    return pointer
+
+
+def _str(data, address):
+    pointer = _object(data, address, 4)
+    start = pointer + 4
+    end = start + _u32(data, pointer)
+    if end > len(data):
+        raise FormatError(f'String out of bounds: {pointer}.')
+    return str(data[start:end], 'utf-8')
+
+
+def _seq(data, address, size, read):
+    pointer = _object(data, address, 4)
+    count = _u32(data, pointer)
+    if pointer + 4 + count * size > len(data):
+        raise FormatError(f'Sequence out of bounds: {pointer}.')
+    return [read(data, pointer + 4 + i * size) for i in range(count)]
+
+
+def _option(data, address, read):
+    discriminant = data[address]
+    if discriminant == 0:
+        return None
+    if discriminant == 1:
+        return read(data, _object(data, address + 1, 0))
+    raise FormatError(f'Invalid Option discriminant: {discriminant}.')
+
+
+def _result(data, address, read_ok, read_err):
+    pointer = _object(data, address, 4)
+    discriminant = _u32(data, pointer)
+    if discriminant == 0:
+        return Ok(read_ok(data, pointer + 4))
+    if discriminant == 1:
+        return Err(read_err(data, pointer + 4))
+    raise FormatError(f'Invalid Result discriminant: {discriminant}.')
+
+
+def _enum(readers, data, address):
+    pointer = _object(data, address, 4)
+    discriminant = _u32(data, pointer)
+    if discriminant >= len(readers):
+        raise FormatError(f'Invalid enum discriminant: {discriminant}.')
+    return readers[discriminant](data, pointer + 4)
+"#;
+
+
+
+// =====================================
+// === Implementing Python Datatypes ===
+// =====================================
+
+/// Produce a Python module implementing all the types in a [`TypeGraph`].
+pub fn implement(graph: &TypeGraph) -> String {
+    let layout = Layout::new(graph);
+    let mut implementation = Implementation { graph, layout, code: String::new() };
+    let roots = graph.types.iter().filter(|(_, ty)| ty.parent.is_none());
+    let roots: Vec<_> = roots.filter(|(_, ty)| matches!(ty.data, Data::Struct(_))).collect();
+    let mut names = BTreeSet::new();
+    for (id, _) in graph.types.iter() {
+        if matches!(graph[id].data, Data::Struct(_)) {
+            let name = implementation.class_name(id);
+            assert!(names.insert(name.clone()), "Multiple types named {name}.");
+        }
+    }
+    for (id, _) in roots {
+        implementation.type_(id);
+    }
+    let mut code = String::new();
+    writeln!(code, "# *** THIS FILE GENERATED BY `enso-metamodel` ***").unwrap();
+    code.push_str(PRELUDE);
+    code.push_str(&implementation.code);
+    code
+}
+
+#[derive(Debug)]
+struct Implementation<'g> {
+    graph:  &'g TypeGraph,
+    layout: Layout,
+    code:   String,
+}
+
+impl<'g> Implementation<'g> {
+    fn type_(&mut self, id: TypeId) {
+        let graph = self.graph;
+        let ty = &graph[id];
+        let name = self.class_name(id);
+        let abstract_ = !ty.discriminants.is_empty();
+        let base = ty.parent.map(|parent| format!("({})", self.class_name(parent)));
+        let mut out = String::new();
+        writeln!(out, "\n\n@dataclass").unwrap();
+        writeln!(out, "class {name}{}:", base.unwrap_or_default()).unwrap();
+        let fields = ty.data.fields().unwrap_or_default();
+        for (i, field) in fields.iter().enumerate() {
+            let type_ = self.type_name(field.type_);
+            writeln!(out, "    {}: {type_}", field_name(field, i)).unwrap();
+        }
+        if !fields.is_empty() {
+            writeln!(out).unwrap();
+        }
+        writeln!(out, "    _SIZE: ClassVar[int] = {}", self.layout.inline_size(id)).unwrap();
+        if abstract_ {
+            writeln!(out, "    _VARIANTS: ClassVar[list]").unwrap();
+        }
+        writeln!(out).unwrap();
+        writeln!(out, "    @staticmethod").unwrap();
+        writeln!(out, "    def _read(data: memoryview, address: int) -> {name}:").unwrap();
+        match abstract_ {
+            true => writeln!(out, "        return _enum({name}._VARIANTS, data, address)").unwrap(),
+            false => {
+                writeln!(out, "        return {name}(").unwrap();
+                self.check_field_names(id);
+                let mut hierarchy = graph.hierarchy(id);
+                hierarchy.reverse();
+                for ancestor in hierarchy {
+                    let fields = graph[ancestor].data.fields().unwrap_or_default();
+                    let offsets = self.layout.field_offsets(ancestor).to_vec();
+                    for (field, offset) in fields.iter().zip(offsets) {
+                        let address = match offset {
+                            0 => "address".to_owned(),
+                            offset => format!("address + {offset}"),
+                        };
+                        writeln!(out, "            {},", self.read(field.type_, &address)).unwrap();
+                    }
+                }
+                writeln!(out, "        )").unwrap();
+            }
+        }
+        self.code.push_str(&out);
+        if abstract_ {
+            let children: Vec<_> = ty.discriminants.values().copied().collect();
+            let contiguous = ty.discriminants.keys().copied().eq(0..children.len());
+            assert!(contiguous, "Discriminants of {name} are not contiguous.");
+            for &child in &children {
+                self.type_(child);
+            }
+            let readers: Vec<_> =
+                children.iter().map(|&child| format!("{}._read", self.class_name(child))).collect();
+            write!(self.code, "\n\n{name}._VARIANTS = [{}]\n", readers.join(", ")).unwrap();
+        }
+    }
+
+    /// Dataclass fields are identified by name, so a class can't have two fields with the same
+    /// name, even if one is inherited.
+    fn check_field_names(&self, id: TypeId) {
+        let mut names = BTreeSet::new();
+        for ancestor in self.graph.hierarchy(id) {
+            let fields = self.graph[ancestor].data.fields().unwrap_or_default();
+            for (i, field) in fields.iter().enumerate() {
+                let name = field_name(field, i);
+                let class = self.class_name(id);
+                assert!(names.insert(name.clone()), "Multiple fields named {name} in {class}.");
+            }
+        }
+    }
+
+    /// The name of a type's class: the concatenated names of the type and its ancestors.
+    fn class_name(&self, id: TypeId) -> String {
+        let mut hierarchy = self.graph.hierarchy(id);
+        hierarchy.reverse();
+        hierarchy.iter().map(|id| self.graph[id].name.to_pascal_case()).collect()
+    }
+}
+
+
+// === Types and Decoding ===
+
+impl<'g> Implementation<'g> {
+    /// The Python type annotation for a value of the given type.
+    fn type_name(&self, id: TypeId) -> String {
+        match &self.graph[id].data {
+            Data::Struct(_) => self.class_name(id),
+            Data::Primitive(primitive) => match *primitive {
+                Primitive::Bool => "bool".to_owned(),
+                Primitive::U32
+                | Primitive::I32
+                | Primitive::U64
+                | Primitive::I64
+                | Primitive::Char => "int".to_owned(),
+                Primitive::String => "str".to_owned(),
+                Primitive::Sequence(t0) => format!("list[{}]", self.type_name(t0)),
+                Primitive::Option(t0) => format!("{} | None", self.type_name(t0)),
+                Primitive::Result(t0, t1) =>
+                    format!("Ok[{}] | Err[{}]", self.type_name(t0), self.type_name(t1)),
+            },
+        }
+    }
+
+    /// An expression decoding a value of the given type, located at an address in `data`.
+    fn read(&self, id: TypeId, address: &str) -> String {
+        let primitive = match &self.graph[id].data {
+            Data::Struct(_) => return format!("{}._read(data, {address})", self.class_name(id)),
+            Data::Primitive(primitive) => *primitive,
+        };
+        match primitive {
+            Primitive::Sequence(t0) => {
+                let size = self.layout.inline_size(t0);
+                format!("_seq(data, {address}, {size}, {})", self.reader(t0))
+            }
+            Primitive::Option(t0) => format!("_option(data, {address}, {})", self.reader(t0)),
+            Primitive::Result(t0, t1) =>
+                format!("_result(data, {address}, {}, {})", self.reader(t0), self.reader(t1)),
+            _ => format!("{}(data, {address})", self.reader(id)),
+        }
+    }
+
+    /// A function decoding a value of the given type, given the data and an address.
+    fn reader(&self, id: TypeId) -> String {
+        let primitive = match &self.graph[id].data {
+            Data::Struct(_) => return format!("{}._read", self.class_name(id)),
+            Data::Primitive(primitive) => *primitive,
+        };
+        match primitive {
+            Primitive::Bool => "_bool".to_owned(),
+            Primitive::U32 | Primitive::Char => "_u32".to_owned(),
+            Primitive::I32 => "_i32".to_owned(),
+            Primitive::U64 => "_u64".to_owned(),
+            Primitive::I64 => "_i64".to_owned(),
+            Primitive::String => "_str".to_owned(),
+            Primitive::Sequence(_) | Primitive::Option(_) | Primitive::Result(..) =>
+                format!("lambda data, address: {}", self.read(id, "address")),
+        }
+    }
+}
+
+fn field_name(field: &Field, index: usize) -> String {
+    let name = field.name.to_snake_case().unwrap_or_else(|| format!("field_{index}"));
+    match KEYWORDS.contains(&name.as_str()) {
+        true => format!("{name}_"),
+        false => name,
+    }
+}
@@ -0,0 +1,377 @@
+//! Given a [`crate::meta`] representation of a data model, produce TypeScript code implementing
+//! lazy deserialization of the data model.
+//!
+//! # Generated types
+//!
+//! A type without discriminants is implemented as a class. A type with discriminants is
+//! implemented as a namespace containing:
+//! - An abstract class `AbstractBase`, defining the type's own fields.
+//! - A `const enum` named `Type`, with a value identifying each child type, and a `typeNames` array
+//!   of the names of the child types.
+//! - The child types, each of which has a `type` field identifying it.
+//! - A type alias for the union of the child types, named the same as the namespace (and also
+//!   exported from the parent namespace).
+//! - Functions to `read` a value of the type and to check whether an object `isInstance` of it.
+
+use crate::meta::*;
+
+use crate::meta::format::Layout;
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::fmt::Write;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// Field names that would conflict with members of the generated classes, and their replacements.
+const RENAME: &[(&str, &str)] = &[("constructor", "ident"), ("type", "typeNode")];
+
+/// Exports of the support module that are used only as types.
+const TYPE_IMPORTS: &[&str] = &["ObjectVisitor", "Result"];
+
+
+
+// =========================================
+// === Implementing TypeScript Datatypes ===
+// =========================================
+
+/// Produce TypeScript code implementing all the types in a [`TypeGraph`]. The code imports its
+/// runtime support from the module at the given path.
+pub fn implement(graph: &TypeGraph, support: &str) -> String {
+    let layout = Layout::new(graph);
+    let imports = Default::default();
+    let out = Default::default();
+    let mut implementation = Implementation { graph, layout, imports, out };
+    let roots: Vec<_> = graph
+        .types
+        .iter()
+        .filter(|(_, ty)| ty.parent.is_none() && matches!(ty.data, Data::Struct(_)))
+        .map(|(id, _)| id)
+        .collect();
+    check_unique_names(graph, &roots);
+    for id in roots {
+        implementation.type_(id);
+    }
+    let Implementation { imports, out, .. } = implementation;
+    let (types, values): (Vec<_>, Vec<_>) =
+        imports.into_iter().partition(|name| TYPE_IMPORTS.contains(name));
+    let mut code = String::new();
+    writeln!(code, "// *** THIS FILE GENERATED BY `enso-metamodel` ***").unwrap();
+    writeln!(code, "import {{ {} }} from '{support}'", values.join(", ")).unwrap();
+    if !types.is_empty() {
+        writeln!(code, "import type {{ {} }} from '{support}'", types.join(", ")).unwrap();
+    }
+    code.push_str(&out.code);
+    code
+}
+
+fn check_unique_names(graph: &TypeGraph, ids: &[TypeId]) {
+    let mut names = BTreeSet::new();
+    for id in ids {
+        let name = graph[id].name.to_pascal_case();
+        assert!(names.insert(name.clone()), "Multiple types named {name} in one namespace.");
+    }
+}
+
+#[derive(Debug)]
+struct Implementation<'g> {
+    graph:   &'g TypeGraph,
+    layout:  Layout,
+    /// Exports of the support module used by the generated code.
+    imports: BTreeSet<&'static str>,
+    out:     Writer,
+}
+
+impl<'g> Implementation<'g> {
+    fn type_(&mut self, id: TypeId) {
+        match self.graph[id].discriminants.is_empty() {
+            true => self.concrete(id),
+            false => self.abstract_(id),
+        }
+    }
+
+    /// Implement a type that can be instantiated, as a class.
+    fn concrete(&mut self, id: TypeId) {
+        let name = self.graph[id].name.to_pascal_case();
+        self.out.line("");
+        match self.graph[id].parent {
+            Some(_) => {
+                self.out.open(format!("export class {name} extends AbstractBase {{"));
+                self.out.line(format!("readonly type: Type.{name}"));
+                self.out.line("");
+                self.out.open("constructor(view: DataView) {");
+                self.out.line("super(view)");
+                self.out.line(format!("this.type = Type.{name}"));
+                self.out.close("}");
+            }
+            None => {
+                let base = self.support("LazyObject");
+                self.out.open(format!("export class {name} extends {base} {{"));
+                self.out.open("constructor(view: DataView) {");
+                self.out.line("super(view)");
+                self.out.close("}");
+            }
+        }
+        self.out.line("");
+        self.out.open(format!("static read(view: DataView, address: number): {name} {{"));
+        let offset = self.support("readOffset");
+        self.out.line(format!("return new {name}({offset}(view, address))"));
+        self.out.close("}");
+        self.fields(id);
+        self.out.close("}");
+    }
+
+    /// Implement a type with discriminants, as a namespace containing its child types.
+    fn abstract_(&mut self, id: TypeId) {
+        let graph = self.graph;
+        let ty = &graph[id];
+        let name = ty.name.to_pascal_case();
+        let children: Vec<_> = ty.discriminants.values().copied().collect();
+        let contiguous = ty.discriminants.keys().copied().eq(0..children.len());
+        assert!(contiguous, "Discriminants of {name} are not contiguous.");
+        check_unique_names(graph, &children);
+        let child_names: Vec<_> =
+            children.iter().map(|id| graph[id].name.to_pascal_case()).collect();
+        let base = match ty.parent {
+            Some(parent) => format!("{}.AbstractBase", self.path(parent)),
+            None => self.support("LazyObject").to_owned(),
+        };
+        self.out.line("");
+        self.out.open(format!("export namespace {name} {{"));
+        self.out.open(format!("export abstract class AbstractBase extends {base} {{"));
+        self.out.open("protected constructor(view: DataView) {");
+        self.out.line("super(view)");
+        self.out.close("}");
+        self.fields(id);
+        self.out.close("}");
+        self.out.line("");
+        self.out.open("export const enum Type {");
+        for (i, child) in child_names.iter().enumerate() {
+            self.out.line(format!("{child} = {i},"));
+        }
+        self.out.close("}");
+        self.out.line("");
+        let quoted: Vec<_> = child_names.iter().map(|name| format!("'{name}'")).collect();
+        self.out.line(format!("export const typeNames = [{}] as const", quoted.join(", ")));
+        for child in children {
+            self.type_(child);
+        }
+        self.out.line("");
+        self.out.line(format!("export type {name} = {}", child_names.join(" | ")));
+        self.out.line("");
+        let readers: Vec<_> = child_names.iter().map(|name| format!("{name}.read")).collect();
+        self.out.line(format!("const VARIANT_READERS = [{}]", readers.join(", ")));
+        self.out.line("");
+        self.out.open(format!("export function read(view: DataView, address: number): {name} {{"));
+        let read_enum = self.support("readEnum");
+        self.out.line(format!("return {read_enum}<{name}>(VARIANT_READERS, view, address)"));
+        self.out.close("}");
+        self.out.line("");
+        self.out.open(format!("export function isInstance(obj: unknown): obj is {name} {{"));
+        self.out.line("return obj instanceof AbstractBase");
+        self.out.close("}");
+        self.out.close("}");
+        self.out.line("");
+        self.out.line(format!("export type {name} = {name}.{name}"));
+    }
+
+    /// Define accessors for the type's own fields, and a method visiting the objects they contain.
+    fn fields(&mut self, id: TypeId) {
+        let graph = self.graph;
+        let fields = graph[id].data.fields().unwrap_or_default();
+        let offsets = self.layout.field_offsets(id).to_vec();
+        let mut visits = vec![];
+        for (i, (field, offset)) in fields.iter().zip(offsets).enumerate() {
+            let name = field_name(field, i);
+            let type_ = self.type_name(field.type_);
+            let offset = offset.to_string();
+            let read = self.read(field.type_, "this._v", &offset);
+            self.out.line("");
+            self.out.open(format!("get {name}(): {type_} {{"));
+            self.out.line(format!("return {read}"));
+            self.out.close("}");
+            visits.extend(self.visit(field.type_, "this._v", &offset));
+        }
+        if !visits.is_empty() {
+            let visitor = self.support("ObjectVisitor");
+            self.out.line("");
+            self.out.open(format!("visitChildren(visitor: {visitor}): boolean {{"));
+            self.out
+                .line(format!("return super.visitChildren(visitor) || {}", visits.join(" || ")));
+            self.out.close("}");
+        }
+    }
+
+    /// Note that the generated code uses the given export of the support module; return its name.
+    fn support(&mut self, name: &'static str) -> &'static str {
+        self.imports.insert(name);
+        name
+    }
+
+    /// The qualified name of a type.
+    fn path(&self, id: TypeId) -> String {
+        let mut hierarchy = self.graph.hierarchy(id);
+        hierarchy.reverse();
+        let names: Vec<_> =
+            hierarchy.iter().map(|id| self.graph[id].name.to_pascal_case()).collect();
+        names.join(".")
+    }
+}
+
+
+// === Types and Accessors ===
+
+impl<'g> Implementation<'g> {
+    /// The TypeScript type of a value of the given type.
+    fn type_name(&mut self, id: TypeId) -> String {
+        match &self.graph[id].data {
+            Data::Struct(_) => self.path(id),
+            Data::Primitive(primitive) => match *primitive {
+                Primitive::Bool => "boolean".to_owned(),
+                Primitive::U32 | Primitive::I32 | Primitive::Char => "number".to_owned(),
+                Primitive::U64 | Primitive::I64 => "bigint".to_owned(),
+                Primitive::String => "string".to_owned(),
+                Primitive::Sequence(t0) => format!("Iterable<{}>", self.type_name(t0)),
+                Primitive::Option(t0) => format!("{} | undefined", self.type_name(t0)),
+                Primitive::Result(t0, t1) => {
+                    let result = self.support("Result");
+                    format!("{result}<{}, {}>", self.type_name(t0), self.type_name(t1))
+                }
+            },
+        }
+    }
+
+    /// An expression reading a value of the given type, located at an address in a view.
+    fn read(&mut self, id: TypeId, view: &str, address: &str) -> String {
+        let primitive = match &self.graph[id].data {
+            Data::Struct(_) => return format!("{}.read({view}, {address})", self.path(id)),
+            Data::Primitive(primitive) => *primitive,
+        };
+        let reader = match primitive {
+            Primitive::Bool => self.support("readBool"),
+            Primitive::U32 | Primitive::Char => self.support("readU32"),
+            Primitive::I32 => self.support("readI32"),
+            Primitive::U64 => self.support("readU64"),
+            Primitive::I64 => self.support("readI64"),
+            Primitive::String => self.support("readString"),
+            Primitive::Sequence(t0) => {
+                let size = self.layout.inline_size(t0);
+                let element = self.reader(t0);
+                let read = self.support("readSequence");
+                return format!("{read}({view}, {address}, {size}, {element})");
+            }
+            Primitive::Option(t0) => {
+                let value = self.reader(t0);
+                let read = self.support("readOption");
+                return format!("{read}({view}, {address}, {value})");
+            }
+            Primitive::Result(t0, t1) => {
+                let (ok, err) = (self.reader(t0), self.reader(t1));
+                let read = self.support("readResult");
+                return format!("{read}({view}, {address}, {ok}, {err})");
+            }
+        };
+        format!("{reader}({view}, {address})")
+    }
+
+    /// A function reading a value of the given type, given a view and an address.
+    fn reader(&mut self, id: TypeId) -> String {
+        let read = self.read(id, "view", "address");
+        match read.strip_suffix("(view, address)") {
+            Some(function) if !function.contains('(') => function.to_owned(),
+            _ => arrow(read),
+        }
+    }
+
+    /// An expression visiting the objects in a value of the given type, located at an address in a
+    /// view; returns [`None`] if values of the type don't contain any objects.
+    fn visit(&mut self, id: TypeId, view: &str, address: &str) -> Option<String> {
+        let primitive = match &self.graph[id].data {
+            Data::Struct(_) => return Some(format!("!!visitor({})", self.read(id, view, address))),
+            Data::Primitive(primitive) => *primitive,
+        };
+        match primitive {
+            Primitive::Sequence(t0) => {
+                let element = arrow(self.visit(t0, "view", "address")?);
+                let size = self.layout.inline_size(t0);
+                let visit = self.support("visitSequence");
+                Some(format!("{visit}({view}, {address}, {size}, {element})"))
+            }
+            Primitive::Option(t0) => {
+                let value = arrow(self.visit(t0, "view", "address")?);
+                let visit = self.support("visitOption");
+                Some(format!("{visit}({view}, {address}, {value})"))
+            }
+            Primitive::Result(t0, t1) => {
+                let ok = self.visit(t0, "view", "address");
+                let err = self.visit(t1, "view", "address");
+                if ok.is_none() && err.is_none() {
+                    return None;
+                }
+                let [ok, err] = [ok, err].map(|visit| visit.map_or("null".to_owned(), arrow));
+                let visit = self.support("visitResult");
+                Some(format!("{visit}({view}, {address}, {ok}, {err})"))
+            }
+            Primitive::Bool
+            | Primitive::U32
+            | Primitive::U64
+            | Primitive::I32
+            | Primitive::I64
+            | Primitive::Char
+            | Primitive::String => None,
+        }
+    }
+}
+
+fn arrow(body: impl Display) -> String {
+    format!("(view, address) => {body}")
+}
+
+fn field_name(field: &Field, index: usize) -> String {
+    let name = field.name.to_camel_case().unwrap_or_else(|| format!("field{index}"));
+    match RENAME.iter().find(|(from, _)| *from == name) {
+        Some((_, to)) => to.to_string(),
+        None => name,
+    }
+}
+
+
+
+// ==============
+// === Writer ===
+// ==============
+
+/// Accumulates lines of code, with indentation.
+#[derive(Debug, Default)]
+struct Writer {
+    code:   String,
+    indent: usize,
+}
+
+impl Writer {
+    fn line(&mut self, line: impl AsRef<str>) {
+        let line = line.as_ref();
+        if !line.is_empty() {
+            for _ in 0..self.indent {
+                self.code.push_str("  ");
+            }
+        }
+        self.code.push_str(line);
+        self.code.push('\n');
+    }
+
+    /// Write a line, and indent the following lines.
+    fn open(&mut self, line: impl AsRef<str>) {
+        self.line(line);
+        self.indent += 1;
+    }
+
+    /// Write a line at the indentation level before the matching [`Self::open`].
+    fn close(&mut self, line: impl AsRef<str>) {
+        self.indent -= 1;
+        self.line(line);
+    }
+}
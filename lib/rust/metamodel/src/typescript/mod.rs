@@ -0,0 +1,43 @@
+//! Generation of TypeScript types that lazily deserialize data models from a binary format.
+//!
+//! The format is the one produced by `enso_parser::format`; see [`crate::meta::format`] for a
+//! description. Each generated class is a view of an object in a serialized buffer; its fields are
+//! deserialized only when accessed. This benefits performance: If we eagerly deserialized a
+//! serialized tree to a tree of objects in memory, creating the tree would produce many
+//! heap-allocated objects, and visiting the tree would require dereferencing chains of heap
+//! pointers.
+//!
+//! Unlike the [`crate::java`] backend, this backend generates code directly from the
+//! [`crate::meta`] representation: The TypeScript types are a direct reflection of the serialized
+//! layout, so there are no language-specific transformations that would call for a TypeScript data
+//! model.
+//!
+//! # Runtime support
+//!
+//! The generated code depends on a support module (whose path is given when generating the code),
+//! which must export:
+//! - `LazyObject`: The base class of all generated classes. Its constructor accepts a `DataView`,
+//!   which it stores as the protected field `_v`; it provides a `visitChildren` method that is
+//!   overridden by the generated types to visit their object-typed fields.
+//! - `ObjectVisitor`, `Result`: Types used in the signatures of generated methods.
+//! - Functions reading primitive values and compound values from the buffer: `readBool`, `readU32`,
+//!   `readI32`, `readU64`, `readI64`, `readString`, `readOffset`, `readOption`, `readResult`,
+//!   `readSequence`, `readEnum`, `visitOption`, `visitResult`, `visitSequence`.
+//!
+//! # Testing
+//!
+//! The same test cases used to validate the [`crate::java`] deserializers
+//! ([`crate::meta::serialization::testcases`]) can be obtained in this format with
+//! [`crate::meta::format::testcases`].
+
+
+
+mod implementation;
+
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub use implementation::implement as to_syntax;
@@ -20,7 +20,6 @@ fn main() {
         code = code_;
     }
     let ast = enso_parser::Parser::new().run(code);
-    let data =
-        enso_parser::format::serialize(&ast).expect("Failed to serialize AST to binary format");
+    let data = enso_parser::format::encode(&ast);
     std::io::stdout().write_all(&data).unwrap();
 }
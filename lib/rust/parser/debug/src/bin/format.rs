@@ -0,0 +1,56 @@
+//! Format Enso source code in the canonical style.
+//!
+//! With no arguments, reads code from the standard input and writes the formatted code to the
+//! standard output. Otherwise, formats the given files in place; with `--check`, the files are not
+//! modified, and the exit status is nonzero if any of them is not formatted.
+//!
+//! A metadata section at the end of the input is left unchanged.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+use std::io::Read;
+use std::io::Write;
+
+
+
+fn main() {
+    let mut args: Vec<_> = std::env::args().skip(1).collect();
+    let check = args.iter().any(|arg| arg == "--check");
+    args.retain(|arg| arg != "--check");
+    let parser = enso_parser::Parser::new();
+    if args.is_empty() {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input).unwrap();
+        std::io::stdout().write_all(format(&parser, &input).as_bytes()).unwrap();
+        return;
+    }
+    let mut unformatted = false;
+    for path in args {
+        let input = std::fs::read_to_string(&path).unwrap();
+        let output = format(&parser, &input);
+        if output != input {
+            match check {
+                true => {
+                    eprintln!("Not formatted: {path}");
+                    unformatted = true;
+                }
+                false => std::fs::write(&path, output).unwrap(),
+            }
+        }
+    }
+    if unformatted {
+        std::process::exit(1);
+    }
+}
+
+fn format(parser: &enso_parser::Parser, input: &str) -> String {
+    let (code, _meta) = enso_parser::metadata::extract(input);
+    let ast = parser.run(code);
+    let mut output = enso_parser::format::format(&ast);
+    output.push_str(&input[code.len()..]);
+    output
+}
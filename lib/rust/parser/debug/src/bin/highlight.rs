@@ -0,0 +1,31 @@
+//! Print Enso source code with syntax highlighting.
+//!
+//! Reads code from the standard input, and writes it to the standard output highlighted with ANSI
+//! escape sequences; with `--html`, writes it as an HTML `pre` element instead.
+//!
+//! A metadata section at the end of the input is not highlighted.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+use std::io::Read;
+use std::io::Write;
+
+
+
+fn main() {
+    let html = std::env::args().skip(1).any(|arg| arg == "--html");
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input).unwrap();
+    let (code, _meta) = enso_parser::metadata::extract(&input);
+    let ast = enso_parser::Parser::new().run(code);
+    let highlights = enso_parser::highlight::highlight(&ast);
+    let output = match html {
+        true => enso_parser::highlight::html(&input, &highlights),
+        false => enso_parser::highlight::ansi(&input, &highlights),
+    };
+    std::io::stdout().write_all(output.as_bytes()).unwrap();
+}
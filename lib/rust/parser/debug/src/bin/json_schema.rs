@@ -0,0 +1,16 @@
+//! Output a JSON Schema describing the JSON serialization of the AST produced by `json_ast`.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+
+
+use enso_reflect::Reflect;
+
+fn main() {
+    let schema = enso_metamodel_json_schema::schema(enso_parser::syntax::Tree::reflect());
+    serde_json::to_writer_pretty(std::io::stdout(), &schema).unwrap();
+}
@@ -0,0 +1,85 @@
+//! Compare the peak memory usage of the batch lexer ([`enso_parser::lexer::run`]) and the streaming
+//! lexer ([`enso_parser::lexer::stream`]).
+//!
+//! Reads code from the standard input, lexes it both ways, and prints the peak number of bytes
+//! allocated on the heap during each run, in addition to the memory holding the input.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+use std::alloc::GlobalAlloc;
+use std::alloc::Layout;
+use std::alloc::System;
+use std::io::Read;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+
+
+// ==========================
+// === Counting Allocator ===
+// ==========================
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// Delegates to the system allocator, keeping track of the number of bytes allocated, and of its
+/// peak since the last call to [`reset_peak`].
+struct CountingAllocator;
+
+#[allow(unsafe_code)]
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+/// Reset the peak to the current allocation, and return the current allocation.
+fn reset_peak() -> usize {
+    let allocated = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(allocated, Ordering::Relaxed);
+    allocated
+}
+
+/// Return the peak number of bytes allocated while running the given function, in excess of the
+/// bytes allocated before.
+fn peak_during(f: impl FnOnce()) -> usize {
+    let baseline = reset_peak();
+    f();
+    PEAK.load(Ordering::Relaxed) - baseline
+}
+
+
+
+// ============
+// === Main ===
+// ============
+
+fn main() {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input).unwrap();
+    let mut batch_tokens = 0;
+    let batch = peak_during(|| batch_tokens = enso_parser::lexer::run(&input).value.len());
+    let mut stream_tokens = 0;
+    let stream = peak_during(|| stream_tokens = enso_parser::lexer::stream(&input).count());
+    assert_eq!(stream_tokens, batch_tokens);
+    println!("Input:  {} bytes, {batch_tokens} tokens.", input.len());
+    println!("Batch:  {batch} bytes peak.");
+    println!("Stream: {stream} bytes peak.");
+}
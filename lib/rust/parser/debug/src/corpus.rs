@@ -0,0 +1,17 @@
+//! A corpus of representative Enso source files, for the tests that check properties of the parser
+//! over whole modules, such as that formatting preserves the meaning of the code, or that
+//! incremental reparsing produces the same tree as a full parse.
+
+
+
+// ==============
+// === Corpus ===
+// ==============
+
+/// The directory containing the corpus.
+pub const CORPUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/corpus");
+
+/// Return the name and contents of each file of the corpus.
+pub fn cases() -> std::io::Result<Vec<(String, String)>> {
+    crate::fuzz::read_cases(CORPUS)
+}
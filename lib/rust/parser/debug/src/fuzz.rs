@@ -0,0 +1,239 @@
+//! Support for fuzzing the parser: checking the invariants that must hold for any input, and
+//! reducing failing inputs to regression test cases.
+//!
+//! The fuzz targets (in `lib/rust/parser/fuzz`) and the property tests (in `tests/fuzz.rs`) check
+//! each input with [`check`]. When an input fails, [`record_failure`] minimizes it and saves it.
+//! The fuzz targets save it to [`REGRESSION_CASES`], from where it is run by the test suite of this
+//! crate; the property tests save it to a temporary directory, so that running the tests doesn't
+//! modify the source tree.
+
+use enso_parser::source::code::debug::LocationCheck;
+use enso_parser::source::code::StrRef;
+use enso_parser::source::Code;
+use enso_parser::syntax::tree::CodeVisitableMut;
+use enso_parser::syntax::tree::CodeVisitorMut;
+use enso_parser::syntax::tree::Visitor;
+use enso_parser::syntax::Tree;
+use std::panic;
+use std::path::Path;
+use std::path::PathBuf;
+
+
+
+// ==================
+// === Invariants ===
+// ==================
+
+/// A violation of an invariant of the parser.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Failure {
+    /// The parser panicked.
+    Panic(String),
+    /// The code of the tree differs from the input.
+    RoundTrip {
+        /// The code of the tree.
+        printed: String,
+    },
+    /// The spans of the tree are inconsistent, overlapping, or out of order.
+    Spans(String),
+    /// The tree could not be serialized, or the serialized tree could not be read back.
+    Serialization(String),
+}
+
+impl Failure {
+    /// Return whether the failures violate the same invariant.
+    pub fn is_same_kind(&self, other: &Failure) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Failure::Panic(message) => write!(f, "The parser panicked: {message}"),
+            Failure::RoundTrip { printed } => write!(f, "The tree represents the code {printed:?}"),
+            Failure::Spans(message) => write!(f, "Inconsistent spans: {message}"),
+            Failure::Serialization(message) => write!(f, "Serialization failed: {message}"),
+        }
+    }
+}
+
+/// Parse the input, and check that:
+/// - The parser doesn't panic.
+/// - The tree represents exactly the input code.
+/// - The spans of the tree are consistent, and cover the input in order, without overlapping.
+/// - The tree can be serialized, and the serialized tree can be read back.
+pub fn check(code: &str) -> Result<(), Failure> {
+    let tree = catch_panic(|| enso_parser::Parser::new().run(code)).map_err(Failure::Panic)?;
+    let printed = tree.code();
+    if printed != code {
+        return Err(Failure::RoundTrip { printed });
+    }
+    catch_panic(|| {
+        let expected_span = 0..(code.encode_utf16().count() as u32);
+        let mut locations = LocationCheck::new();
+        crate::validate_spans(&tree, expected_span, &mut locations);
+        locations.check(code);
+    })
+    .map_err(Failure::Spans)?;
+    let serialization = || -> Result<(), String> {
+        let serialized = enso_parser::serialization::serialize_tree(&tree);
+        let serialized = serialized.map_err(|error| error.to_string())?;
+        let deserialized = enso_parser::serialization::deserialize_tree(&serialized);
+        let deserialized = deserialized.map_err(|error| error.to_string())?;
+        // The deserialized tree doesn't refer to the source code, so the code references are
+        // detached from both trees before comparing their serialized representations.
+        match serialize_detached(deserialized)? == serialize_detached(tree.clone())? {
+            true => Ok(()),
+            false => Err("The deserialized tree differs from the serialized tree.".into()),
+        }
+    };
+    catch_panic(serialization).and_then(|result| result).map_err(Failure::Serialization)
+}
+
+/// Serialize the tree, with all of its code references replaced by the same empty reference.
+fn serialize_detached(mut tree: Tree<'_>) -> Result<Vec<u8>, String> {
+    tree.visit_code_mut(&mut DetachCode);
+    enso_parser::serialization::serialize_tree(&tree).map_err(|error| error.to_string())
+}
+
+/// The code referred to by trees detached from their source code. Being a single static, it has
+/// the same address in every detached tree.
+static DETACHED_CODE: &str = "";
+
+/// Replaces all code references with [`DETACHED_CODE`].
+struct DetachCode;
+
+impl Visitor for DetachCode {}
+impl<'s> CodeVisitorMut<'s> for DetachCode {
+    fn visit_mut(&mut self, code: &mut Code<'s>) {
+        code.repr = StrRef(DETACHED_CODE);
+    }
+}
+
+/// Run the function, returning the message of the panic if it panics. The panic hook is suppressed
+/// while the function runs, so that expected panics aren't reported.
+fn catch_panic<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(f));
+    panic::set_hook(hook);
+    result.map_err(|payload| {
+        let message = payload.downcast_ref::<&str>().map(|message| message.to_string());
+        let message = message.or_else(|| payload.downcast_ref::<String>().cloned());
+        message.unwrap_or_default()
+    })
+}
+
+
+
+// ====================
+// === Minimization ===
+// ====================
+
+/// Find a small input that violates the same invariant as the given input, by removing parts of it
+/// for as long as the failure persists. Lines are removed first, and then smaller runs of
+/// characters, down to single characters.
+pub fn minimize(code: &str, failure: &Failure) -> String {
+    let fails = |candidate: &[char]| {
+        let candidate: String = candidate.iter().collect();
+        check(&candidate).err().map_or(false, |other| other.is_same_kind(failure))
+    };
+    let mut chars: Vec<char> = code.chars().collect();
+    let mut lines: Vec<_> = code.split_inclusive('\n').collect();
+    let mut i = 0;
+    while i < lines.len() && lines.len() > 1 {
+        let candidate: Vec<char> = lines
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .flat_map(|(_, l)| l.chars())
+            .collect();
+        if fails(&candidate) {
+            lines.remove(i);
+            chars = candidate;
+        } else {
+            i += 1;
+        }
+    }
+    let mut chunk = chars.len() / 2;
+    while chunk > 0 {
+        let mut removed = false;
+        let mut start = 0;
+        while start < chars.len() {
+            let end = (start + chunk).min(chars.len());
+            let candidate = [&chars[..start], &chars[end..]].concat();
+            if fails(&candidate) {
+                chars = candidate;
+                removed = true;
+            } else {
+                start += chunk;
+            }
+        }
+        if !removed {
+            chunk /= 2;
+        }
+    }
+    chars.into_iter().collect()
+}
+
+
+
+// ========================
+// === Regression cases ===
+// ========================
+
+/// The directory containing the inputs that have been found to violate an invariant. Each file is
+/// checked by the tests of this crate.
+pub const REGRESSION_CASES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fuzz");
+
+/// Minimize the failing input, save it as a regression case in the given directory, and panic with
+/// a description of the failure.
+pub fn record_failure(code: &str, failure: &Failure, dir: &Path) -> ! {
+    let minimized = minimize(code, failure);
+    let failure = check(&minimized).err().unwrap_or_else(|| failure.clone());
+    let path = save_regression_case(&minimized, dir);
+    let path = match &path {
+        Ok(path) => path.display().to_string(),
+        Err(error) => format!("<not saved: {error}>"),
+    };
+    panic!("{failure}\nInput: {code:?}\nMinimized: {minimized:?}\nSaved as: {path}");
+}
+
+/// Save the input as a regression case in the given directory, named after a hash of its contents.
+/// Returns the path.
+pub fn save_regression_case(code: &str, dir: &Path) -> std::io::Result<PathBuf> {
+    use std::hash::Hash;
+    use std::hash::Hasher;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    code.hash(&mut hasher);
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{:016x}.enso", hasher.finish()));
+    std::fs::write(&path, code)?;
+    Ok(path)
+}
+
+/// Return the name and contents of each saved regression case.
+pub fn regression_cases() -> std::io::Result<Vec<(String, String)>> {
+    read_cases(REGRESSION_CASES)
+}
+
+/// Return the name and contents of each Enso file in the given directory, sorted by name. A missing
+/// directory contains no files.
+pub(crate) fn read_cases(dir: &str) -> std::io::Result<Vec<(String, String)>> {
+    let mut cases = vec![];
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(cases),
+        Err(error) => return Err(error),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().map_or(false, |extension| extension == "enso") {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            cases.push((name, std::fs::read_to_string(&path)?));
+        }
+    }
+    cases.sort();
+    Ok(cases)
+}
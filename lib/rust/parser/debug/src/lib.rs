@@ -29,6 +29,14 @@ use lexpr::Value;
 use std::collections::HashSet;
 
 
+// ==============
+// === Export ===
+// ==============
+
+pub mod corpus;
+pub mod fuzz;
+
+
 
 // =====================
 // === S-expressions ===
@@ -0,0 +1,52 @@
+//! Check that formatting the files of the corpus (see [`enso_parser_debug::corpus`]) is idempotent,
+//! and doesn't change the meaning of the code.
+
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(non_ascii_idents)]
+#![deny(unconditional_recursion)]
+#![warn(unsafe_code)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_parser_debug::to_s_expr;
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn corpus() {
+    let cases = enso_parser_debug::corpus::cases().unwrap();
+    assert!(!cases.is_empty());
+    for (name, code) in cases {
+        expect_format_preserves_meaning(&name, &code);
+    }
+}
+
+/// Check that formatting the given code produces code that is unchanged by formatting it again,
+/// and, if the code is free of syntax errors, that parses to an equivalent tree.
+fn expect_format_preserves_meaning(name: &str, code: &str) {
+    let parser = enso_parser::Parser::new();
+    let ast = parser.run(code);
+    let formatted = enso_parser::format::format(&ast);
+    let reparsed = parser.run(&formatted);
+    let reformatted = enso_parser::format::format(&reparsed);
+    assert_eq!(reformatted, formatted, "{name}: Formatting is not idempotent.");
+    if enso_parser::diagnostic::collect(&ast).is_empty() {
+        // Whitespace at the end of the input is represented as an empty line.
+        let code = code.trim_end_matches(' ');
+        let expected = to_s_expr(&parser.run(code), code);
+        let actual = to_s_expr(&reparsed, &formatted);
+        assert_eq!(actual, expected, "{name}: Formatting produced {formatted:?}.");
+    }
+}
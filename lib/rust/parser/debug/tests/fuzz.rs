@@ -0,0 +1,228 @@
+//! Check the invariants of the parser (see [`enso_parser_debug::fuzz::check`]) for generated
+//! inputs, and for the inputs that have previously been found to violate them.
+
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(non_ascii_idents)]
+#![deny(unconditional_recursion)]
+#![warn(unsafe_code)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_parser_debug::fuzz;
+use proptest::prelude::*;
+use proptest::sample::select;
+use proptest::test_runner::Config;
+use proptest::test_runner::TestError;
+use proptest::test_runner::TestRng;
+use proptest::test_runner::TestRunner;
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn regression_cases() {
+    for (name, code) in fuzz::regression_cases().unwrap() {
+        if let Err(failure) = fuzz::check(&code) {
+            panic!("{name}: {failure}\nInput: {code:?}");
+        }
+    }
+}
+
+/// Check generated inputs. When an input fails, it is minimized and saved to a temporary directory;
+/// to add it to the regression cases, move it to [`fuzz::REGRESSION_CASES`].
+///
+/// The inputs are generated from a fixed seed, so that the test is reproducible; the fuzz targets
+/// are used to explore new inputs.
+#[test]
+fn generated_inputs() {
+    let config = Config { cases: 512, failure_persistence: None, ..Config::default() };
+    let rng = TestRng::deterministic_rng(config.rng_algorithm);
+    let mut runner = TestRunner::new_with_rng(config, rng);
+    let result = runner.run(&module(), |code| {
+        fuzz::check(&code).map_err(|failure| TestCaseError::fail(failure.to_string()))
+    });
+    match result {
+        Ok(()) => (),
+        Err(TestError::Fail(_, code)) => match fuzz::check(&code) {
+            Err(failure) => {
+                let dir = std::env::temp_dir().join("enso-parser-fuzz");
+                fuzz::record_failure(&code, &failure, &dir)
+            }
+            Ok(()) => panic!("Nondeterministic failure: {code:?}"),
+        },
+        Err(TestError::Abort(reason)) => panic!("{reason}"),
+    }
+}
+
+
+
+// ==================
+// === Generation ===
+// ==================
+
+/// Generate syntactically plausible Enso source code: a module made of common statement forms,
+/// nested blocks, and expressions, with a few random edits to exercise error recovery.
+fn module() -> impl Strategy<Value = String> {
+    let edits = prop::collection::vec((any::<prop::sample::Index>(), edit()), 0..3);
+    (prop::collection::vec(block_line(), 0..6), edits).prop_map(|(lines, edits)| {
+        let mut code = String::new();
+        lines.iter().for_each(|line| line.render(0, &mut code));
+        let mut chars: Vec<char> = code.chars().collect();
+        for (index, edit) in edits {
+            let at = index.index(chars.len() + 1);
+            match edit {
+                Edit::Delete => drop(chars.drain(at..(at + 1).min(chars.len()))),
+                Edit::Insert(text) => drop(chars.splice(at..at, text.chars())),
+            }
+        }
+        chars.into_iter().collect()
+    })
+}
+
+/// A change to the generated code.
+#[derive(Clone, Debug)]
+enum Edit {
+    Delete,
+    Insert(&'static str),
+}
+
+fn edit() -> impl Strategy<Value = Edit> {
+    const INSERTED: &[&str] = &[
+        "(", ")", "[", "]", "{", "}", "\"", "'", "`", "\\", "#", "##", "->", "=", ",", ".", "@",
+        " ", "    ", "\n", "\r\n", "\t", "é", "🦀",
+    ];
+    prop_oneof![Just(Edit::Delete), select(INSERTED).prop_map(Edit::Insert)]
+}
+
+
+// === Blocks ===
+
+/// A line of a block, with an optional child block.
+#[derive(Clone, Debug)]
+struct Line {
+    statement: String,
+    block:     Vec<Line>,
+}
+
+impl Line {
+    fn render(&self, indent: usize, out: &mut String) {
+        out.extend(std::iter::repeat(' ').take(indent));
+        out.push_str(&self.statement);
+        out.push('\n');
+        self.block.iter().for_each(|line| line.render(indent + 4, out));
+    }
+}
+
+fn block_line() -> impl Strategy<Value = Line> {
+    let leaf = statement().prop_map(|statement| Line { statement, block: vec![] });
+    leaf.prop_recursive(3, 24, 4, |line| {
+        let block = prop::collection::vec(line, 1..4);
+        (block_head(), block).prop_map(|(statement, block)| Line { statement, block })
+    })
+}
+
+/// A statement that may be followed by a child block.
+fn block_head() -> impl Strategy<Value = String> {
+    prop_oneof![
+        (ident(), prop::collection::vec(ident(), 0..3))
+            .prop_map(|(name, args)| format!("{name} {} =", args.join(" "))),
+        constructor().prop_map(|name| format!("type {name}")),
+        expression().prop_map(|scrutinee| format!("case {scrutinee} of")),
+        expression(),
+        Just("if a then".to_owned()),
+        Just("x = (".to_owned()),
+    ]
+}
+
+fn statement() -> impl Strategy<Value = String> {
+    let definition = prop_oneof![
+        (ident(), prop::collection::vec(ident(), 1..3), expression())
+            .prop_map(|(name, args, body)| format!("{name} {} = {body}", args.join(" "))),
+        (expression(), expression()).prop_map(|(pattern, value)| format!("{pattern} -> {value}")),
+        (constructor(), prop::collection::vec(ident(), 0..3))
+            .prop_map(|(name, fields)| format!("{name} {}", fields.join(" "))),
+        (ident(), expression()).prop_map(|(name, value)| format!("@{name} {value}")),
+        Just("foreign js f x = \"\"\"\n    return x".to_owned()),
+    ];
+    let other = prop_oneof![
+        (constructor(), constructor()).prop_map(|(a, b)| format!("from Standard.{a} import {b}")),
+        constructor().prop_map(|name| format!("import Standard.Base.{name}")),
+        Just("## Documentation.".to_owned()),
+        Just("# Comment.".to_owned()),
+        Just(String::new()),
+    ];
+    prop_oneof![
+        3 => expression(),
+        3 => (ident(), expression()).prop_map(|(name, value)| format!("{name} = {value}")),
+        2 => definition,
+        1 => other,
+    ]
+}
+
+
+// === Expressions ===
+
+fn expression() -> impl Strategy<Value = String> {
+    let leaf = prop_oneof![ident(), constructor(), number(), text()];
+    leaf.prop_recursive(4, 24, 3, |e| {
+        let spacing = select(&["", " "][..]);
+        let operators = prop_oneof![
+            (operator(), e.clone()).prop_map(|(op, e)| format!("({op} {e})")),
+            (e.clone(), operator()).prop_map(|(e, op)| format!("({e} {op})")),
+            (operator(), e.clone()).prop_map(|(op, e)| format!("{op}{e}")),
+            (e.clone(), constructor()).prop_map(|(e, t)| format!("{e} : {t}")),
+            (e.clone(), ident()).prop_map(|(e, field)| format!("{e}.{field}")),
+        ];
+        let syntax = prop_oneof![
+            e.clone().prop_map(|e| format!("({e})")),
+            prop::collection::vec(e.clone(), 0..4).prop_map(|es| format!("[{}]", es.join(", "))),
+            (e.clone(), e.clone()).prop_map(|(a, b)| format!("({a}, {b})")),
+            (ident(), e.clone()).prop_map(|(x, body)| format!("\\{x}-> {body}")),
+            (e.clone(), e.clone(), e.clone())
+                .prop_map(|(c, t, f)| format!("if {c} then {t} else {f}")),
+            e.clone().prop_map(|e| format!("'text `{e}` text'")),
+        ];
+        prop_oneof![
+            3 => (e.clone(), e.clone()).prop_map(|(f, x)| format!("{f} {x}")),
+            3 => (e.clone(), operator(), spacing, e)
+                .prop_map(|(a, op, sp, b)| format!("{a}{sp}{op}{sp}{b}")),
+            2 => operators,
+            2 => syntax,
+        ]
+    })
+}
+
+fn ident() -> impl Strategy<Value = String> {
+    const IDENTS: &[&str] = &["a", "b", "x", "foo", "bar_baz", "self", "_", "x1", "a'"];
+    select(IDENTS).prop_map(str::to_owned)
+}
+
+fn constructor() -> impl Strategy<Value = String> {
+    select(&["Foo", "Bar", "Nothing", "Self", "Type_1"][..]).prop_map(str::to_owned)
+}
+
+fn number() -> impl Strategy<Value = String> {
+    select(&["0", "1", "42", "1.5", "1_000", "0x1F", "2_0.5", "-1"][..]).prop_map(str::to_owned)
+}
+
+fn text() -> impl Strategy<Value = String> {
+    const TEXTS: &[&str] = &["\"a\"", "''", "'b c'", "'\\n'", "\"\"\"", "'''", "'\\u{1F600}'"];
+    select(TEXTS).prop_map(str::to_owned)
+}
+
+fn operator() -> impl Strategy<Value = String> {
+    const OPERATORS: &[&str] =
+        &["+", "-", "*", "/", "==", "!=", "<", ">=", "&&", "||", "<|", "|>", "..", "~", "=", "<-"];
+    select(OPERATORS).prop_map(str::to_owned)
+}
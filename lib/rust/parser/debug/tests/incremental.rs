@@ -0,0 +1,68 @@
+//! Check that incrementally reparsing the files of the corpus (see [`enso_parser_debug::corpus`])
+//! after various edits produces the same result as parsing them from scratch.
+
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(non_ascii_idents)]
+#![deny(unconditional_recursion)]
+#![warn(unsafe_code)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_text::Byte;
+use enso_text::Change;
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn corpus() {
+    let cases = enso_parser_debug::corpus::cases().unwrap();
+    assert!(!cases.is_empty());
+    for (name, code) in cases {
+        expect_incremental_reparse(&name, &code);
+    }
+}
+
+/// Check that incrementally reparsing the given code after various edits produces the same result
+/// as parsing it from scratch.
+///
+/// Each edit produces the given code from a variant of it with some lines removed, some lines
+/// inserted, or with part of a line replaced.
+fn expect_incremental_reparse(name: &str, code: &str) {
+    const MAX_LINES: usize = 32;
+    let parser = enso_parser::Parser::new();
+    let expected = parser.run(code);
+    let line_starts: Vec<_> = std::iter::once(0)
+        .chain(code.match_indices('\n').map(|(i, _)| i + 1))
+        .take(MAX_LINES)
+        .collect();
+    let mut edits = vec![];
+    for (i, &start) in line_starts.iter().enumerate() {
+        for &end in line_starts[i..].iter().chain([&code.len()]) {
+            edits.push((start, end, ""));
+        }
+        let line_end = line_starts.get(i + 1).copied().unwrap_or(code.len());
+        let middle = code[start..line_end].char_indices().nth(1).map_or(start, |(i, _)| start + i);
+        edits.push((middle, line_end, "x\n\n  y"));
+        edits.push((start, start, "## Doc.\n"));
+    }
+    for (start, end, replaced) in edits {
+        let old_code = [&code[..start], replaced, &code[end..]].concat();
+        let previous = parser.run(&old_code);
+        let range = (Byte(start)..Byte(start + replaced.len())).into();
+        let edit = Change { range, text: &code[start..end] };
+        let reparsed = parser.reparse(previous, &[edit], code);
+        assert_eq!(reparsed, expected, "{name}: Reparsing after editing {old_code:?}.");
+    }
+}
@@ -0,0 +1,158 @@
+//! Check that the JSON serialization of parsed code conforms to the AST's JSON Schema.
+
+// === Features ===
+#![feature(let_chains)]
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(non_ascii_idents)]
+#![deny(unconditional_recursion)]
+#![warn(unsafe_code)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_reflect::Reflect;
+use serde_json::Value;
+
+
+
+// =============
+// === Tests ===
+// =============
+
+const CASES: &[&str] = &[
+    "main = foo 1 'bar' x.y",
+    concat!(
+        "type Maybe a\n    Some value:a\n    None\n\n",
+        "    is_some self = case self of\n        Some _ -> True\n        None -> False",
+    ),
+    concat!(
+        "from Standard.Base import all\nimport Standard.Table.Data.Table\n\n",
+        "f (x : Integer = 1) ~y = x + y * 2.5",
+    ),
+    "x = [1, 2, 3] . map (+ 1)\n    . filter _.is_even",
+    "text = '''\n    multi\n    line \\n\n\"a\\u{1F600}b `interp` c\"",
+    "@Builtin_Type\nforeign js foo a = \"\"\"\n    return a\nf = \\x -> x\ny = (\nz = ]",
+    "## Documentation\n   comment.\nvalue = 0x_ff + 1_000 - -5",
+    "a = 'bad \\q escape' + _",
+];
+
+#[test]
+fn parsed_code_conforms_to_schema() {
+    let schema = enso_metamodel_json_schema::schema(enso_parser::syntax::Tree::reflect());
+    let parser = enso_parser::Parser::new();
+    for case in CASES {
+        let ast = parser.run(case);
+        let json = serde_json::to_value(&ast).unwrap();
+        if let Err(error) = validate(&schema, &schema, &json, "$") {
+            panic!("{case:?}: {error}");
+        }
+    }
+}
+
+
+
+// =================
+// === Validator ===
+// =================
+
+/// Check that the value conforms to the schema. Supports the subset of JSON Schema used by
+/// `enso_metamodel_json_schema`.
+fn validate(root: &Value, schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+    let fail = |reason: &str| Err(format!("{path}: {reason}: {value}"));
+    if let Some(reference) = schema.get("$ref") {
+        let name = reference.as_str().unwrap().strip_prefix("#/$defs/").unwrap();
+        return validate(root, &root["$defs"][name], value, path);
+    }
+    if let Some(constant) = schema.get("const") && constant != value {
+        return fail(&format!("expected {constant}"));
+    }
+    if let Some(options) = schema.get("oneOf") {
+        let options = options.as_array().unwrap();
+        let results: Vec<_> = options.iter().map(|s| validate(root, s, value, path)).collect();
+        let valid = results.iter().filter(|result| result.is_ok()).count();
+        if valid != 1 {
+            // Report the failures of alternatives that failed below this level.
+            let nested = results.into_iter().filter_map(Result::err);
+            let nested: Vec<_> =
+                nested.filter(|error| !error.starts_with(&format!("{path}:"))).collect();
+            return fail(&format!("matches {valid} alternatives of oneOf: {nested:?}"));
+        }
+    }
+    if let Some(options) = schema.get("anyOf") {
+        let options = options.as_array().unwrap();
+        if !options.iter().any(|s| validate(root, s, value, path).is_ok()) {
+            let errors: Vec<_> =
+                options.iter().filter_map(|s| validate(root, s, value, path).err()).collect();
+            return fail(&format!("matches no alternative of anyOf: {errors:?}"));
+        }
+    }
+    match schema.get("type").and_then(Value::as_str) {
+        None => Ok(()),
+        Some("null") if value.is_null() => Ok(()),
+        Some("boolean") if value.is_boolean() => Ok(()),
+        Some("string") => {
+            let Some(string) = value.as_str() else { return fail("expected string") };
+            let len = string.chars().count() as u64;
+            let min = schema.get("minLength").and_then(Value::as_u64).unwrap_or(0);
+            let max = schema.get("maxLength").and_then(Value::as_u64).unwrap_or(u64::MAX);
+            match (min..=max).contains(&len) {
+                true => Ok(()),
+                false => fail("string length out of range"),
+            }
+        }
+        Some("integer") => {
+            let Some(number) = value.as_i64().or_else(|| value.as_u64().map(|n| n as i64))
+                else { return fail("expected integer") };
+            let min = schema.get("minimum").and_then(Value::as_i64).unwrap_or(i64::MIN);
+            let max = schema.get("maximum").and_then(Value::as_i64).unwrap_or(i64::MAX);
+            match (min..=max).contains(&number) {
+                true => Ok(()),
+                false => fail("integer out of range"),
+            }
+        }
+        Some("array") => {
+            let Some(elements) = value.as_array() else { return fail("expected array") };
+            let prefix = schema.get("prefixItems").and_then(Value::as_array);
+            let prefix = prefix.map(|prefix| &prefix[..]).unwrap_or_default();
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+                && (elements.len() as u64) < min {
+                return fail("too few elements");
+            }
+            for (i, element) in elements.iter().enumerate() {
+                let path = format!("{path}[{i}]");
+                match (prefix.get(i), &schema["items"]) {
+                    (Some(item), _) => validate(root, item, element, &path)?,
+                    (None, Value::Bool(false)) => return fail("too many elements"),
+                    (None, Value::Null) => (),
+                    (None, item) => validate(root, item, element, &path)?,
+                }
+            }
+            Ok(())
+        }
+        Some("object") => {
+            let Some(object) = value.as_object() else { return fail("expected object") };
+            let properties = schema["properties"].as_object().unwrap();
+            for name in schema["required"].as_array().unwrap() {
+                if !object.contains_key(name.as_str().unwrap()) {
+                    return fail(&format!("missing property {name}"));
+                }
+            }
+            for (name, property) in object {
+                let path = format!("{path}.{name}");
+                match properties.get(name) {
+                    Some(schema) => validate(root, schema, property, &path)?,
+                    None => return fail(&format!("unexpected property {name}")),
+                }
+            }
+            Ok(())
+        }
+        Some(type_) => fail(&format!("expected {type_}")),
+    }
+}
@@ -62,7 +62,7 @@ fn application() {
 fn parentheses() {
     test("(a b)", block![(Group (App (Ident a) (Ident b)))]);
     test("x)", block![(App (Ident x) (Invalid))]);
-    test("(x", block![(App (Invalid) (Ident x))]);
+    test("(x", block![(Invalid)]);
     test("(a) (b)", block![(App (Group (Ident a)) (Group (Ident b)))]);
     #[rustfmt::skip]
     test("((a b) c)", block![
@@ -896,6 +896,9 @@ fn metadata_parsing() {
     let (meta, code) = enso_parser::metadata::parse(code).unwrap();
     let _ast = parse(code);
     let _meta: enso_parser::metadata::Metadata = meta.unwrap();
+    let input = metadata::ORDERS_WITH_METADATA;
+    let (code, section) = enso_parser::metadata::Section::extract(input);
+    assert_eq!(section.unwrap().unwrap().attach(code), input);
 }
 
 
@@ -1388,6 +1391,78 @@ fn skip() {
 
 
 
+// === User-defined macros ===
+
+fn user_macro_parser() -> enso_parser::Parser {
+    use enso_parser::macros::pattern::*;
+    use enso_parser::macros::*;
+    let mut parser = enso_parser::Parser::new();
+    let given_show = [
+        SegmentDefinition::new("given", everything()),
+        SegmentDefinition::new("show", identifier()),
+    ];
+    parser.register_macro(Context::Expression, Definition::new(given_show).unwrap());
+    let rule = Definition::new([SegmentDefinition::new("rule", everything())]).unwrap();
+    parser.register_macro(Context::Statement, rule);
+    let quote = Definition::new([SegmentDefinition::new("quote", everything())]).unwrap();
+    let quote = quote.with_body(|segments, precedence| {
+        use enso_parser::syntax::Tree;
+        let MatchedSegment { header, result } = segments.first().clone();
+        let header = enso_parser::syntax::tree::to_ast(header);
+        Tree::app(header, Tree::group(None, precedence.resolve(result.tokens()), None))
+    });
+    parser.register_macro(Context::Expression, quote);
+    parser
+}
+
+fn test_user_macros(code: &str, expect: lexpr::Value) {
+    let ast = user_macro_parser().run(code);
+    let ast_s_expr = to_s_expr(&ast, code);
+    assert_eq!(ast_s_expr.to_string(), expect.to_string(), "{:?}", &ast);
+}
+
+#[test]
+fn user_defined_macros() {
+    #[rustfmt::skip]
+    let cases = [
+        ("x = given a b show c",
+         block![(Assignment (Ident x) "=" (MultiSegmentApp
+            #(((Ident given) (App (Ident a) (Ident b))) ((Ident show) (Ident c)))))]),
+        ("rule a b", block![(MultiSegmentApp #(((Ident rule) (App (Ident a) (Ident b)))))]),
+        ("x = rule a", block![(Assignment (Ident x) "=" (App (Ident rule) (Ident a)))]),
+        ("given a", block![(Invalid)]),
+        ("x = quote a b", block![(Assignment (Ident x) "="
+            (App (Ident quote) (Group (App (Ident a) (Ident b)))))]),
+    ];
+    cases.into_iter().for_each(|(code, expected)| test_user_macros(code, expected));
+}
+
+#[test]
+fn user_defined_macro_without_closing_token() {
+    use enso_parser::macros::pattern::*;
+    use enso_parser::macros::*;
+    let mut parser = enso_parser::Parser::new();
+    let segments =
+        [SegmentDefinition::new("open", everything()), SegmentDefinition::new("", nothing())];
+    parser.register_macro(Context::Expression, Definition::new(segments).unwrap());
+    let code = "x = open a\ny = 2";
+    let ast = parser.run(code);
+    #[rustfmt::skip]
+    let expected = block![
+        (Assignment (Ident x) "=" (App (Invalid) (Ident a)))
+        (Assignment (Ident y) "=" (Number () "2" ()))];
+    assert_eq!(to_s_expr(&ast, code).to_string(), expected.to_string(), "{:?}", &ast);
+}
+
+#[test]
+fn user_defined_macros_not_registered_by_default() {
+    test!("x = given a show c",
+        (Assignment (Ident x) "="
+         (App (App (App (Ident given) (Ident a)) (Ident show)) (Ident c))));
+}
+
+
+
 // =========================
 // === Scalability Tests ===
 // =========================
@@ -1450,6 +1525,47 @@ fn unmatched_delimiter() {
     expect_invalid_node("foo = ]");
 }
 
+#[test]
+fn unclosed_macro_recovery() {
+    #[rustfmt::skip]
+    test_recovery("x = (a + b\ny = 2", block![
+        (Assignment (Ident x) "=" (Invalid))
+        (Assignment (Ident y) "=" (Number () "2" ()))],
+        &[lexpr::sexp![(Group (OprApp (Ident a) (Ok "+") (Ident b)))]]);
+    test_recovery("x = [1, 2", block![(Assignment (Ident x) "=" (Invalid))], &[
+        lexpr::sexp![(Array (Number () "1" ()) #(("," (Number () "2" ()))))],
+    ]);
+    test_recovery("x = case a", block![(Assignment (Ident x) "=" (Invalid))], &[
+        lexpr::sexp![(CaseOf (Ident a) #())],
+    ]);
+    #[rustfmt::skip]
+    test_recovery("x = if (a then b", block![
+        (Assignment (Ident x) "="
+         (MultiSegmentApp #(((Ident if) (Invalid)) ((Ident then) (Ident b)))))],
+        &[lexpr::sexp![(Group (Ident a))]]);
+    #[rustfmt::skip]
+    test_recovery("main = (\n    x = 1\n    y = 2", block![
+        (Assignment (Ident main) "=" (App (Invalid) (BodyBlock #(
+            (Assignment (Ident x) "=" (Number () "1" ()))
+            (Assignment (Ident y) "=" (Number () "2" ()))))))],
+        &[lexpr::sexp![(Group ())]]);
+    #[rustfmt::skip]
+    test_recovery("x = foo (a\n    b\ny = 2", block![
+        (Assignment (Ident x) "="
+         (ArgumentBlockApplication (App (Ident foo) (Invalid)) #((Ident b))))
+        (Assignment (Ident y) "=" (Number () "2" ()))],
+        &[lexpr::sexp![(Group (Ident a))]]);
+    expect_invalid_node("x = if a\ny = 2");
+}
+
+#[test]
+fn operator_error_recovery() {
+    #[rustfmt::skip]
+    test("x = a + + b\ny = 2", block![
+        (Assignment (Ident x) "=" (OprApp (Ident a) (Err (#("+" "+"))) (Ident b)))
+        (Assignment (Ident y) "=" (Number () "2" ()))]);
+}
+
 #[test]
 fn unexpected_special_operator() {
     expect_invalid_node("foo = 1, 2");
@@ -1534,6 +1650,49 @@ fn invalid_unspaced_operator_sequence() {
 
 
 
+// ===========================
+// === Fuzzer-Found Inputs ===
+// ===========================
+
+// Minimized inputs found by the fuzzer (see `enso_parser_debug::fuzz`). Each of them used to
+// violate an invariant of the parser; the full set of such inputs is also checked by
+// `tests/fuzz.rs`.
+
+#[test]
+fn fuzzed_blank_first_line() {
+    test(" ", block![()]);
+}
+
+#[test]
+fn fuzzed_number_base_followed_by_space() {
+    expect_parses("0x 1");
+}
+
+#[test]
+fn fuzzed_text_splices() {
+    test!("'`a`' b", (App (TextLiteral #((Splice (Ident a)))) (Ident b)));
+    expect_parses("'''` `");
+    expect_parses("'`'`'`");
+}
+
+#[test]
+fn fuzzed_backslash_before_newline_in_text() {
+    test("('\\\n", block![(Invalid) ()]);
+}
+
+#[test]
+fn fuzzed_type_definitions() {
+    expect_invalid_node("type\n a\n");
+    expect_parses("type F\n F=a\n");
+}
+
+#[test]
+fn fuzzed_operator_missing_lhs() {
+    expect_parses("o z-> +");
+}
+
+
+
 // ====================
 // === Test Support ===
 // ====================
@@ -1580,10 +1739,10 @@ fn parse(code: &str) -> enso_parser::syntax::tree::Tree {
     let mut locations = enso_parser::source::code::debug::LocationCheck::new();
     enso_parser_debug::validate_spans(&ast, expected_span, &mut locations);
     locations.check(code);
+    expect_classified_diagnostics(code, &ast);
     ast
 }
 
-
 // === Testing inputs containing syntax errors ===
 
 #[derive(Debug, Eq, PartialEq, Default, Copy, Clone)]
@@ -1596,6 +1755,10 @@ impl Errors {
     fn collect(code: &str) -> Self {
         let ast = parse(code);
         expect_tree_representing_code(code, &ast);
+        Self::collect_from(&ast)
+    }
+
+    fn collect_from(ast: &enso_parser::syntax::tree::Tree) -> Self {
         let errors = core::cell::Cell::new(Errors::default());
         ast.map(|tree| match &*tree.variant {
             enso_parser::syntax::tree::Variant::Invalid(_) => {
@@ -1610,6 +1773,37 @@ impl Errors {
     }
 }
 
+/// Check that every error in the tree is reported with a specific kind, and that the diagnostics
+/// are in source order.
+fn expect_classified_diagnostics(code: &str, ast: &enso_parser::syntax::tree::Tree) {
+    use enso_parser::diagnostic::ErrorKind;
+    let diagnostics = enso_parser::diagnostic::collect(ast);
+    for diagnostic in &diagnostics {
+        assert_ne!(diagnostic.kind, ErrorKind::Unknown, "Unclassified error in {code:?}.");
+    }
+    assert!(diagnostics.windows(2).all(|pair| pair[0].span.start <= pair[1].span.start));
+}
+
+/// Check that the input parses to the AST represented by the given S-expression, and that the
+/// outermost `Invalid` nodes in it, in source order, contain the recovered ASTs given.
+fn test_recovery(code: &str, expect: lexpr::Value, recovered: &[lexpr::Value]) {
+    use enso_parser::syntax::tree::*;
+    let ast = parse(code);
+    assert_eq!(to_s_expr(&ast, code).to_string(), expect.to_string(), "{:?}", &ast);
+    fn collect_invalid(item: enso_parser::syntax::item::Ref, code: &str, out: &mut Vec<String>) {
+        if let enso_parser::syntax::item::Ref::Tree(tree) = item {
+            match &*tree.variant {
+                Variant::Invalid(Invalid { ast, .. }) => out.push(to_s_expr(ast, code).to_string()),
+                _ => tree.visit_items(|item| collect_invalid(item, code, out)),
+            }
+        }
+    }
+    let mut actual = vec![];
+    ast.visit_items(|item| collect_invalid(item, code, &mut actual));
+    let expected: Vec<_> = recovered.iter().map(|ast| ast.to_string()).collect();
+    assert_eq!(actual, expected);
+}
+
 /// Checks that an input contains an `Invalid` node somewhere.
 fn expect_invalid_node(code: &str) {
     let errors = Errors::collect(code);
@@ -1622,6 +1816,13 @@ fn expect_multiple_operator_error(code: &str) {
     assert!(errors.multiple_operator, "{:?}", enso_parser::Parser::new().run(code));
 }
 
+/// Check that the input satisfies the invariants checked for all inputs, without checking whether
+/// it contains syntax errors.
+fn expect_parses(code: &str) {
+    let ast = parse(code);
+    expect_tree_representing_code(code, &ast);
+}
+
 /// Check that the input can be parsed, and doesn't yield any `Invalid` nodes.
 fn expect_valid(code: &str) {
     let errors = Errors::collect(code);
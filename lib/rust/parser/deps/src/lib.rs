@@ -0,0 +1,523 @@
+//! Module dependency graphs of Enso projects.
+//!
+//! A [`Graph`] has a [`Module`] for each source file of the loaded [`Project`]s, and for each
+//! directory of sources without a corresponding file. The [`Dependency`]s of a module are its
+//! `import` and `export` statements; each is resolved to the module it refers to, which is the
+//! longest prefix of its qualified path naming a loaded module: `import Standard.Base.Data.Vector`
+//! refers to the type `Vector` in the module `Standard.Base.Data.Vector`. Paths starting with
+//! `project` are relative to the project containing the module. Paths not naming a loaded module
+//! are unresolved; `polyglot` imports refer to the foreign language, not a module.
+//!
+//! The graph can be checked for [cycles](Graph::cycles) and
+//! [unused exports](Graph::unused_exports), and rendered with the [`output`] module.
+
+// === Features ===
+#![feature(let_chains)]
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_parser::syntax::tree::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub mod output;
+pub mod project;
+
+pub use project::Error;
+pub use project::Project;
+
+
+
+// ==============
+// === Module ===
+// ==============
+
+/// Identifies a [`Module`] in a [`Graph`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ModuleId(pub usize);
+
+/// A module of a project.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Module {
+    /// The qualified name of the module, e.g. `Standard.Base.Data.Vector`.
+    pub name:         String,
+    /// The source file of the module; `None` for a directory without a corresponding file.
+    pub file:         Option<PathBuf>,
+    /// The imports and exports of the module, in source order.
+    pub dependencies: Vec<Dependency>,
+}
+
+/// An `import` or `export` statement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dependency {
+    /// Whether this is an import or an export.
+    pub kind:   Kind,
+    /// The qualified path, with a leading `project` replaced by the name of the project.
+    pub path:   String,
+    /// What the path refers to. Until the graph is [resolved](Graph::resolve), paths of modules
+    /// are [`Target::Unresolved`].
+    pub target: Target,
+    /// The names listed in a `from ... import` or `from ... export` statement.
+    pub names:  Vec<String>,
+    /// Whether this is a statement of the form `from ... import all`.
+    pub all:    bool,
+    /// The names excluded by a `hiding` clause.
+    pub hiding: Vec<String>,
+    /// The alias given with `as`.
+    pub alias:  Option<String>,
+    /// The line of the statement, starting from 1.
+    pub line:   u32,
+}
+
+/// Whether a [`Dependency`] is an import or an export.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Import,
+    Export,
+}
+
+/// What the path of a [`Dependency`] refers to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Target {
+    /// A module of the graph.
+    Module(ModuleId),
+    /// An entity of a foreign language, imported with `polyglot <language> import`.
+    Polyglot(String),
+    /// No loaded module.
+    Unresolved,
+}
+
+impl Dependency {
+    /// Return the name the dependency binds or exports, if it refers to a single entity: the alias,
+    /// or the last segment of the path of an `import` or `export` that isn't of the `from` form.
+    pub fn entity_name(&self) -> Option<&str> {
+        let is_from = self.all || !self.names.is_empty();
+        match &self.alias {
+            Some(alias) => Some(alias),
+            None if is_from => None,
+            None => self.path.rsplit('.').next(),
+        }
+    }
+}
+
+/// Return the imports and exports of a module. Dependencies are not resolved. Paths starting with
+/// `project` are made relative to the given project name, e.g. `Standard.Base`.
+pub fn dependencies(tree: &Tree, project: &str) -> Vec<Dependency> {
+    let Variant::BodyBlock(BodyBlock { statements }) = &*tree.variant else { return vec![] };
+    let mut dependencies = vec![];
+    for statement in statements.iter().filter_map(|line| line.expression.as_ref()) {
+        let mut statement = statement;
+        while let Variant::Documented(Documented { expression: Some(expression), .. })
+        | Variant::Private(Private { body: Some(expression), .. }) = &*statement.variant
+        {
+            statement = expression;
+        }
+        let line = statement.span.range().start.line + 1;
+        let dependency = match &*statement.variant {
+            Variant::Import(import) => {
+                let target = match &import.polyglot {
+                    Some(polyglot) =>
+                        Target::Polyglot(polyglot.body.iter().map(Tree::trimmed_code).collect()),
+                    None => Target::Unresolved,
+                };
+                let from = import.from.as_ref();
+                let segments = [from, Some(&import.import), import.as_.as_ref()];
+                let all = import.all.is_some();
+                dependency(Kind::Import, segments, &import.hiding, all, target, project)
+            }
+            Variant::Export(export) => {
+                let from = export.from.as_ref();
+                let segments = [from, Some(&export.export), export.as_.as_ref()];
+                let all = export.all.is_some();
+                dependency(Kind::Export, segments, &export.hiding, all, Target::Unresolved, project)
+            }
+            _ => continue,
+        };
+        dependencies.extend(dependency.map(|dependency| Dependency { line, ..dependency }));
+    }
+    dependencies
+}
+
+/// Build a dependency from the `from`, `import`/`export`, and `as` segments of a statement.
+fn dependency(
+    kind: Kind,
+    [from, body, as_]: [Option<&MultiSegmentAppSegment>; 3],
+    hiding: &Option<MultiSegmentAppSegment>,
+    all: bool,
+    target: Target,
+    project: &str,
+) -> Option<Dependency> {
+    let body = body?.body.as_ref();
+    let (path, names) = match from {
+        Some(from) => (from.body.as_ref()?, body.map(sequence).unwrap_or_default()),
+        None => (body?, vec![]),
+    };
+    let path = path.trimmed_code();
+    let path = match path.strip_prefix("project") {
+        Some(rest) if rest.is_empty() || rest.starts_with('.') => format!("{project}{rest}"),
+        _ => path,
+    };
+    let hiding = hiding.as_ref().and_then(|hiding| hiding.body.as_ref()).map(sequence);
+    let alias = as_.and_then(|as_| as_.body.as_ref()).map(Tree::trimmed_code);
+    let hiding = hiding.unwrap_or_default();
+    Some(Dependency { kind, path, target, names, all, hiding, alias, line: 0 })
+}
+
+/// Return the names of a comma-separated list, such as `a, b, c`.
+fn sequence(tree: &Tree) -> Vec<String> {
+    match &*tree.variant {
+        Variant::OprApp(OprApp { lhs, opr: Ok(opr), rhs }) if opr.properties.is_sequence() =>
+            lhs.iter().chain(rhs).flat_map(sequence).collect(),
+        _ => vec![tree.trimmed_code()],
+    }
+}
+
+
+
+// =============
+// === Graph ===
+// =============
+
+/// The modules of one or more projects, and the dependencies between them.
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    /// The modules, in the order they were added.
+    pub modules: Vec<Module>,
+    index:       HashMap<String, ModuleId>,
+}
+
+/// An exported name not imported by any module of the graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnusedExport {
+    /// The exporting module.
+    pub module: ModuleId,
+    /// The exported name.
+    pub name:   String,
+    /// The line of the export statement, starting from 1.
+    pub line:   u32,
+}
+
+impl Graph {
+    /// Load the modules of the given projects, and resolve their dependencies.
+    pub fn load(projects: &[Project]) -> Result<Self, Error> {
+        let mut graph = Self::default();
+        let parser = enso_parser::Parser::new();
+        for project in projects {
+            for (name, file) in project.modules()? {
+                let id = graph.add_module(name, file.clone());
+                if let Some(file) = file {
+                    let input = std::fs::read_to_string(&file)
+                        .map_err(|error| Error::Io(format!("{}: {error}", file.display())))?;
+                    let (code, _metadata) = enso_parser::metadata::extract(&input);
+                    let tree = parser.run(code);
+                    graph.modules[id.0].dependencies =
+                        dependencies(&tree, &project.qualified_name());
+                }
+            }
+        }
+        graph.resolve();
+        Ok(graph)
+    }
+
+    /// Add a module with the given qualified name, if it is not already in the graph. Return its
+    /// ID.
+    pub fn add_module(&mut self, name: impl Into<String>, file: Option<PathBuf>) -> ModuleId {
+        let name = name.into();
+        if let Some(&id) = self.index.get(&name) {
+            return id;
+        }
+        let id = ModuleId(self.modules.len());
+        self.index.insert(name.clone(), id);
+        self.modules.push(Module { name, file, dependencies: vec![] });
+        id
+    }
+
+    /// Return the module with the given ID.
+    pub fn module(&self, id: ModuleId) -> &Module {
+        &self.modules[id.0]
+    }
+
+    /// Return the module with the given qualified name.
+    pub fn lookup(&self, name: &str) -> Option<ModuleId> {
+        self.index.get(name).copied()
+    }
+
+    /// Resolve the paths of the dependencies of all modules to the modules they refer to.
+    pub fn resolve(&mut self) {
+        let mut modules = std::mem::take(&mut self.modules);
+        for dependency in modules.iter_mut().flat_map(|module| &mut module.dependencies) {
+            if let Target::Polyglot(_) = dependency.target {
+                continue;
+            }
+            let mut path = dependency.path.as_str();
+            dependency.target = loop {
+                if let Some(id) = self.lookup(path) {
+                    break Target::Module(id);
+                }
+                match path.rsplit_once('.') {
+                    Some((prefix, _)) => path = prefix,
+                    None => break Target::Unresolved,
+                }
+            };
+        }
+        self.modules = modules;
+    }
+
+    /// Return the modules the given module depends on, with the kind of each dependency.
+    pub fn edges(&self, id: ModuleId) -> impl Iterator<Item = (Kind, ModuleId)> + '_ {
+        self.module(id).dependencies.iter().filter_map(|dependency| match dependency.target {
+            Target::Module(target) => Some((dependency.kind, target)),
+            _ => None,
+        })
+    }
+
+    /// Return the import cycles: the sets of two or more modules that each depend on the others.
+    /// A module referring to itself, e.g. to export the constructors of its type, is not a cycle.
+    /// Each cycle is sorted by module ID; cycles are ordered by their first module.
+    pub fn cycles(&self) -> Vec<Vec<ModuleId>> {
+        self.strongly_connected(|_| true)
+    }
+
+    /// Return the cycles of exports, as [`cycles`](Self::cycles) does for all dependencies. An
+    /// export cycle makes the names exported by its modules depend on the order the modules are
+    /// loaded in.
+    pub fn export_cycles(&self) -> Vec<Vec<ModuleId>> {
+        self.strongly_connected(|kind| kind == Kind::Export)
+    }
+
+    /// Return the strongly-connected components of the graph of the dependencies of the given
+    /// kinds that are cycles, using Tarjan's algorithm.
+    fn strongly_connected(&self, include: impl Fn(Kind) -> bool) -> Vec<Vec<ModuleId>> {
+        #[derive(Clone, Copy)]
+        struct Visit {
+            index:    usize,
+            low_link: usize,
+            on_stack: bool,
+        }
+        let edges = |id| self.edges(id).filter(|(kind, _)| include(*kind)).map(|(_, id)| id);
+        let mut visits: Vec<Option<Visit>> = vec![None; self.modules.len()];
+        let mut stack = vec![];
+        let mut components = vec![];
+        let mut next_index = 0;
+        for root in (0..self.modules.len()).map(ModuleId) {
+            if visits[root.0].is_some() {
+                continue;
+            }
+            // Each frame is a module being visited, and its successors not yet visited.
+            let mut frames = vec![(root, edges(root).collect::<Vec<_>>())];
+            visits[root.0] =
+                Some(Visit { index: next_index, low_link: next_index, on_stack: true });
+            stack.push(root);
+            next_index += 1;
+            while let Some((id, successors)) = frames.last_mut() {
+                let id = *id;
+                if let Some(successor) = successors.pop() {
+                    match visits[successor.0] {
+                        None => {
+                            let index = next_index;
+                            visits[successor.0] =
+                                Some(Visit { index, low_link: index, on_stack: true });
+                            stack.push(successor);
+                            next_index += 1;
+                            frames.push((successor, edges(successor).collect()));
+                        }
+                        Some(visit) if visit.on_stack => {
+                            let low_link = &mut visits[id.0].as_mut().unwrap().low_link;
+                            *low_link = (*low_link).min(visit.index);
+                        }
+                        Some(_) => {}
+                    }
+                    continue;
+                }
+                frames.pop();
+                let visit = visits[id.0].unwrap();
+                if let Some((parent, _)) = frames.last() {
+                    let low_link = &mut visits[parent.0].as_mut().unwrap().low_link;
+                    *low_link = (*low_link).min(visit.low_link);
+                }
+                if visit.low_link == visit.index {
+                    let mut component = vec![];
+                    while let Some(member) = stack.pop() {
+                        visits[member.0].as_mut().unwrap().on_stack = false;
+                        component.push(member);
+                        if member == id {
+                            break;
+                        }
+                    }
+                    if component.len() > 1 {
+                        component.sort();
+                        components.push(component);
+                    }
+                }
+            }
+        }
+        components.sort();
+        components
+    }
+
+    /// Return the names exported by a module that no module of the graph imports or re-exports.
+    ///
+    /// A name is used if some other module imports or exports it by name, imports or exports
+    /// `all` names of the module without hiding it, refers to a path through it (e.g.
+    /// `Standard.Base.Data.Vector` uses `Data` if it is exported by `Standard.Base`), or imports
+    /// the module itself, which allows qualified access to any of its names. Exports of `all`
+    /// names are not checked.
+    pub fn unused_exports(&self) -> Vec<UnusedExport> {
+        let mut unused = vec![];
+        for (index, module) in self.modules.iter().enumerate() {
+            let id = ModuleId(index);
+            for export in module.dependencies.iter().filter(|d| d.kind == Kind::Export) {
+                let names = match export.entity_name() {
+                    Some(name) => vec![name],
+                    None => export.names.iter().map(String::as_str).collect(),
+                };
+                for name in names {
+                    if !self.is_used(id, name) {
+                        let name = name.to_owned();
+                        unused.push(UnusedExport { module: id, name, line: export.line });
+                    }
+                }
+            }
+        }
+        unused
+    }
+
+    fn is_used(&self, id: ModuleId, name: &str) -> bool {
+        let module = &self.module(id).name;
+        let qualified = format!("{module}.{name}");
+        let others = self.modules.iter().enumerate().filter(|(index, _)| *index != id.0);
+        let mut dependencies = others.flat_map(|(_, module)| &module.dependencies);
+        dependencies.any(|dependency| {
+            let is_through = dependency.path == qualified
+                || dependency.path.starts_with(&qualified)
+                    && dependency.path[qualified.len()..].starts_with('.');
+            let is_of_module = dependency.path == *module;
+            let is_whole_module = is_of_module && !dependency.all && dependency.names.is_empty();
+            let is_listed = dependency.names.iter().any(|listed| listed == name);
+            let is_all = dependency.all && !dependency.hiding.iter().any(|hidden| hidden == name);
+            is_through || is_whole_module || is_of_module && (is_listed || is_all)
+        })
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(modules: &[(&str, &[&str])]) -> Graph {
+        let mut graph = Graph::default();
+        let parser = enso_parser::Parser::new();
+        for (name, code) in modules {
+            let id = graph.add_module(*name, None);
+            let project = name.split('.').take(2).collect::<Vec<_>>().join(".");
+            let code = code.join("\n");
+            let tree = parser.run(&code);
+            graph.modules[id.0].dependencies = dependencies(&tree, &project);
+        }
+        graph.resolve();
+        graph
+    }
+
+    #[test]
+    fn test_dependencies() {
+        let code = [
+            "## Docs.",
+            "import project.Data.Vector.Vector",
+            "import Standard.Base.Data.Text as Text_Module",
+            "from Standard.Base import all hiding First, Last",
+            "from project.Data import Map, Set",
+            "polyglot java import java.util.Random as Java_Random",
+            "export project.Data.Vector",
+            "from project.Data export all",
+            "main = 1",
+        ];
+        let code = code.join("\n");
+        let tree = enso_parser::Parser::new().run(&code);
+        let dependencies = dependencies(&tree, "Local.Project");
+        let summary: Vec<_> = dependencies
+            .iter()
+            .map(|d| (d.kind, d.path.as_str(), d.entity_name(), d.names.len(), d.all, d.line))
+            .collect();
+        let expected = [
+            (Kind::Import, "Local.Project.Data.Vector.Vector", Some("Vector"), 0, false, 2),
+            (Kind::Import, "Standard.Base.Data.Text", Some("Text_Module"), 0, false, 3),
+            (Kind::Import, "Standard.Base", None, 0, true, 4),
+            (Kind::Import, "Local.Project.Data", None, 2, false, 5),
+            (Kind::Import, "java.util.Random", Some("Java_Random"), 0, false, 6),
+            (Kind::Export, "Local.Project.Data.Vector", Some("Vector"), 0, false, 7),
+            (Kind::Export, "Local.Project.Data", None, 0, true, 8),
+        ];
+        assert_eq!(summary, expected);
+        assert_eq!(dependencies[2].hiding, ["First", "Last"]);
+        assert_eq!(dependencies[3].names, ["Map", "Set"]);
+        assert_eq!(dependencies[4].target, Target::Polyglot("java".into()));
+    }
+
+    #[test]
+    fn test_resolve_and_cycles() {
+        let graph = graph(&[
+            ("A.B", &["import project.C.Type", "export project.D"]),
+            ("A.B.C", &["from Standard.Base import all", "import project.D.x"]),
+            ("A.B.D", &["export project.E.Thing", "from project.C import f"]),
+            ("A.B.E", &["from project export all"]),
+        ]);
+        let targets = |name| {
+            let id = graph.lookup(name).unwrap();
+            let targets = graph.module(id).dependencies.iter().map(|d| match d.target {
+                Target::Module(id) => graph.module(id).name.as_str(),
+                _ => "?",
+            });
+            targets.collect::<Vec<_>>()
+        };
+        assert_eq!(targets("A.B"), ["A.B.C", "A.B.D"]);
+        assert_eq!(targets("A.B.C"), ["?", "A.B.D"]);
+        assert_eq!(targets("A.B.D"), ["A.B.E", "A.B.C"]);
+        let ids =
+            |names: &[&str]| names.iter().map(|n| graph.lookup(n).unwrap()).collect::<Vec<_>>();
+        assert_eq!(graph.cycles(), [ids(&["A.B", "A.B.C", "A.B.D", "A.B.E"])]);
+        assert_eq!(graph.export_cycles(), [ids(&["A.B", "A.B.D", "A.B.E"])]);
+    }
+
+    #[test]
+    fn test_unused_exports() {
+        let graph = graph(&[
+            ("P.Lib", &[
+                "export project.X.One",
+                "export project.X.Two",
+                "from project.X export Three, Four",
+            ]),
+            ("P.Lib.X", &[]),
+            ("Q.App", &["from P.Lib import all hiding Two, Four"]),
+            ("Q.App.Y", &["from P.Lib import Four"]),
+        ]);
+        let unused: Vec<_> = graph.unused_exports().into_iter().map(|e| (e.name, e.line)).collect();
+        assert_eq!(unused, [("Two".to_owned(), 2)]);
+    }
+}
@@ -0,0 +1,68 @@
+//! Print the module dependency graph of Enso projects.
+//!
+//! Usage: `enso-deps [--json] <project>...`
+//!
+//! Each argument is the directory of a project, containing its `package.yaml`. Imports between the
+//! given projects are resolved; imports of other projects are shown unresolved. The graph is
+//! written to the standard output in the DOT language, and its cycles and unused exports are
+//! reported on the standard error; with `--json`, everything is written to the standard output as
+//! a JSON object. The exit status is 2 if the arguments or projects couldn't be read.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+use enso_deps::Graph;
+use enso_deps::Project;
+
+
+
+const USAGE: &str = "Usage: enso-deps [--json] <project>...";
+
+fn main() {
+    let mut json = false;
+    let mut roots = vec![];
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => json = true,
+            _ if arg.starts_with("--") => fail(USAGE),
+            _ => roots.push(arg),
+        }
+    }
+    if roots.is_empty() {
+        fail(USAGE);
+    }
+    let projects: Result<Vec<_>, _> = roots.into_iter().map(Project::load).collect();
+    let graph = projects
+        .and_then(|projects| Graph::load(&projects))
+        .unwrap_or_else(|error| fail(&error.to_string()));
+    if json {
+        println!("{}", enso_deps::output::json(&graph));
+        return;
+    }
+    print!("{}", enso_deps::output::dot(&graph));
+    let name = |id| graph.module(id).name.as_str();
+    for cycle in graph.cycles() {
+        let modules: Vec<_> = cycle.into_iter().map(name).collect();
+        eprintln!("Import cycle: {}", modules.join(", "));
+    }
+    for cycle in graph.export_cycles() {
+        let modules: Vec<_> = cycle.into_iter().map(name).collect();
+        eprintln!("Export cycle: {}", modules.join(", "));
+    }
+    for unused in graph.unused_exports() {
+        let module = graph.module(unused.module);
+        let location = match &module.file {
+            Some(file) => format!("{}:{}", file.display(), unused.line),
+            None => module.name.clone(),
+        };
+        eprintln!("{location}: `{}` is exported, but not imported by any module.", unused.name);
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{message}");
+    std::process::exit(2)
+}
@@ -0,0 +1,186 @@
+//! Rendering of a dependency [`Graph`], as DOT for Graphviz or as JSON for tools.
+
+use crate::Graph;
+use crate::Kind;
+use crate::Target;
+
+use serde_json::json;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+
+
+// ===========
+// === DOT ===
+// ===========
+
+/// Render the graph in the DOT language. Each module with a source file is a node; imports are
+/// solid edges, and exports dashed edges. Dependencies that are unresolved or `polyglot` imports
+/// are drawn as dotted boxes labeled with their paths. Modules in cycles are colored red.
+/// ```text
+/// digraph modules {
+///     "Standard.Base";
+///     "Standard.Base.Any" [color=red];
+///     "Standard.Base" -> "Standard.Base.Any" [style=dashed];
+///     "java:java.util.Random" [shape=box, style=dotted];
+///     "Standard.Base.Random" -> "java:java.util.Random";
+/// }
+/// ```
+pub fn dot(graph: &Graph) -> String {
+    let in_cycle: BTreeSet<_> = graph.cycles().into_iter().flatten().collect();
+    let mut external = BTreeSet::new();
+    let mut out = String::from("digraph modules {\n");
+    // Writing to a `String` cannot fail.
+    for (index, module) in graph.modules.iter().enumerate() {
+        if module.file.is_none() && module.dependencies.is_empty() {
+            continue;
+        }
+        let attributes = match in_cycle.contains(&crate::ModuleId(index)) {
+            true => " [color=red]",
+            false => "",
+        };
+        let _ = writeln!(out, "    {}{attributes};", quote(&module.name));
+    }
+    for module in &graph.modules {
+        for dependency in &module.dependencies {
+            let target = match &dependency.target {
+                Target::Module(id) => graph.module(*id).name.clone(),
+                Target::Polyglot(language) => format!("{language}:{}", dependency.path),
+                Target::Unresolved => dependency.path.clone(),
+            };
+            if !matches!(dependency.target, Target::Module(_)) && external.insert(target.clone()) {
+                let _ = writeln!(out, "    {} [shape=box, style=dotted];", quote(&target));
+            }
+            let style = match dependency.kind {
+                Kind::Import => "",
+                Kind::Export => " [style=dashed]",
+            };
+            let _ = writeln!(out, "    {} -> {}{style};", quote(&module.name), quote(&target));
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+
+
+// ============
+// === JSON ===
+// ============
+
+/// Return a JSON object describing the graph, its cycles, and its unused exports. Modules are
+/// referred to by name; lines are 1-based.
+/// ```json
+/// {
+///   "modules": [{
+///     "name": "Standard.Base.Random",
+///     "file": "src/Random.enso",
+///     "dependencies": [{
+///       "kind": "import",
+///       "path": "java.util.Random",
+///       "module": null,
+///       "polyglot": "java",
+///       "names": [],
+///       "all": false,
+///       "hiding": [],
+///       "alias": "Java_Random",
+///       "line": 3
+///     }]
+///   }],
+///   "cycles": [["Standard.Base.Any", "Standard.Base.Error"]],
+///   "export_cycles": [],
+///   "unused_exports": [{ "module": "Standard.Base", "name": "Foo", "line": 12 }]
+/// }
+/// ```
+pub fn json(graph: &Graph) -> serde_json::Value {
+    let name = |id: crate::ModuleId| graph.module(id).name.as_str();
+    let modules = graph.modules.iter().map(|module| {
+        let dependencies = module.dependencies.iter().map(|dependency| {
+            let (module, polyglot) = match &dependency.target {
+                Target::Module(id) => (Some(name(*id)), None),
+                Target::Polyglot(language) => (None, Some(language)),
+                Target::Unresolved => (None, None),
+            };
+            let kind = match dependency.kind {
+                Kind::Import => "import",
+                Kind::Export => "export",
+            };
+            json!({
+                "kind": kind,
+                "path": dependency.path,
+                "module": module,
+                "polyglot": polyglot,
+                "names": dependency.names,
+                "all": dependency.all,
+                "hiding": dependency.hiding,
+                "alias": dependency.alias,
+                "line": dependency.line,
+            })
+        });
+        json!({
+            "name": module.name,
+            "file": module.file.as_ref().map(|file| file.display().to_string()),
+            "dependencies": dependencies.collect::<Vec<_>>(),
+        })
+    });
+    let cycles = |cycles: Vec<Vec<crate::ModuleId>>| {
+        let cycles = cycles.into_iter().map(|cycle| cycle.into_iter().map(name).collect());
+        cycles.collect::<Vec<Vec<_>>>()
+    };
+    let unused_exports = graph.unused_exports().into_iter().map(
+        |unused| json!({ "module": name(unused.module), "name": unused.name, "line": unused.line }),
+    );
+    json!({
+        "modules": modules.collect::<Vec<_>>(),
+        "cycles": cycles(graph.cycles()),
+        "export_cycles": cycles(graph.export_cycles()),
+        "unused_exports": unused_exports.collect::<Vec<_>>(),
+    })
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output() {
+        let mut graph = Graph::default();
+        let parser = enso_parser::Parser::new();
+        let modules = [
+            ("A.B", "import project.C\nexport project.C.X as Y"),
+            ("A.B.C", "from A.B import Z\npolyglot java import java.util.Random"),
+        ];
+        for (name, code) in modules {
+            let id = graph.add_module(name, None);
+            graph.modules[id.0].dependencies = crate::dependencies(&parser.run(code), "A.B");
+        }
+        graph.resolve();
+        let expected = [
+            "digraph modules {",
+            "    \"A.B\" [color=red];",
+            "    \"A.B.C\" [color=red];",
+            "    \"A.B\" -> \"A.B.C\";",
+            "    \"A.B\" -> \"A.B.C\" [style=dashed];",
+            "    \"A.B.C\" -> \"A.B\";",
+            "    \"java:java.util.Random\" [shape=box, style=dotted];",
+            "    \"A.B.C\" -> \"java:java.util.Random\";",
+            "}",
+            "",
+        ];
+        assert_eq!(dot(&graph), expected.join("\n"));
+        let json = json(&graph);
+        assert_eq!(json["cycles"], json!([["A.B", "A.B.C"]]));
+        assert_eq!(json["modules"][1]["dependencies"][1]["polyglot"], "java");
+        assert_eq!(json["unused_exports"], json!([{ "module": "A.B", "name": "Y", "line": 2 }]));
+    }
+}
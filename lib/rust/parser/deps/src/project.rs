@@ -0,0 +1,124 @@
+//! Enso projects: a `package.yaml` file naming the project, and a `src` directory of modules.
+
+use serde::Deserialize;
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+
+
+
+// ===============
+// === Project ===
+// ===============
+
+/// An Enso project.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Project {
+    /// The directory containing `package.yaml`.
+    pub root:      PathBuf,
+    /// The namespace of the project, e.g. `Standard`.
+    pub namespace: String,
+    /// The name of the project, e.g. `Base`.
+    pub name:      String,
+}
+
+/// The fields of `package.yaml` identifying the project.
+#[derive(Debug, Deserialize)]
+struct Package {
+    name:      String,
+    #[serde(default = "default_namespace")]
+    namespace: String,
+}
+
+fn default_namespace() -> String {
+    "local".into()
+}
+
+impl Project {
+    /// Read the `package.yaml` of the project in the given directory.
+    pub fn load(root: impl Into<PathBuf>) -> Result<Self, Error> {
+        let root = root.into();
+        let path = root.join("package.yaml");
+        let text = std::fs::read_to_string(&path).map_err(|error| io_error(&path, error))?;
+        let package: Package = serde_yaml::from_str(&text)
+            .map_err(|error| Error::Package(format!("{}: {error}", path.display())))?;
+        Ok(Self { root, namespace: package.namespace, name: package.name })
+    }
+
+    /// Return the qualified name of the project, e.g. `Standard.Base`. This is also the name of its
+    /// `Main` module.
+    pub fn qualified_name(&self) -> String {
+        format!("{}.{}", self.namespace, self.name)
+    }
+
+    /// Return the qualified names of the modules of the project, with their source files. A
+    /// directory without a corresponding file is a module without a file. Modules are ordered by
+    /// path.
+    pub fn modules(&self) -> Result<Vec<(String, Option<PathBuf>)>, Error> {
+        let mut modules = vec![];
+        let src = self.root.join("src");
+        self.collect_modules(&src, &self.qualified_name(), &mut modules)?;
+        Ok(modules)
+    }
+
+    fn collect_modules(
+        &self,
+        dir: &Path,
+        prefix: &str,
+        modules: &mut Vec<(String, Option<PathBuf>)>,
+    ) -> Result<(), Error> {
+        let entries = std::fs::read_dir(dir).map_err(|error| io_error(dir, error))?;
+        let mut paths = vec![];
+        for entry in entries {
+            paths.push(entry.map_err(|error| io_error(dir, error))?.path());
+        }
+        paths.sort();
+        for path in paths {
+            let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else { continue };
+            let is_root = dir == self.root.join("src");
+            let name = match is_root && stem == "Main" {
+                true => prefix.to_owned(),
+                false => format!("{prefix}.{stem}"),
+            };
+            if path.is_dir() {
+                if !path.with_extension("enso").is_file() {
+                    modules.push((name.clone(), None));
+                }
+                self.collect_modules(&path, &name, modules)?;
+            } else if path.extension().map_or(false, |extension| extension == "enso") {
+                modules.push((name, Some(path)));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> Error {
+    Error::Io(format!("{}: {error}", path.display()))
+}
+
+
+
+// =============
+// === Error ===
+// =============
+
+/// An error loading a project.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// A file or directory could not be read.
+    Io(String),
+    /// The `package.yaml` file is not valid.
+    Package(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "Failed to read the project: {error}"),
+            Error::Package(error) => write!(f, "Invalid package: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
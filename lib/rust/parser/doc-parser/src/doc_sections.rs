@@ -8,16 +8,24 @@ use crate::*;
 // === High-level Parsing API ===
 // ==============================
 
-/// Parse the given documentation text to a collection of [`DocSection`]s.
+// Although parsing is semantically a pure function, for efficiency we use one persistent parser to
+// reuse its buffers.
+thread_local! {
+    static PARSER: RefCell<DocParser> = Default::default();
+}
+
+/// Parse the given documentation text to a collection of [`DocSection`]s, with bodies rendered as
+/// HTML. See [`DocParser::parse`].
 pub fn parse(docs: &str) -> Vec<DocSection> {
-    // Although this is semantically a pure function, for efficiency we use one persistent parser
-    // to reuse its buffers.
-    thread_local! {
-        static PARSER: RefCell<DocParser> = Default::default();
-    }
     PARSER.with_borrow_mut(|parser| parser.parse(docs))
 }
 
+/// Parse the given documentation text to a collection of [`DocSection`]s, with bodies represented
+/// as [`RichText`].
+pub fn parse_structured(docs: &str) -> Vec<DocSection<RichText>> {
+    PARSER.with_borrow_mut(|parser| parser.parse_structured(docs))
+}
+
 
 
 // ============================
@@ -40,9 +48,24 @@ impl DocParser {
         Self::default()
     }
 
+    /// Parse the documentation, rendering the section bodies as HTML.
+    ///
+    /// Emphasis and links are not recognized, so they are rendered as they appear in the source
+    /// text, as expected by the existing consumers of the HTML. To render them as `<em>` and `<a>`
+    /// elements, use [`Self::parse_structured`] and [`render::html`].
+    pub fn parse(&mut self, input: &str) -> Vec<DocSection> {
+        let sections = self.parse_rich_text(input, false);
+        sections.into_iter().map(|section| section.map_body(render::html)).collect()
+    }
+
     /// Parse the documentation.
+    pub fn parse_structured(&mut self, input: &str) -> Vec<DocSection<RichText>> {
+        self.parse_rich_text(input, true)
+    }
+
     #[profile(Detail)]
-    pub fn parse(&mut self, input: &str) -> Vec<DocSection> {
+    fn parse_rich_text(&mut self, input: &str, inline_markup: bool) -> Vec<DocSection<RichText>> {
+        self.lexer.ignore_inline_markup = !inline_markup;
         for (line_number, line) in input.trim_start().lines().enumerate() {
             let location = Location::start_of_line(line_number);
             let line = Span { location, text: line };
@@ -62,14 +85,88 @@ impl DocParser {
 /// Text rendered as HTML (may contain HTML tags).
 pub type HtmlString = String;
 
+/// Text with inline markup.
+pub type RichText = Vec<Inline>;
+
+/// An element of [`RichText`].
+#[derive(Hash, Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub enum Inline {
+    /// Plain text.
+    Text(String),
+    /// Code, quoted with backticks.
+    Code(String),
+    /// A quoted name of a type, e.g. `Standard.Base.Data.Text`.
+    TypeReference(String),
+    /// Emphasized text, delimited by `_` or `*`.
+    Emphasis(RichText),
+    /// A link, in the form `[label](target)`.
+    Link {
+        /// The text of the link.
+        label:  String,
+        /// The URL the link refers to.
+        target: String,
+    },
+    /// The code of an example. Lines are separated by newline characters.
+    Example(String),
+    /// The start of a new paragraph.
+    ParagraphBreak,
+}
+
+impl Inline {
+    /// Return the element representing the given quoted text: a [`Inline::TypeReference`] if it is
+    /// a (possibly qualified) type name, otherwise [`Inline::Code`].
+    pub fn quote(text: String) -> Self {
+        let is_type_name = |segment: &str| {
+            segment.starts_with(|c: char| c.is_ascii_uppercase())
+                && segment.chars().all(|c| c.is_alphanumeric() || c == '_')
+        };
+        match text.split('.').all(is_type_name) {
+            true => Inline::TypeReference(text),
+            false => Inline::Code(text),
+        }
+    }
+}
+
 /// A description of a single argument in the documentation. The name is delimited from the
 /// description using a colon.
 #[derive(Debug, Clone, Hash, PartialEq, Eq, serde::Serialize)]
-pub struct Argument {
+pub struct Argument<Body = HtmlString> {
     /// Name of the argument.
     pub name:        String,
     /// Description of the argument.
-    pub description: HtmlString,
+    pub description: Body,
+}
+
+impl<Body> Argument<Body> {
+    /// Convert the description with the given function.
+    pub fn map_description<T>(self, f: impl FnOnce(Body) -> T) -> Argument<T> {
+        Argument { name: self.name, description: f(self.description) }
+    }
+}
+
+impl Argument<RichText> {
+    /// Convert the given text to the argument description. The name is taken from the leading
+    /// code and plain text, as in [`Argument::new`]; a name quoted as code is rendered as HTML.
+    pub fn from_rich_text(mut text: RichText) -> Self {
+        let mut name = String::new();
+        while let Some(Inline::Code(_) | Inline::TypeReference(_)) = text.first() {
+            name.push_str(&render::html(vec![text.remove(0)]));
+        }
+        if let Some(Inline::Text(first)) = text.first_mut() {
+            let split = first.split_once(|c| c == ':' || c == ' ');
+            let (prefix, description) = split.unwrap_or((first, ""));
+            name.push_str(prefix.trim());
+            let description = description.trim_start().to_string();
+            match description.is_empty() {
+                true => drop(text.remove(0)),
+                false => *first = description,
+            }
+        }
+        if let Some(Inline::Text(last)) = text.last_mut() {
+            last.truncate(last.trim_end().len());
+        }
+        Self { name, description: text }
+    }
 }
 
 impl Argument {
@@ -89,29 +186,29 @@ impl Argument {
 /// A single section of the documentation.
 #[derive(Hash, Debug, Clone, PartialEq, Eq, serde::Serialize)]
 #[allow(missing_docs)]
-pub enum DocSection {
+pub enum DocSection<Body = HtmlString> {
     /// The documentation tag.
     Tag {
         /// The tag name.
         tag:  Tag,
         /// The tag text.
-        body: HtmlString,
+        body: Body,
     },
     /// The paragraph of the text.
     Paragraph {
         /// The elements that make up this paragraph.
-        body: HtmlString,
+        body: Body,
     },
     /// A list of items. Each item starts with a dash (`-`).
-    List { items: Vec<HtmlString> },
+    List { items: Vec<Body> },
     /// A list of items, but each item is an [`Argument`]. Starts with `Arguments:` keyword.
-    Arguments { args: Vec<Argument> },
+    Arguments { args: Vec<Argument<Body>> },
     /// The section that starts with the key followed by the colon and the body.
     Keyed {
         /// The section key.
         key:  String,
         /// The elements that make up the body of the section.
-        body: HtmlString,
+        body: Body,
     },
     /// The section that starts with the mark followed by the header and the body.
     Marked {
@@ -120,10 +217,28 @@ pub enum DocSection {
         /// The section header.
         header: Option<String>,
         /// The elements that make up the body of the section.
-        body:   HtmlString,
+        body:   Body,
     },
 }
 
+impl<Body> DocSection<Body> {
+    /// Convert each body in the section with the given function.
+    pub fn map_body<T>(self, mut f: impl FnMut(Body) -> T) -> DocSection<T> {
+        match self {
+            DocSection::Tag { tag, body } => DocSection::Tag { tag, body: f(body) },
+            DocSection::Paragraph { body } => DocSection::Paragraph { body: f(body) },
+            DocSection::List { items } =>
+                DocSection::List { items: items.into_iter().map(f).collect() },
+            DocSection::Arguments { args } => DocSection::Arguments {
+                args: args.into_iter().map(|arg| arg.map_description(&mut f)).collect(),
+            },
+            DocSection::Keyed { key, body } => DocSection::Keyed { key, body: f(body) },
+            DocSection::Marked { mark, header, body } =>
+                DocSection::Marked { mark, header, body: f(body) },
+        }
+    }
+}
+
 
 
 // ============================
@@ -132,11 +247,15 @@ pub enum DocSection {
 
 #[derive(Default, Debug)]
 struct DocSectionCollector {
-    sections:             Vec<DocSection>,
+    sections:             Vec<DocSection<RichText>>,
     in_secondary_section: bool,
     inside_arguments:     bool,
-    current_body:         String,
-    current_list:         Vec<String>,
+    current_body:         RichText,
+    current_list:         Vec<RichText>,
+    /// For each open emphasis span, the content preceding it.
+    outer_bodies:         Vec<RichText>,
+    current_quote:        Option<String>,
+    current_example:      Option<String>,
 }
 
 impl DocSectionCollector {
@@ -154,29 +273,44 @@ impl DocSectionCollector {
         }
     }
 
-    fn finish(&mut self) -> Vec<DocSection> {
+    fn finish(&mut self) -> Vec<DocSection<RichText>> {
         self.finish_section();
         let result = self.sections.drain(..).collect();
         let current_body = std::mem::take(&mut self.current_body);
         let current_list = std::mem::take(&mut self.current_list);
         let sections = std::mem::take(&mut self.sections);
+        let outer_bodies = std::mem::take(&mut self.outer_bodies);
         *self = Self {
             // Reuse the (empty) buffers.
             current_body,
             current_list,
             sections,
+            outer_bodies,
             // Reset the rest of state.
             in_secondary_section: Default::default(),
             inside_arguments: Default::default(),
+            current_quote: Default::default(),
+            current_example: Default::default(),
         };
         result
     }
+
+    fn push_text(&mut self, text: &str) {
+        if let Some(quote) = &mut self.current_quote {
+            quote.push_str(text);
+        } else if let Some(Inline::Text(current)) = self.current_body.last_mut() {
+            current.push_str(text);
+        } else if !text.is_empty() {
+            self.current_body.push(Inline::Text(text.to_owned()));
+        }
+    }
 }
 
 impl<L> TokenConsumer<L> for DocSectionCollector {
     fn tag(&mut self, tag: Tag, description: Option<Span<'_, L>>) {
-        let body = description.map(|description| description.to_string()).unwrap_or_default();
-        self.sections.push(DocSection::Tag { tag, body });
+        let description = description.filter(|description| !description.text.is_empty());
+        let body = description.map(|description| vec![Inline::Text(description.to_string())]);
+        self.sections.push(DocSection::Tag { tag, body: body.unwrap_or_default() });
     }
 
     fn enter_marked_section(&mut self, mark: Mark, header: Option<Span<'_, L>>) {
@@ -197,7 +331,7 @@ impl<L> TokenConsumer<L> for DocSectionCollector {
     }
 
     fn text(&mut self, text: Span<'_, L>) {
-        self.current_body.push_str(text.as_ref());
+        self.push_text(text.as_ref());
     }
 
     fn start_list(&mut self) {
@@ -209,31 +343,49 @@ impl<L> TokenConsumer<L> for DocSectionCollector {
     fn start_paragraph(&mut self) {
         let first_content = !self.in_secondary_section && self.current_body.is_empty();
         if !first_content {
-            self.current_body.push_str("<p>");
+            self.current_body.push(Inline::ParagraphBreak);
         }
     }
 
     fn start_raw(&mut self) {
-        self.current_body.push_str("<div class=\"example\">");
+        self.current_example = Some(default());
     }
 
     fn start_quote(&mut self) {
-        self.current_body.push_str("<code>");
+        self.current_quote = Some(default());
     }
 
     fn end_quote(&mut self) {
-        self.current_body.push_str("</code>");
+        if let Some(quote) = self.current_quote.take() {
+            self.current_body.push(Inline::quote(quote));
+        }
+    }
+
+    fn start_emphasis(&mut self) {
+        self.outer_bodies.push(mem::take(&mut self.current_body));
+    }
+
+    fn end_emphasis(&mut self) {
+        if let Some(outer_body) = self.outer_bodies.pop() {
+            let emphasized = mem::replace(&mut self.current_body, outer_body);
+            self.current_body.push(Inline::Emphasis(emphasized));
+        }
+    }
+
+    fn link(&mut self, label: Span<'_, L>, target: Span<'_, L>) {
+        self.current_body.push(Inline::Link { label: label.into(), target: target.into() });
     }
 
     fn whitespace(&mut self) {
-        self.current_body.push(' ');
+        self.push_text(" ");
     }
 
     fn raw_line(&mut self, text: Span<'_, L>) {
-        if !self.current_body.is_empty() {
-            self.current_body.push('\n');
+        let example = self.current_example.get_or_insert_with(default);
+        if !example.is_empty() {
+            example.push('\n');
         }
-        self.current_body.push_str(text.as_ref());
+        example.push_str(text.as_ref());
     }
 
     fn end(&mut self, scope: ScopeType) {
@@ -241,7 +393,7 @@ impl<L> TokenConsumer<L> for DocSectionCollector {
             ScopeType::List => {
                 let items = mem::take(&mut self.current_list);
                 if self.inside_arguments {
-                    let args = items.iter().map(|arg| Argument::new(arg)).collect();
+                    let args = items.into_iter().map(Argument::from_rich_text).collect();
                     self.sections.push(DocSection::Arguments { args });
                     self.inside_arguments = false;
                 } else {
@@ -252,7 +404,10 @@ impl<L> TokenConsumer<L> for DocSectionCollector {
                 self.current_list.push(self.current_body.drain(..).collect());
             }
             ScopeType::Paragraph => (),
-            ScopeType::Raw => self.current_body.push_str("</div>"),
+            ScopeType::Raw =>
+                if let Some(example) = self.current_example.take() {
+                    self.current_body.push(Inline::Example(example));
+                },
         }
     }
 }
@@ -32,10 +32,14 @@ use enso_prelude::*;
 // ==============
 
 pub mod doc_sections;
+pub mod render;
 
 pub use doc_sections::parse;
+pub use doc_sections::parse_structured;
 pub use doc_sections::Argument;
 pub use doc_sections::DocSection;
+pub use doc_sections::Inline;
+pub use doc_sections::RichText;
 
 
 
@@ -317,6 +321,13 @@ impl<'a, L: Location> Span<'a, L> {
         let b_ = Self { location: self.location.offset_text(a), text: b };
         (a_, b_)
     }
+
+    /// Return the part of the span in the given byte range. Panics if the range does not start and
+    /// end at character boundaries.
+    pub fn slice(self, range: Range<usize>) -> Self {
+        let (_, after_start) = self.split_at(range.start);
+        after_start.split_at(range.end - range.start).0
+    }
 }
 
 impl<'a, L> AsRef<str> for Span<'a, L> {
@@ -379,8 +390,10 @@ impl Location for IgnoredLocation {
 /// Breaks input state into tokens to be fed to a [`TokenConsumer`].
 #[derive(Default, Debug)]
 pub struct Lexer {
-    scopes: Scopes,
-    state:  State,
+    scopes:               Scopes,
+    state:                State,
+    /// If set, emphasis and links are not recognized, and are lexed as plain text.
+    ignore_inline_markup: bool,
 }
 
 /// Lexer state.
@@ -463,6 +476,8 @@ impl Lexer {
         *self = Self {
             // Reuse buffers.
             scopes,
+            // Keep configuration.
+            ignore_inline_markup: self.ignore_inline_markup,
             // Reset state.
             state: Default::default(),
         };
@@ -507,36 +522,151 @@ impl Lexer {
     }
 
     fn text<L: Location>(&mut self, text: Span<L>, docs: &mut impl TokenConsumer<L>) {
+        let bytes = text.text.as_bytes();
+        let inline_markup = !self.ignore_inline_markup;
         let mut quote_open = None;
+        let mut emphasis: Vec<Delimiter> = default();
+        let mut plain_start = 0;
         let mut i = 0;
-        let mut remaining = text;
-        for c in text.text.bytes() {
-            match c {
-                b'`' => {
-                    let (before_quote, including_quote) = remaining.split_at(i);
-                    let (quote, next_remaining) = including_quote.split_at(1);
-                    remaining = next_remaining;
-                    i = 0;
-                    docs.text(before_quote);
-                    if quote_open.is_some() {
-                        docs.end_quote();
-                        quote_open = None;
-                    } else {
-                        docs.start_quote();
-                        quote_open = Some(quote);
-                    }
+        while i < bytes.len() {
+            let byte = bytes[i];
+            if byte == b'`' {
+                docs.text(text.slice(plain_start..i));
+                if quote_open.is_some() {
+                    docs.end_quote();
+                    quote_open = None;
+                } else {
+                    docs.start_quote();
+                    quote_open = Some(text.slice(i..i + 1));
                 }
-                _ => i += 1,
+                i += 1;
+                plain_start = i;
+            } else if quote_open.is_some() {
+                i += 1;
+            } else if inline_markup && byte == b'[' && let Some(link) = Link::at(text.text, i) {
+                docs.text(text.slice(plain_start..i));
+                docs.link(text.slice(link.label), text.slice(link.target.clone()));
+                i = link.target.end + 1;
+                plain_start = i;
+            } else if inline_markup && let Some(delimiter) = Delimiter::at(bytes, i) {
+                let end = i + delimiter.len;
+                if emphasis.last() == Some(&delimiter) && delimiter.can_close(bytes, i) {
+                    docs.text(text.slice(plain_start..i));
+                    docs.end_emphasis();
+                    emphasis.pop();
+                    plain_start = end;
+                } else if delimiter.can_open(bytes, i) && delimiter.is_closed_after(bytes, end) {
+                    docs.text(text.slice(plain_start..i));
+                    docs.start_emphasis();
+                    emphasis.push(delimiter);
+                    plain_start = end;
+                }
+                i = end;
+            } else {
+                i += 1;
             }
         }
-        docs.text(remaining);
+        docs.text(text.slice(plain_start..bytes.len()));
         if let Some(quote) = quote_open {
             quote.warn("Unclosed quote.");
             docs.end_quote();
         }
+        for _ in emphasis {
+            text.after().warn("Unclosed emphasis.");
+            docs.end_emphasis();
+        }
+    }
+}
+
+
+// === Inline Markup ===
+
+/// A link, in the form `[label](target)`.
+#[derive(Debug)]
+struct Link {
+    label:  Range<usize>,
+    target: Range<usize>,
+}
+
+impl Link {
+    /// If a link starts at the given byte index, return the byte ranges of its parts.
+    fn at(text: &str, i: usize) -> Option<Self> {
+        let label_start = i + 1;
+        let label_end = label_start + text[label_start..].find(']')?;
+        let target_start = label_end + 2;
+        let target_text = text[label_end + 1..].strip_prefix('(')?;
+        let target_end = target_start + target_text.find(')')?;
+        let label = label_start..label_end;
+        let target = target_start..target_end;
+        let label_is_valid = !label.is_empty() && !text[label.clone()].contains(['[', '`']);
+        let target_is_valid =
+            !target.is_empty() && !text[target.clone()].contains(char::is_whitespace);
+        (label_is_valid && target_is_valid).then_some(Self { label, target })
+    }
+}
+
+/// A run of emphasis characters (`_` or `*`), e.g. `_` or `**`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Delimiter {
+    char: u8,
+    len:  usize,
+}
+
+impl Delimiter {
+    /// If a run of emphasis characters starts at the given byte index, return it. Runs of more
+    /// than two characters are not delimiters.
+    fn at(bytes: &[u8], i: usize) -> Option<Self> {
+        let char = bytes[i];
+        if char != b'_' && char != b'*' {
+            return None;
+        }
+        let len = bytes[i..].iter().take_while(|b| **b == char).count();
+        (len <= 2).then_some(Self { char, len })
+    }
+
+    /// Whether the delimiter at the given index can start an emphasis span: It must be at the start
+    /// of a word, and be followed by non-whitespace.
+    fn can_open(self, bytes: &[u8], i: usize) -> bool {
+        let before = i.checked_sub(1).map(|j| bytes[j]);
+        let after = bytes.get(i + self.len);
+        !before.map_or(false, is_word_byte) && after.map_or(false, |b| !b.is_ascii_whitespace())
+    }
+
+    /// Whether the delimiter at the given index can end an emphasis span: It must be at the end of
+    /// a word, and be preceded by non-whitespace.
+    fn can_close(self, bytes: &[u8], i: usize) -> bool {
+        let before = i.checked_sub(1).map(|j| bytes[j]);
+        let after = bytes.get(i + self.len).copied();
+        before.map_or(false, |b| !b.is_ascii_whitespace()) && !after.map_or(false, is_word_byte)
+    }
+
+    /// Whether the text after the given index contains a delimiter that can close this one.
+    fn is_closed_after(self, bytes: &[u8], mut i: usize) -> bool {
+        let mut in_quote = false;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'`' => in_quote = !in_quote,
+                _ if in_quote => (),
+                _ if let Some(other) = Delimiter::at(bytes, i) => {
+                    if other == self && self.can_close(bytes, i) {
+                        return true;
+                    }
+                    i += other.len;
+                    continue;
+                }
+                _ => (),
+            }
+            i += 1;
+        }
+        false
     }
 }
 
+/// Whether the byte is part of a word. Any non-ASCII character is considered part of a word.
+fn is_word_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || !byte.is_ascii()
+}
+
 
 // === Scopes ===
 
@@ -684,6 +814,12 @@ pub trait TokenConsumer<L> {
     fn start_quote(&mut self);
     /// A closing-quote.
     fn end_quote(&mut self);
+    /// The start of emphasized text.
+    fn start_emphasis(&mut self);
+    /// The end of emphasized text.
+    fn end_emphasis(&mut self);
+    /// A link to the given target.
+    fn link(&mut self, label: Span<'_, L>, target: Span<'_, L>);
     /// Space between two [`text`] tokens.
     fn whitespace(&mut self);
     /// A line of preformatted text. No newline character is included.
@@ -717,6 +853,7 @@ mod tests {
             must be between 2 and 36 (inclusive)
         - arg argument without colon
         - argument_without_description
+        - `quoted`: An argument with a quoted name.
 
         - List item 1
         - List item 2
@@ -748,6 +885,10 @@ mod tests {
                 Argument {
                     name: "argument_without_description".into(),
                     description: default(),
+                },
+                Argument {
+                    name: "<code>quoted</code>".into(),
+                    description: "An argument with a quoted name.".into(),
                 }].to_vec()
             },
             List { items: ["List item 1".into(), "List item 2".into(), "List item 3".into()].to_vec() },
@@ -758,4 +899,101 @@ mod tests {
             }].to_vec();
         assert_eq!(res, expected);
     }
+
+    #[test]
+    fn test_inline_markup() {
+        use crate::DocSection::*;
+        use crate::Inline::*;
+
+        let docs = r#"
+        Returns _either_ a `Text` or a `Standard.Base.Any`, see `self.at 0`
+        and [the *reference*](https://example.com/ref). Not: snake_case, 2*3*4, `_a_`.
+
+        Arguments:
+        - x: The *first* value."#;
+        let res = parse_structured(docs);
+        let expected = [
+            Paragraph {
+                body: [
+                    Text("Returns ".into()),
+                    Emphasis([Text("either".into())].to_vec()),
+                    Text(" a ".into()),
+                    TypeReference("Text".into()),
+                    Text(" or a ".into()),
+                    TypeReference("Standard.Base.Any".into()),
+                    Text(", see ".into()),
+                    Code("self.at 0".into()),
+                    Text(" and ".into()),
+                    Link {
+                        label:  "the *reference*".into(),
+                        target: "https://example.com/ref".into(),
+                    },
+                    Text(". Not: snake_case, 2*3*4, ".into()),
+                    Code("_a_".into()),
+                    Text(".".into()),
+                ]
+                .to_vec(),
+            },
+            Keyed { key: "Arguments".into(), body: default() },
+            Arguments {
+                args: [Argument {
+                    name:        "x".into(),
+                    description: [
+                        Text("The ".into()),
+                        Emphasis([Text("first".into())].to_vec()),
+                        Text(" value.".into()),
+                    ]
+                    .to_vec(),
+                }]
+                .to_vec(),
+            },
+        ]
+        .to_vec();
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_renderers() {
+        let docs = r#"
+        A _short_ `Text` with [a link](https://example.com).
+
+        > Example
+          Some code.
+
+              main = 42"#;
+        let sections = parse_structured(docs);
+        let render = |f: fn(RichText) -> String| {
+            let sections = sections.iter().cloned().map(|section| section.map_body(f));
+            sections.map(|section| format!("{section:?}")).collect::<Vec<_>>()
+        };
+        assert_eq!(render(render::html), [
+            r#"Paragraph { body: "A <em>short</em> <code>Text</code> with <a href=\"https://example.com\">a link</a>." }"#,
+            r#"Marked { mark: Example, header: Some("Example"), body: "<p>Some code.<div class=\"example\">\nmain = 42</div>" }"#,
+        ]);
+        assert_eq!(render(render::markdown), [
+            r#"Paragraph { body: "A _short_ `Text` with [a link](https://example.com)." }"#,
+            r#"Marked { mark: Example, header: Some("Example"), body: "Some code.\n\n```\nmain = 42\n```\n" }"#,
+        ]);
+        assert_eq!(render(render::plain_text), [
+            r#"Paragraph { body: "A short Text with a link." }"#,
+            r#"Marked { mark: Example, header: Some("Example"), body: "Some code.\n\nmain = 42\n" }"#,
+        ]);
+    }
+
+    #[test]
+    fn test_html_escapes_links() {
+        let docs = r#"See [a < "b"](https://example.com/?a=1&b=">)."#;
+        let body = parse_structured(docs).into_iter().next().unwrap().map_body(render::html);
+        let expected =
+            r#"See <a href="https://example.com/?a=1&amp;b=&quot;&gt;">a &lt; &quot;b&quot;</a>."#;
+        assert_eq!(body, DocSection::Paragraph { body: expected.into() });
+    }
+
+    #[test]
+    fn test_html_ignores_inline_markup() {
+        let docs = "A _short_ text with [a link](https://example.com).";
+        let res = parse(docs);
+        let expected = [DocSection::Paragraph { body: docs.into() }].to_vec();
+        assert_eq!(res, expected);
+    }
 }
@@ -116,6 +116,9 @@ enum Token {
     End(ScopeType),
     StartQuote,
     EndQuote,
+    StartEmphasis,
+    EndEmphasis,
+    Link { label: String, target: String },
     Text(String),
     RawLine(String),
 }
@@ -173,6 +176,18 @@ impl<L> TokenConsumer<L> for TokenCollector<L> {
         self.tokens.push(Token::EndQuote);
     }
 
+    fn start_emphasis(&mut self) {
+        self.tokens.push(Token::StartEmphasis);
+    }
+
+    fn end_emphasis(&mut self) {
+        self.tokens.push(Token::EndEmphasis);
+    }
+
+    fn link(&mut self, label: Span<L>, target: Span<L>) {
+        self.tokens.push(Token::Link { label: label.into(), target: target.into() });
+    }
+
     fn whitespace(&mut self) {
         self.tokens.push(Token::Text(" ".to_owned()));
     }
@@ -0,0 +1,150 @@
+//! Renderers converting [`RichText`] to textual formats.
+
+use crate::*;
+
+use crate::doc_sections::HtmlString;
+
+
+
+// ============
+// === HTML ===
+// ============
+
+/// Render the text as HTML.
+pub fn html(text: RichText) -> HtmlString {
+    let mut out = String::new();
+    html_to(&text, &mut out);
+    out
+}
+
+fn html_to(text: &[Inline], out: &mut String) {
+    for inline in text {
+        match inline {
+            Inline::Text(text) => out.push_str(text),
+            Inline::Code(code) | Inline::TypeReference(code) => {
+                out.push_str("<code>");
+                out.push_str(code);
+                out.push_str("</code>");
+            }
+            Inline::Emphasis(text) => {
+                out.push_str("<em>");
+                html_to(text, out);
+                out.push_str("</em>");
+            }
+            Inline::Link { label, target } => {
+                out.push_str("<a href=\"");
+                escape_html_to(target, out);
+                out.push_str("\">");
+                escape_html_to(label, out);
+                out.push_str("</a>");
+            }
+            Inline::Example(code) => {
+                out.push_str("<div class=\"example\">\n");
+                out.push_str(code);
+                out.push_str("</div>");
+            }
+            Inline::ParagraphBreak => out.push_str("<p>"),
+        }
+    }
+}
+
+/// Write the text with the characters that are special in HTML text or in quoted attribute values
+/// replaced by character references.
+fn escape_html_to(text: &str, out: &mut String) {
+    for char in text.chars() {
+        match char {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            char => out.push(char),
+        }
+    }
+}
+
+
+
+// ================
+// === Markdown ===
+// ================
+
+/// Render the text as Markdown.
+pub fn markdown(text: RichText) -> String {
+    let mut out = String::new();
+    markdown_to(&text, &mut out);
+    out
+}
+
+fn markdown_to(text: &[Inline], out: &mut String) {
+    for inline in text {
+        match inline {
+            Inline::Text(text) => out.push_str(text),
+            Inline::Code(code) | Inline::TypeReference(code) => {
+                out.push('`');
+                out.push_str(code);
+                out.push('`');
+            }
+            Inline::Emphasis(text) => {
+                out.push('_');
+                markdown_to(text, out);
+                out.push('_');
+            }
+            Inline::Link { label, target } => {
+                out.push('[');
+                out.push_str(label);
+                out.push_str("](");
+                out.push_str(target);
+                out.push(')');
+            }
+            Inline::Example(code) => {
+                start_block(out);
+                out.push_str("```\n");
+                out.push_str(code);
+                out.push_str("\n```\n");
+            }
+            Inline::ParagraphBreak => start_block(out),
+        }
+    }
+}
+
+
+
+// ==================
+// === Plain Text ===
+// ==================
+
+/// Render the text without any markup. Links are represented by their labels.
+pub fn plain_text(text: RichText) -> String {
+    let mut out = String::new();
+    plain_text_to(&text, &mut out);
+    out
+}
+
+fn plain_text_to(text: &[Inline], out: &mut String) {
+    for inline in text {
+        match inline {
+            Inline::Text(text) | Inline::Code(text) | Inline::TypeReference(text) =>
+                out.push_str(text),
+            Inline::Emphasis(text) => plain_text_to(text, out),
+            Inline::Link { label, .. } => out.push_str(label),
+            Inline::Example(code) => {
+                start_block(out);
+                out.push_str(code);
+                out.push('\n');
+            }
+            Inline::ParagraphBreak => start_block(out),
+        }
+    }
+}
+
+
+// === Helpers ===
+
+/// Unless the output is empty, end it with a blank line, so that the following content starts a
+/// new block.
+fn start_block(out: &mut String) {
+    if !out.is_empty() {
+        let newlines = out.len() - out.trim_end_matches('\n').len();
+        out.extend(iter::repeat('\n').take(2usize.saturating_sub(newlines)));
+    }
+}
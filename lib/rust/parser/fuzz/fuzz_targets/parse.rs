@@ -0,0 +1,16 @@
+//! Check the invariants of the parser (see [`enso_parser_debug::fuzz::check`]) for arbitrary
+//! inputs. Inputs that violate them are minimized and saved as regression cases of the
+//! `enso-parser-debug` crate.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(code) = std::str::from_utf8(data) {
+        if let Err(failure) = enso_parser_debug::fuzz::check(code) {
+            let dir = std::path::Path::new(enso_parser_debug::fuzz::REGRESSION_CASES);
+            enso_parser_debug::fuzz::record_failure(code, &failure, dir);
+        }
+    }
+});
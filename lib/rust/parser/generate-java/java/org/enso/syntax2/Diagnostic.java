@@ -0,0 +1,71 @@
+package org.enso.syntax2;
+
+import java.nio.ByteBuffer;
+import java.nio.charset.StandardCharsets;
+import java.util.ArrayList;
+import java.util.List;
+
+/**
+ * A syntax error found in parsed code. Locations are offsets in UTF-16 code units from the start of the
+ * module; an end offset is exclusive.
+ *
+ * @param code the stable code identifying the kind of problem
+ * @param message a description of the problem
+ * @param start the start of the code containing the problem
+ * @param end the end of the code containing the problem
+ * @param labels other code related to the problem
+ * @param fixes changes that could be made to resolve the problem
+ */
+public record Diagnostic(
+    String code, String message, int start, int end, List<Label> labels, List<Fix> fixes) {
+
+  /** Code related to a diagnostic, with a description of how it is related. */
+  public record Label(int start, int end, String message) {}
+
+  /** A suggested change to the code, consisting of non-overlapping edits. */
+  public record Fix(String message, List<Edit> edits) {}
+
+  /** A replacement of a range of code. */
+  public record Edit(int start, int end, String text) {}
+
+  /** Read diagnostics in the format written by the native parser. */
+  static List<Diagnostic> readAll(ByteBuffer buffer) {
+    int count = buffer.getInt();
+    var diagnostics = new ArrayList<Diagnostic>(count);
+    for (int i = 0; i < count; i++) {
+      var code = getString(buffer);
+      var message = getString(buffer);
+      int start = buffer.getInt();
+      int end = buffer.getInt();
+      int labelCount = buffer.getInt();
+      var labels = new ArrayList<Label>(labelCount);
+      for (int j = 0; j < labelCount; j++) {
+        int labelStart = buffer.getInt();
+        int labelEnd = buffer.getInt();
+        labels.add(new Label(labelStart, labelEnd, getString(buffer)));
+      }
+      int fixCount = buffer.getInt();
+      var fixes = new ArrayList<Fix>(fixCount);
+      for (int j = 0; j < fixCount; j++) {
+        var fixMessage = getString(buffer);
+        int editCount = buffer.getInt();
+        var edits = new ArrayList<Edit>(editCount);
+        for (int k = 0; k < editCount; k++) {
+          int editStart = buffer.getInt();
+          int editEnd = buffer.getInt();
+          edits.add(new Edit(editStart, editEnd, getString(buffer)));
+        }
+        fixes.add(new Fix(fixMessage, List.copyOf(edits)));
+      }
+      diagnostics.add(
+          new Diagnostic(code, message, start, end, List.copyOf(labels), List.copyOf(fixes)));
+    }
+    return List.copyOf(diagnostics);
+  }
+
+  private static String getString(ByteBuffer buffer) {
+    byte[] bytes = new byte[buffer.getInt()];
+    buffer.get(bytes);
+    return new String(bytes, StandardCharsets.UTF_8);
+  }
+}
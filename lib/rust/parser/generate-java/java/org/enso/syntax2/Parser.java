@@ -5,6 +5,8 @@ import java.net.URISyntaxException;
 import java.nio.ByteBuffer;
 import java.nio.ByteOrder;
 import java.nio.charset.StandardCharsets;
+import java.util.ArrayList;
+import java.util.List;
 
 public final class Parser implements AutoCloseable {
   static {
@@ -64,6 +66,10 @@ public final class Parser implements AutoCloseable {
 
   private static native ByteBuffer parseInput(long state, ByteBuffer input);
 
+  private static native ByteBuffer parseBatch(long state, ByteBuffer input);
+
+  private static native ByteBuffer parseIncremental(long state, ByteBuffer input);
+
   private static native long getLastInputBase(long state);
 
   private static native long getMetadata(long state);
@@ -89,6 +95,80 @@ public final class Parser implements AutoCloseable {
     return Tree.deserialize(message);
   }
 
+  /**
+   * Parse many modules in one native call.
+   *
+   * @param inputs the code of each module
+   * @return the tree and diagnostics of each module, in the order of the inputs
+   * @throws ParserException if the native parser fails
+   */
+  public List<Result> parseBatch(List<? extends CharSequence> inputs) {
+    var encoded = new ArrayList<byte[]>(inputs.size());
+    int size = 4 + 4 * inputs.size();
+    for (var input : inputs) {
+      var bytes = input.toString().getBytes(StandardCharsets.UTF_8);
+      encoded.add(bytes);
+      size += bytes.length;
+    }
+    ByteBuffer inputBuf = ByteBuffer.allocateDirect(size);
+    inputBuf.order(ByteOrder.LITTLE_ENDIAN);
+    inputBuf.putInt(inputs.size());
+    for (var bytes : encoded) {
+      inputBuf.putInt(bytes.length);
+    }
+    for (var bytes : encoded) {
+      inputBuf.put(bytes);
+    }
+    return readResults(parseBatch(state, inputBuf), inputs);
+  }
+
+  /**
+   * Parse a module, reusing the result of the previous call to this method where possible. Only
+   * the top-level statements affected by the changes since the previous input are parsed again.
+   *
+   * @param input the code of the module
+   * @return the tree and diagnostics of the module
+   * @throws ParserException if the native parser fails
+   */
+  public Result parseIncremental(CharSequence input) {
+    byte[] inputBytes = input.toString().getBytes(StandardCharsets.UTF_8);
+    ByteBuffer inputBuf = ByteBuffer.allocateDirect(inputBytes.length);
+    inputBuf.put(inputBytes);
+    return readResults(parseIncremental(state, inputBuf), List.of(input)).get(0);
+  }
+
+  private static List<Result> readResults(
+      ByteBuffer output, List<? extends CharSequence> inputs) {
+    output.order(ByteOrder.LITTLE_ENDIAN);
+    int count = output.getInt();
+    var results = new ArrayList<Result>(count);
+    for (int i = 0; i < count; i++) {
+      long base = output.getLong();
+      long metadata = output.getLong();
+      var treeBuf = slice(output, output.getLong(), output.getLong());
+      var diagnosticsBuf = slice(output, output.getLong(), output.getLong());
+      var message = new Message(treeBuf, inputs.get(i), base, metadata);
+      var tree = Tree.deserialize(message);
+      results.add(new Result(tree, Diagnostic.readAll(diagnosticsBuf)));
+    }
+    return results;
+  }
+
+  private static ByteBuffer slice(ByteBuffer buffer, long offset, long length) {
+    var slice = buffer.duplicate();
+    slice.position((int) offset);
+    slice.limit((int) (offset + length));
+    return slice.slice().order(ByteOrder.LITTLE_ENDIAN);
+  }
+
+  /**
+   * The result of parsing a module.
+   *
+   * @param tree the parse tree
+   * @param diagnostics the syntax errors in the tree, in source order
+   */
+  public record Result(Tree tree, List<Diagnostic> diagnostics) {}
+
   @Override
   public void close() {
     freeState(state);
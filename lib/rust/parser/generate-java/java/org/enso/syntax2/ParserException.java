@@ -0,0 +1,8 @@
+package org.enso.syntax2;
+
+/** Thrown when the native parser fails, due to invalid input or an internal error. */
+public final class ParserException extends RuntimeException {
+  public ParserException(String errorMessage) {
+    super(errorMessage);
+  }
+}
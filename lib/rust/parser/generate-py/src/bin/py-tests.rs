@@ -0,0 +1,61 @@
+//! Generates Python format tests.
+//!
+//! Usage:
+//! ```console
+//! generate-py enso_ast.py
+//! py-tests > format_tests.py
+//! python3 format_tests.py
+//! ```
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+
+
+// ==============================
+// === Python Test Generation ===
+// ==============================
+
+fn main() {
+    let cases = enso_parser_generate_py::generate_testcases();
+    let fmt_cases = |cases: &[Vec<u8>]| {
+        let cases: Vec<_> = cases
+            .iter()
+            .map(|case| {
+                let case: Vec<_> = case.iter().map(|byte| byte.to_string()).collect();
+                format!("    bytes([{}]),", case.join(", "))
+            })
+            .collect();
+        cases.join("\n")
+    };
+    let module = enso_parser_generate_py::MODULE;
+    println!("import sys");
+    println!();
+    println!("from {module} import FormatError, Tree, decode");
+    println!();
+    println!("accept = [\n{}\n]", fmt_cases(&cases.accept));
+    println!("reject = [\n{}\n]", fmt_cases(&cases.reject));
+    println!();
+    println!("result = 0");
+    println!("for case in accept:");
+    println!("    try:");
+    println!("        decode(Tree, case)");
+    println!("        print('- pass')");
+    println!("    except Exception as error:");
+    println!("        print(f'- fail: {{error!r}}')");
+    println!("        result = 1");
+    println!("for case in reject:");
+    println!("    try:");
+    println!("        decode(Tree, case)");
+    println!("        print('- fail: accepted')");
+    println!("        result = 1");
+    println!("    except FormatError:");
+    println!("        print('- pass: (rejected)')");
+    println!("    except Exception as error:");
+    println!("        print(f'- fail: wrong exception: {{error!r}}')");
+    println!("        result = 1");
+    println!("sys.exit(result)");
+}
@@ -0,0 +1,60 @@
+//! Supports generation of Python types corresponding to `enso-parser`'s AST types, and testing the
+//! generated decoder.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_metamodel::meta;
+use enso_parser::syntax;
+use enso_reflect::Reflect;
+
+
+
+// =====================
+// === Configuration ===
+// =====================
+
+/// The name of the generated module.
+pub const MODULE: &str = "enso_ast";
+
+
+
+// ==================
+// === Generation ===
+// ==================
+
+/// Generate a Python module implementing the parser types rooted at `syntax::Tree`.
+pub fn generate() -> String {
+    let (graph, _) = graph();
+    enso_metamodel::python::implement(&graph)
+}
+
+/// Generate accept/reject test case set for the parser types rooted at `syntax::Tree`, in the
+/// format read by the generated code.
+pub fn generate_testcases() -> meta::serialization::TestCases {
+    let (graph, root) = graph();
+    meta::format::testcases(&graph, root)
+}
+
+fn graph() -> (meta::TypeGraph, meta::TypeId) {
+    let root = syntax::Tree::reflect();
+    let root_id = root.id;
+    let (graph, rust_to_meta) = enso_metamodel::rust::to_meta(root);
+    let root = rust_to_meta[&root_id];
+    (graph, root)
+}
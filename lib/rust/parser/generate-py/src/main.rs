@@ -0,0 +1,33 @@
+//! Generate the Python types corresponding to `enso-parser`'s AST types.
+//!
+//! # Usage
+//!
+//! Generated code will be written to the file given as an argument:
+//! ```console
+//! generate-py enso_ast.py
+//! ```
+//!
+//! The module can then be used to decode the output of the parser's `format` serialization:
+//! ```python
+//! import enso_ast
+//! tree = enso_ast.decode(enso_ast.Tree, data)
+//! ```
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+
+
+// =========================
+// === Python Generation ===
+// =========================
+
+fn main() {
+    let mut args = std::env::args();
+    args.next().unwrap();
+    let path = args.next().expect("Usage: generate-py <output-file>");
+    std::fs::write(path, enso_parser_generate_py::generate()).unwrap();
+}
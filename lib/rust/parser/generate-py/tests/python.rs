@@ -0,0 +1,42 @@
+//! Check that the generated Python module is valid Python code.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+use std::process::Command;
+
+
+
+// =============
+// === Tests ===
+// =============
+
+/// Import the generated module with a Python interpreter. Importing the module executes its class
+/// definitions, so this catches more errors than compiling it. The test is skipped if no Python
+/// interpreter is installed.
+#[test]
+fn generated_module_imports() {
+    let module = enso_parser_generate_py::MODULE;
+    let dir = std::env::temp_dir().join("enso-parser-generate-py");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join(format!("{module}.py")), enso_parser_generate_py::generate()).unwrap();
+    let output = Command::new("python3")
+        .arg("-B")
+        .arg("-c")
+        .arg(format!("import {module}"))
+        .current_dir(&dir)
+        .output();
+    let output = match output {
+        Ok(output) => output,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            eprintln!("Skipping the test: `python3` not found.");
+            return;
+        }
+        Err(error) => panic!("Failed to run `python3`: {error}."),
+    };
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "Importing the generated module failed:\n{stderr}");
+}
@@ -0,0 +1,89 @@
+//! Generates TypeScript format tests.
+//!
+//! The test reads each case with the generated code, and accesses every field of every object it
+//! contains. Accept cases must be read without error; reject cases must cause an exception.
+//!
+//! Usage:
+//! ```console
+//! generate-ts ast.ts
+//! ts-tests > formatTests.ts
+//! ```
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+
+
+// ==================================
+// === TypeScript Test Generation ===
+// ==================================
+
+fn main() {
+    let cases = enso_parser_generate_ts::generate_testcases();
+    let fmt_cases = |cases: &[Vec<u8>]| {
+        let cases: Vec<_> = cases
+            .iter()
+            .map(|case| {
+                let case: Vec<_> = case.iter().map(|byte| byte.to_string()).collect();
+                format!("  new Uint8Array([{}]),", case.join(", "))
+            })
+            .collect();
+        cases.join("\n")
+    };
+    let support = enso_parser_generate_ts::SUPPORT;
+    println!("import {{ LazyObject, type Result }} from '{support}'");
+    println!("import {{ Tree }} from './ast'");
+    println!();
+    println!("const accept = [\n{}\n]", fmt_cases(&cases.accept));
+    println!("const reject = [\n{}\n]", fmt_cases(&cases.reject));
+    println!();
+    println!("function walk(value: unknown) {{");
+    println!("  if (value instanceof LazyObject) {{");
+    println!("    let proto = Object.getPrototypeOf(value)");
+    println!("    while (proto !== LazyObject.prototype) {{");
+    println!("      const descriptors = Object.getOwnPropertyDescriptors(proto)");
+    println!("      for (const descriptor of Object.values(descriptors)) {{");
+    println!("        if (descriptor.get) walk(descriptor.get.call(value))");
+    println!("      }}");
+    println!("      proto = Object.getPrototypeOf(proto)");
+    println!("    }}");
+    println!("    value.visitChildren((child) => walk(child))");
+    println!("  }} else if (typeof value === 'object' && value !== null) {{");
+    println!("    if (Symbol.iterator in value) {{");
+    println!("      for (const element of value as Iterable<unknown>) walk(element)");
+    println!("    }} else if ('ok' in value) {{");
+    println!("      const result = value as Result<unknown, unknown>");
+    println!("      walk(result.ok ? result.value : result.error.payload)");
+    println!("    }}");
+    println!("  }}");
+    println!("}}");
+    println!();
+    println!("function read(testCase: Uint8Array) {{");
+    println!("  const view = new DataView(testCase.buffer)");
+    println!("  walk(Tree.read(view, testCase.byteLength - 4))");
+    println!("}}");
+    println!();
+    println!("let result = 0");
+    println!("for (const testCase of accept) {{");
+    println!("  try {{");
+    println!("    read(testCase)");
+    println!("    console.log('- pass')");
+    println!("  }} catch (e) {{");
+    println!("    console.log('- fail:', e)");
+    println!("    result = 1");
+    println!("  }}");
+    println!("}}");
+    println!("for (const testCase of reject) {{");
+    println!("  try {{");
+    println!("    read(testCase)");
+    println!("    console.log('- fail: accepted')");
+    println!("    result = 1");
+    println!("  }} catch (e) {{");
+    println!("    console.log('- pass: (rejected)')");
+    println!("  }}");
+    println!("}}");
+    println!("process.exit(result)");
+}
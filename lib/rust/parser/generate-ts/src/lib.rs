@@ -0,0 +1,88 @@
+//! Supports generation of TypeScript types corresponding to `enso-parser`'s AST types, and testing
+//! the generated deserialization.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_metamodel::meta;
+use enso_metamodel::typescript;
+use enso_parser::syntax;
+use enso_reflect::Reflect;
+
+
+
+// =====================
+// === Configuration ===
+// =====================
+
+/// The module providing runtime support for the generated code.
+pub const SUPPORT: &str = "@/util/parserSupport";
+
+
+
+// ==================
+// === Generation ===
+// ==================
+
+/// Generate TypeScript code implementing the parser types rooted at `syntax::Tree`.
+pub fn generate() -> String {
+    let (graph, _) = graph();
+    typescript::to_syntax(&graph, SUPPORT)
+}
+
+/// Generate accept/reject test case set for the parser types rooted at `syntax::Tree`, in the
+/// format read by the generated code.
+pub fn generate_testcases() -> meta::serialization::TestCases {
+    let (graph, root) = graph();
+    meta::format::testcases(&graph, root)
+}
+
+fn graph() -> (meta::TypeGraph, meta::TypeId) {
+    let root = syntax::Tree::reflect();
+    let root_id = root.id;
+    let (graph, rust_to_meta) = enso_metamodel::rust::to_meta(root);
+    let root = rust_to_meta[&root_id];
+    (graph, root)
+}
+
+
+
+// ===========================
+// === Rust Format Testing ===
+// ===========================
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Check that the test cases are transcoded to the format produced by `enso_parser::format`.
+    #[test]
+    fn test_format() {
+        let (graph, root) = graph();
+        let cases = meta::serialization::testcases(&graph, root);
+        for (i, case) in cases.accept.iter().enumerate() {
+            let tree = enso_parser::serialization::deserialize_tree(case).unwrap();
+            // Deserialization doesn't preserve some hidden fields; reserialize the tree, so that
+            // both encodings are of the same value.
+            let bincode = enso_parser::serialization::serialize_tree(&tree).unwrap();
+            let expected = enso_parser::format::serialize(&tree).unwrap();
+            let transcoded = meta::format::transcode(&graph, root, &bincode);
+            assert_eq!(transcoded, expected, "accept{i}");
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! Generate the TypeScript types corresponding to `enso-parser`'s AST types.
+//!
+//! # Usage
+//!
+//! Generated code will be written to the file given as an argument:
+//! ```console
+//! generate-ts src/generated/ast.ts
+//! ```
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+
+
+// =============================
+// === TypeScript Generation ===
+// =============================
+
+fn main() {
+    let mut args = std::env::args();
+    args.next().unwrap();
+    let path = args.next().expect("Usage: generate-ts <output-file>");
+    std::fs::write(path, enso_parser_generate_ts::generate()).unwrap();
+}
@@ -20,10 +20,20 @@
 
 use enso_prelude::*;
 
+use enso_parser::diagnostic::Diagnostic;
+use enso_parser::metadata::Metadata;
+use enso_parser::source::Code;
+use enso_parser::syntax::tree::CodeVisitableMut;
+use enso_parser::syntax::tree::CodeVisitorMut;
+use enso_parser::syntax::tree::Visitor;
+use enso_parser::syntax::Tree;
+use enso_text::Byte;
+use enso_text::Change;
 use jni::objects::JByteBuffer;
 use jni::objects::JClass;
 use jni::sys::jobject;
 use jni::JNIEnv;
+use std::panic;
 
 
 
@@ -49,36 +59,87 @@ pub extern "system" fn Java_org_enso_syntax2_Parser_parseInput(
     input: JByteBuffer,
 ) -> jobject {
     let state = unsafe { &mut *(state as usize as *mut State) };
-    let direct_allocated = "Internal Error: ByteBuffer must be direct-allocated.";
-    let input = env.get_direct_buffer_address(input).expect(direct_allocated);
-    let input = if cfg!(debug_assertions) {
-        std::str::from_utf8(input).unwrap()
-    } else {
-        unsafe { std::str::from_utf8_unchecked(input) }
-    };
-    let mut code = input;
-    let mut meta = None;
-    if let Some((meta_, code_)) = enso_parser::metadata::parse(input) {
-        match meta_ {
-            Ok(meta_) => meta = Some(meta_),
-            Err(e) => error!("Ignoring invalid metadata: {e}."),
-        }
-        code = code_;
-    }
-    state.base = str::as_ptr(code) as usize as u64;
-    let tree = enso_parser::Parser::new().run(code);
-    state.output = match enso_parser::serialization::serialize_tree(&tree) {
-        Ok(tree) => tree,
-        // `Tree` does not contain any types with fallible `serialize` implementations, so this
-        // cannot fail.
-        Err(_) => {
-            debug_assert!(false);
-            default()
+    run_native(&env, std::ptr::null_mut(), || {
+        let input = input_str(&env, input)?;
+        let (code, meta) = split_metadata(input);
+        state.base = str::as_ptr(code) as usize as u64;
+        let tree = state.parser.run(code);
+        state.output = serialize_tree(&tree);
+        state.metadata = meta;
+        output_buffer(&env, &mut state.output)
+    })
+}
+
+/// Parse a batch of modules. Returns a buffer containing the serialized parse tree and the
+/// diagnostics of each module; see [`write_modules`] for its format. The caller is responsible for
+/// freeing the memory associated with the returned buffer.
+///
+/// The input buffer contains the number of modules, and the length in bytes of each module, as
+/// little-endian 32-bit integers; they are followed by the code of the modules.
+///
+/// # Safety
+///
+/// The state MUST be a value returned by `allocState` that has not been passed to `freeState`.
+/// The input buffer contents MUST be valid UTF-8.
+/// The contents of the returned buffer, and the metadata pointers it contains, MUST not be
+/// accessed after another call to a parsing function, or a call to `freeState`.
+#[allow(unsafe_code)]
+#[no_mangle]
+pub extern "system" fn Java_org_enso_syntax2_Parser_parseBatch(
+    env: JNIEnv,
+    _class: JClass,
+    state: u64,
+    input: JByteBuffer,
+) -> jobject {
+    let state = unsafe { &mut *(state as usize as *mut State) };
+    run_native(&env, std::ptr::null_mut(), || {
+        let inputs = read_batch(input_bytes(&env, input)?)?;
+        let mut modules = Vec::with_capacity(inputs.len());
+        state.batch_metadata.clear();
+        for input in inputs {
+            let input = input_utf8(input)?;
+            let (code, meta) = split_metadata(input);
+            let (tree, diagnostics) = state.parser.run_with_diagnostics(code);
+            let base = str::as_ptr(code) as usize as u64;
+            modules.push(Module { base, tree: serialize_tree(&tree), diagnostics });
+            state.batch_metadata.push(meta);
         }
-    };
-    state.metadata = meta;
-    let result = env.new_direct_byte_buffer(&mut state.output);
-    result.unwrap().into_inner()
+        state.output = write_modules(&modules, &state.batch_metadata);
+        output_buffer(&env, &mut state.output)
+    })
+}
+
+/// Parse a module, reusing the result of the previous call to this function if possible: Only
+/// the top-level statements affected by the differences between the previous input and this input
+/// are parsed again. Returns a buffer in the format of the result of `parseBatch`, for one module.
+///
+/// # Safety
+///
+/// The state MUST be a value returned by `allocState` that has not been passed to `freeState`.
+/// The input buffer contents MUST be valid UTF-8.
+/// The contents of the returned buffer, and the metadata pointers it contains, MUST not be
+/// accessed after another call to a parsing function, or a call to `freeState`.
+#[allow(unsafe_code)]
+#[no_mangle]
+pub extern "system" fn Java_org_enso_syntax2_Parser_parseIncremental(
+    env: JNIEnv,
+    _class: JClass,
+    state: u64,
+    input: JByteBuffer,
+) -> jobject {
+    let state = unsafe { &mut *(state as usize as *mut State) };
+    run_native(&env, std::ptr::null_mut(), || {
+        let input = input_str(&env, input)?;
+        let (code, meta) = split_metadata(input);
+        let module = Previous::parse(&state.parser, state.previous.take(), code);
+        let diagnostics = enso_parser::diagnostic::collect(&module.tree);
+        let base = str::as_ptr(&module.code) as usize as u64;
+        let modules = [Module { base, tree: serialize_tree(&module.tree), diagnostics }];
+        state.previous = Some(module);
+        state.batch_metadata = vec![meta];
+        state.output = write_modules(&modules, &state.batch_metadata);
+        output_buffer(&env, &mut state.output)
+    })
 }
 
 /// Return the `base` parameter to pass to the `Message` class along with the other output of the
@@ -195,7 +256,7 @@ fn get_uuid(metadata: u64, code_offset: u64, code_length: u64) -> (u64, u64) {
     if metadata == 0 {
         return (0, 0);
     }
-    let metadata = unsafe { &*(metadata as usize as *const enso_parser::metadata::Metadata) };
+    let metadata = unsafe { &*(metadata as usize as *const Metadata) };
     let data = metadata.get_uuid(code_offset as usize, code_length as usize);
     match data {
         Some(uuid) => uuid.as_u64_pair(),
@@ -211,7 +272,333 @@ fn get_uuid(metadata: u64, code_offset: u64, code_length: u64) -> (u64, u64) {
 
 #[derive(Default, Debug)]
 struct State {
-    base:     u64,
-    output:   Vec<u8>,
-    metadata: Option<enso_parser::metadata::Metadata>,
+    parser:         enso_parser::Parser,
+    base:           u64,
+    output:         Vec<u8>,
+    metadata:       Option<Metadata>,
+    /// The metadata of each module in the output of the most recent call to `parseBatch` or
+    /// `parseIncremental`.
+    batch_metadata: Vec<Option<Metadata>>,
+    /// The result of the most recent call to `parseIncremental`.
+    previous:       Option<Previous>,
+}
+
+
+// === Previous ===
+
+/// A parse tree, and the code it was parsed from.
+#[derive(Debug)]
+struct Previous {
+    // Declared before `code`, so that it is dropped first.
+    tree: Tree<'static>,
+    code: Box<str>,
+}
+
+impl Previous {
+    /// Parse the code, reusing the previous result if available.
+    #[allow(unsafe_code)]
+    fn parse(parser: &enso_parser::Parser, previous: Option<Self>, code: &str) -> Self {
+        let code: Box<str> = code.into();
+        // SAFETY: The tree refers to the heap allocation of the code, which doesn't move when the
+        // `Box` is moved; both are stored in the result, which never exposes the tree with a
+        // lifetime outliving the code.
+        let code_ptr: *const str = &*code;
+        let code_ref: &'static str = unsafe { &*code_ptr };
+        let tree = match previous {
+            Some(Self { tree, code: previous_code }) => {
+                let change = change_between(&previous_code, code_ref);
+                // The reparsed tree refers only to the new code, so the previous code can be freed.
+                let mut tree = parser.reparse(tree, &[change], code_ref);
+                debug_assert!(refers_only_to(&mut tree, code_ref));
+                drop(previous_code);
+                tree
+            }
+            None => parser.run(code_ref),
+        };
+        Self { tree, code }
+    }
+}
+
+/// Return whether all the non-empty code references in the tree point into the given code.
+fn refers_only_to<'s>(tree: &mut Tree<'s>, code: &'s str) -> bool {
+    struct Check {
+        range:   Range<usize>,
+        outside: bool,
+    }
+    impl Visitor for Check {}
+    impl<'s> CodeVisitorMut<'s> for Check {
+        fn visit_mut(&mut self, code: &mut Code<'s>) {
+            let repr = code.repr.0;
+            let start = repr.as_ptr() as usize;
+            let within = self.range.start <= start && start + repr.len() <= self.range.end;
+            self.outside |= !repr.is_empty() && !within;
+        }
+    }
+    let start = code.as_ptr() as usize;
+    let mut check = Check { range: start..start + code.len(), outside: false };
+    tree.visit_code_mut(&mut check);
+    !check.outside
+}
+
+/// Return an edit that changes the old code to the new code.
+fn change_between<'a>(old: &str, new: &'a str) -> Change<Byte, &'a str> {
+    let common = |old: &mut dyn Iterator<Item = u8>, new: &mut dyn Iterator<Item = u8>| {
+        old.zip(new).take_while(|(old, new)| old == new).count()
+    };
+    let mut prefix = common(&mut old.bytes(), &mut new.bytes());
+    while !new.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let mut suffix = common(&mut old.bytes().rev().take(max_suffix), &mut new.bytes().rev());
+    while !new.is_char_boundary(new.len() - suffix) {
+        suffix -= 1;
+    }
+    let range = (Byte(prefix)..Byte(old.len() - suffix)).into();
+    Change { range, text: &new[prefix..new.len() - suffix] }
+}
+
+
+
+// ===============
+// === Helpers ===
+// ===============
+
+/// Run the body of a native method. If it returns an error or panics, throw a
+/// `ParserException` describing the error, and return `fallback`; the JVM ignores the value
+/// returned while an exception is pending.
+fn run_native<T>(env: &JNIEnv, fallback: T, body: impl FnOnce() -> Result<T, String>) -> T {
+    let error = match panic::catch_unwind(panic::AssertUnwindSafe(body)) {
+        Ok(Ok(value)) => return value,
+        Ok(Err(error)) => error,
+        Err(payload) => {
+            let message = match payload.downcast_ref::<&str>() {
+                Some(message) => message,
+                None => payload.downcast_ref::<String>().map_or("(no message)", |s| s.as_str()),
+            };
+            format!("Internal error: the parser panicked: {message}")
+        }
+    };
+    if env.throw_new("org/enso/syntax2/ParserException", &error).is_err() {
+        error!("Failed to throw exception for parser error: {error}");
+    }
+    fallback
+}
+
+fn input_bytes<'e>(env: &'e JNIEnv, input: JByteBuffer) -> Result<&'e [u8], String> {
+    let direct_allocated = "Internal Error: ByteBuffer must be direct-allocated.";
+    env.get_direct_buffer_address(input).map(|input| &*input).map_err(|_| direct_allocated.into())
+}
+
+fn input_utf8(input: &[u8]) -> Result<&str, String> {
+    std::str::from_utf8(input).map_err(|e| format!("Internal Error: Input is not UTF-8: {e}."))
+}
+
+fn input_str<'e>(env: &'e JNIEnv, input: JByteBuffer) -> Result<&'e str, String> {
+    input_utf8(input_bytes(env, input)?)
+}
+
+/// Split a source file into its code, and its metadata if present and valid.
+fn split_metadata(input: &str) -> (&str, Option<Metadata>) {
+    match enso_parser::metadata::parse(input) {
+        Some((Ok(meta), code)) => (code, Some(meta)),
+        Some((Err(e), code)) => {
+            error!("Ignoring invalid metadata: {e}.");
+            (code, None)
+        }
+        None => (input, None),
+    }
+}
+
+fn serialize_tree(tree: &Tree) -> Vec<u8> {
+    match enso_parser::serialization::serialize_tree(tree) {
+        Ok(tree) => tree,
+        // `Tree` does not contain any types with fallible `serialize` implementations, so this
+        // cannot fail.
+        Err(_) => {
+            debug_assert!(false);
+            default()
+        }
+    }
+}
+
+fn output_buffer(env: &JNIEnv, output: &mut [u8]) -> Result<jobject, String> {
+    let buffer = env.new_direct_byte_buffer(output);
+    buffer.map(|buffer| buffer.into_inner()).map_err(|e| format!("Internal Error: {e}."))
+}
+
+
+
+// ===================
+// === Batch input ===
+// ===================
+
+/// Split the input of `parseBatch` into the code of each module.
+fn read_batch(input: &[u8]) -> Result<Vec<&[u8]>, String> {
+    let malformed = || "Internal Error: Malformed batch input.".to_owned();
+    let mut words = input.chunks_exact(4).map(|word| u32::from_le_bytes(word.try_into().unwrap()));
+    let count = words.next().ok_or_else(malformed)? as usize;
+    let lengths: Vec<_> = words.take(count).map(|len| len as usize).collect();
+    if lengths.len() != count {
+        return Err(malformed());
+    }
+    let mut rest = &input[4 * (count + 1)..];
+    let mut modules = Vec::with_capacity(count);
+    for len in lengths {
+        if len > rest.len() {
+            return Err(malformed());
+        }
+        let (module, next) = rest.split_at(len);
+        modules.push(module);
+        rest = next;
+    }
+    Ok(modules)
+}
+
+
+
+// ====================
+// === Batch output ===
+// ====================
+
+/// The result of parsing a module.
+#[derive(Debug)]
+struct Module {
+    /// The `base` parameter to pass to the `Message` class when deserializing the tree.
+    base:        u64,
+    /// The serialized tree.
+    tree:        Vec<u8>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// The size of the entry describing each module in the output of `parseBatch`.
+const MODULE_HEADER_SIZE: usize = 6 * 8;
+
+/// Write the output of `parseBatch`. All numbers are little-endian. The output starts with the
+/// number of modules (32 bits); it is followed by an entry for each module, consisting of six
+/// 64-bit numbers:
+/// - The `base` parameter to pass to the `Message` class with the serialized tree.
+/// - The metadata pointer to pass to the `Message` class; `0` if the module has no metadata.
+/// - The offset and length of the serialized tree within the output.
+/// - The offset and length of the serialized diagnostics within the output; see
+///   [`write_diagnostics`].
+fn write_modules(modules: &[Module], metadata: &[Option<Metadata>]) -> Vec<u8> {
+    let header_size = 4 + modules.len() * MODULE_HEADER_SIZE;
+    let mut header = Vec::with_capacity(header_size);
+    let mut data = vec![];
+    header.extend((modules.len() as u32).to_le_bytes());
+    for (module, metadata) in modules.iter().zip(metadata) {
+        let metadata = metadata.as_ref().map_or(0, |metadata| {
+            let metadata: *const Metadata = metadata;
+            metadata as usize as u64
+        });
+        let tree_offset = header_size + data.len();
+        data.extend(&module.tree);
+        let diagnostics_offset = header_size + data.len();
+        write_diagnostics(&module.diagnostics, &mut data);
+        let diagnostics_len = header_size + data.len() - diagnostics_offset;
+        let entry = [module.base, metadata, tree_offset as u64, module.tree.len() as u64];
+        let entry = entry.into_iter().chain([diagnostics_offset as u64, diagnostics_len as u64]);
+        header.extend(entry.flat_map(u64::to_le_bytes));
+    }
+    header.append(&mut data);
+    header
+}
+
+/// Write diagnostics in the format read by `Diagnostic.java`. All numbers are little-endian and
+/// 32 bits. Strings are written as their length in bytes, followed by their UTF-8 encoding.
+/// Locations are offsets in UTF-16 code units from the start of the module. The diagnostics are
+/// preceded by their number; each consists of:
+/// - Its code, message, start, and end.
+/// - The number of secondary labels, and for each: its start, end, and message.
+/// - The number of fixes, and for each: its message; the number of its edits, and for each: its
+///   start, end, and replacement text.
+fn write_diagnostics(diagnostics: &[Diagnostic], out: &mut Vec<u8>) {
+    let write_u32 = |out: &mut Vec<u8>, value: usize| out.extend((value as u32).to_le_bytes());
+    let write_str = |out: &mut Vec<u8>, value: &str| {
+        write_u32(out, value.len());
+        out.extend(value.as_bytes());
+    };
+    let write_span = |out: &mut Vec<u8>, span: &Range<enso_parser::source::code::Location>| {
+        write_u32(out, span.start.utf16 as usize);
+        write_u32(out, span.end.utf16 as usize);
+    };
+    write_u32(out, diagnostics.len());
+    for diagnostic in diagnostics {
+        write_str(out, diagnostic.code());
+        write_str(out, &diagnostic.message);
+        write_span(out, &diagnostic.span);
+        write_u32(out, diagnostic.secondary.len());
+        for label in &diagnostic.secondary {
+            write_span(out, &label.span);
+            write_str(out, &label.message);
+        }
+        write_u32(out, diagnostic.fixes.len());
+        for fix in &diagnostic.fixes {
+            write_str(out, &fix.message);
+            write_u32(out, fix.edits.len());
+            for edit in &fix.edits {
+                write_span(out, &edit.range);
+                write_str(out, &edit.text);
+            }
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_between() {
+        let apply = |old: &str, new: &str| {
+            let change = change_between(old, new);
+            let (start, end) = (change.range.start.value, change.range.end.value);
+            (start, end, change.text.to_owned())
+        };
+        assert_eq!(apply("a = 1\nb = 2", "a = 1\nb = 23"), (11, 11, "3".into()));
+        assert_eq!(apply("x = 1", "x = 1"), (5, 5, "".into()));
+        assert_eq!(apply("aaa", "aa"), (2, 3, "".into()));
+        // The edit is extended to character boundaries: `ą` and `ć` share their first byte.
+        assert_eq!(apply("x = 'ą'", "x = 'ć'"), (5, 7, "ć".into()));
+    }
+
+    #[test]
+    fn test_read_batch() {
+        let mut input = vec![];
+        for word in [2u32, 3, 0] {
+            input.extend(word.to_le_bytes());
+        }
+        input.extend(b"a b");
+        assert_eq!(read_batch(&input), Ok(vec![&b"a b"[..], &b""[..]]));
+        assert!(read_batch(&input[..input.len() - 1]).is_err());
+        assert!(read_batch(&[1, 0, 0, 0]).is_err());
+        assert_eq!(read_batch(&[0, 0, 0, 0]), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_write_modules() {
+        let parser = enso_parser::Parser::new();
+        let code = "x = (1";
+        let (tree, diagnostics) = parser.run_with_diagnostics(code);
+        let tree = serialize_tree(&tree);
+        let module = Module { base: 7, tree: tree.clone(), diagnostics };
+        let output = write_modules(&[module], &[None]);
+        let word = |i: usize| u64::from_le_bytes(output[4 + 8 * i..][..8].try_into().unwrap());
+        assert_eq!(output[..4], 1u32.to_le_bytes());
+        assert_eq!((word(0), word(1)), (7, 0));
+        let (tree_offset, tree_len) = (word(2) as usize, word(3) as usize);
+        assert_eq!(output[tree_offset..][..tree_len], tree);
+        let (diagnostics_offset, diagnostics_len) = (word(4) as usize, word(5) as usize);
+        assert_eq!(diagnostics_offset + diagnostics_len, output.len());
+        let diagnostics = &output[diagnostics_offset..];
+        assert_eq!(diagnostics[..8], [1, 0, 0, 0, 4, 0, 0, 0]);
+        assert_eq!(&diagnostics[8..12], b"E003");
+    }
 }
@@ -0,0 +1,169 @@
+//! Configuration of the enabled rules, loaded from TOML or YAML.
+//!
+//! A configuration file has a `rules` table, mapping rule names or identifiers to levels. Rules
+//! that are not mentioned have their default levels. In TOML:
+//! ```toml
+//! [rules]
+//! unused-import = "deny"
+//! L002 = "allow"
+//! ```
+//! And in YAML:
+//! ```yaml
+//! rules:
+//!   unused-import: deny
+//!   L002: allow
+//! ```
+
+use crate::Rule;
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+
+
+// =============
+// === Level ===
+// =============
+
+/// Whether a rule is checked, and how serious the problems it finds are.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    /// The rule is not checked.
+    #[serde(alias = "off")]
+    Allow,
+    /// Problems are reported as warnings.
+    #[serde(alias = "warning")]
+    Warn,
+    /// Problems are reported as errors.
+    #[serde(alias = "error")]
+    Deny,
+}
+
+
+
+// ==============
+// === Config ===
+// ==============
+
+/// The levels of the rules.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Config {
+    levels: HashMap<Rule, Level>,
+}
+
+/// The format of a configuration file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct File {
+    #[serde(default)]
+    rules: BTreeMap<String, Level>,
+}
+
+impl Config {
+    /// Return the level of the given rule.
+    pub fn level(&self, rule: Rule) -> Level {
+        self.levels.get(&rule).copied().unwrap_or_else(|| rule.default_level())
+    }
+
+    /// Set the level of the given rule.
+    pub fn set_level(&mut self, rule: Rule, level: Level) {
+        self.levels.insert(rule, level);
+    }
+
+    /// Parse a configuration in TOML.
+    pub fn from_toml(text: &str) -> Result<Self, Error> {
+        let file = toml::from_str(text).map_err(|error| Error::Syntax(error.to_string()))?;
+        Self::from_file(file)
+    }
+
+    /// Parse a configuration in YAML.
+    pub fn from_yaml(text: &str) -> Result<Self, Error> {
+        let file = serde_yaml::from_str(text).map_err(|error| Error::Syntax(error.to_string()))?;
+        Self::from_file(file)
+    }
+
+    /// Read a configuration file; its format is determined by its extension, which must be `toml`,
+    /// `yaml`, or `yml`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        let parse = match extension {
+            Some("toml") => Self::from_toml,
+            Some("yaml" | "yml") => Self::from_yaml,
+            _ => return Err(Error::UnknownFormat(path.display().to_string())),
+        };
+        let text = std::fs::read_to_string(path).map_err(|error| Error::Io(error.to_string()))?;
+        parse(&text)
+    }
+
+    fn from_file(file: File) -> Result<Self, Error> {
+        let mut config = Self::default();
+        for (name, level) in file.rules {
+            let rule = Rule::from_name(&name).ok_or(Error::UnknownRule(name))?;
+            config.set_level(rule, level);
+        }
+        Ok(config)
+    }
+}
+
+
+
+// =============
+// === Error ===
+// =============
+
+/// An error loading a configuration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The file could not be read.
+    Io(String),
+    /// The file extension does not identify a supported format.
+    UnknownFormat(String),
+    /// The configuration is not valid TOML or YAML, or does not have the expected structure.
+    Syntax(String),
+    /// The configuration refers to a rule that doesn't exist.
+    UnknownRule(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "Failed to read the configuration: {error}"),
+            Error::UnknownFormat(path) =>
+                write!(f, "Unknown configuration format (expected TOML or YAML): {path}"),
+            Error::Syntax(error) => write!(f, "Invalid configuration: {error}"),
+            Error::UnknownRule(name) => write!(f, "Unknown rule: {name}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config() {
+        let toml = "[rules]\nunused-import = \"deny\"\nL002 = \"off\"\n";
+        let yaml = "rules:\n  unused-import: deny\n  L002: off\n";
+        for config in [Config::from_toml(toml).unwrap(), Config::from_yaml(yaml).unwrap()] {
+            assert_eq!(config.level(Rule::UnusedImport), Level::Deny);
+            assert_eq!(config.level(Rule::ShadowedName), Level::Allow);
+            assert_eq!(config.level(Rule::UnreachableCaseArm), Level::Warn);
+        }
+        let error = Config::from_toml("[rules]\nno-such-rule = \"warn\"\n").unwrap_err();
+        assert_eq!(error, Error::UnknownRule("no-such-rule".into()));
+        assert!(matches!(Config::from_yaml("rules:\n  L001: sometimes\n"), Err(Error::Syntax(_))));
+    }
+}
@@ -0,0 +1,122 @@
+//! Configurable lints for Enso source files.
+//!
+//! A lint is a [`Rule`] checking a parsed [`Tree`] for code that is syntactically valid, but likely
+//! to be a mistake or hard to read: for example, an unused import, or an operator section whose
+//! meaning depends on subtle differences in spacing. Each rule has a stable identifier, e.g.
+//! `L001`, and a name, e.g. `unused-import`; a [`Config`] determines which rules are enabled, and
+//! at which [`Level`].
+//!
+//! The [`output`] module renders the resulting [`Diagnostic`]s, as text for humans or as JSON for
+//! tools.
+
+// === Features ===
+#![feature(let_chains)]
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_parser::diagnostic::Label;
+use enso_parser::source::code::Location;
+use enso_parser::syntax::Tree;
+use std::ops::Range;
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub mod config;
+pub mod output;
+pub mod rules;
+
+pub use config::Config;
+pub use config::Level;
+pub use rules::Rule;
+
+
+
+// ==================
+// === Diagnostic ===
+// ==================
+
+/// How serious a problem reported by a [`Diagnostic`] is.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[allow(missing_docs)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem found by a lint [`Rule`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The rule that found the problem.
+    pub rule:      Rule,
+    /// How serious the problem is, according to the level of the rule.
+    pub severity:  Severity,
+    /// A description of the problem.
+    pub message:   String,
+    /// The code containing the problem.
+    pub span:      Range<Location>,
+    /// Other code related to the problem.
+    pub secondary: Vec<Label>,
+}
+
+impl Diagnostic {
+    /// Constructor. The diagnostic has [`Severity::Warning`] until the level of its rule is
+    /// applied.
+    pub fn new(rule: Rule, message: impl Into<String>, span: Range<Location>) -> Self {
+        let message = message.into();
+        Self { rule, severity: Severity::Warning, message, span, secondary: vec![] }
+    }
+
+    /// Add a secondary label.
+    pub fn with_label(mut self, message: impl Into<String>, span: Range<Location>) -> Self {
+        let message: String = message.into();
+        self.secondary.push(Label { span, message: message.into() });
+        self
+    }
+
+    /// Return the stable identifier of the rule that found the problem.
+    pub fn id(&self) -> &'static str {
+        self.rule.id()
+    }
+}
+
+
+
+// ============
+// === Lint ===
+// ============
+
+/// Run the rules enabled by the configuration over the tree. Return the problems found, in source
+/// order.
+pub fn lint(tree: &Tree, config: &Config) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    for rule in Rule::all() {
+        let severity = match config.level(rule) {
+            Level::Allow => continue,
+            Level::Warn => Severity::Warning,
+            Level::Deny => Severity::Error,
+        };
+        let start = diagnostics.len();
+        rule.check(tree, &mut diagnostics);
+        diagnostics[start..].iter_mut().for_each(|diagnostic| diagnostic.severity = severity);
+    }
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+    diagnostics
+}
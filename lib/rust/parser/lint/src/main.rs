@@ -0,0 +1,68 @@
+//! Check Enso source files with the lint rules.
+//!
+//! Usage: `enso-lint [--config <file>] [--json] <file>...`
+//!
+//! The configuration file is in TOML or YAML; see [`enso_lint::config`]. With `--json`, the
+//! diagnostics are written to the standard output as a JSON array; otherwise, as text. The exit
+//! status is 1 if any problem was found by a rule at the `deny` level, and 2 if the arguments,
+//! configuration, or files couldn't be read.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+use enso_lint::Config;
+use enso_lint::Severity;
+
+
+
+const USAGE: &str = "Usage: enso-lint [--config <file>] [--json] <file>...";
+
+fn main() {
+    let mut config = Config::default();
+    let mut json = false;
+    let mut paths = vec![];
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--config" => {
+                let path = args.next().unwrap_or_else(|| fail(USAGE));
+                config = Config::load(path).unwrap_or_else(|error| fail(&error.to_string()));
+            }
+            _ if arg.starts_with("--") => fail(USAGE),
+            _ => paths.push(arg),
+        }
+    }
+    if paths.is_empty() {
+        fail(USAGE);
+    }
+    let parser = enso_parser::Parser::new();
+    let mut records = vec![];
+    let mut denied = false;
+    for path in paths {
+        let input = std::fs::read_to_string(&path)
+            .unwrap_or_else(|error| fail(&format!("Failed to read {path}: {error}")));
+        let (code, _metadata) = enso_parser::metadata::extract(&input);
+        let tree = parser.run(code);
+        let diagnostics = enso_lint::lint(&tree, &config);
+        denied |= diagnostics.iter().any(|diagnostic| diagnostic.severity == Severity::Error);
+        match json {
+            true => records.extend(diagnostics.iter().map(|d| enso_lint::output::json(&path, d))),
+            false => print!("{}", enso_lint::output::text(&path, &diagnostics)),
+        }
+    }
+    if json {
+        println!("{}", serde_json::Value::Array(records));
+    }
+    if denied {
+        std::process::exit(1);
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{message}");
+    std::process::exit(2)
+}
@@ -0,0 +1,132 @@
+//! Rendering of diagnostics, as text or as JSON.
+//!
+//! In both formats, lines and columns are 1-based; columns are counted in UTF-16 code units.
+
+use crate::Diagnostic;
+use crate::Severity;
+
+use enso_parser::source::code::Location;
+use serde_json::json;
+use std::fmt::Write;
+use std::ops::Range;
+
+
+
+// ============
+// === Text ===
+// ============
+
+/// Render the diagnostics of a file as text, with one line per diagnostic, followed by an indented
+/// line for each of its labels:
+/// ```text
+/// Main.enso:3:5: warning[L002/shadowed-name]: The binding of `x` shadows an earlier binding.
+///     Main.enso:2:7: The earlier binding.
+/// ```
+pub fn text(path: &str, diagnostics: &[Diagnostic]) -> String {
+    let mut out = String::new();
+    for diagnostic in diagnostics {
+        let Diagnostic { rule, severity, message, span, secondary } = diagnostic;
+        let (line, column) = position(span.start);
+        let severity = severity_name(*severity);
+        let (id, name) = (rule.id(), rule.name());
+        // Writing to a `String` cannot fail.
+        let _ = writeln!(out, "{path}:{line}:{column}: {severity}[{id}/{name}]: {message}");
+        for label in secondary {
+            let (line, column) = position(label.span.start);
+            let _ = writeln!(out, "    {path}:{line}:{column}: {}", label.message);
+        }
+    }
+    out
+}
+
+
+
+// ============
+// === JSON ===
+// ============
+
+/// Return a JSON object describing the diagnostic. The format is stable; fields may be added, but
+/// not removed or changed:
+/// ```json
+/// {
+///   "file": "Main.enso",
+///   "id": "L002",
+///   "rule": "shadowed-name",
+///   "severity": "warning",
+///   "message": "The binding of `x` shadows an earlier binding.",
+///   "range": { "start": { "line": 3, "column": 5 }, "end": { "line": 3, "column": 6 } },
+///   "related": [{ "message": "The earlier binding.", "range": { ... } }]
+/// }
+/// ```
+pub fn json(path: &str, diagnostic: &Diagnostic) -> serde_json::Value {
+    let related: Vec<_> = diagnostic
+        .secondary
+        .iter()
+        .map(|label| json!({ "message": label.message, "range": range(&label.span) }))
+        .collect();
+    json!({
+        "file": path,
+        "id": diagnostic.rule.id(),
+        "rule": diagnostic.rule.name(),
+        "severity": severity_name(diagnostic.severity),
+        "message": diagnostic.message,
+        "range": range(&diagnostic.span),
+        "related": related,
+    })
+}
+
+fn range(range: &Range<Location>) -> serde_json::Value {
+    let point = |location| {
+        let (line, column) = position(location);
+        json!({ "line": line, "column": column })
+    };
+    json!({ "start": point(range.start), "end": point(range.end) })
+}
+
+
+
+// ===============
+// === Helpers ===
+// ===============
+
+fn position(location: Location) -> (u32, u32) {
+    (location.line + 1, location.col16 + 1)
+}
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+
+    #[test]
+    fn test_output() {
+        let code = "f x =\n    x = 1\n    x";
+        let tree = enso_parser::Parser::new().run(code);
+        let diagnostics = crate::lint(&tree, &Config::default());
+        let expected = [
+            "Main.enso:2:5: warning[L002/shadowed-name]: The binding of `x` shadows an earlier \
+            binding.",
+            "    Main.enso:1:3: The earlier binding.",
+            "",
+        ];
+        assert_eq!(text("Main.enso", &diagnostics), expected.join("\n"));
+        let json = json("Main.enso", &diagnostics[0]);
+        assert_eq!(json["id"], "L002");
+        assert_eq!(json["rule"], "shadowed-name");
+        assert_eq!(json["range"]["end"], json!({ "line": 2, "column": 6 }));
+        assert_eq!(json["related"][0]["range"]["start"]["column"], 3);
+    }
+}
@@ -0,0 +1,103 @@
+//! The lint rules.
+
+use crate::Diagnostic;
+use crate::Level;
+
+use enso_parser::syntax::Tree;
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub mod ambiguous_section;
+pub mod documented_private;
+pub mod shadowed_name;
+pub mod unreachable_case_arm;
+pub mod unused_import;
+
+
+
+// ============
+// === Rule ===
+// ============
+
+/// Identifies a lint rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Rule {
+    /// An imported name is not used in the module. See [`unused_import`].
+    UnusedImport,
+    /// A binding in a function body has the same name as an argument or an earlier binding in an
+    /// enclosing block. See [`shadowed_name`].
+    ShadowedName,
+    /// An arm of a `case` expression follows an arm matching every value. See
+    /// [`unreachable_case_arm`].
+    UnreachableCaseArm,
+    /// A private item is documented. See [`documented_private`].
+    DocumentedPrivate,
+    /// An operator section is applied to an argument, or is an argument, in a way that is easy to
+    /// misread as an infix expression. See [`ambiguous_section`].
+    AmbiguousSection,
+}
+
+impl Rule {
+    /// Return all the rules.
+    pub fn all() -> [Rule; 5] {
+        [
+            Rule::UnusedImport,
+            Rule::ShadowedName,
+            Rule::UnreachableCaseArm,
+            Rule::DocumentedPrivate,
+            Rule::AmbiguousSection,
+        ]
+    }
+
+    /// Return the stable identifier of the rule. Identifiers are never reused for a different rule.
+    pub fn id(self) -> &'static str {
+        match self {
+            Rule::UnusedImport => "L001",
+            Rule::ShadowedName => "L002",
+            Rule::UnreachableCaseArm => "L003",
+            Rule::DocumentedPrivate => "L004",
+            Rule::AmbiguousSection => "L005",
+        }
+    }
+
+    /// Return the name of the rule, as used in configuration files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::UnusedImport => "unused-import",
+            Rule::ShadowedName => "shadowed-name",
+            Rule::UnreachableCaseArm => "unreachable-case-arm",
+            Rule::DocumentedPrivate => "documented-private",
+            Rule::AmbiguousSection => "ambiguous-section",
+        }
+    }
+
+    /// Return the rule with the given name or identifier.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().into_iter().find(|rule| rule.name() == name || rule.id() == name)
+    }
+
+    /// Return the level of the rule when it is not configured.
+    pub fn default_level(self) -> Level {
+        match self {
+            Rule::UnusedImport
+            | Rule::ShadowedName
+            | Rule::UnreachableCaseArm
+            | Rule::DocumentedPrivate
+            | Rule::AmbiguousSection => Level::Warn,
+        }
+    }
+
+    /// Check the tree, adding a diagnostic for each problem found.
+    pub fn check(self, tree: &Tree, diagnostics: &mut Vec<Diagnostic>) {
+        match self {
+            Rule::UnusedImport => unused_import::check(tree, diagnostics),
+            Rule::ShadowedName => shadowed_name::check(tree, diagnostics),
+            Rule::UnreachableCaseArm => unreachable_case_arm::check(tree, diagnostics),
+            Rule::DocumentedPrivate => documented_private::check(tree, diagnostics),
+            Rule::AmbiguousSection => ambiguous_section::check(tree, diagnostics),
+        }
+    }
+}
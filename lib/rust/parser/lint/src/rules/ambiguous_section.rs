@@ -0,0 +1,102 @@
+//! Operator sections whose meaning depends on spacing.
+//!
+//! The precedence of an operator depends on the spacing around it: an operator without spaces on
+//! one side forms a section with the operand on that side, so `a+ b` is the section `(a+)` applied
+//! to `b`, and `f +b` is `f` applied to the section `(+b)`. Both are easily misread as the infix
+//! expression `a + b`. Sections using the `.` operator, like `map .name`, are idiomatic and are
+//! not reported.
+
+use crate::Diagnostic;
+use crate::Rule;
+
+use enso_parser::syntax::token;
+use enso_parser::syntax::tree::*;
+
+
+
+/// Add a diagnostic for each operator section that is applied to an argument, or passed as an
+/// argument, where the spacing makes it look like an infix expression.
+pub fn check(tree: &Tree, diagnostics: &mut Vec<Diagnostic>) {
+    for tree in tree.collect_vec_ref() {
+        let Variant::App(App { func, arg }) = &*tree.variant else { continue };
+        let message = if let Some((Some(lhs), opr, None)) = section(func)
+            && !is_spaced(&opr.left_offset)
+            && is_spaced(&arg.span.left_offset)
+        {
+            let (lhs, opr, arg) = (lhs.trimmed_code(), &opr.code, arg.trimmed_code());
+            format!(
+                "The operator section `{lhs}{opr}` is applied to `{arg}`. Write \
+                `{lhs} {opr} {arg}` for an infix expression, or `({lhs}{opr}) {arg}` to apply the \
+                section."
+            )
+        } else if let Some((None, opr, Some(rhs))) = section(arg)
+            && !opr.properties.is_dot()
+            && !is_spaced(&rhs.span.left_offset)
+        {
+            let (func, opr, rhs) = (func.trimmed_code(), &opr.code, rhs.trimmed_code());
+            format!(
+                "The operator section `{opr}{rhs}` is an argument of `{func}`. Write \
+                `{func} {opr} {rhs}` for an infix expression, or `{func} ({opr}{rhs})` to pass the \
+                section."
+            )
+        } else {
+            continue;
+        };
+        diagnostics.push(Diagnostic::new(Rule::AmbiguousSection, message, tree.span.range()));
+    }
+}
+
+/// If the tree is an operator section, return its operands and operator.
+fn section<'s, 'a>(
+    tree: &'a Tree<'s>,
+) -> Option<(Option<&'a Tree<'s>>, &'a token::Operator<'s>, Option<&'a Tree<'s>>)> {
+    let tree = match &*tree.variant {
+        Variant::OprSectionBoundary(OprSectionBoundary { ast, .. }) => ast,
+        _ => tree,
+    };
+    match &*tree.variant {
+        Variant::OprApp(OprApp { lhs, opr: Ok(opr), rhs }) =>
+            Some((lhs.as_ref(), opr, rhs.as_ref())),
+        _ => None,
+    }
+}
+
+fn is_spaced(offset: &enso_parser::source::Offset) -> bool {
+    offset.visible.width_in_spaces != 0
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ambiguous_section() {
+        let code =
+            ["a = x+ y", "b = x +y", "c = x + y", "d = f x+y", "e = map .name", "f = (x+) y"];
+        let code = code.join("\n");
+        let tree = enso_parser::Parser::new().run(&code);
+        let mut diagnostics = vec![];
+        check(&tree, &mut diagnostics);
+        let messages: Vec<_> =
+            diagnostics.iter().map(|d| (d.span.start.line, &d.message[..])).collect();
+        let expected = [
+            (
+                0,
+                "The operator section `x+` is applied to `y`. Write `x + y` for an infix \
+                expression, or `(x+) y` to apply the section.",
+            ),
+            (
+                1,
+                "The operator section `+y` is an argument of `x`. Write `x + y` for an infix \
+                expression, or `x (+y)` to pass the section.",
+            ),
+        ];
+        assert_eq!(messages, expected);
+    }
+}
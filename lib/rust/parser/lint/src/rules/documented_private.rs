@@ -0,0 +1,49 @@
+//! Documentation of private items.
+//!
+//! The documentation of a module describes its interface; private items are not part of it, and
+//! their documentation is not shown to users of the module. Implementation notes on a private item
+//! should be written as ordinary comments.
+
+use crate::Diagnostic;
+use crate::Rule;
+
+use enso_parser::syntax::tree::*;
+
+
+
+/// Add a diagnostic for each documented private item.
+pub fn check(tree: &Tree, diagnostics: &mut Vec<Diagnostic>) {
+    for tree in tree.collect_vec_ref() {
+        if let Variant::Documented(Documented { expression: Some(expression), .. }) =
+            &*tree.variant
+            && let Variant::Private(Private { keyword, .. }) = &*expression.variant
+        {
+            let message = "Private items should not have documentation comments.";
+            let diagnostic = Diagnostic::new(Rule::DocumentedPrivate, message, tree.span.range())
+                .with_label("The item is private.", keyword.code.range());
+            diagnostics.push(diagnostic);
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_documented_private() {
+        let code = ["## Public.", "foo = 1", "## Private.", "private bar = 2", "private baz = 3"];
+        let code = code.join("\n");
+        let tree = enso_parser::Parser::new().run(&code);
+        let mut diagnostics = vec![];
+        check(&tree, &mut diagnostics);
+        let spans: Vec<_> = diagnostics.iter().map(|d| d.span.start.line).collect();
+        assert_eq!(spans, [2]);
+    }
+}
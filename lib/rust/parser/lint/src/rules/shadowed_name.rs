@@ -0,0 +1,125 @@
+//! Bindings shadowing other names in a function.
+//!
+//! Within a function body, a binding with the same name as an argument of the function, or as an
+//! earlier binding in the same or an enclosing block, makes the earlier value inaccessible; this is
+//! often a mistake, and makes it harder to follow which value a name refers to. Top-level bindings
+//! define methods rather than variables, and are not checked.
+
+use crate::Diagnostic;
+use crate::Rule;
+
+use enso_parser::source::code::Location;
+use enso_parser::syntax::item;
+use enso_parser::syntax::tree::*;
+use std::ops::Range;
+
+
+
+/// Add a diagnostic for each binding in a function body that shadows another name.
+pub fn check(tree: &Tree, diagnostics: &mut Vec<Diagnostic>) {
+    Checker { scopes: vec![], diagnostics }.visit(tree);
+}
+
+struct Checker<'d> {
+    /// The names bound in each enclosing scope, from the outermost. If empty, the current tree is
+    /// not in a function.
+    scopes:      Vec<Vec<Binding>>,
+    diagnostics: &'d mut Vec<Diagnostic>,
+}
+
+struct Binding {
+    name: String,
+    span: Range<Location>,
+}
+
+impl<'d> Checker<'d> {
+    fn visit(&mut self, tree: &Tree) {
+        match &*tree.variant {
+            Variant::Function(Function { args, body, .. }) => {
+                let mut scope = vec![];
+                for arg in args {
+                    scope.extend(arg.pattern.collect_vec_ref().into_iter().filter_map(variable));
+                }
+                self.scopes.push(scope);
+                body.iter().for_each(|body| self.visit(body));
+                self.scopes.pop();
+            }
+            Variant::BodyBlock(BodyBlock { statements }) if !self.scopes.is_empty() => {
+                self.scopes.push(vec![]);
+                statements.iter().filter_map(|line| line.expression.as_ref()).for_each(|tree| {
+                    self.visit(tree);
+                });
+                self.scopes.pop();
+            }
+            Variant::Assignment(Assignment { pattern, expr, .. }) if !self.scopes.is_empty() => {
+                // The expression is evaluated before the name is bound.
+                self.visit(expr);
+                if let Some(binding) = variable(pattern) {
+                    self.bind(binding);
+                }
+            }
+            _ => tree.visit_items(|item| {
+                if let item::Ref::Tree(tree) = item {
+                    self.visit(tree);
+                }
+            }),
+        }
+    }
+
+    fn bind(&mut self, binding: Binding) {
+        let previous = self.scopes.iter().flatten().rfind(|previous| previous.name == binding.name);
+        if let Some(previous) = previous {
+            let message = format!("The binding of `{}` shadows an earlier binding.", binding.name);
+            let diagnostic = Diagnostic::new(Rule::ShadowedName, message, binding.span.clone())
+                .with_label("The earlier binding.", previous.span.clone());
+            self.diagnostics.push(diagnostic);
+        }
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(binding);
+        }
+    }
+}
+
+/// If the tree is a variable name, return its binding.
+fn variable(tree: &Tree) -> Option<Binding> {
+    match &*tree.variant {
+        Variant::Ident(Ident { token }) if !token.is_type =>
+            Some(Binding { name: token.code.to_string(), span: token.code.range() }),
+        _ => None,
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shadowed_name() {
+        let code = [
+            "x = 1",
+            "f (Pair a b) c =",
+            "    x = a",
+            "    y =",
+            "        b = x",
+            "        b",
+            "    c = y",
+            "    x",
+            "g = f 1 2 3",
+        ];
+        let code = code.join("\n");
+        let tree = enso_parser::Parser::new().run(&code);
+        let mut diagnostics = vec![];
+        check(&tree, &mut diagnostics);
+        let lines: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.span.start.line, d.secondary[0].span.start.line))
+            .collect();
+        assert_eq!(lines, [(4, 1), (6, 1)]);
+    }
+}
@@ -0,0 +1,77 @@
+//! Unreachable arms of `case` expressions.
+//!
+//! The arms of a `case` expression are tried in order; an arm whose pattern is a wildcard (`_`) or
+//! a variable matches every value, so the arms following it are never used.
+
+use crate::Diagnostic;
+use crate::Rule;
+
+use enso_parser::syntax::tree::*;
+
+
+
+/// Add a diagnostic for each arm following an arm that matches every value.
+pub fn check(tree: &Tree, diagnostics: &mut Vec<Diagnostic>) {
+    for tree in tree.collect_vec_ref() {
+        let Variant::CaseOf(CaseOf { cases, .. }) = &*tree.variant else { continue };
+        let mut catch_all: Option<&Tree> = None;
+        for case in cases.iter().filter_map(|line| line.case.as_ref()) {
+            let Some(pattern) = &case.pattern else { continue };
+            match catch_all {
+                Some(previous) => {
+                    let end = case.expression.as_ref().unwrap_or(pattern).span.range().end;
+                    let span = pattern.span.range().start..end;
+                    let message = format!(
+                        "This case is unreachable, because the pattern `{}` matches every value.",
+                        previous.trimmed_code()
+                    );
+                    let diagnostic = Diagnostic::new(Rule::UnreachableCaseArm, message, span)
+                        .with_label("This pattern matches every value.", previous.span.range());
+                    diagnostics.push(diagnostic);
+                }
+                None if matches_everything(pattern) => catch_all = Some(pattern),
+                None => {}
+            }
+        }
+    }
+}
+
+fn matches_everything(pattern: &Tree) -> bool {
+    match &*pattern.variant {
+        Variant::Wildcard(_) => true,
+        Variant::Ident(Ident { token }) => !token.is_type,
+        _ => false,
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unreachable_case_arm() {
+        let code = [
+            "f x = case x of",
+            "    0 -> 'zero'",
+            "    _ -> 'other'",
+            "    1 -> 'one'",
+            "    y -> 'also unreachable'",
+            "g x = case x of",
+            "    Nothing -> 0",
+            "    value -> value",
+        ];
+        let code = code.join("\n");
+        let tree = enso_parser::Parser::new().run(&code);
+        let mut diagnostics = vec![];
+        check(&tree, &mut diagnostics);
+        let lines: Vec<_> = diagnostics.iter().map(|d| d.span.start.line).collect();
+        assert_eq!(lines, [3, 4]);
+        assert_eq!(diagnostics[0].secondary[0].span.start.line, 2);
+    }
+}
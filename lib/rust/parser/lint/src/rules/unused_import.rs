@@ -0,0 +1,114 @@
+//! Unused imports.
+//!
+//! An import binds a name in the module: the last segment of the imported path (`import A.B` binds
+//! `B`), the alias given with `as`, or each name listed in a `from ... import` statement. The
+//! import is unused if the name doesn't occur elsewhere in the module. Imports of `all` names are
+//! not checked.
+
+use crate::Diagnostic;
+use crate::Rule;
+
+use enso_parser::source::code::Location;
+use enso_parser::syntax::item;
+use enso_parser::syntax::token;
+use enso_parser::syntax::tree::*;
+use std::collections::HashSet;
+use std::ops::Range;
+
+
+
+/// Add a diagnostic for each imported name that is not used.
+pub fn check(tree: &Tree, diagnostics: &mut Vec<Diagnostic>) {
+    let mut imported = vec![];
+    let mut used = HashSet::new();
+    collect(tree, &mut imported, &mut used);
+    for (name, span) in imported {
+        if !used.contains(&name) {
+            let message = format!("`{name}` is imported, but not used.");
+            diagnostics.push(Diagnostic::new(Rule::UnusedImport, message, span));
+        }
+    }
+}
+
+/// Collect the names bound by imports, with their locations, and the identifiers used outside
+/// imports.
+fn collect(tree: &Tree, imported: &mut Vec<(String, Range<Location>)>, used: &mut HashSet<String>) {
+    match &*tree.variant {
+        Variant::Import(import) => imported.extend(
+            imported_names(import)
+                .into_iter()
+                .map(|name| (name.code.to_string(), name.code.range())),
+        ),
+        Variant::Ident(Ident { token }) => {
+            used.insert(token.code.to_string());
+        }
+        _ => tree.visit_items(|item| {
+            if let item::Ref::Tree(child) = item {
+                collect(child, imported, used);
+            }
+        }),
+    }
+}
+
+/// Return the name tokens bound by the import.
+fn imported_names<'s, 'a>(import: &'a Import<'s>) -> Vec<&'a token::Ident<'s>> {
+    if import.all.is_some() {
+        return vec![];
+    }
+    if let Some(alias) = &import.as_ {
+        return alias.body.iter().flat_map(|body| idents(body).into_iter().last()).collect();
+    }
+    let Some(body) = &import.import.body else { return vec![] };
+    let names = idents(body);
+    match import.from {
+        Some(_) => names,
+        None => names.last().copied().into_iter().collect(),
+    }
+}
+
+/// Return the identifiers in the tree, in source order.
+fn idents<'s, 'a>(tree: &'a Tree<'s>) -> Vec<&'a token::Ident<'s>> {
+    let trees = tree.collect_vec_ref().into_iter();
+    trees
+        .filter_map(|tree| match &*tree.variant {
+            Variant::Ident(Ident { token }) => Some(token),
+            _ => None,
+        })
+        .collect()
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unused_import() {
+        let code = [
+            "import Standard.Base.Data.Vector",
+            "import Standard.Base.Data.Text",
+            "import Standard.Table as Tables",
+            "from Standard.Base import Integer, Boolean",
+            "from Standard.Base import all",
+            "polyglot java import java.util.Random",
+            "",
+            "main = Vector.new (Tables.read 'x') Integer",
+        ];
+        let code = code.join("\n");
+        let tree = enso_parser::Parser::new().run(&code);
+        let mut diagnostics = vec![];
+        check(&tree, &mut diagnostics);
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        let expected = [
+            "`Text` is imported, but not used.",
+            "`Boolean` is imported, but not used.",
+            "`Random` is imported, but not used.",
+        ];
+        assert_eq!(messages, expected);
+    }
+}
@@ -0,0 +1,64 @@
+//! Diagnostics for syntax errors.
+
+use enso_parser::diagnostic;
+use enso_parser::syntax::Tree;
+use lsp_types::DiagnosticRelatedInformation;
+use lsp_types::DiagnosticSeverity;
+use lsp_types::NumberOrString;
+use lsp_types::Url;
+
+
+
+// ===================
+// === Diagnostics ===
+// ===================
+
+/// The name of the source of the diagnostics, shown by clients.
+pub const SOURCE: &str = "enso-parser";
+
+/// Return diagnostics for the syntax errors in the given tree, which was parsed from the document
+/// with the given URI.
+pub fn diagnostics(tree: &Tree, uri: &Url) -> Vec<lsp_types::Diagnostic> {
+    diagnostic::collect(tree).into_iter().map(|diagnostic| convert(diagnostic, uri)).collect()
+}
+
+fn convert(diagnostic: diagnostic::Diagnostic, uri: &Url) -> lsp_types::Diagnostic {
+    let related_information = diagnostic.secondary.into_iter().map(|label| {
+        let location = lsp_types::Location::new(uri.clone(), crate::range(label.span));
+        DiagnosticRelatedInformation { location, message: label.message.into() }
+    });
+    let related_information: Vec<_> = related_information.collect();
+    lsp_types::Diagnostic {
+        range: crate::range(diagnostic.span),
+        severity: Some(DiagnosticSeverity::ERROR),
+        code: Some(NumberOrString::String(diagnostic.kind.code().into())),
+        source: Some(SOURCE.into()),
+        message: diagnostic.message.into(),
+        related_information: (!related_information.is_empty()).then_some(related_information),
+        ..Default::default()
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Position;
+
+    #[test]
+    fn test_diagnostics() {
+        let uri = Url::parse("file:///main.enso").unwrap();
+        let tree = enso_parser::Parser::new().run("main =\n    x = (a");
+        let diagnostics = diagnostics(&tree, &uri);
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = &diagnostics[0];
+        assert_eq!(diagnostic.range.start, Position::new(1, 8));
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+        assert_eq!(diagnostic.code, Some(NumberOrString::String("E003".into())));
+    }
+}
@@ -0,0 +1,75 @@
+//! Folding ranges for the code blocks of a file.
+
+use enso_parser::syntax::item;
+use enso_parser::syntax::tree::*;
+
+use lsp_types::FoldingRange;
+
+
+
+// ======================
+// === Folding Ranges ===
+// ======================
+
+/// Return a folding range for each multi-line block in the given tree: the bodies of functions and
+/// other block expressions, and the bodies of type definitions. A range starts at the line
+/// introducing the block, and ends at the last non-empty line of the block.
+pub fn folding_ranges(tree: &Tree) -> Vec<FoldingRange> {
+    let mut ranges = vec![];
+    collect(tree, &mut ranges);
+    ranges
+}
+
+fn collect(tree: &Tree, ranges: &mut Vec<FoldingRange>) {
+    match &*tree.variant {
+        Variant::BodyBlock(BodyBlock { statements: lines })
+        | Variant::TypeDef(TypeDef { body: lines, .. }) => ranges.extend(block_range(lines)),
+        _ => {}
+    }
+    tree.visit_items(|item| {
+        if let item::Ref::Tree(child) = item {
+            collect(child, ranges);
+        }
+    });
+}
+
+fn block_range(lines: &[block::Line]) -> Option<FoldingRange> {
+    let first = lines.first()?;
+    // The first line of the top-level block of a file is not introduced by a newline.
+    if first.newline.code.is_empty() {
+        return None;
+    }
+    let start_line = first.newline.code.range().start.line;
+    let last = lines.iter().rev().find_map(|line| line.expression.as_ref())?;
+    let end_line = last.span.range().end.line;
+    (end_line > start_line).then(|| FoldingRange { start_line, end_line, ..Default::default() })
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_folding_ranges() {
+        let code = [
+            "type Point",
+            "    Cartesian x y",
+            "",
+            "    length self =",
+            "        self.x",
+            "",
+            "main = 42",
+        ];
+        let code = code.join("\n");
+        let tree = enso_parser::Parser::new().run(&code);
+        let ranges = folding_ranges(&tree);
+        let lines: Vec<_> = ranges.iter().map(|range| (range.start_line, range.end_line)).collect();
+        assert_eq!(lines, [(0, 4), (3, 4)]);
+    }
+}
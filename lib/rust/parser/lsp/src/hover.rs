@@ -0,0 +1,127 @@
+//! Hover text, showing the documentation of the definition at a position.
+
+use enso_parser::syntax::tree::*;
+
+use enso_doc_parser::DocSection;
+use enso_doc_parser::Mark;
+use enso_parser::syntax::tree::cursor::Cursor;
+use lsp_types::Hover;
+use lsp_types::HoverContents;
+use lsp_types::MarkupContent;
+use lsp_types::MarkupKind;
+use std::fmt::Display;
+use std::fmt::Write;
+
+
+
+// =============
+// === Hover ===
+// =============
+
+/// Return the documentation of the innermost documented item containing the given offset, in
+/// UTF-16 code units from the start of the code, rendered as Markdown. The range of the result is
+/// the range of the documented item, including its documentation.
+pub fn hover(tree: &Tree, offset: u32) -> Option<Hover> {
+    let cursor = Cursor::node_at_utf16_offset(tree, offset);
+    let mut nodes = cursor.tree().into_iter().chain(cursor.ancestors());
+    let (documented, documentation) = nodes.find_map(|tree| match &*tree.variant {
+        Variant::Documented(Documented { documentation, .. }) => Some((tree, documentation)),
+        _ => None,
+    })?;
+    let sections = enso_doc_parser::parse(&documentation.content());
+    let value = markdown(&sections);
+    if value.is_empty() {
+        return None;
+    }
+    let contents = HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value });
+    Some(Hover { contents, range: Some(crate::range(documented.span.range())) })
+}
+
+/// Render documentation as Markdown. The section bodies may contain HTML, which is valid in
+/// Markdown.
+pub fn markdown(sections: &[DocSection]) -> String {
+    let mut out = String::new();
+    for section in sections {
+        if !out.is_empty() {
+            out.push_str("\n\n");
+        }
+        // Writing to a `String` cannot fail.
+        let _ = match section {
+            DocSection::Tag { tag, body } if body.is_empty() => write!(out, "**{}**", tag.to_str()),
+            DocSection::Tag { tag, body } => write!(out, "**{}** {body}", tag.to_str()),
+            DocSection::Paragraph { body } => write!(out, "{body}"),
+            DocSection::List { items } => write_list(&mut out, items.iter()),
+            // The `Arguments:` header is a preceding `Keyed` section.
+            DocSection::Arguments { args } => {
+                let items = args.iter().map(|arg| format!("`{}`: {}", arg.name, arg.description));
+                write_list(&mut out, items)
+            }
+            DocSection::Keyed { key, body } if body.is_empty() => write!(out, "**{key}:**"),
+            DocSection::Keyed { key, body } => write!(out, "**{key}:** {body}"),
+            DocSection::Marked { mark, header, body } => {
+                let mark = match mark {
+                    Mark::Important => "Important",
+                    Mark::Info => "Info",
+                    Mark::Example => "Example",
+                };
+                let header = header.as_deref().unwrap_or(mark);
+                write!(out, "**{header}**\n\n{body}")
+            }
+        };
+    }
+    out
+}
+
+fn write_list(out: &mut String, items: impl Iterator<Item = impl Display>) -> std::fmt::Result {
+    let mut first = true;
+    for item in items {
+        if !first {
+            out.push('\n');
+        }
+        first = false;
+        write!(out, "- {item}")?;
+    }
+    Ok(())
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Position;
+
+    #[test]
+    fn test_hover() {
+        let code = [
+            "## Adds two numbers.",
+            "",
+            "   Arguments:",
+            "   - a: The first number.",
+            "   - b: The second number.",
+            "add a b = a + b",
+            "",
+            "main = add 1 2",
+        ];
+        let code = code.join("\n");
+        let tree = enso_parser::Parser::new().run(&code);
+        let offset = |position| crate::utf16_offset(&code, position);
+        let hover = hover(&tree, offset(Position::new(5, 10))).unwrap();
+        let HoverContents::Markup(contents) = hover.contents else { panic!() };
+        let expected = [
+            "Adds two numbers.",
+            "",
+            "**Arguments:**",
+            "",
+            "- `a`: The first number.",
+            "- `b`: The second number.",
+        ];
+        assert_eq!(contents.value, expected.join("\n"));
+        assert_eq!(hover.range.unwrap().start, Position::new(0, 0));
+        assert!(super::hover(&tree, offset(Position::new(7, 8))).is_none());
+    }
+}
@@ -0,0 +1,111 @@
+//! A Language Server Protocol front-end for Enso source files, based on the Enso parser.
+//!
+//! The server provides the editor support that can be derived from the syntax of a single file:
+//! - Diagnostics for syntax errors.
+//! - Document symbols for type, constructor, and function definitions.
+//! - Folding ranges for code blocks.
+//! - Semantic tokens, classifying the code as the parser's syntax highlighter does.
+//! - Hover text, showing the documentation of the definition at the cursor.
+//!
+//! Each of the modules [`diagnostics`], [`symbols`], [`folding`], [`semantic_tokens`], and
+//! [`hover`] computes the results of one kind of request from a parsed file; the [`server`] module
+//! implements the protocol.
+
+// === Features ===
+#![feature(let_chains)]
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+// === Non-Standard Linter Configuration ===
+#![allow(clippy::option_map_unit_fn)]
+#![allow(clippy::precedence)]
+#![allow(dead_code)]
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use enso_parser::source::code::Location;
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub mod diagnostics;
+pub mod folding;
+pub mod hover;
+pub mod semantic_tokens;
+pub mod server;
+pub mod symbols;
+
+
+
+// =================
+// === Positions ===
+// =================
+
+/// Return the protocol position of the given location. Positions are measured in UTF-16 code
+/// units, which is the default position encoding of the protocol.
+pub fn position(location: Location) -> lsp_types::Position {
+    lsp_types::Position::new(location.line, location.col16)
+}
+
+/// Return the protocol range of the given code range.
+pub fn range(range: std::ops::Range<Location>) -> lsp_types::Range {
+    lsp_types::Range::new(position(range.start), position(range.end))
+}
+
+/// Return the offset of the given position from the start of the code, in UTF-16 code units. A
+/// position past the end of a line refers to the end of the line.
+pub fn utf16_offset(code: &str, position: lsp_types::Position) -> u32 {
+    let mut offset = 0;
+    let mut line = 0;
+    let mut column = 0;
+    let mut chars = code.chars().peekable();
+    while let Some(char) = chars.next() {
+        let is_line_end = char == '\n' || char == '\r';
+        if line == position.line && (column == position.character || is_line_end) {
+            break;
+        }
+        offset += char.len_utf16() as u32;
+        column += char.len_utf16() as u32;
+        if is_line_end {
+            if char == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+                offset += 1;
+            }
+            line += 1;
+            column = 0;
+        }
+    }
+    offset
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Position;
+
+    #[test]
+    fn test_utf16_offset() {
+        let code = "ab\r\nąb\ncd";
+        assert_eq!(utf16_offset(code, Position::new(0, 1)), 1);
+        assert_eq!(utf16_offset(code, Position::new(0, 5)), 2);
+        assert_eq!(utf16_offset(code, Position::new(1, 1)), 5);
+        assert_eq!(utf16_offset(code, Position::new(2, 2)), 9);
+        assert_eq!(utf16_offset(code, Position::new(3, 0)), 9);
+    }
+}
@@ -0,0 +1,19 @@
+//! Language Server Protocol server for Enso source files, communicating over the standard input
+//! and output.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+
+
+fn main() -> Result<(), enso_parser_lsp::server::Error> {
+    let (connection, io_threads) = lsp_server::Connection::stdio();
+    enso_parser_lsp::server::run(&connection)?;
+    // The output thread finishes when the connection is dropped.
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
@@ -0,0 +1,213 @@
+//! Semantic tokens, classifying the code of a file for syntax highlighting.
+//!
+//! The classification is computed by [`enso_parser::highlight`], so that the editor shows the same
+//! kinds of code as the other renderers of the parser; this module maps each
+//! [`HighlightKind`] to a protocol token type, and encodes the runs as the protocol requires.
+
+use enso_parser::highlight::HighlightKind;
+use enso_parser::syntax::Tree;
+use lsp_types::Position;
+use lsp_types::SemanticToken;
+use lsp_types::SemanticTokenModifier;
+use lsp_types::SemanticTokenType;
+use lsp_types::SemanticTokensLegend;
+
+
+
+// ==============
+// === Legend ===
+// ==============
+
+/// The token types reported by the server; the type of a token is reported as its index.
+pub const TOKEN_TYPES: [SemanticTokenType; 10] = [
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::ENUM_MEMBER,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::OPERATOR,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::STRING,
+    SemanticTokenType::COMMENT,
+    SemanticTokenType::DECORATOR,
+];
+
+/// The token modifiers reported by the server; the modifiers of a token are reported as a bitset
+/// of their indices.
+pub const TOKEN_MODIFIERS: [SemanticTokenModifier; 1] = [SemanticTokenModifier::DOCUMENTATION];
+
+/// Return the legend of the semantic tokens reported by the server.
+pub fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types:     TOKEN_TYPES.to_vec(),
+        token_modifiers: TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+/// Return the index of the token type of the given kind of code in [`TOKEN_TYPES`], and the bitset
+/// of its modifiers. Invalid code has no corresponding token type, and is not reported.
+pub fn token_type(kind: HighlightKind) -> Option<(u32, u32)> {
+    let documentation = 1 << 0;
+    Some(match kind {
+        HighlightKind::Keyword => (0, 0),
+        HighlightKind::Type => (1, 0),
+        HighlightKind::Constructor => (2, 0),
+        HighlightKind::Function => (3, 0),
+        HighlightKind::Variable => (4, 0),
+        HighlightKind::Operator | HighlightKind::Interpolation => (5, 0),
+        HighlightKind::Number => (6, 0),
+        HighlightKind::Text | HighlightKind::TextEscape => (7, 0),
+        HighlightKind::Comment => (8, 0),
+        HighlightKind::DocComment => (8, documentation),
+        HighlightKind::Annotation => (9, 0),
+        HighlightKind::Invalid => return None,
+    })
+}
+
+
+
+// =======================
+// === Semantic Tokens ===
+// =======================
+
+/// Return the semantic tokens of the given tree of the given code, in source order and encoded
+/// relative to each other, as required by the protocol. Runs of code spanning multiple lines are
+/// reported as one token per line.
+pub fn semantic_tokens(tree: &Tree, code: &str) -> Vec<SemanticToken> {
+    let mut locator = Locator::new(code);
+    let mut previous = Position::default();
+    let mut encoded = vec![];
+    for (range, kind) in enso_parser::highlight::highlight(tree) {
+        let Some((token_type, token_modifiers_bitset)) = token_type(kind) else { continue };
+        let mut start = range.start;
+        for line in code[range].split_inclusive(|c| c == '\n' || c == '\r') {
+            let text = line.trim_end_matches(|c| c == '\n' || c == '\r');
+            let position = locator.position(start);
+            start += line.len();
+            if text.is_empty() {
+                continue;
+            }
+            let delta_line = position.line - previous.line;
+            let delta_start = match delta_line {
+                0 => position.character - previous.character,
+                _ => position.character,
+            };
+            previous = position;
+            encoded.push(SemanticToken {
+                delta_line,
+                delta_start,
+                length: text.encode_utf16().count() as u32,
+                token_type,
+                token_modifiers_bitset,
+            });
+        }
+    }
+    encoded
+}
+
+
+// === Locator ===
+
+/// Computes the protocol positions of increasing byte offsets in the code.
+#[derive(Debug)]
+struct Locator<'a> {
+    code:     &'a str,
+    offset:   usize,
+    position: Position,
+    after_cr: bool,
+}
+
+impl<'a> Locator<'a> {
+    fn new(code: &'a str) -> Self {
+        Self { code, offset: 0, position: Position::default(), after_cr: false }
+    }
+
+    /// Return the position of the given offset, which must not precede the last offset located.
+    fn position(&mut self, offset: usize) -> Position {
+        for char in self.code[self.offset..offset].chars() {
+            match char {
+                '\n' if self.after_cr => {}
+                '\n' | '\r' => self.position = Position::new(self.position.line + 1, 0),
+                char => self.position.character += char.len_utf16() as u32,
+            }
+            self.after_cr = char == '\r';
+        }
+        self.offset = offset;
+        self.position
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decode the tokens into absolute positions.
+    fn decode(tokens: &[SemanticToken]) -> Vec<(u32, u32, u32, SemanticTokenType, u32)> {
+        let mut position = (0, 0);
+        let mut decoded = vec![];
+        for token in tokens {
+            position = match token.delta_line {
+                0 => (position.0, position.1 + token.delta_start),
+                delta_line => (position.0 + delta_line, token.delta_start),
+            };
+            let token_type = TOKEN_TYPES[token.token_type as usize].clone();
+            let modifiers = token.token_modifiers_bitset;
+            decoded.push((position.0, position.1, token.length, token_type, modifiers));
+        }
+        decoded
+    }
+
+    fn tokens(code: &str) -> Vec<(u32, u32, u32, SemanticTokenType, u32)> {
+        let tree = enso_parser::Parser::new().run(code);
+        decode(&semantic_tokens(&tree, code))
+    }
+
+    #[test]
+    fn test_semantic_tokens() {
+        let code = [
+            "## Docs.",
+            "type Point",
+            "    @x Widget",
+            "    length self = if self.x > 0 then 'ą' else 1.5 # Comment",
+        ];
+        use SemanticTokenType as T;
+        let expected = [
+            (0, 0, 8, T::COMMENT, 1),
+            (1, 0, 4, T::KEYWORD, 0),
+            (1, 5, 5, T::TYPE, 0),
+            (2, 4, 2, T::DECORATOR, 0),
+            (2, 7, 6, T::TYPE, 0),
+            (3, 4, 6, T::FUNCTION, 0),
+            (3, 11, 4, T::VARIABLE, 0),
+            (3, 16, 1, T::OPERATOR, 0),
+            (3, 18, 2, T::KEYWORD, 0),
+            (3, 21, 4, T::VARIABLE, 0),
+            (3, 25, 1, T::OPERATOR, 0),
+            (3, 26, 1, T::VARIABLE, 0),
+            (3, 28, 1, T::OPERATOR, 0),
+            (3, 30, 1, T::NUMBER, 0),
+            (3, 32, 4, T::KEYWORD, 0),
+            (3, 37, 3, T::STRING, 0),
+            (3, 41, 4, T::KEYWORD, 0),
+            (3, 46, 3, T::NUMBER, 0),
+            (3, 50, 9, T::COMMENT, 0),
+        ];
+        assert_eq!(tokens(&code.join("\n")), expected);
+    }
+
+    #[test]
+    fn test_locator() {
+        let code = "x = 'ą\r\nb'";
+        let mut locator = Locator::new(code);
+        assert_eq!(locator.position(4), Position::new(0, 4));
+        assert_eq!(locator.position(7), Position::new(0, 6));
+        assert_eq!(locator.position(9), Position::new(1, 0));
+        assert_eq!(locator.position(code.len()), Position::new(1, 2));
+    }
+}
@@ -0,0 +1,239 @@
+//! The protocol implementation: the server keeps the text of the open documents, and answers
+//! requests by parsing them.
+//!
+//! Documents are synchronized in full; each request parses the current text of the document. The
+//! parser is fast enough that caching the trees is not necessary for files of typical sizes.
+
+use enso_parser::syntax::Tree;
+use lsp_server::Connection;
+use lsp_server::ErrorCode;
+use lsp_server::Message;
+use lsp_server::Notification;
+use lsp_server::Request;
+use lsp_server::Response;
+use lsp_types::notification::Notification as _;
+use lsp_types::request::Request as _;
+use lsp_types::*;
+use std::collections::HashMap;
+
+
+
+// ==============
+// === Server ===
+// ==============
+
+/// An error that terminates the server: a failure of the connection to the client.
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Return the capabilities of the server.
+pub fn capabilities() -> ServerCapabilities {
+    let semantic_tokens = SemanticTokensOptions {
+        legend: crate::semantic_tokens::legend(),
+        full: Some(SemanticTokensFullOptions::Bool(true)),
+        ..Default::default()
+    };
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncKind::FULL.into()),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        semantic_tokens_provider: Some(semantic_tokens.into()),
+        ..Default::default()
+    }
+}
+
+/// Initialize the connection, and serve requests until the client shuts the server down.
+pub fn run(connection: &Connection) -> Result<(), Error> {
+    let capabilities = serde_json::to_value(capabilities())?;
+    connection.initialize(capabilities)?;
+    Server::new(connection).run()
+}
+
+/// The state of a running server.
+struct Server<'c> {
+    connection: &'c Connection,
+    parser:     enso_parser::Parser,
+    /// The text of each open document.
+    documents:  HashMap<Url, String>,
+}
+
+impl<'c> Server<'c> {
+    fn new(connection: &'c Connection) -> Self {
+        let parser = enso_parser::Parser::new();
+        Self { connection, parser, documents: HashMap::new() }
+    }
+
+    fn run(&mut self) -> Result<(), Error> {
+        while let Ok(message) = self.connection.receiver.recv() {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    let response = self.handle_request(request);
+                    self.connection.sender.send(response.into())?;
+                }
+                Message::Notification(notification) => self.handle_notification(notification)?,
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            request::DocumentSymbolRequest::METHOD => self
+                .respond::<request::DocumentSymbolRequest>(request, |tree, _, _| {
+                    Some(DocumentSymbolResponse::Nested(crate::symbols::document_symbols(tree)))
+                }),
+            request::FoldingRangeRequest::METHOD => self
+                .respond::<request::FoldingRangeRequest>(request, |tree, _, _| {
+                    Some(crate::folding::folding_ranges(tree))
+                }),
+            request::SemanticTokensFullRequest::METHOD => self
+                .respond::<request::SemanticTokensFullRequest>(request, |tree, code, _| {
+                    let data = crate::semantic_tokens::semantic_tokens(tree, code);
+                    Some(SemanticTokens { result_id: None, data }.into())
+                }),
+            request::HoverRequest::METHOD =>
+                self.respond::<request::HoverRequest>(request, |tree, code, params| {
+                    let position = params.text_document_position_params.position;
+                    crate::hover::hover(tree, crate::utf16_offset(code, position))
+                }),
+            method => {
+                let message = format!("Unsupported request: {method}.");
+                Response::new_err(request.id, ErrorCode::MethodNotFound as i32, message)
+            }
+        }
+    }
+
+    /// Respond to a request of type `R` with the result of `f`, applied to the tree and the code of
+    /// the document the request refers to.
+    fn respond<R>(
+        &self,
+        request: Request,
+        f: impl FnOnce(&Tree, &str, R::Params) -> R::Result,
+    ) -> Response
+    where
+        R: request::Request,
+        R::Params: DocumentParams,
+    {
+        let params = match serde_json::from_value::<R::Params>(request.params) {
+            Ok(params) => params,
+            Err(error) =>
+                return Response::new_err(
+                    request.id,
+                    ErrorCode::InvalidParams as i32,
+                    error.to_string(),
+                ),
+        };
+        let Some(text) = self.documents.get(params.uri()) else {
+            let message = format!("Unknown document: {}.", params.uri());
+            return Response::new_err(request.id, ErrorCode::InvalidParams as i32, message);
+        };
+        let code = code(text);
+        let tree = self.parser.run(code);
+        Response::new_ok(request.id, f(&tree, code, params))
+    }
+
+    /// Handle a notification. Malformed notifications are logged and ignored, as the protocol
+    /// provides no response to report them in; only errors of the connection are returned.
+    fn handle_notification(&mut self, notification: Notification) -> Result<(), Error> {
+        let uri = match notification.method.as_str() {
+            notification::DidOpenTextDocument::METHOD => {
+                let Some(params) = params::<notification::DidOpenTextDocument>(notification) else {
+                    return Ok(());
+                };
+                let document = params.text_document;
+                self.documents.insert(document.uri.clone(), document.text);
+                document.uri
+            }
+            notification::DidChangeTextDocument::METHOD => {
+                let Some(params) = params::<notification::DidChangeTextDocument>(notification)
+                else {
+                    return Ok(());
+                };
+                // With full synchronization, the last change holds the whole text.
+                let Some(change) = params.content_changes.into_iter().last() else { return Ok(()) };
+                let uri = params.text_document.uri;
+                self.documents.insert(uri.clone(), change.text);
+                uri
+            }
+            notification::DidCloseTextDocument::METHOD => {
+                let Some(params) = params::<notification::DidCloseTextDocument>(notification)
+                else {
+                    return Ok(());
+                };
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+                // Clear the diagnostics of the closed document.
+                return self.publish_diagnostics(uri, vec![]);
+            }
+            _ => return Ok(()),
+        };
+        let diagnostics = match self.documents.get(&uri) {
+            Some(text) => crate::diagnostics::diagnostics(&self.parser.run(code(text)), &uri),
+            None => vec![],
+        };
+        self.publish_diagnostics(uri, diagnostics)
+    }
+
+    fn publish_diagnostics(&self, uri: Url, diagnostics: Vec<Diagnostic>) -> Result<(), Error> {
+        let params = PublishDiagnosticsParams { uri, diagnostics, version: None };
+        let method = notification::PublishDiagnostics::METHOD.to_owned();
+        let notification = Notification::new(method, params);
+        self.connection.sender.send(notification.into())?;
+        Ok(())
+    }
+}
+
+/// Return the parameters of a notification of type `N`, or log the error and return `None` if they
+/// are malformed.
+fn params<N: notification::Notification>(notification: Notification) -> Option<N::Params> {
+    match serde_json::from_value(notification.params) {
+        Ok(params) => Some(params),
+        Err(error) => {
+            eprintln!("Ignoring malformed notification {}: {error}", notification.method);
+            None
+        }
+    }
+}
+
+/// Return the code of a document, without the metadata section, if any.
+fn code(text: &str) -> &str {
+    let (code, _metadata) = enso_parser::metadata::extract(text);
+    code
+}
+
+
+// === Document Params ===
+
+/// The parameters of a request concerning a document.
+trait DocumentParams {
+    /// Return the URI of the document.
+    fn uri(&self) -> &Url;
+}
+
+impl DocumentParams for DocumentSymbolParams {
+    fn uri(&self) -> &Url {
+        &self.text_document.uri
+    }
+}
+
+impl DocumentParams for FoldingRangeParams {
+    fn uri(&self) -> &Url {
+        &self.text_document.uri
+    }
+}
+
+impl DocumentParams for SemanticTokensParams {
+    fn uri(&self) -> &Url {
+        &self.text_document.uri
+    }
+}
+
+impl DocumentParams for HoverParams {
+    fn uri(&self) -> &Url {
+        &self.text_document_position_params.text_document.uri
+    }
+}
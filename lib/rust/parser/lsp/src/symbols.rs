@@ -0,0 +1,167 @@
+//! Document symbols, outlining the definitions of a file.
+
+use enso_parser::syntax::item;
+use enso_parser::syntax::tree::*;
+
+use lsp_types::DocumentSymbol;
+use lsp_types::SymbolKind;
+
+
+
+// ========================
+// === Document Symbols ===
+// ========================
+
+/// Return the symbols defined in the given tree: types, their constructors, and methods. Symbols
+/// defined in the body of another definition are its children.
+pub fn document_symbols(tree: &Tree) -> Vec<DocumentSymbol> {
+    let mut symbols = vec![];
+    collect(tree, None, Scope::Module, &mut symbols);
+    symbols
+}
+
+/// The kind of definition that contains a tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Scope {
+    Module,
+    Type,
+    /// The body of a function; bindings here define local variables, not methods.
+    Function,
+}
+
+impl Scope {
+    fn function_kind(self) -> SymbolKind {
+        match self {
+            Scope::Type => SymbolKind::METHOD,
+            Scope::Module | Scope::Function => SymbolKind::FUNCTION,
+        }
+    }
+}
+
+/// Add the symbols defined in `tree` to `symbols`. If the tree is the expression of a
+/// [`Documented`] node, `documented` is the range of that node, which includes the documentation.
+fn collect(
+    tree: &Tree,
+    documented: Option<lsp_types::Range>,
+    scope: Scope,
+    symbols: &mut Vec<DocumentSymbol>,
+) {
+    let range = documented.unwrap_or_else(|| crate::range(tree.span.range()));
+    match &*tree.variant {
+        Variant::Documented(Documented { expression: Some(expression), .. }) =>
+            collect(expression, Some(range), scope, symbols),
+        Variant::TypeDef(TypeDef { name, body, .. }) => {
+            let mut children = vec![];
+            for line in body {
+                if let Some(expression) = &line.expression {
+                    collect(expression, None, Scope::Type, &mut children);
+                }
+            }
+            let selection_range = crate::range(name.code.range());
+            let name = name.code.to_string();
+            symbols.push(symbol(name, SymbolKind::CLASS, range, selection_range, children));
+        }
+        Variant::ConstructorDefinition(ConstructorDefinition { constructor, .. }) => {
+            let selection_range = crate::range(constructor.code.range());
+            let name = constructor.code.to_string();
+            symbols.push(symbol(name, SymbolKind::CONSTRUCTOR, range, selection_range, vec![]));
+        }
+        Variant::Function(Function { name, body: Some(body), .. }) =>
+            symbols.push(function(name, body, scope, range)),
+        // A binding without arguments outside a function body defines a method.
+        Variant::Assignment(Assignment { pattern, expr, .. }) if scope != Scope::Function =>
+            symbols.push(function(pattern, expr, scope, range)),
+        Variant::ForeignFunction(ForeignFunction { name, .. }) => {
+            let selection_range = crate::range(name.code.range());
+            let name = name.code.to_string();
+            symbols.push(symbol(name, scope.function_kind(), range, selection_range, vec![]));
+        }
+        _ => collect_children(tree, scope, symbols),
+    }
+}
+
+/// Add the symbols defined in the descendants of `tree` to `symbols`.
+fn collect_children(tree: &Tree, scope: Scope, symbols: &mut Vec<DocumentSymbol>) {
+    tree.visit_items(|item| {
+        if let item::Ref::Tree(child) = item {
+            collect(child, None, scope, symbols);
+        }
+    });
+}
+
+fn function(name: &Tree, body: &Tree, scope: Scope, range: lsp_types::Range) -> DocumentSymbol {
+    let mut children = vec![];
+    collect(body, None, Scope::Function, &mut children);
+    let selection_range = crate::range(name.span.range());
+    symbol(name.trimmed_code(), scope.function_kind(), range, selection_range, children)
+}
+
+fn symbol(
+    name: String,
+    kind: SymbolKind,
+    range: lsp_types::Range,
+    selection_range: lsp_types::Range,
+    children: Vec<DocumentSymbol>,
+) -> DocumentSymbol {
+    let children = (!children.is_empty()).then_some(children);
+    #[allow(deprecated)]
+    DocumentSymbol {
+        name,
+        detail: None,
+        kind,
+        tags: None,
+        deprecated: None,
+        range,
+        selection_range,
+        children,
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Position;
+
+    fn outline(symbols: &[DocumentSymbol]) -> Vec<(String, SymbolKind, Vec<String>)> {
+        let names = |symbols: &[DocumentSymbol]| symbols.iter().map(|s| s.name.clone()).collect();
+        let children = |symbol: &DocumentSymbol| names(symbol.children.as_deref().unwrap_or(&[]));
+        symbols.iter().map(|s| (s.name.clone(), s.kind, children(s))).collect()
+    }
+
+    #[test]
+    fn test_document_symbols() {
+        let code = [
+            "## A point.",
+            "type Point",
+            "    Cartesian x y",
+            "    length self = self.x",
+            "",
+            "main =",
+            "    helper a = a",
+            "    x = helper 1",
+            "    x",
+        ];
+        let code = code.join("\n");
+        let tree = enso_parser::Parser::new().run(&code);
+        let symbols = document_symbols(&tree);
+        let expected = [
+            ("Point".into(), SymbolKind::CLASS, vec!["Cartesian".into(), "length".into()]),
+            ("main".into(), SymbolKind::FUNCTION, vec!["helper".into()]),
+        ];
+        assert_eq!(outline(&symbols), expected);
+        let point = &symbols[0];
+        assert_eq!(point.range.start, Position::new(0, 0));
+        assert_eq!(point.selection_range.start, Position::new(1, 5));
+        let members = point.children.as_deref().unwrap();
+        assert_eq!(
+            (members[0].kind, members[1].kind),
+            (SymbolKind::CONSTRUCTOR, SymbolKind::METHOD)
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! Tests of the protocol implementation, communicating with a server over an in-memory connection.
+
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+#![allow(clippy::bool_to_int_with_if)]
+#![allow(clippy::let_and_return)]
+
+use lsp_server::Connection;
+use lsp_server::Message;
+use lsp_server::Notification;
+use lsp_server::Request;
+use lsp_server::RequestId;
+use lsp_types::notification::Notification as _;
+use lsp_types::*;
+
+
+
+#[test]
+fn server() {
+    let (server, client) = Connection::memory();
+    let server = std::thread::spawn(move || enso_parser_lsp::server::run(&server).unwrap());
+    let uri = Url::parse("file:///Main.enso").unwrap();
+
+    let initialize = InitializeParams::default();
+    let result = request::<request::Initialize>(&client, 1, initialize);
+    assert!(result.capabilities.semantic_tokens_provider.is_some());
+    notify::<notification::Initialized>(&client, InitializedParams {});
+
+    // A malformed notification is ignored, and the server keeps serving.
+    let message = Notification::new(notification::DidOpenTextDocument::METHOD.into(), ());
+    client.sender.send(message.into()).unwrap();
+
+    let text = "main =\n    x = (a\n    x\n".to_owned();
+    let text_document = TextDocumentItem::new(uri.clone(), "enso".into(), 1, text);
+    notify::<notification::DidOpenTextDocument>(&client, DidOpenTextDocumentParams {
+        text_document,
+    });
+    let diagnostics = receive_diagnostics(&client);
+    assert_eq!(diagnostics.uri, uri);
+    assert_eq!(diagnostics.diagnostics.len(), 1);
+    assert_eq!(diagnostics.diagnostics[0].range.start, Position::new(1, 8));
+
+    let text = "type A\n    B\n\nmain = 42\n".to_owned();
+    let changes = vec![TextDocumentContentChangeEvent { range: None, range_length: None, text }];
+    let text_document = VersionedTextDocumentIdentifier::new(uri.clone(), 2);
+    let params = DidChangeTextDocumentParams { text_document, content_changes: changes };
+    notify::<notification::DidChangeTextDocument>(&client, params);
+    assert!(receive_diagnostics(&client).diagnostics.is_empty());
+
+    let text_document = TextDocumentIdentifier::new(uri);
+    let params = DocumentSymbolParams {
+        text_document,
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+    let symbols = request::<request::DocumentSymbolRequest>(&client, 2, params);
+    let Some(DocumentSymbolResponse::Nested(symbols)) = symbols else { panic!("{symbols:?}") };
+    let names: Vec<_> = symbols.iter().map(|symbol| symbol.name.as_str()).collect();
+    assert_eq!(names, ["A", "main"]);
+
+    let id = RequestId::from(3);
+    let message = Request::new(id.clone(), "enso/unknown".into(), ());
+    client.sender.send(message.into()).unwrap();
+    let Message::Response(response) = client.receiver.recv().unwrap() else { panic!() };
+    assert_eq!(response.id, id);
+    assert!(response.error.is_some());
+
+    request::<request::Shutdown>(&client, 4, ());
+    notify::<notification::Exit>(&client, ());
+    server.join().unwrap();
+}
+
+fn request<R: request::Request>(client: &Connection, id: i32, params: R::Params) -> R::Result {
+    let id = RequestId::from(id);
+    let message = Request::new(id.clone(), R::METHOD.into(), params);
+    client.sender.send(message.into()).unwrap();
+    match client.receiver.recv().unwrap() {
+        Message::Response(response) => {
+            assert_eq!(response.id, id);
+            assert!(response.error.is_none(), "{:?}", response.error);
+            serde_json::from_value(response.result.unwrap()).unwrap()
+        }
+        message => panic!("Expected a response, got: {message:?}."),
+    }
+}
+
+fn notify<N: notification::Notification>(client: &Connection, params: N::Params) {
+    let message = Notification::new(N::METHOD.into(), params);
+    client.sender.send(message.into()).unwrap();
+}
+
+fn receive_diagnostics(client: &Connection) -> PublishDiagnosticsParams {
+    match client.receiver.recv().unwrap() {
+        Message::Notification(message)
+            if message.method == notification::PublishDiagnostics::METHOD =>
+            serde_json::from_value(message.params).unwrap(),
+        message => panic!("Expected diagnostics, got: {message:?}."),
+    }
+}
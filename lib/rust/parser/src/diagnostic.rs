@@ -0,0 +1,370 @@
+//! Structured descriptions of the syntax errors found in a [`Tree`].
+//!
+//! Each error in a tree is represented by an [`Invalid`] node or a [`MultipleOperatorError`]; the
+//! [`collect`] function finds them and produces a [`Diagnostic`] for each, with the locations of
+//! the relevant code and, where possible, suggested fixes.
+
+use crate::prelude::*;
+
+use crate::source::code::Location;
+use crate::syntax::tree::*;
+use crate::syntax::*;
+
+use enso_parser_syntax_tree_visitor::Encode;
+
+
+
+// =================
+// === ErrorKind ===
+// =================
+
+/// Identifies the kind of a syntax error.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash, Encode, Serialize, Reflect, Deserialize)]
+#[allow(missing_docs)]
+pub enum ErrorKind {
+    /// The error was not classified.
+    #[default]
+    Unknown,
+    /// An error in the parser's implementation.
+    Internal,
+    UnexpectedToken,
+    UnmatchedDelimiter,
+    MultipleOperators,
+    MissingOperands,
+    MissingOperand,
+    UnspacedUnaryOperator,
+    InvalidSpecialOperator,
+    ExpectedExpressionBeforeBlock,
+    UnexpectedMacroTokens,
+    InvalidMacroInvocation,
+    ExpectedTokens,
+    ExpectedIdentifier,
+    ExpectedQualifiedName,
+    ExpectedImportTarget,
+    ExpectedTypeName,
+    InvalidCase,
+    MalformedSequence,
+    InvalidForeignFunction,
+    ExpectedMacroSegment,
+}
+
+impl ErrorKind {
+    /// Return the stable code identifying this kind of error. Codes are never reused for a
+    /// different kind of error.
+    pub fn code(self) -> &'static str {
+        match self {
+            ErrorKind::Unknown => "E000",
+            ErrorKind::Internal => "E001",
+            ErrorKind::UnexpectedToken => "E002",
+            ErrorKind::UnmatchedDelimiter => "E003",
+            ErrorKind::MultipleOperators => "E004",
+            ErrorKind::MissingOperands => "E005",
+            ErrorKind::MissingOperand => "E006",
+            ErrorKind::UnspacedUnaryOperator => "E007",
+            ErrorKind::InvalidSpecialOperator => "E008",
+            ErrorKind::ExpectedExpressionBeforeBlock => "E009",
+            ErrorKind::UnexpectedMacroTokens => "E010",
+            ErrorKind::InvalidMacroInvocation => "E011",
+            ErrorKind::ExpectedTokens => "E012",
+            ErrorKind::ExpectedIdentifier => "E013",
+            ErrorKind::ExpectedQualifiedName => "E014",
+            ErrorKind::ExpectedImportTarget => "E015",
+            ErrorKind::ExpectedTypeName => "E016",
+            ErrorKind::InvalidCase => "E017",
+            ErrorKind::MalformedSequence => "E018",
+            ErrorKind::InvalidForeignFunction => "E019",
+            ErrorKind::ExpectedMacroSegment => "E020",
+        }
+    }
+}
+
+
+
+// ==================
+// === Diagnostic ===
+// ==================
+
+/// A description of a syntax error found in source code.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// The kind of problem.
+    pub kind:      ErrorKind,
+    /// A description of the problem.
+    pub message:   Cow<'static, str>,
+    /// The code containing the problem.
+    pub span:      Range<Location>,
+    /// Other code related to the problem.
+    pub secondary: Vec<Label>,
+    /// Changes that could be made to resolve the problem.
+    pub fixes:     Vec<Fix>,
+}
+
+impl Diagnostic {
+    /// Return the stable code identifying the kind of problem.
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+}
+
+/// A range of code related to a [`Diagnostic`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Label {
+    /// The code.
+    pub span:    Range<Location>,
+    /// Describes how the code is related to the problem.
+    pub message: Cow<'static, str>,
+}
+
+/// A suggested change to source code.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Fix {
+    /// A description of the change.
+    pub message: Cow<'static, str>,
+    /// The edits comprising the change. They don't overlap.
+    pub edits:   Vec<Edit>,
+}
+
+/// A replacement of a range of source code.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Edit {
+    /// The range of code to be replaced.
+    pub range: Range<Location>,
+    /// The replacement text.
+    pub text:  Cow<'static, str>,
+}
+
+
+
+// ===============
+// === Collect ===
+// ===============
+
+/// Return diagnostics for all the errors in the given tree, in source order.
+pub fn collect(tree: &Tree) -> Vec<Diagnostic> {
+    let mut collector = Collector::default();
+    tree.visit_item(&mut collector);
+    // The sort is stable, so nested errors starting at the same location remain in outer-to-inner
+    // order.
+    collector.diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+    collector.diagnostics
+}
+
+#[derive(Debug, Default)]
+struct Collector {
+    diagnostics:    Vec<Diagnostic>,
+    /// The header of the macro segment being visited, if any.
+    segment_header: Option<Range<Location>>,
+}
+
+impl Collector {
+    fn invalid(&mut self, tree: &Tree, error: &Error, ast: &Tree) {
+        let Error { message, kind } = error;
+        let mut secondary = vec![];
+        let mut fixes = vec![];
+        match (kind, &*ast.variant) {
+            (
+                ErrorKind::UnmatchedDelimiter,
+                Variant::Group(Group { open: Some(open), close: Some(close), .. })
+                | Variant::Array(Array { left: open, right: close, .. })
+                | Variant::Tuple(Tuple { left: open, right: close, .. }),
+            ) if close.code.is_empty() =>
+                if let Some(text) = closing_delimiter(&open.code) {
+                    let end = close.code.range().end;
+                    fixes.push(Fix {
+                        message: format!("Insert `{text}`.").into(),
+                        edits:   vec![Edit { range: end..end, text: text.into() }],
+                    });
+                },
+            (ErrorKind::UnmatchedDelimiter, Variant::Group(Group { close: Some(close), .. })) =>
+                fixes.push(Fix {
+                    message: "Remove the unmatched delimiter.".into(),
+                    edits:   vec![Edit { range: close.code.range(), text: "".into() }],
+                }),
+            (ErrorKind::UnspacedUnaryOperator, Variant::UnaryOprApp(UnaryOprApp { opr, .. })) => {
+                let start = opr.code.range().start;
+                fixes.push(Fix {
+                    message: "Insert a space before the operator.".into(),
+                    edits:   vec![Edit { range: start..start, text: " ".into() }],
+                });
+            }
+            (ErrorKind::UnexpectedMacroTokens, _) =>
+                if let Some(header) = self.segment_header.clone() {
+                    let message = "The macro segment containing the unexpected tokens.".into();
+                    secondary.push(Label { span: header, message });
+                },
+            (ErrorKind::InvalidCase, Variant::CaseOf(CaseOf { cases, .. })) =>
+                for case in cases.iter().filter_map(|line| line.case.as_ref()) {
+                    if case.pattern.is_none() || case.arrow.is_none() || case.expression.is_none() {
+                        let message = "Expected a case of the form `pattern -> expression`.".into();
+                        secondary.push(Label { span: case_range(case), message });
+                    }
+                },
+            (ErrorKind::MalformedSequence, _) => {
+                let mut tree = ast;
+                while let Variant::OprApp(OprApp { lhs, opr: Ok(opr), rhs }) = &*tree.variant {
+                    if rhs.is_none() {
+                        let message = format!("Expected an expression after `{}`.", opr.code);
+                        secondary
+                            .push(Label { span: opr.code.range(), message: message.into() });
+                    }
+                    match lhs {
+                        Some(lhs) => tree = lhs,
+                        None => {
+                            let message = format!("Expected an expression before `{}`.", opr.code);
+                            let span = opr.code.range();
+                            secondary.push(Label { span, message: message.into() });
+                            break;
+                        }
+                    }
+                }
+                secondary.reverse();
+            }
+            _ => (),
+        }
+        self.diagnostics.push(Diagnostic {
+            kind: *kind,
+            message: message.clone(),
+            span: tree.span.range(),
+            secondary,
+            fixes,
+        });
+    }
+
+    fn multiple_operators(&mut self, error: &MultipleOperatorError) {
+        let operators = &error.operators;
+        let start = operators.first().code.range().start;
+        let end = operators.last().code.range().end;
+        let kind = ErrorKind::MultipleOperators;
+        let message = "Operators must be separated by operands.".into();
+        let (span, secondary, fixes) = (start..end, vec![], vec![]);
+        self.diagnostics.push(Diagnostic { kind, message, span, secondary, fixes });
+    }
+}
+
+impl Visitor for Collector {}
+
+impl<'s, 'a> ItemVisitor<'s, 'a> for Collector {
+    fn visit_item(&mut self, item: item::Ref<'s, 'a>) -> bool {
+        let item::Ref::Tree(tree) = item else { return false };
+        match &*tree.variant {
+            // An error wrapping another error at the same node adds no information; the inner
+            // error is the more specific.
+            Variant::Invalid(Invalid { ast, .. })
+                if matches!(&*ast.variant, Variant::Invalid(_)) =>
+                (),
+            Variant::Invalid(Invalid { error, ast }) => self.invalid(tree, error, ast),
+            Variant::OprApp(OprApp { opr: Err(error), .. }) => self.multiple_operators(error),
+            Variant::MultiSegmentApp(MultiSegmentApp { segments }) => {
+                for MultiSegmentAppSegment { header, body } in segments {
+                    let header = Some(header.code.range());
+                    let outer = mem::replace(&mut self.segment_header, header);
+                    body.visit_item(self);
+                    self.segment_header = outer;
+                }
+                return false;
+            }
+            _ => (),
+        }
+        true
+    }
+}
+
+fn closing_delimiter(open: &str) -> Option<&'static str> {
+    match open {
+        "(" => Some(")"),
+        "[" => Some("]"),
+        "{" => Some("}"),
+        _ => None,
+    }
+}
+
+fn case_range(case: &Case) -> Range<Location> {
+    let Case { documentation, pattern, arrow, expression } = case;
+    let start = None
+        .or_else(|| documentation.as_ref().map(|doc| doc.open.code.range().start))
+        .or_else(|| pattern.as_ref().map(|tree| tree.span.range().start))
+        .or_else(|| arrow.as_ref().map(|arrow| arrow.code.range().start));
+    let end = None
+        .or_else(|| expression.as_ref().map(|tree| tree.span.range().end))
+        .or_else(|| arrow.as_ref().map(|arrow| arrow.code.range().end))
+        .or_else(|| pattern.as_ref().map(|tree| tree.span.range().end));
+    let start = start.unwrap_or_default();
+    start..end.unwrap_or(start)
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnose(code: &str) -> Vec<Diagnostic> {
+        crate::Parser::new().run_with_diagnostics(code).1
+    }
+
+    fn utf8_range(range: &Range<Location>) -> Range<u32> {
+        range.start.utf8..range.end.utf8
+    }
+
+    fn codes(code: &str) -> Vec<&'static str> {
+        diagnose(code).iter().map(Diagnostic::code).collect()
+    }
+
+    #[test]
+    fn test_no_errors() {
+        assert_eq!(diagnose("main = foo 1 (bar 2)"), vec![]);
+    }
+
+    #[test]
+    fn test_kinds() {
+        assert_eq!(codes("x = (a"), vec!["E003"]);
+        assert_eq!(codes("x = a + * b"), vec!["E004"]);
+        assert_eq!(codes("x = a~b"), vec!["E007"]);
+        assert_eq!(codes("import"), vec!["E015"]);
+        assert_eq!(codes("type"), vec!["E016"]);
+        assert_eq!(codes("from A import a, , b"), vec!["E018"]);
+        assert_eq!(codes("x = if a"), vec!["E020"]);
+    }
+
+    #[test]
+    fn test_source_order() {
+        let diagnostics = diagnose("a = (b\nc = d)\ne = f + * g");
+        let spans: Vec<_> = diagnostics.iter().map(|d| utf8_range(&d.span)).collect();
+        assert_eq!(spans, vec![4..6, 12..13, 20..23]);
+    }
+
+    #[test]
+    fn test_locations() {
+        let diagnostics = diagnose("x = 'ą'\ny = (");
+        let span = &diagnostics[0].span;
+        assert_eq!((span.start.utf8, span.start.utf16), (13, 12));
+        assert_eq!((span.start.line, span.start.col16), (1, 4));
+    }
+
+    #[test]
+    fn test_fixes() {
+        let diagnostics = diagnose("x = a)");
+        let fix = &diagnostics[0].fixes[0];
+        assert_eq!(fix.edits.len(), 1);
+        assert_eq!(utf8_range(&fix.edits[0].range), 5..6);
+        assert_eq!(fix.edits[0].text, "");
+        let diagnostics = diagnose("x = a~b");
+        let edit = &diagnostics[0].fixes[0].edits[0];
+        assert_eq!((utf8_range(&edit.range), &*edit.text), (5..5, " "));
+        let diagnostics = diagnose("x = [a, (b");
+        let edit = &diagnostics[1].fixes[0].edits[0];
+        assert_eq!((utf8_range(&edit.range), &*edit.text), (10..10, ")"));
+    }
+
+    #[test]
+    fn test_secondary_spans() {
+        let diagnostics = diagnose("from A import a, , b");
+        let secondary: Vec<_> =
+            diagnostics[0].secondary.iter().map(|l| utf8_range(&l.span)).collect();
+        assert_eq!(secondary, vec![15..16]);
+    }
+}
@@ -1,6 +1,8 @@
-//! Serializer for a binary format compatible with a lazy deserialization strategy.
+//! Output formats of syntax trees: canonical Enso source code, produced by [`format()`] (see the
+//! [`source`] module), and a binary format compatible with a lazy deserialization strategy,
+//! produced by [`serialize`] and [`encode()`].
 //!
-//! # Design
+//! # Design of the binary format
 //!
 //! In order to support lazy deserialization, fields of each object are located at fixed offsets
 //! from the object; variable-sized objects (sequences, optional values, and discriminated unions)
@@ -17,8 +19,10 @@
 //! moving each boxed object once.
 //!
 //! Alternatives:
-//! - ⏰ Deferred: Generate a proper non-serializer with `metamodel`. This would support higher
-//!   performance, and a recursion-free implementation that would allow arbitrarily-deep trees.
+//! - ✅ Implemented: A proper non-serializer, generated for the syntax tree types by
+//!   `#[derive(Encode)]`. It is faster than the `serde` implementation, and it uses an explicit
+//!   work stack rather than unbounded recursion, so it supports arbitrarily-deep trees; see the
+//!   [`encode`] module.
 //! - ❌ Rejected: Use the `len` hints provided by `serde` to pre-allocate objects of the correct
 //!   size: The requirement that every field have the same size representation would be too onerous.
 
@@ -30,6 +34,18 @@ use std::fmt::Display;
 use std::fmt::Formatter;
 
 
+// ==============
+// === Export ===
+// ==============
+
+pub mod encode;
+pub mod source;
+
+pub use encode::encode;
+pub use encode::Encode;
+pub use source::format;
+
+
 
 // =================
 // === Constants ===
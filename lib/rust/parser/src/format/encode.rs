@@ -0,0 +1,624 @@
+//! Encoder for the binary format of the parent module, supporting trees of any depth.
+//!
+//! The encoder produces exactly the same output as [`super::serialize`], but rather than being
+//! driven by `serde`, it is driven by implementations of the [`Encode`] trait, which are generated
+//! for the syntax tree types with `#[derive(Encode)]`.
+//!
+//! # Design
+//!
+//! Like the `serde`-based serializer, the encoder maintains a stack of the fields of incomplete
+//! objects, and moves each object to the heap when it is completed. However, the encoder only
+//! descends into a [`Tree`]'s children by recursion up to a fixed depth ([`TREE_DEPTH_LIMIT`]);
+//! beyond that depth, encoding a [`Tree`] schedules the encoding of its contents as a [`Task`]. The
+//! tasks are executed in order by a loop, so the depth of the call stack is bounded, and trees of
+//! any depth can be encoded.
+//!
+//! An [`Encode`] implementation encodes its fields in order, using the methods of the [`Encoder`].
+//! While no task has been scheduled by the current step, the methods write their output directly;
+//! once a task is scheduled, the rest of the operations of the step are scheduled too, so that the
+//! output is produced in the same order as if it had been written directly.
+//!
+//! A type whose representation is a fixed sequence of inline data, without any objects (such as
+//! [`Span`](crate::source::Span)), can be written directly, without the encoder's bookkeeping for
+//! each of its fields; see [`Encode::is_inline`]. This, and not needing to track the depth of
+//! every compound value, or identify parent structs by name, make the encoder faster than the
+//! `serde`-based serializer.
+
+use crate::prelude::*;
+
+use crate::source::code::StrRef;
+use crate::syntax::token::Token;
+use crate::syntax::tree::Tree;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// Maximum number of nested [`Tree`]s that are encoded by recursion, within one step of the
+/// [`Encoder`]. The stack space used by one level is comparable to that used by the `serde`-based
+/// serializer for a few levels of its [`super::RECURSION_LIMIT`].
+const TREE_DEPTH_LIMIT: usize = 64;
+
+
+
+// ==============
+// === Encode ===
+// ==============
+
+/// Generate a binary representation of the value, identical to the output of
+/// [`super::serialize`].
+pub fn encode<T: Encode>(value: &T) -> Vec<u8> {
+    let mut encoder = Encoder::default();
+    encoder.run(value);
+    debug_assert!(encoder.objects.is_empty());
+    encoder.heap.append(&mut encoder.stack);
+    encoder.heap
+}
+
+/// A type that can be written in the binary format.
+pub trait Encode {
+    /// Write the value's representation, using the methods of the encoder.
+    fn encode<'a>(&'a self, encoder: &mut Encoder<'a>);
+
+    /// If this value is a variant of an enum that can be merged into the representation of a
+    /// parent struct (see [`super::Serializer`]), return its discriminant and its contents.
+    fn variant(&self) -> Option<(u32, &dyn Encode)> {
+        None
+    }
+
+    /// Return whether values of the type are represented only by inline data, without any
+    /// objects. A value of such a type can be written by [`Encode::write_inline`].
+    fn is_inline() -> bool
+    where Self: Sized {
+        false
+    }
+
+    /// Write the representation of a value of a type for which [`Encode::is_inline`] is true.
+    fn write_inline(&self, _out: &mut Vec<u8>) {
+        unreachable!("Value is not of an inline type.")
+    }
+}
+
+
+
+// ===============
+// === Encoder ===
+// ===============
+
+/// Writes values in the binary format. See the module docs.
+#[derive(Default)]
+pub struct Encoder<'a> {
+    /// Complete objects, located at their final addresses.
+    heap:    Vec<u8>,
+    /// All the fields of currently-incomplete objects.
+    stack:   Vec<u8>,
+    /// The start of each incomplete object in the `stack`.
+    objects: Vec<usize>,
+    /// Operations that have been scheduled, in reverse order.
+    tasks:   Vec<Task<'a>>,
+    /// The number of tasks that were scheduled before the current step.
+    step:    usize,
+    /// The number of [`Tree`]s being encoded by recursion.
+    depth:   usize,
+}
+
+impl Debug for Encoder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Encoder")
+            .field("heap", &self.heap.len())
+            .field("stack", &self.stack.len())
+            .field("objects", &self.objects.len())
+            .field("tasks", &self.tasks.len())
+            .finish()
+    }
+}
+
+/// An operation of the [`Encoder`].
+#[derive(Clone, Copy)]
+enum Task<'a> {
+    Value(&'a dyn Encode),
+    U8(u8),
+    U32(u32),
+    Bytes(&'a [u8]),
+    BeginObject,
+    EndObject,
+    EndVariant(u32),
+}
+
+impl<'a> Encoder<'a> {
+    fn run(&mut self, value: &'a dyn Encode) {
+        self.tasks.push(Task::Value(value));
+        while let Some(task) = self.tasks.pop() {
+            self.step = self.tasks.len();
+            self.execute(task);
+            // The operations of a step are scheduled in order; they must be executed in order.
+            self.tasks[self.step..].reverse();
+        }
+    }
+
+    /// Return whether an operation has been scheduled by the current step; if so, the following
+    /// operations of the step must be scheduled too.
+    fn deferring(&self) -> bool {
+        self.tasks.len() > self.step
+    }
+
+    /// Execute the operation now, unless operations are being deferred.
+    #[inline(always)]
+    fn perform(&mut self, task: Task<'a>) {
+        match self.deferring() {
+            true => self.tasks.push(task),
+            false => self.execute(task),
+        }
+    }
+
+    #[inline(always)]
+    fn execute(&mut self, task: Task<'a>) {
+        match task {
+            Task::Value(value) => value.encode(self),
+            Task::U8(value) => self.stack.push(value),
+            Task::U32(value) => self.stack.extend_from_slice(&value.to_le_bytes()),
+            Task::Bytes(value) => self.stack.extend_from_slice(value),
+            Task::BeginObject => self.objects.push(self.stack.len()),
+            Task::EndObject => self.build_object(None),
+            Task::EndVariant(discriminant) => self.build_object(Some(discriminant)),
+        }
+    }
+
+    /// Move the most recently begun object to the heap, and write a reference to it. If a
+    /// discriminant is given, it is written to the heap before the object's fields.
+    fn build_object(&mut self, discriminant: Option<u32>) {
+        let begin = self.objects.pop().expect("Object ended without being begun.");
+        let address = u32::try_from(self.heap.len()).unwrap();
+        if let Some(discriminant) = discriminant {
+            self.heap.extend_from_slice(&discriminant.to_le_bytes());
+        }
+        self.heap.extend_from_slice(&self.stack[begin..]);
+        self.stack.truncate(begin);
+        self.stack.extend_from_slice(&address.to_le_bytes());
+    }
+}
+
+
+// === Operations ===
+
+impl<'a> Encoder<'a> {
+    /// Encode a field of the current object.
+    pub fn field<T: Encode>(&mut self, value: &'a T) {
+        match self.deferring() {
+            true => self.tasks.push(Task::Value(value)),
+            false if T::is_inline() => value.write_inline(&mut self.stack),
+            false => value.encode(self),
+        }
+    }
+
+    /// Write a value of an inline type. This may be used by [`Encode::encode`], which is only
+    /// called when no operation of the current step has been deferred.
+    pub fn inline<T: Encode + ?Sized>(&mut self, value: &T) {
+        debug_assert!(!self.deferring());
+        value.write_inline(&mut self.stack)
+    }
+
+    /// Encode a field of the current object, given as a trait object.
+    pub fn dyn_field(&mut self, value: &'a dyn Encode) {
+        self.perform(Task::Value(value))
+    }
+
+    /// Encode a field of the current object that may (indirectly) contain a value of the type
+    /// being encoded. If the recursion depth limit has been reached, the field is encoded in a
+    /// later step.
+    pub fn recursive_field(&mut self, value: &'a dyn Encode) {
+        if self.deferring() || self.depth >= TREE_DEPTH_LIMIT {
+            self.tasks.push(Task::Value(value));
+        } else {
+            self.depth += 1;
+            value.encode(self);
+            self.depth -= 1;
+        }
+    }
+
+    /// Write a byte.
+    pub fn u8(&mut self, value: u8) {
+        self.perform(Task::U8(value))
+    }
+
+    /// Write a 32-bit integer.
+    pub fn u32(&mut self, value: u32) {
+        self.perform(Task::U32(value))
+    }
+
+    /// Write a sequence of 32-bit integers.
+    pub fn words<const N: usize>(&mut self, value: [u32; N]) {
+        for word in value {
+            self.u32(word);
+        }
+    }
+
+    /// Write raw data.
+    pub fn bytes(&mut self, value: &'a [u8]) {
+        self.perform(Task::Bytes(value))
+    }
+
+    /// Begin an object; its fields will be stored out of band.
+    pub fn begin_object(&mut self) {
+        self.perform(Task::BeginObject)
+    }
+
+    /// Complete the current object, and write a reference to it.
+    pub fn end_object(&mut self) {
+        self.perform(Task::EndObject)
+    }
+
+    /// Complete the current object, prefixed by an enum discriminant, and write a reference to it.
+    pub fn end_variant(&mut self, discriminant: u32) {
+        self.perform(Task::EndVariant(discriminant))
+    }
+}
+
+
+
+// =======================
+// === Implementations ===
+// =======================
+
+impl Encode for bool {
+    fn encode<'a>(&'a self, encoder: &mut Encoder<'a>) {
+        encoder.u8(*self as u8)
+    }
+
+    fn is_inline() -> bool {
+        true
+    }
+
+    fn write_inline(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8)
+    }
+}
+
+impl Encode for u32 {
+    fn encode<'a>(&'a self, encoder: &mut Encoder<'a>) {
+        encoder.u32(*self)
+    }
+
+    fn is_inline() -> bool {
+        true
+    }
+
+    fn write_inline(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes())
+    }
+}
+
+impl Encode for str {
+    fn encode<'a>(&'a self, encoder: &mut Encoder<'a>) {
+        encoder.begin_object();
+        encoder.u32(u32::try_from(self.len()).unwrap());
+        encoder.bytes(self.as_bytes());
+        encoder.end_object();
+    }
+}
+
+impl Encode for String {
+    fn encode<'a>(&'a self, encoder: &mut Encoder<'a>) {
+        self.as_str().encode(encoder)
+    }
+}
+
+impl Encode for Cow<'_, str> {
+    fn encode<'a>(&'a self, encoder: &mut Encoder<'a>) {
+        self.as_ref().encode(encoder)
+    }
+}
+
+impl<T: Encode> Encode for Box<T> {
+    fn encode<'a>(&'a self, encoder: &mut Encoder<'a>) {
+        self.as_ref().encode(encoder)
+    }
+
+    fn variant(&self) -> Option<(u32, &dyn Encode)> {
+        self.as_ref().variant()
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode<'a>(&'a self, encoder: &mut Encoder<'a>) {
+        match self {
+            Some(value) => {
+                encoder.u8(1);
+                encoder.begin_object();
+                encoder.field(value);
+                encoder.end_object();
+            }
+            None => {
+                encoder.u8(0);
+                encoder.u32(0xcdcdcdcd);
+            }
+        }
+    }
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encode<'a>(&'a self, encoder: &mut Encoder<'a>) {
+        encoder.begin_object();
+        encoder.u32(u32::try_from(self.len()).unwrap());
+        for element in self {
+            encoder.field(element);
+        }
+        encoder.end_object();
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode<'a>(&'a self, encoder: &mut Encoder<'a>) {
+        self.as_slice().encode(encoder)
+    }
+}
+
+impl<T: Encode, I> Encode for NonEmptyVec<T, I> {
+    fn encode<'a>(&'a self, encoder: &mut Encoder<'a>) {
+        self.elems.as_slice().encode(encoder)
+    }
+}
+
+impl<T: Encode, E: Encode> Encode for Result<T, E> {
+    fn encode<'a>(&'a self, encoder: &mut Encoder<'a>) {
+        encoder.begin_object();
+        match self {
+            Ok(value) => {
+                encoder.u32(0);
+                encoder.field(value);
+            }
+            Err(error) => {
+                encoder.u32(1);
+                encoder.field(error);
+            }
+        }
+        encoder.end_object();
+    }
+}
+
+
+// === Parent Structs ===
+
+// A [`Tree`] or [`Token`] containing an enum variant is represented by a single object, consisting
+// of the discriminant, the fields of the parent, and the fields of the variant.
+
+impl<'s> Encode for Tree<'s> {
+    fn encode<'a>(&'a self, encoder: &mut Encoder<'a>) {
+        let (discriminant, variant) = self.variant.variant().unwrap();
+        encoder.begin_object();
+        encoder.field(&self.span);
+        encoder.recursive_field(variant);
+        encoder.end_variant(discriminant);
+    }
+}
+
+impl<'s, T: Encode> Encode for Token<'s, T> {
+    fn encode<'a>(&'a self, encoder: &mut Encoder<'a>) {
+        if Self::is_inline() {
+            return encoder.inline(self);
+        }
+        match self.variant.variant() {
+            Some((discriminant, variant)) => {
+                encoder.begin_object();
+                encoder.field(&self.left_offset);
+                encoder.field(&self.code);
+                encoder.dyn_field(variant);
+                encoder.end_variant(discriminant);
+            }
+            None => {
+                encoder.field(&self.left_offset);
+                encoder.field(&self.code);
+                encoder.field(&self.variant);
+            }
+        }
+    }
+
+    fn is_inline() -> bool {
+        T::is_inline()
+    }
+
+    fn write_inline(&self, out: &mut Vec<u8>) {
+        self.left_offset.write_inline(out);
+        self.code.write_inline(out);
+        self.variant.write_inline(out);
+    }
+}
+
+
+// === Customized Representations ===
+
+/// Represent a reference to source code by its address and length.
+pub(crate) fn str_ref(value: &StrRef) -> [u32; 2] {
+    [value.0.as_ptr() as u32, value.0.len() as u32]
+}
+
+/// Represent an optional character by its code point, or `u32::MAX`.
+pub(crate) fn optional_char(value: &Option<char>) -> [u32; 1] {
+    [value.map(|c| c as u32).unwrap_or(0xFFFF_FFFF)]
+}
+
+/// Represent an optional integer by itself, or `-1`.
+pub(crate) fn optional_int(value: &Option<u32>) -> [u32; 1] {
+    [value.map(|x| x as i32).unwrap_or(-1) as u32]
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Source code exercising a variety of tree and token types.
+    const SAMPLES: &[&str] = &[
+        "",
+        "foo",
+        "foo bar baz",
+        "a + b * c - d",
+        "x = y.z 1_000 0b101 0o17 0xFF 1.5",
+        "(a + b) * c",
+        "f x=1 ...",
+        "_ + 1",
+        "-x",
+        "+ 1",
+        "a.b.c",
+        "\"text with `interpolation` and \\n escapes\\u{1F600}\"",
+        "'''\n    multi-line\n    text\n",
+        "[1, 2, 3]\n{a, b}",
+        "main =\n    x = 1\n    y = x + 2\n    y\n",
+        concat!(
+            "type Option a\n    Some value:a\n    None\n\n",
+            "    map self f = case self of\n        Some v -> Some (f v)\n        None -> None\n",
+        ),
+        "from Standard.Base import all\nimport Standard.Table.Table\nexport project.Foo\n",
+        "foo a:Integer=0 (b : Text) ~c = a\n",
+        "if a then b else c\n",
+        "x = if a then\n    b\nelse\n    c\n",
+        "value\n    * 1\n    + 2\n    . foo\n",
+        "f\n    a\n    b\n",
+        "## Doc comment\n   continued\nfoo = bar\n",
+        "# Comment\nfoo : Integer -> Text\nfoo x = x.to_text\n",
+        "@Builtin_Type\ntype Any\n",
+        "\\a b -> a + b",
+        "foreign js foo = \"\"\"\n    return 1\n",
+        "a -> b -> c",
+        "x.y = 1\nprivate\n",
+        "+ + +",
+        "a ++ +- b",
+        "(",
+        "]",
+        "x = \"unclosed\n",
+        "case x of\n    1 -> a\n    _ : Text -> b\n    ## Doc\n    Foo.Bar c -> d\n",
+        "1 . to_text",
+        "type Foo\n    Bar\n        a\n        b : Integer = 1\n",
+    ];
+
+    fn assert_equivalent(tree: &Tree) {
+        let expected = super::super::serialize(tree).unwrap();
+        let encoded = encode(tree);
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn test_equivalence() {
+        let parser = crate::Parser::new();
+        for sample in SAMPLES {
+            let tree = parser.run(sample);
+            assert_equivalent(&tree);
+        }
+    }
+
+    #[test]
+    fn test_deep_equivalence() {
+        let code = format!("x{}", " + x".repeat(200));
+        assert_equivalent(&crate::Parser::new().run(&code));
+    }
+
+    #[test]
+    fn test_deep_operator_chain() {
+        // This is far deeper than a recursive serializer could handle with the default stack size
+        // of a test thread.
+        let depth = 10_000;
+        let code = format!("x{}", " + x".repeat(depth));
+        // Parsing and checking the tree are recursive, so they are done with a large stack.
+        with_large_stack(|| {
+            let tree = crate::Parser::new().run(&code);
+            let serialized = super::super::serialize(&tree);
+            assert!(matches!(serialized, Err(super::super::Error::RecursionLimitExceeded)));
+            assert_encodes(&tree);
+        });
+    }
+
+    #[test]
+    fn test_deeply_nested_blocks() {
+        let depth = 1_000;
+        let mut code = String::new();
+        for i in 0..depth {
+            code.push_str(&" ".repeat(i));
+            code.push_str("x =\n");
+        }
+        code.push_str(&" ".repeat(depth));
+        code.push_str("x\n");
+        // Parsing and checking the tree are recursive, so they are done with a large stack.
+        with_large_stack(|| {
+            let tree = crate::Parser::new().run(&code);
+            let serialized = super::super::serialize(&tree);
+            assert!(matches!(serialized, Err(super::super::Error::RecursionLimitExceeded)));
+            assert_encodes(&tree);
+        });
+    }
+
+    fn with_large_stack(f: impl FnOnce() + Send) {
+        let stack_size = 256 * 1024 * 1024;
+        std::thread::scope(|scope| {
+            let thread = std::thread::Builder::new().stack_size(stack_size);
+            thread.spawn_scoped(scope, f).unwrap().join().unwrap();
+        });
+    }
+
+    /// Check that the tree is encoded exactly as its `bincode` serialization translated to the
+    /// format by `enso_metamodel`, which doesn't have the serializer's depth limit.
+    fn assert_encodes(tree: &Tree) {
+        use enso_reflect::Reflect;
+        let root = Tree::reflect();
+        let root_id = root.id;
+        let (graph, rust_to_meta) = enso_metamodel::rust::to_meta(root);
+        let root = rust_to_meta[&root_id];
+        let bincode = crate::serialization::serialize_tree(tree).unwrap();
+        let expected = enso_metamodel::meta::format::transcode(&graph, root, &bincode);
+        assert_eq!(encode(tree), expected);
+    }
+}
+
+
+
+// ==================
+// === Benchmarks ===
+// ==================
+
+#[cfg(test)]
+mod benches {
+    use super::*;
+    extern crate test;
+    use test::Bencher;
+
+    /// Return Enso source code of roughly the given size, in bytes, exercising a variety of tree
+    /// types.
+    fn source(size: usize) -> String {
+        let mut code = String::with_capacity(size);
+        let mut i = 0;
+        while code.len() < size {
+            code.push_str(&format!("## Documentation of `function_{i}`.\n"));
+            code.push_str(&format!("function_{i} a (b : Integer = 1) =\n"));
+            code.push_str("    x = a + b * 2 - (a . foo [1, 2, 3]) # A comment.\n");
+            code.push_str(&format!("    y = 'text literal {i} `x` \\n'\n"));
+            code.push_str("    z = if x > 0 then x else -x\n");
+            code.push_str("    case x of\n        0 -> y\n        _ : Integer -> x.to_text\n\n");
+            i += 1;
+        }
+        code
+    }
+
+    const INPUT_SIZE: usize = 1024 * 1024;
+
+    #[bench]
+    fn bench_serialize(bencher: &mut Bencher) {
+        let input = source(INPUT_SIZE);
+        let tree = crate::Parser::new().run(&input);
+        bencher.bytes = super::super::serialize(&tree).unwrap().len() as u64;
+        bencher.iter(|| super::super::serialize(test::black_box(&tree)).unwrap());
+    }
+
+    #[bench]
+    fn bench_encode(bencher: &mut Bencher) {
+        let input = source(INPUT_SIZE);
+        let tree = crate::Parser::new().run(&input);
+        bencher.bytes = encode(&tree).len() as u64;
+        bencher.iter(|| encode(test::black_box(&tree)));
+    }
+}
@@ -0,0 +1,446 @@
+//! Canonical formatting of Enso source code.
+//!
+//! The printer regenerates the source code of a [`Tree`] with normalized layout:
+//! - Each block is indented by [`INDENT_WIDTH`] spaces relative to its parent.
+//! - Whitespace between tokens on the same line is reduced to a single space.
+//! - The `=` of assignments and function definitions and the `:` of type signatures are surrounded
+//!   by spaces.
+//! - Binary operators are spaced according to their [`OperatorProperties`]: Operators that join
+//!   their operands into a single term (`.`, `,`, and the operators with special syntax, such as
+//!   `@` and `~`) are left as written; other operators, such as `+` or `->`, are surrounded by
+//!   spaces where that doesn't change the meaning of the code (see below).
+//! - Trailing whitespace is removed, and line endings are normalized to `\n`.
+//!
+//! # Preserving meaning
+//! Enso syntax is whitespace-sensitive, so the printer is careful about what it changes:
+//! - Spacing affects operator precedence, so whether two tokens are separated by whitespace is
+//!   never changed, except around the operators listed above:
+//!   - When the `=` of an assignment or the `:` of a type signature is unspaced in a statement, the
+//!     whole statement is unspaced, so adding spaces around the operator doesn't change how either
+//!     operand is parsed.
+//!   - An unspaced chain of binary operators, such as `a+b*c`, is spaced only when it is a whole
+//!     expression: The content of a line or of parentheses, the value of an assignment, or the type
+//!     of a signature. Such a chain has no neighbouring terms that spacing could detach it from,
+//!     and its operators are grouped by precedence whether they are spaced or not. Spacing stops at
+//!     operators left as written, so their operands keep binding tighter than spaced operators.
+//! - The block structure is determined by the lexer from the indentation of each line. The printer
+//!   follows the same rules to find the block each line belongs to, and indents it according to the
+//!   block's depth.
+//! - The contents of text literals and documentation comments are not changed; their lines are
+//!   shifted along with the block they appear in, which preserves their indentation relative to it.
+//! - Comments, empty lines, and the division of the code into lines are preserved.
+//!
+//! Consequently, parsing the output produces a tree equivalent to the input (differing only in
+//! whitespace), and formatting is idempotent.
+
+use crate::prelude::*;
+
+use crate::source::*;
+use crate::syntax::item;
+use crate::syntax::token;
+use crate::syntax::token::OperatorProperties;
+use crate::syntax::tree::*;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// Number of spaces by which each block is indented, relative to its parent.
+pub const INDENT_WIDTH: u32 = 4;
+
+
+
+// ==============
+// === Format ===
+// ==============
+
+/// Return the source code of the given tree, in canonical format. See the module docs.
+pub fn format(tree: &Tree) -> String {
+    let mut printer = Printer::default();
+    tree.visit_item(&mut printer);
+    printer.output
+}
+
+
+
+// ===============
+// === Printer ===
+// ===============
+
+#[derive(Debug, Default)]
+struct Printer {
+    output:      String,
+    /// The input whitespace preceding the next token.
+    whitespace:  String,
+    /// The visible width of `whitespace`.
+    width:       u32,
+    /// Whether the output so far is in the middle of a line.
+    mid_line:    bool,
+    /// Whether the next token must be separated from a preceding token on the same line.
+    force_space: bool,
+    blocks:      Blocks,
+    /// If the printer is in the contents of a text literal or documentation comment, the number of
+    /// columns by which the lines of the contents are shifted.
+    text_shift:  Option<i64>,
+}
+
+impl Printer {
+    fn offset(&mut self, offset: &Offset) {
+        self.whitespace.push_str(&offset.code);
+        self.width += offset.visible.width_in_spaces;
+    }
+
+    fn token(&mut self, token: token::Ref) {
+        self.offset(token.left_offset);
+        let is_newline = matches!(token.data, token::Variant::Newline(_));
+        let is_comment = is_newline && token.code.starts_with('#');
+        let is_line_break = is_newline && !is_comment && !token.code.is_empty();
+        if let Some(shift) = self.text_shift {
+            let whitespace = mem::take(&mut self.whitespace);
+            let width = mem::take(&mut self.width);
+            let starts_content = !self.mid_line && !is_line_break && !token.code.is_empty();
+            match starts_content && shift != 0 {
+                true => {
+                    let width = (width as i64 + shift).max(0) as usize;
+                    self.output.extend(iter::repeat(' ').take(width));
+                }
+                false => self.output.push_str(&whitespace),
+            }
+            match is_line_break {
+                true => self.output.push('\n'),
+                false => self.output.push_str(token.code),
+            }
+            self.mid_line = !is_line_break;
+            return;
+        }
+        // An empty token doesn't separate whitespace on its left from whitespace on its right.
+        if token.code.is_empty() {
+            return;
+        }
+        self.whitespace.clear();
+        let width = mem::take(&mut self.width);
+        let force_space = mem::take(&mut self.force_space);
+        if is_line_break {
+            self.output.push('\n');
+            self.mid_line = false;
+            return;
+        }
+        if self.mid_line {
+            if width != 0 || force_space {
+                self.output.push(' ');
+            }
+        } else {
+            let depth = self.blocks.line(VisibleOffset(width));
+            self.output.extend(iter::repeat(' ').take((depth * INDENT_WIDTH) as usize));
+        }
+        match is_comment {
+            true => self.output.push_str(token.code.trim_end()),
+            false => self.output.push_str(token.code),
+        }
+        self.mid_line = true;
+    }
+
+    /// Start printing text-literal or documentation contents, which are shifted along with the
+    /// current block. Return the previous state, to be restored at the end of the contents.
+    fn enter_text(&mut self) -> Option<i64> {
+        let indent = self.blocks.depth() * INDENT_WIDTH;
+        let shift = indent as i64 - self.blocks.current.width_in_spaces as i64;
+        self.text_shift.replace(shift)
+    }
+
+    fn text_elements(&mut self, elements: &[TextElement]) {
+        for element in elements {
+            match element {
+                TextElement::Section { text } => self.token(text.into()),
+                TextElement::Escape { token } => self.token(token.into()),
+                TextElement::Newline { newline } => self.token(newline.into()),
+                TextElement::Splice { open, expression, close } => {
+                    self.token(open.into());
+                    let text_shift = self.text_shift.take();
+                    expression.visit_item(self);
+                    self.text_shift = text_shift;
+                    self.token(close.into());
+                }
+            }
+        }
+    }
+
+    fn doc_comment(&mut self, doc: &DocComment) {
+        let DocComment { open, elements, newlines } = doc;
+        self.token(open.into());
+        let outer = self.enter_text();
+        self.text_elements(elements);
+        self.text_shift = outer;
+        newlines.visit_item(self);
+    }
+
+    /// Print the operator and right operand of a binary-operator statement, with the operator
+    /// surrounded by spaces.
+    fn spaced_operator(&mut self, operator: &token::Operator, rhs: Option<&Tree>) {
+        self.force_space = true;
+        self.token(operator.into());
+        self.force_space = true;
+        if let Some(rhs) = rhs {
+            self.expression(rhs);
+        }
+    }
+
+    /// Print a whole expression: one that isn't adjacent to other terms, such as the content of a
+    /// line. If it is an unspaced chain of operators that are spaced in canonical code, the chain
+    /// is spaced; see the module docs.
+    fn expression(&mut self, tree: &Tree) {
+        match respaceable(tree) {
+            Some((lhs, operator, rhs)) => {
+                self.offset(&tree.span.left_offset);
+                self.expression(lhs);
+                self.spaced_operator(operator, Some(rhs));
+            }
+            None => tree.visit_item(self),
+        }
+    }
+
+    fn lines(&mut self, lines: &[block::Line]) {
+        for block::Line { newline, expression } in lines {
+            newline.visit_item(self);
+            if let Some(expression) = expression {
+                self.expression(expression);
+            }
+        }
+    }
+}
+
+/// If the tree is an application of a binary operator that is written without spaces, but is
+/// spaced in canonical code, return its operands and operator.
+fn respaceable<'s, 'a>(
+    tree: &'a Tree<'s>,
+) -> Option<(&'a Tree<'s>, &'a token::Operator<'s>, &'a Tree<'s>)> {
+    let Variant::OprApp(OprApp { lhs: Some(lhs), opr: Ok(operator), rhs: Some(rhs) }) =
+        &*tree.variant else { return None };
+    let is_unspaced = operator.left_offset.visible.width_in_spaces == 0
+        && rhs.span.left_offset.visible.width_in_spaces == 0;
+    (is_unspaced && is_spaced(&operator.properties)).then_some((lhs, operator, rhs))
+}
+
+/// Return whether the binary operator is surrounded by spaces in canonical code. Operators that
+/// join their operands into a single term, and operators with special syntax, are written as they
+/// are in the input.
+fn is_spaced(properties: &OperatorProperties) -> bool {
+    let is_special = properties.is_dot()
+        || properties.is_decimal()
+        || properties.is_sequence()
+        || properties.is_token_joiner()
+        || properties.is_annotation()
+        || properties.is_suspension()
+        || properties.is_assignment()
+        || properties.is_modifier()
+        || properties.is_special();
+    properties.binary_infix_precedence().is_some() && !is_special
+}
+
+impl Visitor for Printer {}
+
+impl<'s, 'a> ItemVisitor<'s, 'a> for Printer {
+    fn visit_item(&mut self, item: item::Ref<'s, 'a>) -> bool {
+        let tree = match item {
+            item::Ref::Token(token) => {
+                self.token(token);
+                return true;
+            }
+            item::Ref::Tree(tree) => tree,
+        };
+        self.offset(&tree.span.left_offset);
+        match &*tree.variant {
+            Variant::TextLiteral(TextLiteral { open, newline, elements, close, closed: _ }) => {
+                open.visit_item(self);
+                let outer = self.enter_text();
+                newline.visit_item(self);
+                self.text_elements(elements);
+                close.visit_item(self);
+                self.text_shift = outer;
+            }
+            Variant::Documented(Documented { documentation, expression }) => {
+                self.doc_comment(documentation);
+                if let Some(expression) = expression {
+                    self.expression(expression);
+                }
+            }
+            Variant::CaseOf(CaseOf { case, expression, of, cases }) => {
+                self.token(case.into());
+                expression.visit_item(self);
+                self.token(of.into());
+                for CaseLine { newline, case } in cases {
+                    newline.visit_item(self);
+                    if let Some(Case { documentation, pattern, arrow, expression }) = case {
+                        if let Some(documentation) = documentation {
+                            self.doc_comment(documentation);
+                        }
+                        pattern.visit_item(self);
+                        arrow.visit_item(self);
+                        if let Some(expression) = expression {
+                            self.expression(expression);
+                        }
+                    }
+                }
+            }
+            Variant::Assignment(Assignment { pattern, equals, expr }) => {
+                pattern.visit_item(self);
+                self.spaced_operator(equals, Some(expr));
+            }
+            Variant::Function(Function { name, args, equals, body }) => {
+                name.visit_item(self);
+                args.visit_item(self);
+                self.spaced_operator(equals, body.as_ref());
+            }
+            Variant::TypeSignature(TypeSignature { variable, operator, type_ }) => {
+                variable.visit_item(self);
+                self.spaced_operator(operator, Some(type_));
+            }
+            Variant::BodyBlock(BodyBlock { statements }) => self.lines(statements),
+            Variant::ArgumentBlockApplication(ArgumentBlockApplication { lhs, arguments }) => {
+                lhs.visit_item(self);
+                self.lines(arguments);
+            }
+            Variant::Group(Group { open, body, close }) => {
+                open.visit_item(self);
+                if let Some(body) = body {
+                    self.expression(body);
+                }
+                close.visit_item(self);
+            }
+            _ => return true,
+        }
+        false
+    }
+}
+
+
+
+// ==============
+// === Blocks ===
+// ==============
+
+/// Tracks the block structure of the input, following the rules used by the lexer.
+#[derive(Debug, Default)]
+struct Blocks {
+    /// The indentation of the enclosing blocks.
+    outer:   Vec<VisibleOffset>,
+    /// The indentation of the current block.
+    current: VisibleOffset,
+}
+
+impl Blocks {
+    /// Update the state for the start of a line with the given indentation; return the depth of the
+    /// block containing the line.
+    fn line(&mut self, indent: VisibleOffset) -> u32 {
+        if indent > self.current {
+            self.outer.push(self.current);
+            self.current = indent;
+        }
+        while indent < self.current && let Some(&outer) = self.outer.last() && indent <= outer {
+            self.outer.pop();
+            self.current = outer;
+        }
+        self.depth()
+    }
+
+    /// Return the depth of the current block.
+    fn depth(&self) -> u32 {
+        self.outer.len() as u32
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test(input: &[&str], expected: &[&str]) {
+        let input = input.join("\n");
+        let expected = expected.join("\n");
+        let formatted = format(&crate::Parser::new().run(&input));
+        assert_eq!(formatted, expected);
+    }
+
+    #[test]
+    fn test_spacing() {
+        test(&["x  =  f  a+b   c"], &["x = f a+b c"]);
+        test(&["x=f.g"], &["x = f.g"]);
+        test(&["foo x=x+1"], &["foo x=x+1"]);
+        test(&["foo x y=x+y"], &["foo x y=x+y"]);
+        test(&["foo:Integer->Text"], &["foo : Integer -> Text"]);
+        test(&["x = f -1"], &["x = f -1"]);
+        test(&["x = (a  .  b)"], &["x = (a . b)"]);
+    }
+
+    #[test]
+    fn test_operator_spacing() {
+        test(&["x = a+b*c"], &["x = a + b * c"]);
+        test(&["a+b"], &["a + b"]);
+        test(&["x = f (a+b) c"], &["x = f (a + b) c"]);
+        test(&["x = f a+b"], &["x = f a+b"]);
+        test(&["x = a.b+c.d"], &["x = a.b + c.d"]);
+        test(&["x = a+b,c"], &["x = a+b,c"]);
+        test(&["x = a +b"], &["x = a +b"]);
+        test(&["f = x->x+1"], &["f = x -> x + 1"]);
+        #[rustfmt::skip]
+        test(
+            &["x = case a of", "  Foo b -> b*2", "  _ -> -1"],
+            &["x = case a of", "    Foo b -> b * 2", "    _ -> -1"],
+        );
+    }
+
+    #[test]
+    fn test_indentation() {
+        #[rustfmt::skip]
+        test(
+            &["main =", "  x = 1", "  y =", "     z", "       w", "  y"],
+            &["main =", "    x = 1", "    y =", "        z", "            w", "    y"],
+        );
+        #[rustfmt::skip]
+        test(
+            &["type Foo", "  Bar x", "", "  baz self =", "      case self of", "        Bar x -> x"],
+            &["type Foo", "    Bar x", "", "    baz self =", "        case self of", "            Bar x -> x"],
+        );
+        test(&["  x", "  y"], &["    x", "    y"]);
+    }
+
+    #[test]
+    fn test_comments() {
+        #[rustfmt::skip]
+        test(
+            &["main =  ", "  # Comment.  ", "  x = 1   # Trailing.", "", "  y"],
+            &["main =", "    # Comment.", "    x = 1 # Trailing.", "", "    y"],
+        );
+    }
+
+    #[test]
+    fn test_text() {
+        #[rustfmt::skip]
+        test(
+            &["main =", "  x = '''", "     a  ", "       b", "  x"],
+            &["main =", "    x = '''", "       a  ", "         b", "    x"],
+        );
+        test(&["x = 'a  `b  +  c`  d'"], &["x = 'a  `b + c`  d'"]);
+    }
+
+    #[test]
+    fn test_documentation() {
+        #[rustfmt::skip]
+        test(
+            &["type Foo", "  ## Doc", "       more doc.", "  bar  =  1"],
+            &["type Foo", "    ## Doc", "         more doc.", "    bar = 1"],
+        );
+    }
+
+    #[test]
+    fn test_line_endings() {
+        test(&["main =\r\n  x\r\n"], &["main =\n    x\n"]);
+    }
+}
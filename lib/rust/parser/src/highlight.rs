@@ -0,0 +1,502 @@
+//! Syntax highlighting: Classifying the code of a [`Tree`] for display.
+//!
+//! [`highlight`] walks a tree and returns runs of code, each with a [`HighlightKind`]. Most tokens
+//! are classified by their lexical variant alone; the tree is used to refine the classification
+//! where the role of a token depends on its context, e.g. an identifier used as a keyword in a type
+//! definition, or as the name of a function. The runs can be rendered with [`ansi`] for terminals,
+//! or [`html`] for documentation.
+
+use crate::prelude::*;
+
+use crate::source::Code;
+use crate::syntax::item;
+use crate::syntax::token;
+use crate::syntax::tree::*;
+
+
+
+// =====================
+// === HighlightKind ===
+// =====================
+
+/// The classification of a run of code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HighlightKind {
+    /// A keyword, including identifiers with a special meaning in their context, such as `type`,
+    /// or the segments of a macro such as `if ... then ... else ...`.
+    Keyword,
+    /// The name of a type, or a reference to a type or constructor.
+    Type,
+    /// The name of a constructor, in a type definition.
+    Constructor,
+    /// The name of a function or method, in its definition.
+    Function,
+    /// An identifier or wildcard not classified otherwise.
+    Variable,
+    /// An operator.
+    Operator,
+    /// A number literal, including its base and decimal point.
+    Number,
+    /// The quotes and contents of a text literal.
+    Text,
+    /// An escape sequence in a text literal.
+    TextEscape,
+    /// The delimiters of an interpolated expression in a text literal.
+    Interpolation,
+    /// A comment.
+    Comment,
+    /// A documentation comment.
+    DocComment,
+    /// An annotation, such as `@Builtin_Type`.
+    Annotation,
+    /// A token that could not be lexed.
+    Invalid,
+}
+
+impl HighlightKind {
+    /// Return the name of the highlight kind, following the capture names used by tree-sitter
+    /// highlighting queries.
+    pub fn capture_name(self) -> &'static str {
+        match self {
+            HighlightKind::Keyword => "keyword",
+            HighlightKind::Type => "type",
+            HighlightKind::Constructor => "constructor",
+            HighlightKind::Function => "function.method",
+            HighlightKind::Variable => "variable",
+            HighlightKind::Operator => "operator",
+            HighlightKind::Number => "number",
+            HighlightKind::Text => "string",
+            HighlightKind::TextEscape => "string.escape",
+            HighlightKind::Interpolation => "punctuation.special",
+            HighlightKind::Comment => "comment",
+            HighlightKind::DocComment => "comment.documentation",
+            HighlightKind::Annotation => "attribute",
+            HighlightKind::Invalid => "error",
+        }
+    }
+
+    /// The ANSI Select Graphic Rendition parameters used to display the kind in a terminal.
+    fn ansi_style(self) -> Option<&'static str> {
+        Some(match self {
+            HighlightKind::Keyword => "1;35",
+            HighlightKind::Type => "33",
+            HighlightKind::Constructor => "1;33",
+            HighlightKind::Function => "34",
+            HighlightKind::Variable | HighlightKind::Operator => return None,
+            HighlightKind::Number => "36",
+            HighlightKind::Text => "32",
+            HighlightKind::TextEscape => "1;32",
+            HighlightKind::Interpolation => "35",
+            HighlightKind::Comment => "90",
+            HighlightKind::DocComment => "3;32",
+            HighlightKind::Annotation => "3;33",
+            HighlightKind::Invalid => "4;31",
+        })
+    }
+}
+
+
+
+// =================
+// === Highlight ===
+// =================
+
+/// Return the highlighted runs of the code of the tree, in source order. Each run is the byte range
+/// of one or more consecutive tokens of the same kind; runs don't include whitespace or line
+/// terminators, and don't overlap. Code that isn't highlighted, such as brackets, is not included.
+pub fn highlight(tree: &Tree) -> Vec<(Range<usize>, HighlightKind)> {
+    highlight_range(tree, 0..usize::MAX)
+}
+
+/// Return the highlighted runs of the code of the tree within the given byte range; runs partially
+/// within the range are clipped to it. Subtrees outside the range are not visited, so this is much
+/// faster than [`highlight`] for a small part of a large tree, such as the visible part of a
+/// document in an editor.
+pub fn highlight_range(tree: &Tree, range: Range<usize>) -> Vec<(Range<usize>, HighlightKind)> {
+    let mut highlighter = Highlighter { range, ..default() };
+    tree.visit_item(&mut highlighter);
+    let mut runs = highlighter.runs;
+    runs.sort_by_key(|(range, _)| range.start);
+    runs.dedup_by(|(next, next_kind), (run, kind)| {
+        let is_continued = run.end == next.start && kind == next_kind;
+        if is_continued {
+            run.end = next.end;
+        }
+        is_continued
+    });
+    runs
+}
+
+
+// === Highlighter ===
+
+/// Visitor classifying tokens, taking into account the context of the tokens in the tree.
+#[derive(Debug, Default)]
+struct Highlighter {
+    range:         Range<usize>,
+    /// Classifications overriding those based on the token variants, by byte offset.
+    overrides:     HashMap<usize, HighlightKind>,
+    /// The byte ranges of documentation comments.
+    documentation: Vec<Range<usize>>,
+    runs:          Vec<(Range<usize>, HighlightKind)>,
+}
+
+impl Visitor for Highlighter {}
+impl<'s, 'a> ItemVisitor<'s, 'a> for Highlighter {
+    fn visit_item(&mut self, item: item::Ref<'s, 'a>) -> bool {
+        match item {
+            item::Ref::Tree(tree) => {
+                let range = byte_range(tree.span.range());
+                let is_visible = range.start < self.range.end && self.range.start < range.end;
+                let is_visible =
+                    is_visible || range.is_empty() && self.range.contains(&range.start);
+                if is_visible {
+                    self.visit_tree(tree, range);
+                }
+                is_visible
+            }
+            item::Ref::Token(token) => {
+                self.visit_token(&token);
+                false
+            }
+        }
+    }
+}
+
+impl Highlighter {
+    fn visit_tree(&mut self, tree: &Tree, range: Range<usize>) {
+        match &*tree.variant {
+            Variant::TypeDef(TypeDef { keyword, name, .. }) => {
+                self.set(&keyword.code, HighlightKind::Keyword);
+                self.set(&name.code, HighlightKind::Type);
+            }
+            Variant::ConstructorDefinition(ConstructorDefinition { constructor, .. }) =>
+                self.set(&constructor.code, HighlightKind::Constructor),
+            Variant::Function(Function { name, .. }) => {
+                // The name of a method may be qualified by the name of its type; the last token is
+                // the name of the method itself.
+                let mut last_token = LastToken::default();
+                name.visit_item(&mut last_token);
+                if let Some(start) = last_token.start {
+                    self.overrides.insert(start, HighlightKind::Function);
+                }
+            }
+            Variant::ForeignFunction(ForeignFunction { foreign, name, .. }) => {
+                self.set(&foreign.code, HighlightKind::Keyword);
+                self.set(&name.code, HighlightKind::Function);
+            }
+            Variant::CaseOf(CaseOf { case, of, .. }) => {
+                self.set(&case.code, HighlightKind::Keyword);
+                self.set(&of.code, HighlightKind::Keyword);
+            }
+            Variant::MultiSegmentApp(MultiSegmentApp { segments }) =>
+                for segment in segments {
+                    self.set_keyword(segment);
+                },
+            Variant::Import(Import { polyglot, from, import, all, as_, hiding }) => {
+                let segments = [polyglot.as_ref(), from.as_ref(), Some(import), as_.as_ref()];
+                for segment in segments.into_iter().chain([hiding.as_ref()]).flatten() {
+                    self.set_keyword(segment);
+                }
+                all.iter().for_each(|all| self.set(&all.code, HighlightKind::Keyword));
+            }
+            Variant::Export(Export { from, export, all, as_, hiding }) => {
+                let segments = [from.as_ref(), Some(export), as_.as_ref(), hiding.as_ref()];
+                for segment in segments.into_iter().flatten() {
+                    self.set_keyword(segment);
+                }
+                all.iter().for_each(|all| self.set(&all.code, HighlightKind::Keyword));
+            }
+            Variant::DefaultApp(DefaultApp { default, .. }) =>
+                self.set(&default.code, HighlightKind::Keyword),
+            Variant::Annotated(Annotated { token, annotation, .. })
+            | Variant::AnnotatedBuiltin(AnnotatedBuiltin { token, annotation, .. }) => {
+                self.set(&token.code, HighlightKind::Annotation);
+                self.set(&annotation.code, HighlightKind::Annotation);
+            }
+            Variant::Number(Number { fractional_digits: Some(digits), .. }) =>
+                self.set(&digits.dot.code, HighlightKind::Number),
+            Variant::TextLiteral(TextLiteral { elements, .. }) =>
+                for element in elements {
+                    if let TextElement::Splice { open, close, .. } = element {
+                        self.set(&open.code, HighlightKind::Interpolation);
+                        self.set(&close.code, HighlightKind::Interpolation);
+                    }
+                },
+            Variant::Documented(Documented { expression, .. }) => {
+                let end = match expression {
+                    Some(expression) => byte_range(expression.span.range()).start,
+                    None => range.end,
+                };
+                self.documentation.push(range.start..end);
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_token(&mut self, token: &token::Ref) {
+        let range = byte_range(token.code.range());
+        let Some(kind) = self.classify(token, range.start) else { return };
+        // Comments are part of the newline tokens following them.
+        let text = token.code.repr.0;
+        let end = range.start + text.trim_end_matches(|c| c == '\r' || c == '\n').len();
+        let start = range.start.max(self.range.start);
+        let end = end.min(self.range.end);
+        if start < end {
+            self.runs.push((start..end, kind));
+        }
+    }
+
+    fn set(&mut self, code: &Code, kind: HighlightKind) {
+        self.overrides.insert(byte_range(code.range()).start, kind);
+    }
+
+    fn set_keyword(&mut self, segment: &MultiSegmentAppSegment) {
+        if let token::Variant::Ident(_) = segment.header.variant {
+            self.set(&segment.header.code, HighlightKind::Keyword);
+        }
+    }
+
+    fn classify(&self, token: &token::Ref, start: usize) -> Option<HighlightKind> {
+        let is_comment = token.code.repr.0.starts_with('#');
+        if let token::Variant::Newline(_) = token.data && !is_comment {
+            return None;
+        }
+        if self.documentation.iter().any(|range| range.contains(&start)) {
+            return Some(HighlightKind::DocComment);
+        }
+        if let Some(&kind) = self.overrides.get(&start) {
+            return Some(kind);
+        }
+        Some(match token.data {
+            token::Variant::Ident(ident) if ident.is_type => HighlightKind::Type,
+            token::Variant::Ident(_) | token::Variant::Wildcard(_) => HighlightKind::Variable,
+            token::Variant::Operator(_) | token::Variant::AutoScope(_) => HighlightKind::Operator,
+            token::Variant::Digits(_) | token::Variant::NumberBase(_) => HighlightKind::Number,
+            token::Variant::TextStart(_)
+            | token::Variant::TextEnd(_)
+            | token::Variant::TextSection(_) => HighlightKind::Text,
+            token::Variant::TextEscape(_) => HighlightKind::TextEscape,
+            token::Variant::Newline(_) => HighlightKind::Comment,
+            token::Variant::Private(_) => HighlightKind::Keyword,
+            token::Variant::Invalid(_) => HighlightKind::Invalid,
+            token::Variant::OpenSymbol(_)
+            | token::Variant::CloseSymbol(_)
+            | token::Variant::BlockStart(_)
+            | token::Variant::BlockEnd(_)
+            | token::Variant::TextInitialNewline(_)
+            | token::Variant::TextNewline(_) => return None,
+        })
+    }
+}
+
+/// Visitor finding the start of the last token of a tree.
+#[derive(Debug, Default)]
+struct LastToken {
+    start: Option<usize>,
+}
+
+impl Visitor for LastToken {}
+impl<'s, 'a> ItemVisitor<'s, 'a> for LastToken {
+    fn visit_item(&mut self, item: item::Ref<'s, 'a>) -> bool {
+        if let item::Ref::Token(token) = item {
+            self.start = Some(byte_range(token.code.range()).start);
+        }
+        true
+    }
+}
+
+fn byte_range(range: Range<crate::source::code::Location>) -> Range<usize> {
+    range.start.utf8 as usize..range.end.utf8 as usize
+}
+
+
+
+// =================
+// === Renderers ===
+// =================
+
+/// Render the code with the given highlights using ANSI escape sequences, for display in a
+/// terminal.
+pub fn ansi(code: &str, highlights: &[(Range<usize>, HighlightKind)]) -> String {
+    let mut out = String::with_capacity(code.len() * 2);
+    render(
+        code,
+        highlights,
+        &mut out,
+        |out, text| out.push_str(text),
+        |out, kind, text| match kind.ansi_style() {
+            Some(style) => {
+                out.push_str("\x1b[");
+                out.push_str(style);
+                out.push('m');
+                out.push_str(text);
+                out.push_str("\x1b[0m");
+            }
+            None => out.push_str(text),
+        },
+    );
+    out
+}
+
+/// Render the code with the given highlights as an HTML `pre` element. Each run is a `span` with
+/// a class derived from its [`HighlightKind::capture_name`], e.g. `hl-string-escape`.
+pub fn html(code: &str, highlights: &[(Range<usize>, HighlightKind)]) -> String {
+    let mut out = String::with_capacity(code.len() * 2);
+    out.push_str("<pre class=\"enso\"><code>");
+    render(code, highlights, &mut out, escape_html, |out, kind, text| {
+        out.push_str("<span class=\"hl-");
+        out.push_str(&kind.capture_name().replace('.', "-"));
+        out.push_str("\">");
+        escape_html(out, text);
+        out.push_str("</span>");
+    });
+    out.push_str("</code></pre>");
+    out
+}
+
+/// Write the code, passing each highlighted run to `highlighted`, and the text between runs to
+/// `plain`.
+fn render(
+    code: &str,
+    highlights: &[(Range<usize>, HighlightKind)],
+    out: &mut String,
+    plain: impl Fn(&mut String, &str),
+    highlighted: impl Fn(&mut String, HighlightKind, &str),
+) {
+    let mut position = 0;
+    for (range, kind) in highlights {
+        let Some(text) = code.get(range.clone()) else { continue };
+        if range.start < position {
+            continue;
+        }
+        plain(out, &code[position..range.start]);
+        highlighted(out, *kind, text);
+        position = range.end;
+    }
+    plain(out, &code[position..]);
+}
+
+fn escape_html(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Highlight the code, and return the code and kind of each run.
+    fn runs(code: &str) -> Vec<(&str, HighlightKind)> {
+        let tree = crate::Parser::new().run(code);
+        highlight(&tree).into_iter().map(|(range, kind)| (&code[range], kind)).collect()
+    }
+
+    #[test]
+    fn test_highlight() {
+        use HighlightKind::*;
+        let code = [
+            "## Docs.",
+            "type Point",
+            "    @x Widget",
+            "    Value x y",
+            "    Point.length self = if self.x > 0 then 'ą\\n' else 1.5 # Comment",
+        ];
+        let expected = [
+            ("## Docs.", DocComment),
+            ("type", Keyword),
+            ("Point", Type),
+            ("@x", Annotation),
+            ("Widget", Type),
+            ("Value", Constructor),
+            ("x", Variable),
+            ("y", Variable),
+            ("Point", Type),
+            (".", Operator),
+            ("length", Function),
+            ("self", Variable),
+            ("=", Operator),
+            ("if", Keyword),
+            ("self", Variable),
+            (".", Operator),
+            ("x", Variable),
+            (">", Operator),
+            ("0", Number),
+            ("then", Keyword),
+            ("'ą", Text),
+            ("\\n", TextEscape),
+            ("'", Text),
+            ("else", Keyword),
+            ("1.5", Number),
+            ("# Comment", Comment),
+        ];
+        assert_eq!(runs(&code.join("\n")), expected);
+    }
+
+    #[test]
+    fn test_context() {
+        use HighlightKind::*;
+        let expected = [
+            ("foreign", Keyword),
+            ("js", Variable),
+            ("f", Function),
+            ("=", Operator),
+            ("\"return 1\"", Text),
+        ];
+        assert_eq!(runs("foreign js f = \"return 1\""), expected);
+        let expected = [
+            ("from", Keyword),
+            ("Standard", Type),
+            (".", Operator),
+            ("Base", Type),
+            ("import", Keyword),
+            ("all", Keyword),
+        ];
+        assert_eq!(runs("from Standard.Base import all"), expected);
+        let expected = [("@Builtin_Type", Annotation), ("type", Keyword), ("X", Type)];
+        assert_eq!(runs("@Builtin_Type\ntype X"), expected);
+        let expected = [
+            ("'a ", Text),
+            ("`", Interpolation),
+            ("b", Variable),
+            ("`", Interpolation),
+            ("'", Text),
+        ];
+        assert_eq!(runs("'a `b`'"), expected);
+    }
+
+    #[test]
+    fn test_highlight_range() {
+        let code = "x = 1\ny = 'abc'\nz = 3";
+        let tree = crate::Parser::new().run(code);
+        let runs = highlight_range(&tree, 8..14);
+        let runs = runs.into_iter().map(|(range, kind)| (&code[range], kind)).collect_vec();
+        assert_eq!(runs, [("=", HighlightKind::Operator), ("'abc", HighlightKind::Text)]);
+    }
+
+    #[test]
+    fn test_renderers() {
+        let code = "x = 'a<b'";
+        let highlights = highlight(&crate::Parser::new().run(code));
+        let expected = concat!(
+            "<pre class=\"enso\"><code><span class=\"hl-variable\">x</span> ",
+            "<span class=\"hl-operator\">=</span> <span class=\"hl-string\">'a&lt;b'</span>",
+            "</code></pre>"
+        );
+        assert_eq!(html(code, &highlights), expected);
+        assert_eq!(ansi(code, &highlights), "x = \x1b[32m'a<b'\x1b[0m");
+    }
+}
@@ -0,0 +1,386 @@
+//! Incremental reparsing: Updating the syntax tree of a document after edits, without parsing the
+//! whole document again.
+//!
+//! # Reparsed range
+//! A top-level line that starts with a non-whitespace character can be lexed and parsed
+//! independently of the preceding code, as long as no splice of an interpolated text literal is
+//! open at its start. After a document is edited, the range of top-level statements that may be
+//! affected is determined by finding such boundaries around the edited range; only the lines in
+//! that range are lexed and parsed again. The trees of the statements outside the range are reused,
+//! after the locations of their tokens are updated to account for the edits. That update is linear
+//! in the size of the reused trees, but much cheaper than parsing them.
+//!
+//! If the reparsed lines would interact with code outside the range (e.g. they end inside a text
+//! literal, or in a documentation comment that applies to the next statement), the range is
+//! extended. When any of the information needed is unavailable, the whole document is parsed. In
+//! all cases, the result is identical to the result of parsing the new document with
+//! [`Parser::run`].
+
+use crate::prelude::*;
+
+use crate::lexer;
+use crate::macros;
+use crate::source::code::Location;
+use crate::source::*;
+use crate::syntax::tree;
+use crate::syntax::tree::block;
+use crate::syntax::tree::CodeVisitableMut;
+use crate::syntax::tree::CodeVisitorMut;
+use crate::syntax::tree::ItemVisitable;
+use crate::syntax::tree::ItemVisitor;
+use crate::syntax::tree::Visitor;
+use crate::syntax::*;
+use crate::Parser;
+
+use enso_text::Byte;
+use enso_text::Change;
+
+
+
+// ===============
+// === Reparse ===
+// ===============
+
+/// Parse the given code, which is the result of applying the given edits to the code that
+/// `previous` was parsed from. See the module docs for details.
+pub fn reparse<'s, S: AsRef<str>>(
+    parser: &Parser,
+    previous: Tree<'s>,
+    edits: &[Change<Byte, S>],
+    code: &'s str,
+) -> Tree<'s> {
+    try_reparse(parser, previous, edits, code).unwrap_or_else(|| parser.run(code))
+}
+
+fn try_reparse<'s, S: AsRef<str>>(
+    parser: &Parser,
+    previous: Tree<'s>,
+    edits: &[Change<Byte, S>],
+    code: &'s str,
+) -> Option<Tree<'s>> {
+    let previous_len = previous.span.left_offset.code.length() + previous.span.code_length;
+    let edited = EditedRange::new(edits, previous_len.utf8 as usize)?;
+    if edited.new_len != code.len() {
+        return None;
+    }
+    let Tree { span, variant } = previous;
+    let box tree::Variant::BodyBlock(tree::BodyBlock { mut statements }) = variant else {
+        return None;
+    };
+    let delta = edited.new_len as isize - edited.old_len as isize;
+    let lines = Lines { statements: &statements, code, delta };
+    let mut start = lines.last_start_at_or_before(edited.start);
+    let mut end = lines.first_end_at_or_after(start + 1, edited.old_end);
+    let mut checked = 0;
+    let mut reparsed = loop {
+        // If the lexer is inside a text literal at the end of some statement, the range can't
+        // start or end at any statement following it.
+        if let Some(unclosed) = (checked..end).find(|&i| lines.has_open_splice(i)) {
+            start = start.min(lines.last_start_at_or_before(lines.start_of(unclosed)));
+            end = lines.len();
+        }
+        checked = end;
+        match parse_lines(parser, &lines, start, end) {
+            Some(reparsed) => break reparsed,
+            None if end == lines.len() => return None,
+            None => {
+                // Grow the range geometrically, so that the total work is linear in its size.
+                let old_start = lines.start_of(start);
+                let old_end = lines.start_of(end);
+                end = lines.first_end_at_or_after(end + 1, old_end + (old_end - old_start));
+            }
+        }
+    };
+    let suffix_base = (end < lines.len()).then(|| lines.location_of(end));
+    let suffix = statements.split_off(end);
+    statements.truncate(start);
+    let mut unmoved = Relocate::new(default(), default(), code);
+    statements.visit_code_mut(&mut unmoved);
+    statements.append(&mut reparsed.statements);
+    if let (Some(newline), Some(old_base)) = (reparsed.newline, suffix_base) {
+        let new_base = newline.code.range().end;
+        let mut moved = Relocate::new(old_base, new_base, code);
+        let mut suffix = suffix.into_iter();
+        let first = suffix.next().unwrap();
+        let mut expression = first.expression;
+        expression.visit_code_mut(&mut moved);
+        statements.push(block::Line { newline, expression });
+        for mut line in suffix {
+            line.visit_code_mut(&mut moved);
+            statements.push(line);
+        }
+        if moved.failed {
+            return None;
+        }
+    }
+    let mut tree = Tree::body_block(statements);
+    if start > 0 {
+        // When the previous tree was built, any whitespace at the start of the document was moved
+        // from the first statement to the root node.
+        let mut left_offset = span.left_offset;
+        unmoved.visit_mut(&mut left_offset.code);
+        tree.span.left_offset = left_offset;
+    }
+    // A reused reference that couldn't be moved would still refer to the previous code, which the
+    // caller may free once this returns; parse the whole document instead.
+    (!unmoved.failed).then_some(tree)
+}
+
+/// Lex and parse the new code for the statements in the given range.
+fn parse_lines<'s>(
+    parser: &Parser,
+    lines: &Lines<'_, 's>,
+    start: usize,
+    end: usize,
+) -> Option<ReparsedLines<'s>> {
+    let code = lines.code;
+    let location = lines.location_of(start);
+    let range_start = location.utf8 as usize;
+    let mut tokens = if end == lines.len() {
+        let tokens = lexer::run(code.get(range_start..)?);
+        if tokens.internal_error.is_some() {
+            return None;
+        }
+        tokens.value
+    } else {
+        let range_end = lines.new_start_of(end);
+        lexer::run_lines(code.get(range_start..range_end)?)?
+    };
+    let mut relocate = Relocate::new(default(), location, code);
+    tokens.visit_code_mut(&mut relocate);
+    let mut newline = lines.statements[start].newline.clone();
+    let mut relocate_newline = Relocate::new(default(), default(), code);
+    newline.visit_code_mut(&mut relocate_newline);
+    if relocate.failed || relocate_newline.failed {
+        return None;
+    }
+    let mut resolver = macros::resolver::Resolver::new_statement();
+    let mut new_lines = resolver.run_lines(&parser.macros, newline, tokens);
+    let newline = if end == lines.len() {
+        None
+    } else {
+        let last = new_lines.pop()?;
+        if last.expression.is_some() {
+            return None;
+        }
+        Some(last.newline)
+    };
+    let statements: Vec<_> = block::statements_from_lines(new_lines).collect();
+    if newline.is_some() && statements.last().map_or(false, is_incomplete_statement) {
+        return None;
+    }
+    Some(ReparsedLines { statements, newline })
+}
+
+/// The result of parsing the lines in the reparsed range.
+#[derive(Debug)]
+struct ReparsedLines<'s> {
+    statements: Vec<block::Line<'s>>,
+    /// The newline introducing the first statement following the reparsed range, if any.
+    newline:    Option<token::Newline<'s>>,
+}
+
+/// Return whether the statement is an annotation or documentation comment that would apply to a
+/// following statement, if there were one.
+fn is_incomplete_statement(line: &block::Line) -> bool {
+    line.expression.as_ref().map_or(false, is_incomplete_prefix)
+}
+
+fn is_incomplete_prefix(tree: &Tree) -> bool {
+    use tree::*;
+    match &*tree.variant {
+        Variant::Annotated(Annotated { expression, .. })
+        | Variant::AnnotatedBuiltin(AnnotatedBuiltin { expression, .. })
+        | Variant::Documented(Documented { expression, .. }) =>
+            expression.as_ref().map_or(true, is_incomplete_prefix),
+        _ => false,
+    }
+}
+
+
+
+// ===================
+// === EditedRange ===
+// ===================
+
+/// The smallest range of a document containing all the changes made by a sequence of edits.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct EditedRange {
+    /// The start of the range; this is the same in the old and new code.
+    start:   usize,
+    /// The end of the range, in the old code.
+    old_end: usize,
+    /// The length of the old code.
+    old_len: usize,
+    /// The length of the new code.
+    new_len: usize,
+}
+
+impl EditedRange {
+    /// Compute the range affected by the edits, which are applied in order, to a document of the
+    /// given length. Returns [`None`] if any edit is out of bounds.
+    fn new<S: AsRef<str>>(edits: &[Change<Byte, S>], old_len: usize) -> Option<Self> {
+        let mut len = old_len;
+        let mut start = old_len;
+        // The end of the edited range, in the current code.
+        let mut end = 0;
+        for edit in edits {
+            let (edit_start, edit_end) = (edit.range.start.value, edit.range.end.value);
+            let inserted = edit.text.as_ref().len();
+            if edit_start > edit_end || edit_end > len {
+                return None;
+            }
+            start = start.min(edit_start);
+            end = if end > edit_end {
+                end + inserted - (edit_end - edit_start)
+            } else {
+                edit_start + inserted
+            };
+            len = len + inserted - (edit_end - edit_start);
+        }
+        let end = end.max(start);
+        let old_end = old_len - (len - end);
+        Some(Self { start, old_end, old_len, new_len: len })
+    }
+}
+
+
+
+// =============
+// === Lines ===
+// =============
+
+/// The top-level statements of the previous tree, and the new code.
+#[derive(Debug)]
+struct Lines<'t, 's> {
+    statements: &'t [block::Line<'s>],
+    code:       &'s str,
+    /// The difference between the new and old length of the code.
+    delta:      isize,
+}
+
+impl<'t, 's> Lines<'t, 's> {
+    fn len(&self) -> usize {
+        self.statements.len()
+    }
+
+    /// The location of the start of the statement's content, in the old code.
+    fn location_of(&self, i: usize) -> Location {
+        self.statements[i].newline.code.range().end
+    }
+
+    /// The offset of the start of the statement's content, in the old code.
+    fn start_of(&self, i: usize) -> usize {
+        self.location_of(i).utf8 as usize
+    }
+
+    /// The offset of the start of the statement's content, in the new code. The statement must be
+    /// located after any edits.
+    fn new_start_of(&self, i: usize) -> usize {
+        (self.start_of(i) as isize + self.delta) as usize
+    }
+
+    /// Whether lexing can start at the beginning of the statement, in the old code and in the code
+    /// at the given offset of the new code: The statement must be at the beginning of a line, and
+    /// start with a non-whitespace character.
+    fn is_boundary(&self, i: usize, new_offset: usize) -> bool {
+        let line = &self.statements[i];
+        let starts_line = matches!(line.newline.code.repr.0, "\n" | "\r" | "\r\n");
+        let starts_unindented =
+            line.expression.as_ref().map_or(false, |tree| tree.span.left_offset.code.is_empty());
+        let next_char = self.code.get(new_offset..).and_then(|code| code.chars().next());
+        let starts_token =
+            next_char.map_or(false, |c| !lexer::is_space_char(c) && !lexer::is_newline_char(c));
+        starts_line && starts_unindented && starts_token
+    }
+
+    /// Return the last statement starting at or before the given offset where parsing can begin.
+    fn last_start_at_or_before(&self, offset: usize) -> usize {
+        (1..self.len())
+            .rev()
+            .find(|&i| self.start_of(i) <= offset && self.is_boundary(i, self.start_of(i)))
+            .unwrap_or_default()
+    }
+
+    /// Return the first statement, with index at least `first`, starting at or after the given
+    /// offset in the old code where parsing can end. If there is no such statement, returns the
+    /// number of statements.
+    fn first_end_at_or_after(&self, first: usize, offset: usize) -> usize {
+        (first..self.len())
+            .find(|&i| self.start_of(i) >= offset && self.is_boundary(i, self.new_start_of(i)))
+            .unwrap_or(self.len())
+    }
+
+    /// Whether the lexer may be inside a text literal at the end of the statement.
+    ///
+    /// The lexer only remains inside a text literal at the end of a line if a splice in an
+    /// interpolated text literal has been opened, and not closed. A statement where that happens
+    /// contains a text literal that is not closed, and a splice quote.
+    fn has_open_splice(&self, i: usize) -> bool {
+        let mut check = SpliceCheck::default();
+        self.statements[i].visit_item(&mut check);
+        check.open_text && check.splice_quote
+    }
+}
+
+
+// === SpliceCheck ===
+
+/// Visitor used to identify statements that may leave a splice open.
+#[derive(Debug, Default)]
+struct SpliceCheck {
+    open_text:    bool,
+    splice_quote: bool,
+}
+
+impl Visitor for SpliceCheck {}
+impl<'s, 'a> ItemVisitor<'s, 'a> for SpliceCheck {
+    fn visit_item(&mut self, item: item::Ref<'s, 'a>) -> bool {
+        match item {
+            item::Ref::Tree(tree) =>
+                if let box tree::Variant::TextLiteral(text) = &tree.variant && !text.closed {
+                    self.open_text = true;
+                },
+            item::Ref::Token(token) =>
+                if token.code.repr.0 == "`" {
+                    self.splice_quote = true;
+                },
+        }
+        !(self.open_text && self.splice_quote)
+    }
+}
+
+
+
+// ================
+// === Relocate ===
+// ================
+
+/// Visitor that moves code references from one document to another, or to a different location.
+///
+/// If any reference can't be moved because the new code doesn't contain its text at the expected
+/// location, the visitor is marked as [`failed`](Self::failed). The visited value then still
+/// contains references to the old code, and must not be used with the new code.
+#[derive(Debug)]
+struct Relocate<'s> {
+    /// A location in the source of the visited references.
+    old_base: Location,
+    /// The corresponding location in the new code.
+    new_base: Location,
+    code:     &'s str,
+    failed:   bool,
+}
+
+impl<'s> Relocate<'s> {
+    fn new(old_base: Location, new_base: Location, code: &'s str) -> Self {
+        Self { old_base, new_base, code, failed: false }
+    }
+}
+
+impl<'s> Visitor for Relocate<'s> {}
+impl<'s> CodeVisitorMut<'s> for Relocate<'s> {
+    fn visit_mut(&mut self, code: &mut Code<'s>) {
+        let start = self.new_base + (code.range().start - self.old_base);
+        self.failed |= code.relocate(start, self.code).is_err();
+    }
+}
@@ -127,8 +127,11 @@ struct Mark<'s> {
 impl<'s> Lexer<'s> {
     /// Constructor.
     pub fn new(input: &'s str) -> Self {
+        Self::with_output_capacity(input, input.len() / AVERAGE_TOKEN_LEN)
+    }
+
+    fn with_output_capacity(input: &'s str, capacity: usize) -> Self {
         let iterator = input.char_indices();
-        let capacity = input.len() / AVERAGE_TOKEN_LEN;
         let output = Vec::with_capacity(capacity);
         let state = default();
         let token_storage = default();
@@ -348,7 +351,7 @@ pub fn space_char_visible_size(t: char) -> Option<VisibleOffset> {
 
 /// Check whether the provided character is a visible space character.
 #[inline(always)]
-fn is_space_char(t: char) -> bool {
+pub(crate) fn is_space_char(t: char) -> bool {
     space_char_visible_size(t).is_some()
 }
 
@@ -395,7 +398,7 @@ impl<'s> Lexer<'s> {
 
 /// Check whether the provided character is a newline character.
 #[inline(always)]
-fn is_newline_char(t: char) -> bool {
+pub(crate) fn is_newline_char(t: char) -> bool {
     t == '\n' || t == '\r'
 }
 
@@ -868,7 +871,7 @@ impl<'s> Lexer<'s> {
         if let Some(token) = token {
             if let Some(base) = base {
                 self.submit_token(token.with_variant(token::Variant::number_base()));
-                let after_base = self.current_offset;
+                let after_base = self.last_spaces_offset;
                 if let Some(digits) = match base {
                     token::Base::Binary => self.token(|this| this.take_while(is_binary_digit)),
                     token::Base::Octal => self.token(|this| this.take_while(is_octal_digit)),
@@ -973,10 +976,10 @@ impl<'s> Lexer<'s> {
                 initial_indent = self.last_spaces_visible_offset.into();
             }
         }
-        self.text_content(None, text_type.is_interpolated(), State::MultilineText {
-            block_indent,
-            initial_indent,
-        });
+        let state = State::MultilineText { block_indent, initial_indent };
+        if self.text_content(None, text_type.is_interpolated(), state) == TextEndedAt::Splice {
+            self.spaces_after_lexeme();
+        }
     }
 
     fn inline_quote(&mut self, quote_char: char, text_type: TextType) {
@@ -985,17 +988,21 @@ impl<'s> Lexer<'s> {
     }
 
     fn end_splice(&mut self, state: State) {
-        let splice_quote_start = self.mark_without_whitespace();
+        let splice_quote_start = self.mark();
         self.take_next();
         let splice_quote_end = self.mark_without_whitespace();
         let token =
             self.make_token(splice_quote_start, splice_quote_end, token::Variant::close_symbol());
         self.output.push(token);
         match state {
-            State::InlineText => self.inline_quote('\'', TextType::Interpolated),
-            State::MultilineText { .. } => {
-                self.text_content(None, true, state);
+            State::InlineText => {
+                self.inline_quote('\'', TextType::Interpolated);
+                self.spaces_after_lexeme();
             }
+            State::MultilineText { .. } =>
+                if self.text_content(None, true, state) == TextEndedAt::Splice {
+                    self.spaces_after_lexeme();
+                },
         }
     }
 
@@ -1092,7 +1099,7 @@ impl<'s> Lexer<'s> {
             if interpolate && char == '\\' {
                 let mut backslash_start = self.mark_without_whitespace();
                 self.take_next();
-                if let Some(char) = self.current_char {
+                if let Some(char) = self.current_char && !is_newline_char(char) {
                     let token = self.make_token(
                         text_start.clone(),
                         backslash_start.clone(),
@@ -1408,17 +1415,63 @@ impl<'s> Lexer<'s> {
     /// Run the lexer. Return non-hierarchical list of tokens (the token groups will be represented
     /// as start and end tokens).
     pub fn run(mut self) -> ParseResult<Vec<Token<'s>>> {
-        // If the first line is indented, open a block for it.
+        self.run_lexers();
+        let internal_error = self.finish();
+        ParseResult { value: self.output, internal_error }
+    }
+
+    /// Run the lexer on input consisting of complete lines of top-level code. Return [`None`] if
+    /// the input doesn't end with a line break, or if the state of the lexer at the end of the
+    /// input is different from its initial state (e.g. because a text literal or a block is
+    /// still open).
+    ///
+    /// If the input starts at the beginning of a top-level line of a larger document, and this
+    /// function succeeds, the result is the same as the corresponding part of the output of
+    /// [`Lexer::run`] for the whole document (except for the locations of the tokens, which are
+    /// relative to the start of the input).
+    pub fn run_lines(mut self) -> Option<Vec<Token<'s>>> {
+        self.run_lexers();
+        let ends_with_line_break = match self.output.last() {
+            Some(Token { variant: token::Variant::Newline(_), code, .. }) =>
+                matches!(code.repr.0, "\n" | "\r" | "\r\n"),
+            _ => false,
+        };
+        let complete = ends_with_line_break
+            && self.current_char.is_none()
+            && self.internal_error.is_none()
+            && self.stack.is_empty()
+            && self.block_indent_stack.is_empty()
+            && self.last_spaces_visible_offset == VisibleOffset(0);
+        complete.then_some(self.output)
+    }
+
+    fn run_lexers(&mut self) {
+        self.start();
+        while self.step() {}
+    }
+
+    fn start(&mut self) {
+        // If the first line is indented, open a block for it. A blank line doesn't open a block.
         self.spaces_after_lexeme();
         let first_block_indent = self.last_spaces_visible_offset;
-        if first_block_indent.width_in_spaces != 0 {
+        let is_blank = self.current_char.map_or(true, is_newline_char);
+        if first_block_indent.width_in_spaces != 0 && !is_blank {
             let start = Location::default();
             self.submit_token(token::block_start(Code::empty(start), Code::empty(start)).into());
             self.start_block(first_block_indent);
             self.submit_token(token::newline(Code::empty(start), Code::empty(start)).into());
         }
-        // Main parsing loop.
-        while PARSERS.iter().any(|f| self.run_and_check_if_progressed(f)) {}
+    }
+
+    /// Run the first parser that accepts the current input. Return [`false`] if no parser made
+    /// progress, which happens at the end of the input.
+    #[inline(always)]
+    fn step(&mut self) -> bool {
+        PARSERS.iter().any(|f| self.run_and_check_if_progressed(f))
+    }
+
+    /// Submit the tokens ending the input. Return an internal error, if any occurred.
+    fn finish(&mut self) -> Option<String> {
         // If any blocks were still open at EOF, close them.
         while self.end_block().is_some() {
             let block_end = self.marker_token(token::Variant::block_end());
@@ -1442,9 +1495,64 @@ impl<'s> Lexer<'s> {
             let message = format!("Lexer did not consume all input. State: {self:?}");
             internal_error.get_or_insert(message);
         }
+        internal_error
+    }
+}
+
+
+
+// ==============
+// === Stream ===
+// ==============
+
+/// A pull-based lexer: an iterator yielding the same tokens as [`Lexer::run`], lexing the input
+/// incrementally as the tokens are consumed.
+///
+/// The lexer state, including the stack of open blocks, is kept between the tokens, so only the
+/// tokens produced by a single step of the lexer are buffered, rather than the tokens of the whole
+/// input. This allows a consumer, such as the macro resolver, to start its work before the input
+/// has been fully tokenized, and lowers the peak memory usage for large inputs.
+#[derive(Debug)]
+pub struct Stream<'s> {
+    lexer:          Lexer<'s>,
+    /// Tokens produced by the lexer but not yet yielded, in reverse order.
+    pending:        Vec<Token<'s>>,
+    finished:       bool,
+    internal_error: Option<String>,
+}
+
+impl<'s> Stream<'s> {
+    /// Constructor.
+    pub fn new(input: &'s str) -> Self {
+        let mut lexer = Lexer::with_output_capacity(input, 0);
+        lexer.start();
+        Self { lexer, pending: default(), finished: false, internal_error: None }
+    }
+
+    /// Return the internal error of the lexer, if any occurred. Errors are detected when the end of
+    /// the input is reached, so the result is [`None`] until the stream has been exhausted.
+    pub fn internal_error(&self) -> Option<&str> {
+        self.internal_error.as_deref()
+    }
+}
 
-        let value = self.output;
-        ParseResult { value, internal_error }
+impl<'s> Iterator for Stream<'s> {
+    type Item = Token<'s>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(token) = self.pending.pop() {
+                return Some(token);
+            }
+            if self.finished {
+                return None;
+            }
+            if !self.lexer.step() {
+                self.internal_error = self.lexer.finish();
+                self.finished = true;
+            }
+            self.pending.extend(self.lexer.output.drain(..).rev());
+        }
     }
 }
 
@@ -1454,6 +1562,16 @@ pub fn run(input: &'_ str) -> ParseResult<Vec<Token<'_>>> {
     Lexer::new(input).run()
 }
 
+/// Return a pull-based lexer for the input. See [`Stream`].
+pub fn stream(input: &'_ str) -> Stream<'_> {
+    Stream::new(input)
+}
+
+/// Run the lexer on a sequence of complete top-level lines. See [`Lexer::run_lines`].
+pub fn run_lines(input: &'_ str) -> Option<Vec<Token<'_>>> {
+    Lexer::new(input).run_lines()
+}
+
 
 
 // =============
@@ -1864,6 +1982,44 @@ mod tests {
         lex_and_validate_spans(&code);
     }
 
+    #[test]
+    fn test_stream() {
+        let inputs = [
+            "",
+            "main = 23",
+            "  indented\nfirst line",
+            "main =\n    x = 'text `splice`'\n        continued\n    # comment\n    x\n",
+            "## Doc\n   comment.\ntype T\n    A\n\n    f self = '''\n        text\n",
+            "trailing whitespace   ",
+            "x = \"unclosed\n  y\r\n",
+        ];
+        for input in inputs {
+            let batch = run(input);
+            let mut stream = stream(input);
+            let streamed: Vec<_> = stream.by_ref().collect();
+            assert_eq!(streamed, batch.value);
+            assert_eq!(stream.internal_error(), batch.internal_error.as_deref());
+            assert_eq!(stream.next(), None);
+        }
+    }
+
+    /// The stream buffers only the tokens produced by a single step of the lexer, so unlike the
+    /// output of the batch lexer, its buffers don't grow with the input.
+    #[test]
+    fn test_stream_buffering() {
+        let input = "main =\n    x = 'text `splice`'\n    # comment\n    x\n".repeat(10_000);
+        let mut stream = stream(&input);
+        let mut count = 0;
+        let mut peak_buffered = 0;
+        while stream.next().is_some() {
+            count += 1;
+            let buffered = stream.pending.capacity() + stream.lexer.output.capacity();
+            peak_buffered = peak_buffered.max(buffered);
+        }
+        assert_eq!(count, run(&input).value.len());
+        assert!(peak_buffered < 64, "Buffered {peak_buffered} of {count} tokens.");
+    }
+
     #[test]
     fn test_text() {
         lex_and_validate_spans("f 'foo' 'bar'");
@@ -1923,6 +2079,38 @@ mod benches {
         });
     }
 
+    /// Return Enso source code of roughly the given size, in bytes, exercising blocks, text
+    /// literals, comments, and documentation.
+    fn source(size: usize) -> String {
+        let mut code = String::with_capacity(size);
+        let mut i = 0;
+        while code.len() < size {
+            code.push_str(&format!("## Documentation of `function_{i}`.\n"));
+            code.push_str(&format!("function_{i} a b =\n"));
+            code.push_str("    x = a + b * 2 # A comment.\n");
+            code.push_str(&format!("    y = 'text literal {i}'\n"));
+            code.push_str("    case x of\n        0 -> y\n        _ -> x.to_text\n\n");
+            i += 1;
+        }
+        code
+    }
+
+    #[bench]
+    fn bench_batch(b: &mut Bencher) {
+        let str = source(1024 * 1024);
+        b.bytes = str.len() as u64;
+        b.iter(move || run(test::black_box(&str)).value.len());
+    }
+
+    /// Compare to [`bench_batch`]. The peak memory usage of the two lexers is compared by the
+    /// `lexer_memory` binary of the `enso-parser-debug` crate.
+    #[bench]
+    fn bench_stream(b: &mut Bencher) {
+        let str = source(1024 * 1024);
+        b.bytes = str.len() as u64;
+        b.iter(move || stream(test::black_box(&str)).count());
+    }
+
     /// 7-13x slowdown in comparison to [`bench_str_iter`] and [`bench_str_iter_and_compare`].
     #[bench]
     fn bench_idents(b: &mut Bencher) {
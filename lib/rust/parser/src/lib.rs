@@ -8,8 +8,8 @@
 //! # Building macro registry.
 //! Macros in Enso are a very powerful mechanism and are used to transform group of tokens into
 //! almost any statement. First, macros need to be discovered and registered. Currently, there is no
-//! real macro discovery process, as there is no support for user-defined macros. Instead, there is
-//! a set of hardcoded macros defined in the compiler.
+//! real macro discovery process. Instead, there is a set of hardcoded macros defined in the
+//! compiler, to which additional macros can be added with [`Parser::register_macro`].
 //!
 //! Each macro defines one or more segments. Every segment starts with a predefined token and can
 //! contain any number of other tokens. For example, the macro `if ... then ... else ...` contains
@@ -102,15 +102,21 @@
 
 use crate::prelude::*;
 
+use crate::diagnostic::ErrorKind;
+
 
 // ==============
 // === Export ===
 // ==============
 
+pub mod diagnostic;
 pub mod format;
+pub mod highlight;
+pub mod incremental;
 pub mod lexer;
 pub mod macros;
 pub mod metadata;
+pub mod scope;
 pub mod serialization;
 pub mod source;
 pub mod syntax;
@@ -175,15 +181,51 @@ impl Parser {
 
     /// Main entry point.
     pub fn run<'s>(&self, code: &'s str) -> syntax::Tree<'s> {
-        let tokens = lexer::run(code);
+        // The tokens are resolved as they are lexed, so that the whole token stream doesn't need
+        // to be stored.
+        let mut tokens = lexer::stream(code);
         let mut resolver = macros::resolver::Resolver::new_statement();
-        let result = tokens.map(|tokens| resolver.run(&self.macros, tokens));
-        let value = result.value;
-        if let Some(error) = result.internal_error {
-            return value.with_error(format!("Internal error: {error}"));
+        let value = resolver.run(&self.macros, &mut tokens);
+        if let Some(error) = tokens.internal_error() {
+            return value.with_error(ErrorKind::Internal, format!("Internal error: {error}"));
         }
         value
     }
+
+    /// Register a macro, in addition to the built-in macros. Macros registered later take
+    /// precedence over earlier ones with the same segments.
+    pub fn register_macro(
+        &mut self,
+        context: macros::Context,
+        definition: macros::Definition<'static>,
+    ) {
+        self.macros.register(context, definition);
+    }
+
+    /// Parse the given code; return the tree, and diagnostics describing the errors in it, in
+    /// source order.
+    pub fn run_with_diagnostics<'s>(
+        &self,
+        code: &'s str,
+    ) -> (syntax::Tree<'s>, Vec<diagnostic::Diagnostic>) {
+        let tree = self.run(code);
+        let diagnostics = diagnostic::collect(&tree);
+        (tree, diagnostics)
+    }
+
+    /// Parse the given code, which is the result of applying the given edits to the code that
+    /// `previous` was parsed from; the edits are applied in order. Only the top-level statements
+    /// affected by the edits are parsed again. The result is the same as the result of [`run`].
+    ///
+    /// [`run`]: Self::run
+    pub fn reparse<'s, S: AsRef<str>>(
+        &self,
+        previous: syntax::Tree<'s>,
+        edits: &[enso_text::Change<enso_text::Byte, S>],
+        code: &'s str,
+    ) -> syntax::Tree<'s> {
+        incremental::reparse(self, previous, edits, code)
+    }
 }
 
 impl Default for Parser {
@@ -226,7 +268,10 @@ fn expression_to_statement(mut tree: syntax::Tree<'_>) -> syntax::Tree<'_> {
         variant: box Variant::ArgumentBlockApplication(ArgumentBlockApplication { lhs: None, .. }),
         ..
     }) {
-        return tree.with_error("Expected expression before indented block.");
+        return tree.with_error(
+            ErrorKind::ExpectedExpressionBeforeBlock,
+            "Expected expression before indented block.",
+        );
     }
     let mut left_offset = tree.span.left_offset.position_before();
     let tree_ = &mut tree;
@@ -484,6 +529,22 @@ mod benches {
         });
     }
 
+    #[bench]
+    fn bench_reparsing_type_defs(bencher: &mut Bencher) {
+        let reps = 1_000;
+        let line = "type Option a b c\n";
+        let old = line.repeat(reps);
+        let offset = enso_text::Byte(line.len() * reps / 2);
+        let edits = [enso_text::Change { range: (offset..offset).into(), text: "x = y\n" }];
+        let mut new = old.clone();
+        new.insert_str(offset.value, edits[0].text);
+        let parser = Parser::new();
+        let previous = parser.run(&old);
+        bencher.iter(move || {
+            parser.reparse(previous.clone(), &edits, &new);
+        });
+    }
+
     #[bench]
     #[cfg(not(target_arch = "wasm32"))]
     fn bench_blocks(bencher: &mut Bencher) {
@@ -20,6 +20,7 @@ pub mod pattern;
 pub mod resolver;
 
 pub use pattern::Pattern;
+pub use resolver::Context;
 
 
 
@@ -44,6 +45,36 @@ pub struct Definition<'a> {
     pub body:     Rc<DefinitionBody>,
 }
 
+impl<'a> Definition<'a> {
+    /// Constructor. Returns `None` if there are no segments. Each segment header must be the code
+    /// of a single token, such as an identifier or an operator.
+    ///
+    /// An application of the macro is represented as a [`syntax::tree::MultiSegmentApp`], with the
+    /// tokens of each segment resolved into an expression; use [`Self::with_body`] to build a
+    /// different tree.
+    pub fn new(segments: impl IntoIterator<Item = SegmentDefinition<'a>>) -> Option<Self> {
+        let segments = segments.into_iter().collect_vec();
+        let segments = im_list::NonEmpty::try_from(segments).ok()?;
+        Some(Self { segments, body: Rc::new(matched_segments_into_multi_segment_app) })
+    }
+
+    /// Set the function that builds the tree for an application of the macro.
+    ///
+    /// If the macro is registered for [`Context::Expression`], the function may be
+    /// applied when the last segment is missing; the segment is then given with an empty header
+    /// (see the error recovery section of [`resolver`]).
+    pub fn with_body(
+        self,
+        body: impl for<'s, 'r> Fn(
+                pattern::MatchedSegments<'s>,
+                &'r mut syntax::operator::Precedence<'s>,
+            ) -> syntax::Tree<'s>
+            + 'static,
+    ) -> Self {
+        Self { body: Rc::new(body), ..self }
+    }
+}
+
 /// A function that transforms matched macro tokens into [`syntax::Tree`].
 pub type DefinitionBody = dyn for<'s, 'r> Fn(
     pattern::MatchedSegments<'s>,
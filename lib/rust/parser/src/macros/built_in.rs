@@ -3,6 +3,7 @@
 use crate::macros::pattern::*;
 use crate::macros::*;
 
+use crate::diagnostic::ErrorKind;
 use crate::source::Code;
 use crate::syntax::operator;
 
@@ -135,7 +136,10 @@ fn import_body<'s>(
     }
     let import = syntax::Tree::import(polyglot, from, import.unwrap(), all, as_, hiding);
     if incomplete_import {
-        return import.with_error("Expected name or `all` keyword following `import` keyword.");
+        return import.with_error(
+            ErrorKind::ExpectedImportTarget,
+            "Expected name or `all` keyword following `import` keyword.",
+        );
     }
     import
 }
@@ -222,7 +226,10 @@ fn export_body<'s>(
     }
     let export = syntax::Tree::export(from, export.unwrap(), all, as_, hiding);
     if incomplete_export {
-        return export.with_error("Expected name or `all` keyword following `export` keyword.");
+        return export.with_error(
+            ErrorKind::ExpectedImportTarget,
+            "Expected name or `all` keyword following `export` keyword.",
+        );
     }
     export
 }
@@ -309,7 +316,15 @@ fn type_def_body<'s>(
             code,
             variant: syntax::token::Variant::Ident(ident),
         })) => syntax::Token(left_offset, code, ident),
-        _ => return Tree::ident(header).with_error("Expected identifier after `type` keyword."),
+        first => {
+            let block = (!block.is_empty()).then_some(syntax::Item::Block(block));
+            let items = first.into_iter().chain(tokens).chain(block);
+            return (match precedence.resolve(items) {
+                Some(rhs) => Tree::app(header.into(), rhs),
+                None => header.into(),
+            })
+            .with_error(ErrorKind::ExpectedTypeName, "Expected identifier after `type` keyword.");
+        }
     };
     let params = precedence
         .resolve_non_section(tokens)
@@ -389,10 +404,13 @@ fn to_body_statement(mut line_expression: syntax::Tree<'_>) -> syntax::Tree<'_>
         let mut constructor = token;
         constructor.left_offset += left_offset;
         constructor.left_offset += span.left_offset;
-        if let Some((equals, expression)) = last_argument_default
-                && let Some(ArgumentDefinition { open: None, default, close: None, .. })
-                = arguments.last_mut() && default.is_none() {
-            *default = Some(ArgumentDefault { equals, expression });
+        if let Some((equals, expression)) = last_argument_default {
+            match arguments.last_mut() {
+                Some(ArgumentDefinition { open: None, default: default @ None, close: None, .. }) =>
+                    *default = Some(ArgumentDefault { equals, expression }),
+                // There is no argument the default can be attached to.
+                _ => return crate::expression_to_statement(line_expression),
+            }
         }
         let block = default();
         return Tree::constructor_definition(constructor, arguments, block);
@@ -456,7 +474,7 @@ fn case_body<'s>(
     let (case_lines, any_invalid) = case_builder.finish();
     let tree = Tree::case_of(case_, expression, of_, case_lines);
     if any_invalid {
-        return tree.with_error("Invalid case expression.");
+        return tree.with_error(ErrorKind::InvalidCase, "Invalid case expression.");
     }
     tree
 }
@@ -657,7 +675,9 @@ fn sequence_tree<'s>(
         tree = Tree::opr_app(tree, Ok(operator), body.map(&mut f)).into();
     }
     if invalid {
-        tree = tree.map(|tree| tree.with_error("Malformed comma-delimited sequence."));
+        tree = tree.map(|tree| {
+            tree.with_error(ErrorKind::MalformedSequence, "Malformed comma-delimited sequence.")
+        });
     }
     tree
 }
@@ -734,7 +754,7 @@ fn foreign_body<'s>(
             Some(rhs) => syntax::Tree::app(keyword.into(), rhs),
             None => keyword.into(),
         })
-        .with_error(error),
+        .with_error(ErrorKind::InvalidForeignFunction, error),
     }
 }
 
@@ -821,7 +841,7 @@ fn expect_ident(tree: syntax::Tree) -> syntax::Tree {
     if matches!(&*tree.variant, syntax::tree::Variant::Ident(_)) {
         tree
     } else {
-        tree.with_error("Expected identifier.")
+        tree.with_error(ErrorKind::ExpectedIdentifier, "Expected identifier.")
     }
 }
 
@@ -829,7 +849,7 @@ fn expect_qualified(tree: syntax::Tree) -> syntax::Tree {
     if crate::is_qualified_name(&tree) {
         tree
     } else {
-        tree.with_error("Expected qualified name.")
+        tree.with_error(ErrorKind::ExpectedQualifiedName, "Expected qualified name.")
     }
 }
 
@@ -843,5 +863,5 @@ fn expected_nonempty(location: Code) -> syntax::Tree {
         false,
         false,
     ));
-    empty.with_error("Expected tokens.")
+    empty.with_error(ErrorKind::ExpectedTokens, "Expected tokens.")
 }
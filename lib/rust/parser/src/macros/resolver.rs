@@ -21,9 +21,27 @@
 //! This is implemented by starting a new macro resolution [`Block`] at the beginning of every
 //! block; the new scope is initialized with only the root macro. Within a scope the state of all
 //! macros defined in parent scopes will never be advanced.
+//!
+//! # Error recovery
+//!
+//! A macro that is still open when its line ends, or when a segment of an enclosing macro begins,
+//! is resolved by `Resolver::resolve`. If exactly one more segment would complete the macro, as
+//! the `)` of an unclosed `(` or the `then` of an `if` missing it, and the header of that segment
+//! is a single token, the segment is assumed to have been omitted: the macro is resolved as if an empty segment had been found at the end of its
+//! input, and the result is marked as invalid. Only macros defined in [`Context::Expression`] are
+//! recovered this way; their bodies must accept an empty header in the last segment. A child block
+//! ends the input of the recovered macro; the block is left to be applied to the recovered node
+//! (see [`syntax::tree::apply`]). Errors are thus contained to the line in which they occur, and
+//! any following lines, or lines of child blocks, are parsed as if the error were not present.
+//!
+//! The operator precedence resolver doesn't need a recovery of its own: every line of a block is
+//! resolved by a separate run of [`syntax::operator::Precedence`] (see
+//! [`syntax::item::build_block`]), so an operator error in a line never changes the trees of the
+//! following lines, or of the lines of its child block.
 
 use crate::prelude::*;
 
+use crate::diagnostic::ErrorKind;
 use crate::macros;
 use crate::macros::pattern;
 use crate::source::Code;
@@ -50,20 +68,36 @@ pub struct MacroMap {
 }
 
 impl MacroMap {
-    /// Return the macro matching the given token in the given context, if any.
-    fn get(&self, key: &str, context: Context) -> Option<&NonEmptyVec<SegmentEntry<'static>>> {
-        let statement_result = || self.statement.get(key);
-        let expression_result = || self.expression.get(key);
+    /// Return the macro matching the given token in the given context, if any, and the context in
+    /// which it is defined.
+    fn get(
+        &self,
+        key: &str,
+        context: Context,
+    ) -> Option<(Context, &NonEmptyVec<SegmentEntry<'static>>)> {
+        let statement_result = || self.statement.get(key).map(|m| (Context::Statement, m));
+        let expression_result = || self.expression.get(key).map(|m| (Context::Expression, m));
         (context == Context::Statement).then(statement_result).flatten().or_else(expression_result)
     }
+
+    /// Register a macro definition in the given context.
+    pub fn register(&mut self, context: Context, definition: macros::Definition<'static>) {
+        match context {
+            Context::Expression => self.expression.register(definition),
+            Context::Statement => self.statement.register(definition),
+        }
+    }
 }
 
 
 // === Context ===
 
+/// The position in which a macro can occur.
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
-enum Context {
+pub enum Context {
+    /// Anywhere in an expression.
     Expression,
+    /// Only at the start of a line.
     Statement,
 }
 
@@ -99,6 +133,17 @@ pub struct SegmentEntry<'s> {
 
 
 impl<'a> SegmentMap<'a> {
+    /// If exactly one of the segments would complete a macro, return its header and the definition
+    /// of the completed macro.
+    fn closing_segment(&self) -> Option<(&'a str, Rc<macros::Definition<'a>>)> {
+        let mut closing = self.iter().flat_map(|(header, entries)| {
+            let closing = entries.iter().filter(|entry| entry.required_segments.is_empty());
+            closing.map(|entry| (*header, entry.definition.clone_ref()))
+        });
+        let segment = closing.next()?;
+        closing.next().is_none().then_some(segment)
+    }
+
     /// Register a new macro definition in this macro tree.
     pub fn register(&mut self, definition: macros::Definition<'a>) {
         let header = definition.segments.head.header;
@@ -160,22 +205,36 @@ impl<'s> Resolver<'s> {
         tokens: impl IntoIterator<Item = Token<'s>>,
     ) -> syntax::Tree<'s> {
         let start = crate::source::code::Location::default();
-        self.lines.push(syntax::item::Line {
-            newline: token::newline(Code::empty(start), Code::empty(start)),
-            items:   default(),
-        });
+        let newline = token::newline(Code::empty(start), Code::empty(start));
+        let lines = self.run_lines(root_macro_map, newline, tokens);
+        syntax::tree::block::body_from_lines(lines)
+    }
+
+    /// Run the resolver on a sequence of top-level lines, the first of which is introduced by the
+    /// given newline token. Returns the resolved lines, before they are interpreted as statements.
+    pub fn run_lines(
+        &mut self,
+        root_macro_map: &MacroMap,
+        newline: token::Newline<'s>,
+        tokens: impl IntoIterator<Item = Token<'s>>,
+    ) -> Vec<syntax::tree::block::Line<'s>> {
+        self.lines.push(syntax::item::Line { newline, items: default() });
         tokens.into_iter().for_each(|t| self.push(root_macro_map, t));
         self.finish_current_line();
-        let lines = self.lines.drain(..).map(|syntax::item::Line { newline, items }| {
-            syntax::tree::block::Line { newline, expression: self.precedence.resolve(items) }
-        });
-        let tree = syntax::tree::block::body_from_lines(lines);
+        let lines = self
+            .lines
+            .drain(..)
+            .map(|syntax::item::Line { newline, items }| syntax::tree::block::Line {
+                newline,
+                expression: self.precedence.resolve(items),
+            })
+            .collect();
         debug_assert!(self.blocks.is_empty());
         debug_assert!(self.lines.is_empty());
         debug_assert!(self.macros.is_empty());
         debug_assert!(self.segments.is_empty());
         debug_assert!(self.items.is_empty());
-        tree
+        lines
     }
 }
 
@@ -313,11 +372,12 @@ impl<'s> Resolver<'s> {
                 return Step::MacroStackPop(token.into());
             }
         }
-        if let Some(segments) = root_macro_map.get(repr, context) {
+        if let Some((context, segments)) = root_macro_map.get(repr, context) {
             trace!("Starting a new nested macro resolution.");
             let mut matched_macro_def = default();
             let segments_start = self.segments.len();
             let new_macro = PartiallyMatchedMacro {
+                context,
                 segments_start,
                 possible_next_segments: Self::move_to_next_segment(
                     &mut matched_macro_def,
@@ -339,14 +399,72 @@ impl<'s> Resolver<'s> {
     /// resolved).
     fn resolve(&mut self, m: PartiallyMatchedMacro<'s>) {
         let PartiallyMatchedMacro { matched_macro_def, segments_start, .. } = m;
+        let closing_segment = || match m.context {
+            Context::Expression => m.possible_next_segments.closing_segment(),
+            Context::Statement => None,
+        };
         if let Some(macro_def) = matched_macro_def {
             self.resolve_match(&macro_def, segments_start)
+        } else if let Some((header, macro_def)) = closing_segment()
+                && let Some(variant) = segment_header_variant(header) {
+            self.resolve_unclosed_match(header, variant, &macro_def, segments_start)
         } else {
             self.resolve_failed_match(segments_start)
         };
     }
 
     fn resolve_match(&mut self, macro_def: &macros::Definition, segments_start: usize) {
+        let (out, unused_items_of_last_segment) = self.apply_macro(macro_def, segments_start);
+        self.items.push(out.into());
+        self.items.extend(unused_items_of_last_segment);
+    }
+
+    /// Resolve a macro that would be matched if it had one more segment, with the given header. An
+    /// empty segment is inserted after the input of the current segment, which ends at the first
+    /// child block, if any; the macro is applied, and the result is marked as invalid.
+    fn resolve_unclosed_match(
+        &mut self,
+        header: &'s str,
+        variant: token::Variant,
+        macro_def: &macros::Definition,
+        segments_start: usize,
+    ) {
+        let segment = self.segments.last().unwrap();
+        let items_start = segment.items_start;
+        let items = &self.items[items_start..];
+        let block = items.iter().position(|item| matches!(item, syntax::Item::Block(_)));
+        let following = match block {
+            Some(index) => self.items.split_off(items_start + index),
+            None => default(),
+        };
+        let end = match self.items[items_start..].last() {
+            Some(syntax::Item::Tree(tree)) => tree_end(tree),
+            Some(syntax::Item::Token(token)) => Some(code_end(&token.code)),
+            Some(syntax::Item::Block(_)) => unreachable!(),
+            None => None,
+        };
+        let end = end.unwrap_or_else(|| code_end(&segment.header.code));
+        let header_token = Token(end.clone(), end, variant);
+        let items_start = self.items.len();
+        self.segments.push(MatchedSegment { header: header_token, items_start });
+        let (out, unused_items_of_last_segment) = self.apply_macro(macro_def, segments_start);
+        debug_assert!(unused_items_of_last_segment.is_empty());
+        let message = format!("Expected `{header}`.");
+        let kind = match variant {
+            token::Variant::CloseSymbol(_) => ErrorKind::UnmatchedDelimiter,
+            _ => ErrorKind::ExpectedMacroSegment,
+        };
+        self.items.push(out.with_error(kind, message).into());
+        self.items.extend(following);
+    }
+
+    /// Apply the macro to its matched segments. Returns the resulting AST, and any items following
+    /// the input of the last segment's pattern.
+    fn apply_macro(
+        &mut self,
+        macro_def: &macros::Definition,
+        segments_start: usize,
+    ) -> (syntax::Tree<'s>, VecDeque<syntax::Item<'s>>) {
         let mut def_segments = macro_def.segments.to_vec().into_iter().rev();
         let segments = self.segments.drain(segments_start..).rev();
         let segments: NonEmptyVec<_> = segments.collect_vec().try_into().unwrap();
@@ -393,7 +511,10 @@ impl<'s> Resolver<'s> {
                     Err(tokens) => tokens,
                 };
                 if let Some(excess) = self.precedence.resolve(excess) {
-                    let excess = excess.with_error("Unexpected tokens in macro invocation.");
+                    let excess = excess.with_error(
+                        ErrorKind::UnexpectedMacroTokens,
+                        "Unexpected tokens in macro invocation.",
+                    );
                     tokens.push(excess.into());
                 }
                 let body = self.precedence.resolve(tokens);
@@ -401,8 +522,7 @@ impl<'s> Resolver<'s> {
             });
             syntax::Tree::multi_segment_app(segments)
         };
-        self.items.push(out.into());
-        self.items.extend(unused_items_of_last_segment);
+        (out, unused_items_of_last_segment)
     }
 
     fn resolve_failed_match(&mut self, segments_start: usize) {
@@ -416,7 +536,8 @@ impl<'s> Resolver<'s> {
             items.push(segment.header.into());
         }
         let segment0 = self.segments.pop().unwrap();
-        let header0 = syntax::tree::to_ast(segment0.header).with_error("Invalid macro invocation.");
+        let header0 = syntax::tree::to_ast(segment0.header)
+            .with_error(ErrorKind::InvalidMacroInvocation, "Invalid macro invocation.");
         items.extend(self.items.drain(segment0.items_start..).rev());
         self.items.push(header0.into());
         self.items.extend(items.into_iter().rev());
@@ -451,6 +572,30 @@ impl<'s> Resolver<'s> {
 
 
 
+/// Return the variant of the token the given segment header consists of, if it is a single token.
+fn segment_header_variant(header: &str) -> Option<token::Variant> {
+    let mut tokens = crate::lexer::run(header).value.into_iter();
+    let token = tokens.next()?;
+    tokens.next().is_none().then_some(token.variant)
+}
+
+/// Return an empty [`Code`] referring to the source immediately after the given code.
+fn code_end<'s>(code: &Code<'s>) -> Code<'s> {
+    code.split_at(code.length()).1
+}
+
+/// Return an empty [`Code`] referring to the source immediately after the last token of the tree.
+fn tree_end<'s>(tree: &syntax::Tree<'s>) -> Option<Code<'s>> {
+    let mut end = None;
+    tree.visit_items(|item| match item {
+        syntax::item::Ref::Token(token) => end = Some(code_end(token.code)),
+        syntax::item::Ref::Tree(tree) => end = tree_end(tree).or(end.take()),
+    });
+    end
+}
+
+
+
 // =============================
 // === PartiallyMatchedMacro ===
 // =============================
@@ -462,6 +607,10 @@ impl<'s> Resolver<'s> {
 /// "else" token, the definition will be replaced with the "if ... then ... else ..." macro one.
 #[derive(Debug)]
 struct PartiallyMatchedMacro<'s> {
+    /// The context in which the macro is defined. Incomplete statement macros are never recovered
+    /// (see the module docs), because the bodies of the statement macros rely on their segment
+    /// headers.
+    context:                Context,
     possible_next_segments: SegmentMap<'s>,
     matched_macro_def:      Option<Rc<macros::Definition<'s>>>,
     /// Height in `segments` where this macro's resolved segments begin.
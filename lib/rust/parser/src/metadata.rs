@@ -1,9 +1,20 @@
 //! Data associated with a syntax tree.
 //!
 //! This data is currently represented as two lines containing one JSON value each, placed at the
-//! end of a file after a line containing exactly the text "#### METADATA ####".
+//! end of a file after a line containing exactly the text "#### METADATA ####". The first line is
+//! the ID map, associating stable IDs with spans of the code; the second contains the state of the
+//! IDE (node positions, visualizations, etc.).
+//!
+//! [`parse`] reads the ID map for lookup; [`Section`] supports reading, editing, and writing back
+//! the whole metadata section.
+
+use crate::syntax::tree::diff::Diff;
 
+use enso_text::Byte;
+use enso_text::Change;
+use serde_json::value::RawValue;
 use std::collections::BTreeMap;
+use std::fmt;
 use std::str::FromStr;
 use uuid::Uuid;
 
@@ -88,6 +99,288 @@ impl FromStr for MetadataFormat {
 }
 
 
+
+// ===============
+// === Section ===
+// ===============
+
+/// The metadata section of a source file, in a form that can be edited and written back.
+///
+/// Writing back an unmodified section reproduces its input exactly. When the section is modified,
+/// only the changed parts are reformatted: The IDE metadata is preserved as written, except for the
+/// objects containing a modified value; the ID map keeps the order of its entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    id_map:      Vec<(Location, Uuid)>,
+    /// The text of the ID map line, unless the ID map has been modified since it was read.
+    id_map_text: Option<String>,
+    /// The text of the IDE metadata line, if present.
+    metadata:    Option<String>,
+    /// Any text following the IDE metadata line.
+    trailing:    String,
+}
+
+impl Section {
+    /// Parse the metadata section (the text after the "#### METADATA ####" line).
+    pub fn parse(input: &str) -> Result<Self> {
+        let (id_map_text, rest) = input.split_once('\n').unwrap_or((input, ""));
+        let (metadata, trailing) = rest.split_at(rest.find('\n').unwrap_or(rest.len()));
+        let id_map = serde_json::from_str(id_map_text).map_err(|e| e.to_string())?;
+        let metadata = match metadata.is_empty() && trailing.is_empty() {
+            true => None,
+            false => {
+                serde_json::from_str::<&RawValue>(metadata).map_err(|e| e.to_string())?;
+                Some(metadata.to_owned())
+            }
+        };
+        let id_map_text = Some(id_map_text.to_owned());
+        Ok(Self { id_map, id_map_text, metadata, trailing: trailing.to_owned() })
+    }
+
+    /// Split a source file into the code and the metadata section, if any was found. See
+    /// [`extract`].
+    pub fn extract(input: &str) -> (&str, Option<Result<Self>>) {
+        let (code, metadata) = extract(input);
+        (code, metadata.map(Self::parse))
+    }
+
+    /// Return the contents of a source file consisting of the given code, followed by this
+    /// metadata section.
+    pub fn attach(&self, code: &str) -> String {
+        format!("{code}{MARKER}{self}")
+    }
+}
+
+
+// === ID Map ===
+
+impl Section {
+    /// Return the UUID associated with the node identified by offset/length, if any is found.
+    pub fn get_uuid(&self, index: usize, size: usize) -> Option<Uuid> {
+        let location = Location::new(index, size);
+        self.id_map.iter().find_map(|(loc, id)| (*loc == location).then_some(*id))
+    }
+
+    /// Return the entries of the ID map, as spans of the code (in bytes) and their IDs.
+    pub fn id_map(&self) -> impl Iterator<Item = (std::ops::Range<usize>, Uuid)> + '_ {
+        self.id_map.iter().map(|(location, id)| (location.range(), *id))
+    }
+
+    /// Associate the node identified by offset/length with the given UUID, replacing any UUID
+    /// previously associated with it.
+    pub fn set_uuid(&mut self, index: usize, size: usize, id: Uuid) {
+        let location = Location::new(index, size);
+        match self.id_map.iter_mut().find(|(loc, _)| *loc == location) {
+            Some((_, old_id)) if *old_id == id => return,
+            Some((_, old_id)) => *old_id = id,
+            None => self.id_map.push((location, id)),
+        }
+        self.id_map_text = None;
+    }
+
+    /// Remove the UUID associated with the node identified by offset/length, if any; return it.
+    pub fn remove_uuid(&mut self, index: usize, size: usize) -> Option<Uuid> {
+        let location = Location::new(index, size);
+        let i = self.id_map.iter().position(|(loc, _)| *loc == location)?;
+        self.id_map_text = None;
+        Some(self.id_map.remove(i).1)
+    }
+
+    /// Update the ID map for a change to the code, so that each ID continues to identify the same
+    /// node:
+    /// - Spans before the change are unaffected; spans after it are moved.
+    /// - A span containing the change is resized; text inserted at the start or end of a span is
+    ///   not considered part of it.
+    /// - A span partially overlapping the change is reduced to its part not removed by the change.
+    /// - An ID is removed if its span is entirely removed by the change.
+    pub fn apply_change<S: AsRef<str>>(&mut self, change: &Change<Byte, S>) {
+        let (start, end) = (change.range.start.value, change.range.end.value);
+        let inserted = change.text.as_ref().len();
+        let after_change = |offset: usize| offset - end + start + inserted;
+        let mut modified = false;
+        self.id_map.retain_mut(|(location, _)| {
+            let span = location.range();
+            let new_span = if span.end <= start {
+                span.clone()
+            } else if span.start >= end {
+                after_change(span.start)..after_change(span.end)
+            } else if start < end && start <= span.start && span.end <= end {
+                modified = true;
+                return false;
+            } else if span.start <= start && end <= span.end {
+                span.start..after_change(span.end)
+            } else if span.start < start {
+                span.start..start
+            } else {
+                start + inserted..after_change(span.end)
+            };
+            if new_span != span {
+                *location = Location::new(new_span.start, new_span.len());
+                modified = true;
+            }
+            true
+        });
+        if modified {
+            self.id_map_text = None;
+        }
+    }
+
+    /// Update the ID map after the code is reparsed, given the [`Diff`] between the old and new
+    /// trees, so that each ID continues to identify the same node: The span of each ID is replaced
+    /// with the span of the node's counterpart in the new tree. An ID is removed if its span is not
+    /// the span of a node with a counterpart.
+    pub fn apply_diff(&mut self, diff: &Diff) {
+        let mut modified = false;
+        self.id_map.retain_mut(|(location, _)| match diff.map_range(location.range()) {
+            Some(span) => {
+                if span != location.range() {
+                    *location = Location::new(span.start, span.len());
+                    modified = true;
+                }
+                true
+            }
+            None => {
+                modified = true;
+                false
+            }
+        });
+        if modified {
+            self.id_map_text = None;
+        }
+    }
+}
+
+
+// === IDE Metadata ===
+
+impl Section {
+    /// Return the value at the given path of object fields within the IDE metadata, if present.
+    /// The type can be [`serde_json::Value`], to inspect arbitrary data.
+    pub fn get<T: serde::de::DeserializeOwned>(&self, path: &[&str]) -> Result<Option<T>> {
+        let Some(metadata) = &self.metadata else { return Ok(None) };
+        let mut value: Box<RawValue> = serde_json::from_str(metadata).map_err(|e| e.to_string())?;
+        for key in path {
+            let object: Object = serde_json::from_str(value.get()).map_err(|e| e.to_string())?;
+            match object.0.into_iter().find(|(field, _)| field == key) {
+                Some((_, field_value)) => value = field_value,
+                None => return Ok(None),
+            }
+        }
+        serde_json::from_str(value.get()).map(Some).map_err(|e| e.to_string())
+    }
+
+    /// Set the value at the given path of object fields within the IDE metadata, creating any
+    /// missing objects along the path.
+    pub fn set(&mut self, path: &[&str], value: &impl serde::Serialize) -> Result<()> {
+        let value = serde_json::value::to_raw_value(value).map_err(|e| e.to_string())?;
+        self.replace(path, Some(value)).map(drop)
+    }
+
+    /// Remove the value at the given path of object fields within the IDE metadata, if present.
+    /// Returns whether a value was removed.
+    pub fn remove(&mut self, path: &[&str]) -> Result<bool> {
+        self.replace(path, None)
+    }
+
+    fn replace(&mut self, path: &[&str], value: Option<Box<RawValue>>) -> Result<bool> {
+        if path.is_empty() {
+            let modified = self.metadata.is_some() || value.is_some();
+            self.metadata = value.map(|value| value.get().to_owned());
+            return Ok(modified);
+        }
+        let metadata = self.metadata.as_deref().map(serde_json::from_str::<&RawValue>);
+        let metadata = metadata.transpose().map_err(|e| e.to_string())?;
+        let replaced = replace(metadata, path, value).map_err(|e| e.to_string())?;
+        let modified = replaced.is_some();
+        if let Some(replaced) = replaced {
+            self.metadata = Some(replaced.get().to_owned());
+        }
+        Ok(modified)
+    }
+}
+
+/// Replace the value at the given (non-empty) path of object fields within the JSON value; if the
+/// new value is [`None`], remove the field. Only the objects along the path are reformatted.
+/// Returns the modified JSON value, or [`None`] if it is unchanged.
+fn replace(
+    json: Option<&RawValue>,
+    path: &[&str],
+    value: Option<Box<RawValue>>,
+) -> serde_json::Result<Option<Box<RawValue>>> {
+    let Some((key, path)) = path.split_first() else { return Ok(value) };
+    let mut object: Object = match json {
+        Some(json) => serde_json::from_str(json.get())?,
+        None => Object::default(),
+    };
+    let index = object.0.iter().position(|(field, _)| field == key);
+    let value = match path.is_empty() {
+        true => value,
+        false => match replace(index.map(|i| &*object.0[i].1), path, value)? {
+            Some(value) => Some(value),
+            None => return Ok(None),
+        },
+    };
+    match (index, value) {
+        (Some(i), Some(value)) => object.0[i].1 = value,
+        (None, Some(value)) => object.0.push((key.to_string(), value)),
+        (Some(i), None) => drop(object.0.remove(i)),
+        (None, None) => return Ok(None),
+    }
+    serde_json::value::to_raw_value(&object).map(Some)
+}
+
+/// A JSON object, with its fields in their original order, and the original text of each value.
+#[derive(Debug, Default)]
+struct Object(Vec<(String, Box<RawValue>)>);
+
+impl<'de> serde::Deserialize<'de> for Object {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where D: serde::Deserializer<'de> {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Object;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a JSON object")
+            }
+            fn visit_map<A>(self, mut map: A) -> std::result::Result<Object, A::Error>
+            where A: serde::de::MapAccess<'de> {
+                let mut fields = vec![];
+                while let Some(field) = map.next_entry()? {
+                    fields.push(field);
+                }
+                Ok(Object(fields))
+            }
+        }
+        deserializer.deserialize_map(Visitor)
+    }
+}
+
+impl serde::Serialize for Object {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where S: serde::Serializer {
+        serializer.collect_map(self.0.iter().map(|(key, value)| (key, value)))
+    }
+}
+
+
+// === Writing ===
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.id_map_text {
+            Some(text) => f.write_str(text)?,
+            None => f.write_str(&serde_json::to_string(&self.id_map).map_err(|_| fmt::Error)?)?,
+        }
+        if let Some(metadata) = &self.metadata {
+            writeln!(f)?;
+            f.write_str(metadata)?;
+        }
+        f.write_str(&self.trailing)
+    }
+}
+
+
+
 // === Location ===
 
 /// Identifies a span in the source code.
@@ -104,6 +397,16 @@ struct Number {
     value: usize,
 }
 
+impl Location {
+    fn new(index: usize, size: usize) -> Self {
+        Self { index: Number { value: index }, size: Number { value: size } }
+    }
+
+    fn range(self) -> std::ops::Range<usize> {
+        self.index.value..(self.index.value + self.size.value)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -118,4 +421,107 @@ mod test {
     fn empty_metadata() {
         MetadataFormat::from_str("[]").expect("Empty sequence is valid.");
     }
+
+    const ID_MAP: &str = concat!(
+        r#"[[{"index":{"value":0},"size":{"value":4}},"00000000-0000-0000-0000-000000000001"],"#,
+        r#"[{"index":{"value":6},"size":{"value":3}},"00000000-0000-0000-0000-000000000002"],"#,
+        r#"[{"index":{"value":0},"size":{"value":9}},"00000000-0000-0000-0000-000000000003"]]"#,
+    );
+
+    fn id(n: u128) -> Uuid {
+        Uuid::from_u128(n)
+    }
+
+    fn ids_after_change(
+        start: usize,
+        end: usize,
+        text: &str,
+    ) -> Vec<(std::ops::Range<usize>, Uuid)> {
+        let mut section = Section::parse(ID_MAP).unwrap();
+        let change = Change { range: (Byte(start)..Byte(end)).into(), text };
+        section.apply_change(&change);
+        section.id_map().collect()
+    }
+
+    #[test]
+    fn section_round_trip() {
+        let inputs = [
+            ID_MAP.to_owned(),
+            format!("{ID_MAP}\n{{\"ide\": {{\"node\": {{}}}}, \"x\":[1, 2]}}"),
+            format!("{ID_MAP}\n{{}}\n"),
+            "[]".to_owned(),
+        ];
+        for input in inputs {
+            let section = Section::parse(&input).unwrap();
+            assert_eq!(section.to_string(), input);
+            assert_eq!(section.attach("main = 4"), format!("main = 4{MARKER}{input}"));
+        }
+        Section::parse("[]\nnot json").expect_err("Invalid metadata is error.");
+    }
+
+    #[test]
+    fn id_map_edits() {
+        let mut section = Section::parse(ID_MAP).unwrap();
+        assert_eq!(section.get_uuid(6, 3), Some(id(2)));
+        section.set_uuid(6, 3, id(2));
+        assert_eq!(section.to_string(), ID_MAP);
+        section.set_uuid(10, 1, id(4));
+        assert_eq!(section.remove_uuid(0, 4), Some(id(1)));
+        let reparsed = Section::parse(&section.to_string()).unwrap();
+        let expected = [(6..9, id(2)), (0..9, id(3)), (10..11, id(4))];
+        assert_eq!(reparsed.id_map().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn id_map_shifting() {
+        // Insertion after all spans.
+        assert_eq!(ids_after_change(9, 9, "xx"), [(0..4, id(1)), (6..9, id(2)), (0..9, id(3))]);
+        // Insertion between spans.
+        assert_eq!(ids_after_change(5, 5, "xx"), [(0..4, id(1)), (8..11, id(2)), (0..11, id(3))]);
+        // Insertion inside a span.
+        assert_eq!(ids_after_change(7, 7, "xx"), [(0..4, id(1)), (6..11, id(2)), (0..11, id(3))]);
+        // Replacement of a span's whole text.
+        assert_eq!(ids_after_change(6, 9, ""), [(0..4, id(1)), (0..6, id(3))]);
+        // Removal overlapping the end of one span and the start of another.
+        assert_eq!(ids_after_change(2, 7, ""), [(0..2, id(1)), (2..4, id(2)), (0..4, id(3))]);
+        // Unchanged ID map is written as read.
+        let mut section = Section::parse(ID_MAP).unwrap();
+        section.apply_change(&Change { range: (Byte(20)..Byte(20)).into(), text: "x" });
+        assert_eq!(section.to_string(), ID_MAP);
+    }
+
+    #[test]
+    fn id_map_diff() {
+        let parser = crate::Parser::new();
+        let old = parser.run("x = 1\ny = 2");
+        let new = parser.run("y = 2\nx = 1");
+        let mut section = Section::parse("[]").unwrap();
+        section.set_uuid(0, 5, id(1));
+        section.set_uuid(6, 5, id(2));
+        section.set_uuid(4, 1, id(3));
+        section.set_uuid(1, 2, id(4));
+        section.apply_diff(&crate::syntax::tree::diff::diff(&old, &new));
+        let expected = [(6..11, id(1)), (0..5, id(2)), (10..11, id(3))];
+        assert_eq!(section.id_map().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn ide_metadata_edits() {
+        let input = format!("{ID_MAP}\n{{\"ide\":{{\"node\":{{}}, \"z\": 1}}, \"x\":[1, 2]}}");
+        let mut section = Section::parse(&input).unwrap();
+        assert_eq!(section.get::<Vec<u32>>(&["x"]), Ok(Some(vec![1, 2])));
+        assert_eq!(section.get::<u32>(&["ide", "y"]), Ok(None));
+        section.set(&["ide", "node", "a"], &3).unwrap();
+        let expected =
+            format!("{ID_MAP}\n{{\"ide\":{{\"node\":{{\"a\":3}},\"z\":1}},\"x\":[1, 2]}}");
+        assert_eq!(section.to_string(), expected);
+        assert_eq!(section.remove(&["ide", "missing", "a"]), Ok(false));
+        assert_eq!(section.to_string(), expected);
+        assert_eq!(section.remove(&["ide", "node"]), Ok(true));
+        let expected = format!("{ID_MAP}\n{{\"ide\":{{\"z\":1}},\"x\":[1, 2]}}");
+        assert_eq!(section.to_string(), expected);
+        let mut section = Section::parse(ID_MAP).unwrap();
+        section.set(&["ide", "a"], &"b").unwrap();
+        assert_eq!(section.to_string(), format!("{ID_MAP}\n{{\"ide\":{{\"a\":\"b\"}}}}"));
+    }
 }
@@ -0,0 +1,878 @@
+//! Scope analysis: Resolving the identifiers of a [`Tree`] to the definitions they refer to.
+//!
+//! [`analyze`] builds the tree of lexical scopes of a module: the module itself, type definitions,
+//! functions, lambdas, the branches of `case` expressions, and blocks. It records each name bound
+//! in a scope--by a definition, an argument, or a pattern of an assignment--and resolves each
+//! identifier used as a value to the binding it refers to, to the import that provides it, or
+//! reports it as unbound.
+//!
+//! Names are resolved lexically:
+//! - Definitions at the top level of a module or a type can be referred to anywhere in its body;
+//!   bindings in a block are only visible after the statement that introduces them.
+//! - Methods of a type are not in scope within the type; they are accessed through `self` or the
+//!   type.
+//! - Names after a `.` operator, and lowercase names in type signatures (which are type variables),
+//!   are not resolved.
+//! - `self` and `Self`, which are bound implicitly, are not recorded.
+
+use crate::prelude::*;
+
+use crate::source::Code;
+use crate::syntax::item;
+use crate::syntax::token;
+use crate::syntax::tree::*;
+
+
+
+// ===========
+// === IDs ===
+// ===========
+
+/// Identifies a [`Scope`] in an [`Analysis`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ScopeId(pub usize);
+
+/// Identifies a [`Binding`] in an [`Analysis`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BindingId(pub usize);
+
+/// Identifies an [`Import`] in an [`Analysis`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ImportId(pub usize);
+
+
+
+// ================
+// === Analysis ===
+// ================
+
+/// The result of [`analyze`]. All ranges are byte ranges in the code of the analyzed tree.
+#[derive(Clone, Debug, Default)]
+pub struct Analysis<'s> {
+    /// The scopes, in pre-order; the first is the module scope.
+    pub scopes:   Vec<Scope>,
+    /// The names bound in the code, in the order they were encountered.
+    pub bindings: Vec<Binding<'s>>,
+    /// The imports of the module.
+    pub imports:  Vec<Import<'s>>,
+    /// The identifiers used as values or types, in the order they were encountered.
+    pub usages:   Vec<Usage<'s>>,
+}
+
+/// A lexical scope.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scope {
+    /// What introduces the scope.
+    pub kind:     ScopeKind,
+    /// The enclosing scope; only the module scope has none.
+    pub parent:   Option<ScopeId>,
+    /// The code in which the scope's bindings may be visible.
+    pub range:    Range<usize>,
+    /// The names bound in the scope.
+    pub bindings: Vec<BindingId>,
+}
+
+/// What introduces a [`Scope`].
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ScopeKind {
+    Module,
+    Type,
+    Function,
+    Lambda,
+    Case,
+    Block,
+}
+
+/// A name bound in a scope.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Binding<'s> {
+    /// The bound name.
+    pub name:  &'s str,
+    /// What kind of definition binds the name.
+    pub kind:  BindingKind,
+    /// The location of the name at its binding site.
+    pub range: Range<usize>,
+    /// The scope the name is bound in.
+    pub scope: ScopeId,
+}
+
+/// What kind of definition binds a name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BindingKind {
+    /// The name of a type definition.
+    Type,
+    /// A parameter of a type definition.
+    TypeParameter,
+    /// The name of a constructor of a type.
+    Constructor,
+    /// A method of a type, or an extension method (such as `Point.length`). Methods are bound in
+    /// the scope they are defined in, but are not visible there.
+    Method,
+    /// A function defined in a module or a block.
+    Function,
+    /// An argument of a function or lambda.
+    Argument,
+    /// A name bound by an assignment or a `case` pattern.
+    Variable,
+}
+
+/// An import statement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Import<'s> {
+    /// The location of the whole statement.
+    pub range:  Range<usize>,
+    /// The names the import binds, and their locations in the statement.
+    pub names:  Vec<(&'s str, Range<usize>)>,
+    /// Whether the import is of the form `from ... import all`, possibly providing any name.
+    pub all:    bool,
+    /// The names excluded by a `hiding` clause.
+    pub hiding: Vec<&'s str>,
+}
+
+/// An occurrence of an identifier referring to a value or type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Usage<'s> {
+    /// The identifier.
+    pub name:       &'s str,
+    /// The location of the identifier.
+    pub range:      Range<usize>,
+    /// The innermost scope containing the identifier.
+    pub scope:      ScopeId,
+    /// What the identifier refers to.
+    pub resolution: Resolution,
+}
+
+/// What an identifier refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Resolution {
+    /// A name bound in an enclosing scope.
+    Binding(BindingId),
+    /// A name bound by an import. If the name is not imported explicitly, this is the first
+    /// `import all` not hiding the name.
+    Import(ImportId),
+    /// The name is not bound.
+    Unbound,
+}
+
+impl<'s> Analysis<'s> {
+    /// Return the scope with the given ID.
+    pub fn scope(&self, id: ScopeId) -> &Scope {
+        &self.scopes[id.0]
+    }
+
+    /// Return the binding with the given ID.
+    pub fn binding(&self, id: BindingId) -> &Binding<'s> {
+        &self.bindings[id.0]
+    }
+
+    /// Return the import with the given ID.
+    pub fn import(&self, id: ImportId) -> &Import<'s> {
+        &self.imports[id.0]
+    }
+
+    /// Return the identifiers that could not be resolved.
+    pub fn unbound(&self) -> impl Iterator<Item = &Usage<'s>> {
+        self.usages.iter().filter(|usage| usage.resolution == Resolution::Unbound)
+    }
+
+    /// Return the identifiers referring to the given binding.
+    pub fn usages_of(&self, binding: BindingId) -> impl Iterator<Item = &Usage<'s>> {
+        let resolution = Resolution::Binding(binding);
+        self.usages.iter().filter(move |usage| usage.resolution == resolution)
+    }
+
+    /// Return the innermost scope containing the given offset.
+    pub fn scope_at(&self, offset: usize) -> ScopeId {
+        // Sibling scopes don't overlap, so the last scope in pre-order containing the offset is
+        // nested in all the others.
+        let innermost = self.scopes.iter().rposition(|scope| scope.range.contains(&offset));
+        ScopeId(innermost.unwrap_or_default())
+    }
+
+    /// Return the binding of the identifier at the given offset, whether it is the binding site or
+    /// a usage of the binding.
+    pub fn binding_at(&self, offset: usize) -> Option<BindingId> {
+        let binding = self.bindings.iter().position(|binding| binding.range.contains(&offset));
+        binding.map(BindingId).or_else(|| {
+            let usage = self.usages.iter().find(|usage| usage.range.contains(&offset))?;
+            match usage.resolution {
+                Resolution::Binding(binding) => Some(binding),
+                _ => None,
+            }
+        })
+    }
+
+    /// Return the locations of all occurrences of the bound name: its binding site, followed by
+    /// its usages. These are the ranges that must be changed to rename the binding.
+    pub fn occurrences(&self, binding: BindingId) -> Vec<Range<usize>> {
+        let usages = self.usages_of(binding).map(|usage| usage.range.clone());
+        iter::once(self.binding(binding).range.clone()).chain(usages).collect()
+    }
+}
+
+
+
+// ===============
+// === Analyze ===
+// ===============
+
+/// Analyze the scopes of the given module.
+pub fn analyze<'s>(tree: &Tree<'s>) -> Analysis<'s> {
+    let mut analyzer = Analyzer::default();
+    analyzer.enter(ScopeKind::Module, tree_range(tree));
+    match &*tree.variant {
+        Variant::BodyBlock(BodyBlock { statements }) =>
+            analyzer.definitions(statements.iter().filter_map(|line| line.expression.as_ref())),
+        _ => analyzer.definitions(iter::once(tree)),
+    }
+    analyzer.analysis
+}
+
+
+// === Analyzer ===
+
+#[derive(Debug, Default)]
+struct Analyzer<'s> {
+    analysis: Analysis<'s>,
+    /// The current scope.
+    scope:    ScopeId,
+    /// The names visible in each scope.
+    visible:  Vec<HashMap<&'s str, BindingId>>,
+    /// Names bound by imports.
+    imported: HashMap<&'s str, ImportId>,
+}
+
+impl<'s> Analyzer<'s> {
+    /// Create a new scope nested in the current scope, and make it current.
+    fn enter(&mut self, kind: ScopeKind, range: Range<usize>) -> ScopeId {
+        let id = ScopeId(self.analysis.scopes.len());
+        let parent = (id.0 != 0).then_some(self.scope);
+        self.analysis.scopes.push(Scope { kind, parent, range, bindings: default() });
+        self.visible.push(default());
+        mem::replace(&mut self.scope, id)
+    }
+
+    /// Run the given function in a new scope nested in the current scope.
+    fn in_scope(&mut self, kind: ScopeKind, range: Range<usize>, f: impl FnOnce(&mut Self)) {
+        let outer = self.enter(kind, range);
+        f(self);
+        self.scope = outer;
+    }
+
+    fn bind(&mut self, ident: &token::Ident<'s>, kind: BindingKind) {
+        let name = ident.code.repr.0;
+        if is_implicit(name) {
+            return;
+        }
+        let id = BindingId(self.analysis.bindings.len());
+        let range = code_range(&ident.code);
+        self.analysis.bindings.push(Binding { name, kind, range, scope: self.scope });
+        self.analysis.scopes[self.scope.0].bindings.push(id);
+        if kind != BindingKind::Method {
+            self.visible[self.scope.0].insert(name, id);
+        }
+    }
+
+    fn refer(&mut self, ident: &token::Ident<'s>) {
+        let name = ident.code.repr.0;
+        if is_implicit(name) {
+            return;
+        }
+        let resolution = self.resolve(name);
+        let range = code_range(&ident.code);
+        self.analysis.usages.push(Usage { name, range, scope: self.scope, resolution });
+    }
+
+    fn resolve(&self, name: &str) -> Resolution {
+        let mut scope = Some(self.scope);
+        while let Some(id) = scope {
+            if let Some(&binding) = self.visible[id.0].get(name) {
+                return Resolution::Binding(binding);
+            }
+            scope = self.analysis.scopes[id.0].parent;
+        }
+        if let Some(&import) = self.imported.get(name) {
+            return Resolution::Import(import);
+        }
+        let mut imports = self.analysis.imports.iter();
+        let all = imports.position(|import| import.all && !import.hiding.contains(&name));
+        all.map_or(Resolution::Unbound, |import| Resolution::Import(ImportId(import)))
+    }
+}
+
+
+// === Definitions ===
+
+impl<'s> Analyzer<'s> {
+    /// Analyze the statements of a module or type body. The names they define are bound before
+    /// any of the statements are analyzed, so they can be referred to in any order.
+    fn definitions<'a>(&mut self, statements: impl Iterator<Item = &'a Tree<'s>> + Clone)
+    where 's: 'a {
+        for statement in statements.clone() {
+            self.declare(statement);
+        }
+        for statement in statements {
+            self.define(statement);
+        }
+    }
+
+    fn declare(&mut self, tree: &Tree<'s>) {
+        let in_type = self.analysis.scopes[self.scope.0].kind == ScopeKind::Type;
+        let function = if in_type { BindingKind::Method } else { BindingKind::Function };
+        match &*tree.variant {
+            Variant::Documented(Documented { expression, .. })
+            | Variant::Annotated(Annotated { expression, .. })
+            | Variant::AnnotatedBuiltin(AnnotatedBuiltin { expression, .. })
+            | Variant::Private(Private { body: expression, .. }) =>
+                expression.iter().for_each(|expression| self.declare(expression)),
+            Variant::TypeDef(TypeDef { name, .. }) => self.bind(name, BindingKind::Type),
+            Variant::ConstructorDefinition(ConstructorDefinition { constructor, .. }) =>
+                self.bind(constructor, BindingKind::Constructor),
+            Variant::Function(Function { name, .. }) => match &*name.variant {
+                Variant::Ident(Ident { token }) => self.bind(token, function),
+                _ =>
+                    if let Some(name) = method_name(name) {
+                        self.bind(name, BindingKind::Method)
+                    },
+            },
+            Variant::ForeignFunction(ForeignFunction { name, .. }) => self.bind(name, function),
+            Variant::Assignment(Assignment { pattern, .. }) =>
+                if let Variant::Ident(Ident { token }) = &*pattern.variant {
+                    self.bind(token, function);
+                },
+            Variant::Import(import) => self.import(import, tree_range(tree)),
+            _ => {}
+        }
+    }
+
+    fn define(&mut self, tree: &Tree<'s>) {
+        match &*tree.variant {
+            Variant::Documented(Documented { expression, .. })
+            | Variant::AnnotatedBuiltin(AnnotatedBuiltin { expression, .. })
+            | Variant::Private(Private { body: expression, .. }) =>
+                expression.iter().for_each(|expression| self.define(expression)),
+            Variant::Annotated(Annotated { argument, expression, .. }) => {
+                argument.iter().for_each(|argument| self.expression(argument));
+                expression.iter().for_each(|expression| self.define(expression));
+            }
+            Variant::TypeDef(TypeDef { params, body, .. }) =>
+                self.in_scope(ScopeKind::Type, tree_range(tree), |this| {
+                    for param in params {
+                        this.argument(param, BindingKind::TypeParameter);
+                    }
+                    this.definitions(body.iter().filter_map(|line| line.expression.as_ref()));
+                }),
+            Variant::ConstructorDefinition(ConstructorDefinition { arguments, block, .. }) => {
+                let block = block.iter().filter_map(|line| line.argument.as_ref());
+                for argument in arguments.iter().chain(block) {
+                    argument.type_.iter().for_each(|type_| self.type_expression(&type_.type_));
+                    argument
+                        .default
+                        .iter()
+                        .for_each(|default| self.expression(&default.expression));
+                }
+            }
+            Variant::Function(Function { name, args, body, .. }) => {
+                self.qualifier(name);
+                self.function(tree, args, body.as_ref());
+            }
+            Variant::ForeignFunction(ForeignFunction { args, .. }) =>
+                self.function(tree, args, None),
+            Variant::Assignment(Assignment { pattern, expr, .. })
+                if matches!(&*pattern.variant, Variant::Ident(_)) =>
+                self.expression(expr),
+            Variant::Import(_) | Variant::Export(_) => {}
+            _ => self.statement(tree),
+        }
+    }
+
+    fn import(&mut self, import: &crate::syntax::tree::Import<'s>, range: Range<usize>) {
+        let id = ImportId(self.analysis.imports.len());
+        let mut names = vec![];
+        if let Some(alias) = segment_body(&import.as_) {
+            sequence(alias, &mut names);
+        } else if let Some(body) = import.import.body.as_ref() {
+            match import.from.is_some() {
+                true => sequence(body, &mut names),
+                false => names.extend(qualified_name(body)),
+            }
+        }
+        let mut hiding = vec![];
+        segment_body(&import.hiding).iter().for_each(|hidden| sequence(hidden, &mut hiding));
+        for name in &names {
+            self.imported.insert(name.code.repr.0, id);
+        }
+        let names = names.into_iter().map(|name| (name.code.repr.0, code_range(&name.code)));
+        let hiding = hiding.into_iter().map(|name| name.code.repr.0).collect();
+        let all = import.all.is_some();
+        self.analysis.imports.push(Import { range, names: names.collect(), all, hiding });
+    }
+}
+
+
+// === Functions ===
+
+impl<'s> Analyzer<'s> {
+    fn function(
+        &mut self,
+        tree: &Tree<'s>,
+        args: &[ArgumentDefinition<'s>],
+        body: Option<&Tree<'s>>,
+    ) {
+        self.in_scope(ScopeKind::Function, tree_range(tree), |this| {
+            for arg in args {
+                this.argument(arg, BindingKind::Argument);
+            }
+            body.iter().for_each(|body| this.body(body));
+        });
+    }
+
+    /// Analyze an argument definition. Its type and default value are analyzed before the
+    /// argument is bound, so that they can refer to the preceding arguments.
+    fn argument(&mut self, argument: &ArgumentDefinition<'s>, kind: BindingKind) {
+        argument.type_.iter().for_each(|type_| self.type_expression(&type_.type_));
+        argument.default.iter().for_each(|default| self.expression(&default.expression));
+        self.pattern(&argument.pattern, kind);
+    }
+
+    /// Analyze the parameters and body of a lambda, `\x y -> ...` or `x -> ...`.
+    fn lambda(
+        &mut self,
+        range: Range<usize>,
+        parameters: Option<&Tree<'s>>,
+        body: Option<&Tree<'s>>,
+    ) {
+        self.in_scope(ScopeKind::Lambda, range, |this| {
+            let mut parameters = parameters.into_iter().collect_vec();
+            while let Some(Variant::App(App { func, arg })) =
+                parameters.last().copied().map(|p| &*p.variant)
+            {
+                parameters.pop();
+                parameters.extend([arg, func]);
+            }
+            for parameter in parameters.into_iter().rev() {
+                if let Variant::Group(Group { body: Some(body), .. }) = &*parameter.variant
+                    && let Variant::OprApp(OprApp { lhs: Some(lhs), opr: Ok(opr), rhs: Some(rhs) })
+                        = &*body.variant
+                    && opr.properties.is_assignment()
+                {
+                    this.expression(rhs);
+                    this.pattern(lhs, BindingKind::Argument);
+                } else {
+                    this.pattern(parameter, BindingKind::Argument);
+                }
+            }
+            body.iter().for_each(|body| this.body(body));
+        });
+    }
+
+    /// Analyze the body of a function, lambda, or case branch; a block body is part of the
+    /// enclosing scope.
+    fn body(&mut self, tree: &Tree<'s>) {
+        match &*tree.variant {
+            Variant::BodyBlock(BodyBlock { statements }) =>
+                for statement in statements.iter().filter_map(|line| line.expression.as_ref()) {
+                    self.statement(statement);
+                },
+            _ => self.expression(tree),
+        }
+    }
+
+    /// Refer to the type qualifying a method name, such as `Point` in `Point.length`.
+    fn qualifier(&mut self, name: &Tree<'s>) {
+        if let Variant::OprApp(OprApp { lhs: Some(lhs), .. }) = &*name.variant {
+            self.expression(lhs);
+        }
+    }
+}
+
+
+// === Statements and Expressions ===
+
+impl<'s> Analyzer<'s> {
+    /// Analyze a statement of a block. Names bound by the statement are visible in the following
+    /// statements.
+    fn statement(&mut self, tree: &Tree<'s>) {
+        match &*tree.variant {
+            Variant::Documented(Documented { expression, .. })
+            | Variant::AnnotatedBuiltin(AnnotatedBuiltin { expression, .. })
+            | Variant::Private(Private { body: expression, .. }) =>
+                expression.iter().for_each(|expression| self.statement(expression)),
+            Variant::Annotated(Annotated { argument, expression, .. }) => {
+                argument.iter().for_each(|argument| self.expression(argument));
+                expression.iter().for_each(|expression| self.statement(expression));
+            }
+            Variant::Function(Function { name, args, body, .. }) => {
+                match &*name.variant {
+                    Variant::Ident(Ident { token }) => self.bind(token, BindingKind::Function),
+                    _ => self.qualifier(name),
+                }
+                self.function(tree, args, body.as_ref());
+            }
+            Variant::Assignment(Assignment { pattern, expr, .. }) => {
+                self.expression(expr);
+                self.pattern(pattern, BindingKind::Variable);
+            }
+            Variant::TypeSignature(TypeSignature { type_, .. }) => self.type_expression(type_),
+            _ => self.expression(tree),
+        }
+    }
+
+    fn expression(&mut self, tree: &Tree<'s>) {
+        match &*tree.variant {
+            Variant::Ident(Ident { token }) => self.refer(token),
+            Variant::BodyBlock(_) =>
+                self.in_scope(ScopeKind::Block, tree_range(tree), |this| this.body(tree)),
+            Variant::OprApp(OprApp { lhs, opr: Ok(opr), rhs }) if opr.properties.is_dot() => {
+                lhs.iter().for_each(|lhs| self.expression(lhs));
+                if let Some(rhs) = rhs && !matches!(&*rhs.variant, Variant::Ident(_)) {
+                    self.expression(rhs);
+                }
+            }
+            Variant::OprApp(OprApp { lhs, opr: Ok(opr), rhs }) if opr.properties.is_arrow() =>
+                self.lambda(tree_range(tree), lhs.as_ref(), rhs.as_ref()),
+            Variant::Lambda(Lambda { arrow: Some(arrow), .. }) => match &*arrow.variant {
+                Variant::OprApp(OprApp { lhs, opr: Ok(opr), rhs }) if opr.properties.is_arrow() =>
+                    self.lambda(tree_range(tree), lhs.as_ref(), rhs.as_ref()),
+                _ => self.expression(arrow),
+            },
+            Variant::CaseOf(CaseOf { expression, cases, .. }) => {
+                expression.iter().for_each(|expression| self.expression(expression));
+                for case in cases.iter().filter_map(|line| line.case.as_ref()) {
+                    let trees = case.pattern.iter().chain(&case.expression);
+                    let start = trees.clone().map(|tree| tree_range(tree).start).min();
+                    let end = trees.map(|tree| tree_range(tree).end).max();
+                    let range = start.unwrap_or_default()..end.unwrap_or_default();
+                    self.in_scope(ScopeKind::Case, range, |this| {
+                        case.pattern.iter().for_each(|p| this.pattern(p, BindingKind::Variable));
+                        case.expression.iter().for_each(|expression| this.body(expression));
+                    });
+                }
+            }
+            Variant::TypeAnnotated(TypeAnnotated { expression, type_, .. }) => {
+                self.expression(expression);
+                self.type_expression(type_);
+            }
+            Variant::Function(_)
+            | Variant::Assignment(_)
+            | Variant::TypeSignature(_)
+            | Variant::Documented(_)
+            | Variant::Annotated(_)
+            | Variant::AnnotatedBuiltin(_)
+            | Variant::Private(_) => self.statement(tree),
+            Variant::TypeDef(_)
+            | Variant::ForeignFunction(_)
+            | Variant::Import(_)
+            | Variant::Export(_) => {}
+            _ => tree.visit_items(|item| {
+                if let item::Ref::Tree(tree) = item {
+                    self.expression(tree);
+                }
+            }),
+        }
+    }
+
+    /// Analyze a pattern, binding the lowercase identifiers in it. Uppercase identifiers refer to
+    /// constructors or types.
+    fn pattern(&mut self, tree: &Tree<'s>, kind: BindingKind) {
+        match &*tree.variant {
+            Variant::Ident(Ident { token }) if token.variant.is_type => self.refer(token),
+            Variant::Ident(Ident { token }) => self.bind(token, kind),
+            Variant::App(App { func, arg }) => {
+                self.constructor(func, kind);
+                self.pattern(arg, kind);
+            }
+            Variant::TypeAnnotated(TypeAnnotated { expression, type_, .. }) => {
+                self.type_expression(type_);
+                self.pattern(expression, kind);
+            }
+            Variant::OprApp(OprApp { opr: Ok(opr), .. }) if opr.properties.is_dot() =>
+                self.expression(tree),
+            _ => tree.visit_items(|item| {
+                if let item::Ref::Tree(tree) = item {
+                    self.pattern(tree, kind);
+                }
+            }),
+        }
+    }
+
+    /// Analyze the constructor of a pattern such as `Cons head tail`, and its leading fields.
+    fn constructor(&mut self, tree: &Tree<'s>, kind: BindingKind) {
+        match &*tree.variant {
+            Variant::App(App { func, arg }) => {
+                self.constructor(func, kind);
+                self.pattern(arg, kind);
+            }
+            _ => self.expression(tree),
+        }
+    }
+
+    /// Analyze a type. Lowercase identifiers in a type are type variables, and are not resolved.
+    fn type_expression(&mut self, tree: &Tree<'s>) {
+        match &*tree.variant {
+            Variant::Ident(Ident { token }) =>
+                if token.variant.is_type {
+                    self.refer(token);
+                },
+            Variant::OprApp(OprApp { lhs: Some(lhs), opr: Ok(opr), .. })
+                if opr.properties.is_dot() =>
+                self.type_expression(lhs),
+            _ => tree.visit_items(|item| {
+                if let item::Ref::Tree(tree) = item {
+                    self.type_expression(tree);
+                }
+            }),
+        }
+    }
+}
+
+
+// === Helpers ===
+
+/// Return whether the name is bound implicitly; such names are not recorded.
+fn is_implicit(name: &str) -> bool {
+    name == "self" || name == "Self"
+}
+
+fn code_range(code: &Code) -> Range<usize> {
+    let range = code.range();
+    range.start.utf8 as usize..range.end.utf8 as usize
+}
+
+fn tree_range(tree: &Tree) -> Range<usize> {
+    let range = tree.span.range();
+    range.start.utf8 as usize..range.end.utf8 as usize
+}
+
+fn segment_body<'a, 's>(segment: &'a Option<MultiSegmentAppSegment<'s>>) -> Option<&'a Tree<'s>> {
+    segment.as_ref().and_then(|segment| segment.body.as_ref())
+}
+
+/// Return the last identifier of a qualified method name, such as `length` in `Point.length`.
+fn method_name<'a, 's>(name: &'a Tree<'s>) -> Option<&'a token::Ident<'s>> {
+    match &*name.variant {
+        Variant::OprApp(OprApp { rhs: Some(rhs), opr: Ok(opr), .. }) if opr.properties.is_dot() =>
+            match &*rhs.variant {
+                Variant::Ident(Ident { token }) => Some(token),
+                _ => None,
+            },
+        _ => None,
+    }
+}
+
+/// Return the last identifier of a qualified name, such as `Table` in `Standard.Table.Table`.
+fn qualified_name<'a, 's>(name: &'a Tree<'s>) -> Option<&'a token::Ident<'s>> {
+    match &*name.variant {
+        Variant::Ident(Ident { token }) => Some(token),
+        _ => method_name(name),
+    }
+}
+
+/// Collect the names of a comma-separated list, such as `a, b, c`.
+fn sequence<'a, 's>(tree: &'a Tree<'s>, names: &mut Vec<&'a token::Ident<'s>>) {
+    match &*tree.variant {
+        Variant::OprApp(OprApp { lhs, opr: Ok(opr), rhs }) if opr.properties.is_sequence() =>
+            for operand in lhs.iter().chain(rhs) {
+                sequence(operand, names);
+            },
+        _ => names.extend(qualified_name(tree)),
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Return the name and kind of each binding, and the name of each usage with the name its
+    /// resolution refers to: the name of the binding, `import` for an import, or `?` if unbound.
+    fn analyze_code(code: &str) -> (Vec<(&str, BindingKind)>, Vec<(&str, &str)>) {
+        let tree = crate::Parser::new().run(code);
+        let analysis = analyze(&tree);
+        let bindings = analysis.bindings.iter().map(|binding| (binding.name, binding.kind));
+        let usages = analysis.usages.iter().map(|usage| {
+            let resolution = match usage.resolution {
+                Resolution::Binding(binding) => {
+                    let binding = analysis.binding(binding);
+                    &code[binding.range.clone()]
+                }
+                Resolution::Import(_) => "import",
+                Resolution::Unbound => "?",
+            };
+            (&code[usage.range.clone()], resolution)
+        });
+        (bindings.collect(), usages.collect())
+    }
+
+    #[test]
+    fn test_functions() {
+        use BindingKind::*;
+        let code = [
+            "main =",
+            "    f x (y = x) = x + y + z",
+            "    g = a -> b -> f a b",
+            "    h = \\c -> g c",
+            "    k = h 1",
+            "    k",
+        ];
+        let code = code.join("\n");
+        let (bindings, usages) = analyze_code(&code);
+        let expected_bindings = [
+            ("main", Function),
+            ("f", Function),
+            ("x", Argument),
+            ("y", Argument),
+            ("a", Argument),
+            ("b", Argument),
+            ("g", Variable),
+            ("c", Argument),
+            ("h", Variable),
+            ("k", Variable),
+        ];
+        assert_eq!(bindings, expected_bindings);
+        let expected_usages = [
+            ("x", "x"),
+            ("x", "x"),
+            ("y", "y"),
+            ("z", "?"),
+            ("f", "f"),
+            ("a", "a"),
+            ("b", "b"),
+            ("g", "g"),
+            ("c", "c"),
+            ("h", "h"),
+            ("k", "k"),
+        ];
+        assert_eq!(usages, expected_usages);
+    }
+
+    #[test]
+    fn test_blocks_are_sequential() {
+        let code = ["main =", "    x = y", "    y = 1", "    x.foo y"].join("\n");
+        let (_, usages) = analyze_code(&code);
+        assert_eq!(usages, [("y", "?"), ("x", "x"), ("y", "y")]);
+    }
+
+    #[test]
+    fn test_case() {
+        let code = [
+            "sum list = case list of",
+            "    Cons head tail -> head + sum tail",
+            "    Nil -> 0",
+            "    x : Integer -> x",
+            "    _ -> head",
+        ];
+        let code = code.join("\n");
+        let (bindings, usages) = analyze_code(&code);
+        let names = bindings.iter().map(|(name, _)| *name).collect_vec();
+        assert_eq!(names, ["sum", "list", "head", "tail", "x"]);
+        let expected = [
+            ("list", "list"),
+            ("Cons", "?"),
+            ("head", "head"),
+            ("sum", "sum"),
+            ("tail", "tail"),
+            ("Nil", "?"),
+            ("Integer", "?"),
+            ("x", "x"),
+            ("head", "?"),
+        ];
+        assert_eq!(usages, expected);
+    }
+
+    #[test]
+    fn test_types() {
+        use BindingKind::*;
+        let code = [
+            "type Point a",
+            "    Value (x : a) (y : Number = origin)",
+            "    length self = Value self.x",
+            "    other self = length self",
+            "origin = 0",
+            "Point.extension self = Point.Value 1",
+        ];
+        let code = code.join("\n");
+        let (bindings, usages) = analyze_code(&code);
+        let expected_bindings = [
+            ("Point", Type),
+            ("origin", Function),
+            ("extension", Method),
+            ("a", TypeParameter),
+            ("Value", Constructor),
+            ("length", Method),
+            ("other", Method),
+        ];
+        assert_eq!(bindings, expected_bindings);
+        let expected_usages = [
+            ("Number", "?"),
+            ("origin", "origin"),
+            ("Value", "Value"),
+            ("length", "?"),
+            ("Point", "Point"),
+            ("Point", "Point"),
+        ];
+        assert_eq!(usages, expected_usages);
+    }
+
+    #[test]
+    fn test_imports() {
+        let code = [
+            "import Standard.Table.Data.Table as T",
+            "from Standard.Base.Data import Vector, Map",
+            "polyglot java import java.lang.Integer",
+            "main = [T, Vector, Map, Integer, Text]",
+        ];
+        let code = code.join("\n");
+        let (_, usages) = analyze_code(&code);
+        let expected = [
+            ("T", "import"),
+            ("Vector", "import"),
+            ("Map", "import"),
+            ("Integer", "import"),
+            ("Text", "?"),
+        ];
+        assert_eq!(usages, expected);
+        let tree = crate::Parser::new().run(&code);
+        let analysis = analyze(&tree);
+        let names = analysis.imports.iter().flat_map(|import| &import.names);
+        let names = names.map(|(name, range)| (*name, &code[range.clone()])).collect_vec();
+        assert_eq!(names, [
+            ("T", "T"),
+            ("Vector", "Vector"),
+            ("Map", "Map"),
+            ("Integer", "Integer")
+        ]);
+        let code = "from Standard.Base import all hiding Text\nmain = Text + Vector";
+        let (_, usages) = analyze_code(code);
+        assert_eq!(usages, [("Text", "?"), ("Vector", "import")]);
+    }
+
+    #[test]
+    fn test_occurrences() {
+        let code = "main =\n    x = 1\n    y = x + x\n    x";
+        let tree = crate::Parser::new().run(code);
+        let analysis = analyze(&tree);
+        let at = code.rfind('x').unwrap();
+        let binding = analysis.binding_at(at).unwrap();
+        let occurrences = analysis.occurrences(binding).into_iter().map(|range| range.start);
+        assert_eq!(occurrences.collect_vec(), [11, 25, 29, 35]);
+        let scope = analysis.scope(analysis.scope_at(at));
+        assert_eq!(scope.kind, ScopeKind::Function);
+        assert_eq!(scope.parent, Some(ScopeId(0)));
+        assert!(analysis.unbound().next().is_none());
+    }
+
+    #[test]
+    fn test_empty_definitions() {
+        let (bindings, usages) = analyze_code("## Documentation only.\n");
+        assert!(bindings.is_empty() && usages.is_empty());
+        let (bindings, _) = analyze_code("@Builtin_Type\ntype T\nprivate\n");
+        assert_eq!(bindings, [("T", BindingKind::Type)]);
+    }
+}
@@ -81,23 +81,6 @@ where D: serde::Deserializer<'de> {
 
 
 
-// =============
-// === Error ===
-// =============
-
-/// Deserialization type for `crate::syntax::tree::Error`.
-#[derive(Deserialize, Debug, Clone)]
-pub(crate) struct Error(String);
-
-impl From<Error> for crate::syntax::tree::Error {
-    fn from(error: Error) -> Self {
-        let message = error.0.into();
-        crate::syntax::tree::Error { message }
-    }
-}
-
-
-
 // ================
 // === Visitors ===
 // ================
@@ -132,24 +115,105 @@ impl<'de> serde::de::Visitor<'de> for DeserializeU32 {
     }
 }
 
+struct DeserializeOptionalInt;
+
+impl<'de> serde::de::Visitor<'de> for DeserializeOptionalInt {
+    type Value = Option<u32>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "A non-negative 32-bit integer, or {OPTIONAL_INT_NONE}.")
+    }
+
+    fn visit_i64<E>(self, i: i64) -> Result<Self::Value, E>
+    where E: serde::de::Error {
+        match i {
+            i if i == OPTIONAL_INT_NONE as i64 => Ok(None),
+            0..=0x7FFF_FFFF => Ok(Some(i as u32)),
+            _ => Err(E::invalid_value(serde::de::Unexpected::Signed(i), &self)),
+        }
+    }
+
+    fn visit_u64<E>(self, i: u64) -> Result<Self::Value, E>
+    where E: serde::de::Error {
+        match i {
+            0..=0x7FFF_FFFF => Ok(Some(i as u32)),
+            _ => Err(E::invalid_value(serde::de::Unexpected::Unsigned(i), &self)),
+        }
+    }
+}
+
 
 
 // ========================================
 // === General purpose value transforms ===
 // ========================================
 
+/// The value representing `None` in the serialized form of an optional integer.
+const OPTIONAL_INT_NONE: i32 = -1;
+
+/// Serialize an optional integer as an `i32`, which is the type it is reflected as. `None` is
+/// represented by [`OPTIONAL_INT_NONE`]; values that don't fit in an `i32` are rejected. In the
+/// binary format, this is the same representation as the `u32::MAX` sentinel.
 pub(crate) fn serialize_optional_int<S>(x: &Option<u32>, s: S) -> Result<S::Ok, S::Error>
 where S: serde::Serializer {
-    s.serialize_u32(x.unwrap_or(0xFFFF_FFFF))
+    use serde::ser::Error;
+    let value = match *x {
+        Some(x) => i32::try_from(x)
+            .map_err(|_| S::Error::custom(format!("Integer out of range of `i32`: {x}.")))?,
+        None => OPTIONAL_INT_NONE,
+    };
+    s.serialize_i32(value)
 }
 
 pub(crate) fn deserialize_optional_int<'c, 'de, D>(
     deserializer: D,
 ) -> Result<Option<u32>, D::Error>
 where D: serde::Deserializer<'de> {
-    let value = deserializer.deserialize_u32(DeserializeU32)?;
-    Ok(match value {
-        0xFFFF_FFFF => None,
-        x => Some(x),
-    })
+    deserializer.deserialize_i32(DeserializeOptionalInt)
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+    struct OptionalInt(
+        #[serde(serialize_with = "super::serialize_optional_int")]
+        #[serde(deserialize_with = "super::deserialize_optional_int")]
+        Option<u32>,
+    );
+
+    #[test]
+    fn optional_int_json() {
+        for (value, json) in [(None, "-1"), (Some(0), "0"), (Some(0x7FFF_FFFF), "2147483647")] {
+            assert_eq!(serde_json::to_string(&OptionalInt(value)).unwrap(), json);
+            assert_eq!(serde_json::from_str::<OptionalInt>(json).unwrap(), OptionalInt(value));
+        }
+        assert!(serde_json::from_str::<OptionalInt>("-2").is_err());
+        assert!(serde_json::from_str::<OptionalInt>("4294967295").is_err());
+    }
+
+    /// Values out of range of the `i32` the integer is reflected as can't be serialized. Casting
+    /// them would produce negative numbers, and `u32::MAX` would be read back as `None`.
+    #[test]
+    fn optional_int_out_of_range() {
+        for value in [0x8000_0000, u32::MAX] {
+            assert!(serde_json::to_string(&OptionalInt(Some(value))).is_err());
+            assert!(bincode::serialize(&OptionalInt(Some(value))).is_err());
+        }
+    }
+
+    #[test]
+    fn optional_int_binary() {
+        let serialized = bincode::serialize(&OptionalInt(None)).unwrap();
+        assert_eq!(serialized, u32::MAX.to_le_bytes());
+        let deserialized: OptionalInt = bincode::deserialize(&serialized).unwrap();
+        assert_eq!(deserialized, OptionalInt(None));
+    }
 }
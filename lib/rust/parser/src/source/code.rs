@@ -2,6 +2,8 @@
 
 use crate::prelude::*;
 
+use enso_parser_syntax_tree_visitor::Encode;
+
 
 
 // ============
@@ -21,6 +23,7 @@ pub struct StrRef<'s>(pub &'s str);
     Default,
     Eq,
     PartialEq,
+    Encode,
     Serialize,
     Reflect,
     Deserialize,
@@ -55,13 +58,30 @@ impl Add<Length> for Location {
     }
 }
 
+impl Sub for Location {
+    type Output = Length;
+
+    /// Return the length of the code between the given location and this one, which must not
+    /// precede it.
+    fn sub(self, rhs: Self) -> Self::Output {
+        let newlines = self.line - rhs.line;
+        Length {
+            utf8: self.utf8 - rhs.utf8,
+            utf16: self.utf16 - rhs.utf16,
+            newlines,
+            line_chars16: if newlines == 0 { self.col16 - rhs.col16 } else { self.col16 },
+        }
+    }
+}
+
 /// A code representation.
-#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Reflect, Deserialize, Deref)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Encode, Serialize, Reflect, Deserialize, Deref)]
 #[allow(missing_docs)]
 pub struct Code<'s> {
     /// The borrowed string data.
     #[serde(serialize_with = "crate::serialization::serialize_cow")]
     #[serde(deserialize_with = "crate::serialization::deserialize_cow")]
+    #[encode(with = "crate::format::encode::str_ref")]
     #[reflect(as = "crate::serialization::Code", flatten, hide)]
     #[deref]
     pub repr: StrRef<'s>,
@@ -165,6 +185,20 @@ impl<'s> Code<'s> {
     pub fn without_location(&self) -> Self {
         Self { repr: self.repr.clone(), start: default(), len: self.len }
     }
+
+    /// Move this value to the given location in the given source code, which must contain the same
+    /// text at that location. If it doesn't, the value is left unchanged and an error is returned.
+    pub fn relocate(&mut self, start: Location, source: &'s str) -> Result<(), RelocationError> {
+        let begin = usize::try_from(start.utf8).map_err(|_| RelocationError { start })?;
+        let repr = begin
+            .checked_add(self.repr.len())
+            .and_then(|end| source.get(begin..end))
+            .filter(|repr| *repr == self.repr.0)
+            .ok_or(RelocationError { start })?;
+        self.repr = StrRef(repr);
+        self.start = start;
+        Ok(())
+    }
 }
 
 impl<'s> Display for Code<'s> {
@@ -235,10 +269,29 @@ impl<'s> AddAssign<&Code<'s>> for Code<'s> {
 }
 
 
+// === Relocation error ===
+
+/// Error returned by [`Code::relocate`] when the source code doesn't contain the text of the code
+/// at the requested location.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct RelocationError {
+    /// The location the code was to be moved to.
+    pub start: Location,
+}
+
+impl Display for RelocationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The source doesn't contain the code at byte offset {}.", self.start.utf8)
+    }
+}
+
+impl std::error::Error for RelocationError {}
+
+
 // === Code length ===
 
 /// The length of a [`Code`] object.
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Reflect, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Encode, Serialize, Reflect, Deserialize)]
 pub struct Length {
     /// An offset, in UTF-8 code units (bytes).
     #[reflect(skip)]
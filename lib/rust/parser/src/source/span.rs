@@ -7,6 +7,7 @@ use crate::syntax::*;
 
 use crate::lexer;
 use crate::source::code::Location;
+use enso_parser_syntax_tree_visitor::Encode;
 
 
 /// Common traits.
@@ -24,7 +25,7 @@ pub mod traits {
 /// tab character has value of 4. For other space-like character sizes, refer to the lexer
 /// implementation.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
-#[derive(From, Into, Add, AddAssign, Sub, Reflect, Serialize, Deserialize)]
+#[derive(From, Into, Add, AddAssign, Sub, Encode, Reflect, Serialize, Deserialize)]
 #[allow(missing_docs)]
 #[reflect(transparent)]
 pub struct VisibleOffset {
@@ -59,7 +60,7 @@ impl From<&str> for VisibleOffset {
 
 /// Offset information. In most cases it is used to express the left-hand-side whitespace offset
 /// for tokens and AST nodes.
-#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Reflect, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Encode, Serialize, Reflect, Deserialize)]
 #[allow(missing_docs)]
 pub struct Offset<'s> {
     #[reflect(hide)]
@@ -148,7 +149,7 @@ impl<'s> AddAssign<&Offset<'s>> for Offset<'s> {
 /// element. This is done in order to not duplicate the data. For example, some AST nodes contain a
 /// lot of tokens. They need to remember their span, but they do not need to remember their code,
 /// because it is already stored in the tokens.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Reflect, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Encode, Serialize, Reflect, Deserialize)]
 #[allow(missing_docs)]
 pub struct Span<'s> {
     #[reflect(hide, flatten)]
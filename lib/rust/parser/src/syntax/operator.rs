@@ -2,6 +2,7 @@
 
 use crate::prelude::*;
 
+use crate::diagnostic::ErrorKind;
 use crate::syntax;
 use crate::syntax::token;
 use crate::syntax::token::Token;
@@ -139,6 +140,8 @@ impl<'s> ExpressionBuilder<'s> {
             if let Some(Operand { value: syntax::Tree { variant: box
                     syntax::tree::Variant::TextLiteral(ref mut lhs), span: lhs_span }, .. }) = self.output.last_mut()
                     && !lhs.closed
+                    && matches!(&*operand.value.variant, syntax::tree::Variant::TextLiteral(
+                        syntax::tree::TextLiteral { open: None, .. }))
                     && let box syntax::tree::Variant::TextLiteral(mut rhs) = operand.value.variant {
                 syntax::tree::join_text_literals(lhs, &mut rhs, lhs_span, operand.value.span);
                 if let syntax::tree::TextLiteral { open: Some(open), newline: None, elements, closed: true, close: None } = lhs
@@ -164,6 +167,7 @@ impl<'s> ExpressionBuilder<'s> {
         let arity = Arity::Binary {
             tokens:                  default(),
             lhs_section_termination: default(),
+            missing_lhs:             default(),
         };
         self.push_operator(precedence, associativity, arity);
     }
@@ -210,7 +214,10 @@ impl<'s> ExpressionBuilder<'s> {
             self.application();
             if self.nospace {
                 if let Unary::Simple(token) = arity {
-                    let error = "Space required between term and unary-operator expression.".into();
+                    let error = syntax::tree::Error::new(
+                        ErrorKind::UnspacedUnaryOperator,
+                        "Space required between term and unary-operator expression.",
+                    );
                     arity = Unary::Invalid { token, error };
                 }
             }
@@ -254,7 +261,8 @@ impl<'s> ExpressionBuilder<'s> {
             tokens.push(opr);
             return;
         }
-        self.push_operator(prec, assoc, Arity::binary(opr));
+        let missing_lhs = self.prev_type != Some(ItemType::Ast);
+        self.push_operator(prec, assoc, Arity::binary(opr, missing_lhs));
     }
 
     /// Add an operator to the stack; [`reduce`] the stack first, as appropriate for the specified
@@ -268,9 +276,12 @@ impl<'s> ExpressionBuilder<'s> {
         let opr = Operator { precedence, associativity, opr };
         // When a unary operator follows another operator, we defer reducing the stack because a
         // unary operator's affinity for its operand is stronger than any operator precedence.
+        // Similarly, a binary operator without a LHS is the start of the operand of the preceding
+        // operator.
         let defer_reducing_stack = match (&self.prev_type, &opr.opr) {
             (Some(ItemType::Opr), Arity::Unary(Unary::Simple(_))) if self.nospace => true,
             (Some(ItemType::Opr), Arity::Unary(Unary::Fragment { .. })) => true,
+            (Some(ItemType::Opr), Arity::Binary { missing_lhs: true, .. }) => true,
             _ => false,
         };
         if !defer_reducing_stack {
@@ -296,16 +307,17 @@ impl<'s> ExpressionBuilder<'s> {
             let ast = match opr.opr {
                 Arity::Unary(Unary::Simple(opr)) =>
                     Operand::new(rhs_).map(|item| syntax::tree::apply_unary_operator(opr, item)),
-                Arity::Unary(Unary::Invalid { token, error }) => Operand::from(rhs_)
-                    .map(|item| syntax::tree::apply_unary_operator(token, item).with_error(error)),
+                Arity::Unary(Unary::Invalid { token, error }) => Operand::from(rhs_).map(|item| {
+                    syntax::Tree::invalid(error, syntax::tree::apply_unary_operator(token, item))
+                }),
                 Arity::Unary(Unary::Fragment { mut fragment }) => {
                     if let Some(rhs_) = rhs_ {
                         fragment.operand(rhs_);
                     }
                     fragment.finish().unwrap()
                 }
-                Arity::Binary { tokens, lhs_section_termination } => {
-                    let lhs = self.output.pop();
+                Arity::Binary { tokens, lhs_section_termination, missing_lhs } => {
+                    let lhs = if missing_lhs { None } else { self.output.pop() };
                     if let Some(lhs_termination) = lhs_section_termination {
                         let lhs = match lhs_termination {
                             SectionTermination::Reify => lhs.map(syntax::Tree::from),
@@ -448,14 +460,15 @@ enum Arity<'s> {
     Binary {
         tokens:                  Vec<token::Operator<'s>>,
         lhs_section_termination: Option<SectionTermination>,
+        missing_lhs:             bool,
     },
 }
 
 impl<'s> Arity<'s> {
-    fn binary(tok: token::Operator<'s>) -> Self {
+    fn binary(tok: token::Operator<'s>, missing_lhs: bool) -> Self {
         let lhs_section_termination = tok.properties.lhs_section_termination();
         let tokens = vec![tok];
-        Self::Binary { tokens, lhs_section_termination }
+        Self::Binary { tokens, lhs_section_termination, missing_lhs }
     }
 
     fn unary(tok: token::Operator<'s>) -> Self {
@@ -466,7 +479,7 @@ impl<'s> Arity<'s> {
 #[derive(Debug, PartialEq, Eq)]
 enum Unary<'s> {
     Simple(token::Operator<'s>),
-    Invalid { token: token::Operator<'s>, error: Cow<'static, str> },
+    Invalid { token: token::Operator<'s>, error: syntax::tree::Error },
     Fragment { fragment: ExpressionBuilder<'s> },
 }
 
@@ -96,6 +96,7 @@
 use crate::prelude::*;
 use crate::source::*;
 
+use enso_parser_syntax_tree_visitor::Encode;
 use enso_shapely_macros::tagged_enum;
 
 
@@ -244,7 +245,7 @@ impl<'s, 'a, T: Debug> Debug for Ref<'s, 'a, T> {
 macro_rules! with_token_definition { ($f:ident ($($args:tt)*)) => { $f! { $($args)*
     /// Elements that can be found in the source code.
     #[tagged_enum]
-    #[derive(Clone, Copy, PartialEq, Eq, Serialize, Reflect, Deserialize)]
+    #[derive(Clone, Copy, PartialEq, Eq, Encode, Serialize, Reflect, Deserialize)]
     #[allow(missing_docs)]
     #[tagged_enum(apply_attributes_to = "variants")]
     #[reflect(inline)]
@@ -286,6 +287,7 @@ macro_rules! with_token_definition { ($f:ident ($($args:tt)*)) => { $f! { $($arg
         TextEscape {
             #[serde(serialize_with = "crate::serialization::serialize_optional_char")]
             #[serde(deserialize_with = "crate::serialization::deserialize_optional_char")]
+            #[encode(with = "crate::format::encode::optional_char")]
             #[reflect(as = "char")]
             pub value: Option<char>,
         },
@@ -574,7 +576,7 @@ pub enum Associativity {
 // === Numbers ===
 
 /// Alternate numeric bases (decimal is the default).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Reflect, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Serialize, Reflect, Deserialize)]
 pub enum Base {
     /// Base 2.
     Binary,
@@ -4,8 +4,10 @@ use crate::prelude::*;
 use crate::source::*;
 use crate::syntax::*;
 
+use crate::diagnostic::ErrorKind;
 use crate::span_builder;
 
+use enso_parser_syntax_tree_visitor::Encode;
 use enso_parser_syntax_tree_visitor::Visitor;
 use enso_shapely_macros::tagged_enum;
 
@@ -15,6 +17,8 @@ use enso_shapely_macros::tagged_enum;
 // ==============
 
 pub mod block;
+pub mod cursor;
+pub mod diff;
 
 
 
@@ -62,7 +66,7 @@ impl<'s> Default for Tree<'s> {
 macro_rules! with_ast_definition { ($f:ident ($($args:tt)*)) => { $f! { $($args)*
     /// [`Tree`] variants definition. See its docs to learn more.
     #[tagged_enum]
-    #[derive(Clone, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+    #[derive(Clone, Eq, PartialEq, Visitor, Encode, Serialize, Reflect, Deserialize)]
     #[allow(clippy::large_enum_variant)] // Inefficient. Will be fixed in #182878443.
     #[tagged_enum(apply_attributes_to = "variants")]
     #[reflect(inline)]
@@ -115,6 +119,7 @@ macro_rules! with_ast_definition { ($f:ident ($($args:tt)*)) => { $f! { $($args)
             pub token: token::Wildcard<'s>,
             #[serde(serialize_with = "crate::serialization::serialize_optional_int")]
             #[serde(deserialize_with = "crate::serialization::deserialize_optional_int")]
+            #[encode(with = "crate::format::encode::optional_int")]
             #[reflect(as = "i32")]
             pub de_bruijn_index: Option<u32>,
         },
@@ -382,27 +387,25 @@ with_ast_definition!(generate_ast_definition());
 // === Invalid ===
 
 /// Error of parsing attached to an [`Tree`] node.
-#[derive(Clone, Debug, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Visitor, Encode, Serialize, Reflect, Deserialize)]
 #[allow(missing_docs)]
-#[reflect(transparent)]
-#[serde(from = "crate::serialization::Error")]
 pub struct Error {
-    #[serde(skip_deserializing)]
     pub message: Cow<'static, str>,
+    pub kind:    ErrorKind,
 }
 
 impl Error {
     /// Constructor.
-    pub fn new(message: impl Into<Cow<'static, str>>) -> Self {
+    pub fn new(kind: ErrorKind, message: impl Into<Cow<'static, str>>) -> Self {
         let message = message.into();
-        Self { message }
+        Self { message, kind }
     }
 }
 
 impl<'s> Tree<'s> {
     /// Constructor.
-    pub fn with_error(self, message: impl Into<Cow<'static, str>>) -> Self {
-        Tree::invalid(Error::new(message), self)
+    pub fn with_error(self, kind: ErrorKind, message: impl Into<Cow<'static, str>>) -> Self {
+        Tree::invalid(Error::new(kind, message), self)
     }
 }
 
@@ -416,7 +419,7 @@ impl<'s> span::Builder<'s> for Error {
 // === Argument blocks ===
 
 /// An argument specification on its own line.
-#[derive(Clone, Debug, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Visitor, Encode, Serialize, Reflect, Deserialize)]
 pub struct ArgumentDefinitionLine<'s> {
     /// The token beginning the line.
     pub newline:  token::Newline<'s>,
@@ -434,7 +437,7 @@ impl<'s> span::Builder<'s> for ArgumentDefinitionLine<'s> {
 // === Text literals ===
 
 /// A component of a text literal, within the quotation marks.
-#[derive(Clone, Debug, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Visitor, Encode, Serialize, Reflect, Deserialize)]
 pub enum TextElement<'s> {
     /// The text content of the literal. If it is multiline, the offset information may contain
     /// part of the content, after trimming appropriately.
@@ -480,7 +483,7 @@ impl<'s> span::Builder<'s> for TextElement<'s> {
 // === Documentation ===
 
 /// A documentation comment.
-#[derive(Debug, Clone, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Visitor, Encode, Serialize, Reflect, Deserialize)]
 pub struct DocComment<'s> {
     /// The comment-initiating token.
     pub open:     token::TextStart<'s>,
@@ -521,7 +524,7 @@ impl<'s> span::Builder<'s> for DocComment<'s> {
 
 // === Number literals ===
 
-#[derive(Clone, Debug, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Visitor, Encode, Serialize, Reflect, Deserialize)]
 #[allow(missing_docs)]
 pub struct FractionalDigits<'s> {
     /// The dot operator.
@@ -540,7 +543,7 @@ impl<'s> span::Builder<'s> for FractionalDigits<'s> {
 // === Functions ===
 
 /// A function argument definition.
-#[derive(Clone, Debug, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Visitor, Encode, Serialize, Reflect, Deserialize)]
 pub struct ArgumentDefinition<'s> {
     /// Opening parenthesis (outer).
     pub open:       Option<token::OpenSymbol<'s>>,
@@ -575,7 +578,7 @@ impl<'s> span::Builder<'s> for ArgumentDefinition<'s> {
 }
 
 /// A default value specification in a function argument definition.
-#[derive(Clone, Debug, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Visitor, Encode, Serialize, Reflect, Deserialize)]
 pub struct ArgumentDefault<'s> {
     /// The `=` token.
     pub equals:     token::Operator<'s>,
@@ -590,7 +593,7 @@ impl<'s> span::Builder<'s> for ArgumentDefault<'s> {
 }
 
 /// A type ascribed to an argument definition.
-#[derive(Clone, Debug, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Visitor, Encode, Serialize, Reflect, Deserialize)]
 pub struct ArgumentType<'s> {
     /// The `:` token.
     pub operator: token::Operator<'s>,
@@ -609,7 +612,7 @@ impl<'s> span::Builder<'s> for ArgumentType<'s> {
 // === CaseOf ===
 
 /// A line that may contain a case-expression in a case-of expression.
-#[derive(Clone, Debug, Default, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Visitor, Encode, Serialize, Reflect, Deserialize)]
 pub struct CaseLine<'s> {
     /// The token beginning the line. This will always be present, unless the first case-expression
     /// is on the same line as the initial case-of.
@@ -636,7 +639,7 @@ impl<'s> span::Builder<'s> for CaseLine<'s> {
 }
 
 /// A case-expression in a case-of expression.
-#[derive(Clone, Debug, Default, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Visitor, Encode, Serialize, Reflect, Deserialize)]
 pub struct Case<'s> {
     /// Documentation, if present.
     pub documentation: Option<DocComment<'s>>,
@@ -675,7 +678,7 @@ impl<'s> span::Builder<'s> for Case<'s> {
 pub type OperatorOrError<'s> = Result<token::Operator<'s>, MultipleOperatorError<'s>>;
 
 /// Error indicating multiple operators found next to each other, like `a + * b`.
-#[derive(Clone, Debug, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Visitor, Encode, Serialize, Reflect, Deserialize)]
 #[allow(missing_docs)]
 pub struct MultipleOperatorError<'s> {
     pub operators: NonEmptyVec<token::Operator<'s>>,
@@ -714,7 +717,7 @@ impl<'s> NonEmptyOperatorSequence<'s> for OperatorOrError<'s> {
 // === MultiSegmentApp ===
 
 /// A segment of [`MultiSegmentApp`], like `if cond` in the `if cond then ok else fail` expression.
-#[derive(Clone, Debug, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Visitor, Encode, Serialize, Reflect, Deserialize)]
 #[allow(missing_docs)]
 pub struct MultiSegmentAppSegment<'s> {
     pub header: Token<'s>,
@@ -731,7 +734,7 @@ impl<'s> span::Builder<'s> for MultiSegmentAppSegment<'s> {
 // === Array and Tuple ===
 
 /// A node following an operator.
-#[derive(Clone, Debug, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+#[derive(Clone, Debug, Eq, PartialEq, Visitor, Encode, Serialize, Reflect, Deserialize)]
 pub struct OperatorDelimitedTree<'s> {
     /// The delimiting operator.
     pub operator: token::Operator<'s>,
@@ -773,6 +776,15 @@ pub fn apply<'s>(mut func: Tree<'s>, mut arg: Tree<'s>) -> Tree<'s> {
             *rhs = block::body_from_lines(mem::take(arguments)).into();
             func
         }
+        (Variant::Invalid(Invalid { error, ast }),
+                Variant::ArgumentBlockApplication(ArgumentBlockApplication { lhs: None, arguments }))
+        if is_unclosed_delimiter(error, ast) => {
+            // The block is the content of the unclosed group; its lines are statements.
+            if let Some(first) = arguments.first_mut() {
+                first.newline.left_offset += arg.span.left_offset.take_as_prefix();
+            }
+            Tree::app(func, block::body_from_lines(mem::take(arguments)))
+        }
         (_, Variant::ArgumentBlockApplication(block)) if block.lhs.is_none() => {
             let code = func.span.code_length + arg.span.left_offset.code.length() + arg.span.code_length;
             arg.span.code_length = code;
@@ -820,6 +832,16 @@ pub fn apply<'s>(mut func: Tree<'s>, mut arg: Tree<'s>) -> Tree<'s> {
     }
 }
 
+/// Return whether the node is an opening delimiter at the end of a line, recovered from a missing
+/// closing delimiter by the macro resolver.
+fn is_unclosed_delimiter(error: &Error, ast: &Tree) -> bool {
+    match &*ast.variant {
+        Variant::Group(Group { open: Some(_), body: None, close: Some(close) }) =>
+            error.kind == ErrorKind::UnmatchedDelimiter && close.code.is_empty(),
+        _ => false,
+    }
+}
+
 fn maybe_apply<'s>(f: Option<Tree<'s>>, x: Tree<'s>) -> Tree<'s> {
     match f {
         Some(f) => apply(f, x),
@@ -885,7 +907,8 @@ pub fn apply_operator<'s>(
     }
     if let Ok(opr_) = &opr && opr_.properties.is_special() {
         let tree = Tree::opr_app(lhs, opr, rhs);
-        return tree.with_error("Invalid use of special operator.");
+        let error = "Invalid use of special operator.";
+        return tree.with_error(ErrorKind::InvalidSpecialOperator, error);
     }
     if let Ok(opr_) = &opr && opr_.properties.is_type_annotation() {
         return match (lhs, rhs) {
@@ -895,14 +918,15 @@ pub fn apply_operator<'s>(
             },
             (lhs, rhs) => {
                 let invalid = Tree::opr_app(lhs, opr, rhs);
-                invalid.with_error("`:` operator must be applied to two operands.")
+                let error = "`:` operator must be applied to two operands.";
+                invalid.with_error(ErrorKind::MissingOperands, error)
             }
         };
     }
     if let Ok(opr_) = &opr && !opr_.properties.can_form_section() && lhs.is_none() && rhs.is_none() {
-        let error = format!("Operator `{opr:?}` must be applied to two operands.");
+        let error = format!("Operator `{}` must be applied to two operands.", opr_.code);
         let invalid = Tree::opr_app(lhs, opr, rhs);
-        return invalid.with_error(error);
+        return invalid.with_error(ErrorKind::MissingOperands, error);
     }
     if let Ok(opr) = &opr && opr.properties.is_decimal()
         && let Some(lhs) = lhs.as_mut()
@@ -944,9 +968,9 @@ pub fn apply_unary_operator<'s>(opr: token::Operator<'s>, rhs: Option<Tree<'s>>)
         };
     }
     if !opr.properties.can_form_section() && rhs.is_none() {
-        let error = format!("Operator `{opr:?}` must be applied to an operand.");
+        let error = format!("Operator `{}` must be applied to an operand.", opr.code);
         let invalid = Tree::unary_opr_app(opr, rhs);
-        return invalid.with_error(error);
+        return invalid.with_error(ErrorKind::MissingOperand, error);
     }
     Tree::unary_opr_app(opr, rhs)
 }
@@ -984,9 +1008,11 @@ pub fn to_ast(token: Token) -> Tree {
         token::Variant::Wildcard(wildcard) => Tree::wildcard(token.with_variant(wildcard), default()),
         token::Variant::AutoScope(t) => Tree::auto_scope(token.with_variant(t)),
         token::Variant::OpenSymbol(s) =>
-            Tree::group(Some(token.with_variant(s)), default(), default()).with_error("Unmatched delimiter"),
+            Tree::group(Some(token.with_variant(s)), default(), default())
+                .with_error(ErrorKind::UnmatchedDelimiter, "Unmatched delimiter"),
         token::Variant::CloseSymbol(s) =>
-            Tree::group(default(), default(), Some(token.with_variant(s))).with_error("Unmatched delimiter"),
+            Tree::group(default(), default(), Some(token.with_variant(s)))
+                .with_error(ErrorKind::UnmatchedDelimiter, "Unmatched delimiter"),
         // These should be unreachable: They are handled when assembling items into blocks,
         // before parsing proper.
         token::Variant::Newline(_)
@@ -997,10 +1023,10 @@ pub fn to_ast(token: Token) -> Tree {
         | token::Variant::Private(_)
         // Map an error case in the lexer to an error in the AST.
         | token::Variant::Invalid(_) => {
-            let message = format!("Unexpected token: {token:?}");
+            let message = format!("Unexpected token: `{}`.", token.code);
             let ident = token::variant::Ident(false, 0, false, false, false);
             let value = Tree::ident(token.with_variant(ident));
-            Tree::with_error(value, message)
+            Tree::with_error(value, ErrorKind::UnexpectedToken, message)
         }
     }
 }
@@ -1021,12 +1047,13 @@ impl<'s> From<token::Ident<'s>> for Tree<'s> {
 ///
 /// # Visitor traits
 /// There are several visitor traits defined allowing for traversal of specific AST elements, such
-/// as AST nodes ([`TreeVisitor`]), span information ([`SpanVisitor`]), and AST nodes or tokens
-/// altogether ([`ItemVisitor`]). A visitor is a struct that is modified when traversing the target
-/// elements. Visitors are also capable of tracking when they entered or exited a nested
-/// [`Tree`] structure, and they can control how deep the traversal should be performed. To learn
-/// more, see the [`RefCollectorVisitor`] implementation, which traverses [`Tree`] and collects
-/// references to all [`Tree`] nodes in a vector.
+/// as AST nodes ([`TreeVisitor`]), span information ([`SpanVisitor`]), source code references
+/// ([`CodeVisitorMut`]), and AST nodes or tokens altogether ([`ItemVisitor`]). A visitor is a
+/// struct that is modified when traversing the target elements. Visitors are also capable of
+/// tracking when they entered or exited a nested [`Tree`] structure, and they can control how deep
+/// the traversal should be performed. To learn more, see the [`RefCollectorVisitor`]
+/// implementation, which traverses [`Tree`] and collects references to all [`Tree`] nodes in a
+/// vector.
 ///
 /// # Visitable traits
 /// This macro also defines visitable traits, such as [`TreeVisitable`] or [`SpanVisitable`], which
@@ -1077,6 +1104,12 @@ pub trait SpanVisitorMut<'s>: Visitor {
     fn visit_mut(&mut self, ast: span::RefMut<'s, '_>) -> bool;
 }
 
+/// The visitor trait allowing for [`Code`] mutable traversal.
+#[allow(missing_docs)]
+pub trait CodeVisitorMut<'s>: Visitor {
+    fn visit_mut(&mut self, code: &mut Code<'s>);
+}
+
 /// The visitor trait allowing for [`Item`] traversal.
 #[allow(missing_docs)]
 pub trait ItemVisitor<'s, 'a>: Visitor {
@@ -1185,11 +1218,13 @@ macro_rules! define_visitor_for_tokens {
     ) => {
         impl<'s, 'a> TreeVisitable<'s, 'a> for token::$kind {}
         impl<'s, 'a> TreeVisitableMut<'s, 'a> for token::$kind {}
+        impl<'s, 'a> CodeVisitableMut<'s, 'a> for token::$kind {}
     };
 }
 
 define_visitor!(Tree, visit);
 define_visitor!(Span, visit_span);
+define_visitor_mut!(Code, visit_code);
 define_visitor_no_mut!(Item, visit_item);
 
 crate::with_token_definition!(define_visitor_for_tokens());
@@ -1203,6 +1238,7 @@ macro_rules! spanless_leaf_impls {
         impl<'s, 'a> TreeVisitableMut<'s, 'a> for $ty {}
         impl<'a, 's> SpanVisitable<'s, 'a> for $ty {}
         impl<'a, 's> SpanVisitableMut<'s, 'a> for $ty {}
+        impl<'a, 's> CodeVisitableMut<'s, 'a> for $ty {}
         impl<'a, 's> ItemVisitable<'s, 'a> for $ty {}
         impl<'s> span::Builder<'s> for $ty {
             fn add_to_span(&mut self, span: Span<'s>) -> Span<'s> {
@@ -1215,6 +1251,7 @@ macro_rules! spanless_leaf_impls {
 spanless_leaf_impls!(u32);
 spanless_leaf_impls!(bool);
 spanless_leaf_impls!(VisibleOffset);
+spanless_leaf_impls!(ErrorKind);
 
 
 // === TreeVisitable special cases ===
@@ -1278,6 +1315,23 @@ impl<'a, 's, T> SpanVisitableMut<'s, 'a> for Token<'s, T> {
 }
 
 
+// === CodeVisitableMut special cases ===
+
+impl<'s, 'a> CodeVisitableMut<'s, 'a> for Tree<'s> {
+    fn visit_code_mut<V: CodeVisitorMut<'s>>(&'a mut self, visitor: &mut V) {
+        visitor.visit_mut(&mut self.span.left_offset.code);
+        self.variant.visit_code_mut(visitor)
+    }
+}
+
+impl<'s, 'a, T> CodeVisitableMut<'s, 'a> for Token<'s, T> {
+    fn visit_code_mut<V: CodeVisitorMut<'s>>(&'a mut self, visitor: &mut V) {
+        visitor.visit_mut(&mut self.left_offset.code);
+        visitor.visit_mut(&mut self.code);
+    }
+}
+
+
 // === ItemVisitable special cases ===
 
 impl<'s, 'a> ItemVisitable<'s, 'a> for Tree<'s> {
@@ -1303,6 +1357,7 @@ impl<'s, 'a> TreeVisitable<'s, 'a> for String {}
 impl<'s, 'a> TreeVisitableMut<'s, 'a> for String {}
 impl<'a, 's> SpanVisitable<'s, 'a> for String {}
 impl<'a, 's> SpanVisitableMut<'s, 'a> for String {}
+impl<'a, 's> CodeVisitableMut<'s, 'a> for String {}
 impl<'a, 's> ItemVisitable<'s, 'a> for String {}
 impl<'s> span::Builder<'s> for String {
     fn add_to_span(&mut self, span: Span<'s>) -> Span<'s> {
@@ -1314,6 +1369,7 @@ impl<'s, 'a> TreeVisitable<'s, 'a> for Cow<'static, str> {}
 impl<'s, 'a> TreeVisitableMut<'s, 'a> for Cow<'static, str> {}
 impl<'a, 's> SpanVisitable<'s, 'a> for Cow<'static, str> {}
 impl<'a, 's> SpanVisitableMut<'s, 'a> for Cow<'static, str> {}
+impl<'a, 's> CodeVisitableMut<'s, 'a> for Cow<'static, str> {}
 impl<'a, 's> ItemVisitable<'s, 'a> for Cow<'static, str> {}
 impl<'s> span::Builder<'s> for Cow<'static, str> {
     fn add_to_span(&mut self, span: Span<'s>) -> Span<'s> {
@@ -12,7 +12,7 @@ use crate::syntax::token;
 // =============
 
 /// A line of code.
-#[derive(Debug, Clone, PartialEq, Eq, Visitor, Reflect, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Visitor, Encode, Reflect, Serialize, Deserialize)]
 pub struct Line<'s> {
     /// Token ending the previous line, if any.
     pub newline:    token::Newline<'s>,
@@ -54,10 +54,17 @@ impl<'s> span::Builder<'s> for Line<'s> {
 /// - Combine sibling lines in case of multi-line statements, such as annotated statements and
 ///   documented statements.
 pub fn body_from_lines<'s>(lines: impl IntoIterator<Item = Line<'s>>) -> Tree<'s> {
+    let statements: Vec<_> = statements_from_lines(lines).collect();
+    Tree::body_block(statements)
+}
+
+/// Interpret a sequence of lines as the statements of a body block. See [`body_from_lines`].
+pub fn statements_from_lines<'s>(
+    lines: impl IntoIterator<Item = Line<'s>>,
+) -> impl Iterator<Item = Line<'s>> {
     use crate::expression_to_statement;
     let lines = lines.into_iter().map(|l| l.map_expression(expression_to_statement));
-    let statements: Vec<_> = compound_lines(lines).collect();
-    Tree::body_block(statements)
+    compound_lines(lines)
 }
 
 
@@ -199,7 +206,7 @@ impl<'s> From<Prefix<'s>> for Tree<'s> {
 // ======================
 
 /// The content of a line in an operator block.
-#[derive(Debug, Clone, PartialEq, Eq, Visitor, Reflect, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Visitor, Encode, Reflect, Serialize, Deserialize)]
 pub struct OperatorBlockExpression<'s> {
     /// The operator at the beginning of the line.
     pub operator:   OperatorOrError<'s>,
@@ -235,7 +242,7 @@ impl<'s> span::Builder<'s> for OperatorBlockExpression<'s> {
 // === Operator block lines ====
 
 /// A line in an operator block.
-#[derive(Debug, Clone, PartialEq, Eq, Visitor, Reflect, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Visitor, Encode, Reflect, Serialize, Deserialize)]
 pub struct OperatorLine<'s> {
     /// Token ending the previous line, if any.
     pub newline:    token::Newline<'s>,
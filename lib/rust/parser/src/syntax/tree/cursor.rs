@@ -0,0 +1,315 @@
+//! Navigation of a [`Tree`] with random access.
+//!
+//! The visitors walk a tree from the top down, in a single pass. A [`Cursor`] points to a node and
+//! remembers the path to it from the root, so it can move to the parent, children, or siblings of
+//! the node; it can also be created pointing to the innermost node at a location in the code.
+
+use crate::source::code::Location;
+use crate::syntax::item;
+use crate::syntax::token;
+use crate::syntax::tree::*;
+
+
+
+// ==============
+// === Cursor ===
+// ==============
+
+/// Points to a node of a tree, which is either a [`Tree`] or a token, and tracks the ancestors of
+/// the node. The children of a [`Tree`] are the trees and tokens it contains directly, in source
+/// order.
+#[derive(Clone, Debug)]
+pub struct Cursor<'s, 'a> {
+    /// The ancestors of the current node, starting with the root.
+    path: Vec<Ancestor<'s, 'a>>,
+    node: item::Ref<'s, 'a>,
+}
+
+#[derive(Clone, Debug)]
+struct Ancestor<'s, 'a> {
+    tree:     &'a Tree<'s>,
+    children: Vec<item::Ref<'s, 'a>>,
+    /// The index in `children` of the node on the path to the current node.
+    index:    usize,
+}
+
+impl<'s, 'a> Cursor<'s, 'a> {
+    /// Return a cursor pointing to the given tree.
+    pub fn new(root: &'a Tree<'s>) -> Self {
+        Self { path: default(), node: item::Ref::Tree(root) }
+    }
+
+    /// Return a cursor pointing to the innermost node containing the given offset, in UTF-8 code
+    /// units (bytes) from the start of the source code. A node contains the offsets from its
+    /// start, excluding its left whitespace, to its end, exclusive. If no node contains the offset,
+    /// the cursor points to the root.
+    pub fn node_at_offset(root: &'a Tree<'s>, offset: u32) -> Self {
+        Self::node_at(root, |location| location.utf8, offset)
+    }
+
+    /// Return a cursor pointing to the innermost node containing the given offset, in UTF-16 code
+    /// units from the start of the source code. See [`Self::node_at_offset`].
+    pub fn node_at_utf16_offset(root: &'a Tree<'s>, offset: u32) -> Self {
+        Self::node_at(root, |location| location.utf16, offset)
+    }
+
+    fn node_at(root: &'a Tree<'s>, unit: impl Fn(Location) -> u32, offset: u32) -> Self {
+        let mut cursor = Self::new(root);
+        let contains = |node: &item::Ref| {
+            let range = range(node);
+            (unit(range.start)..unit(range.end)).contains(&offset)
+        };
+        while let item::Ref::Tree(tree) = cursor.node {
+            let children = children(tree);
+            match children.iter().position(contains) {
+                Some(index) => cursor.enter(tree, children, index),
+                None => break,
+            }
+        }
+        cursor
+    }
+
+    /// Return the node the cursor points to.
+    pub fn node(&self) -> item::Ref<'s, 'a> {
+        self.node
+    }
+
+    /// Return the node the cursor points to, if it is a [`Tree`].
+    pub fn tree(&self) -> Option<&'a Tree<'s>> {
+        match self.node {
+            item::Ref::Tree(tree) => Some(tree),
+            item::Ref::Token(_) => None,
+        }
+    }
+
+    /// Return the node the cursor points to, if it is a token.
+    pub fn token(&self) -> Option<token::Ref<'s, 'a>> {
+        match self.node {
+            item::Ref::Token(token) => Some(token),
+            item::Ref::Tree(_) => None,
+        }
+    }
+
+    /// Return the location of the code of the current node, excluding its left whitespace.
+    pub fn range(&self) -> Range<Location> {
+        range(&self.node)
+    }
+
+    /// Return the number of ancestors of the current node.
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Return the parent of the current node, if it is not the root.
+    pub fn parent(&self) -> Option<&'a Tree<'s>> {
+        self.path.last().map(|ancestor| ancestor.tree)
+    }
+
+    /// Return the ancestors of the current node, starting with its parent and ending with the root.
+    pub fn ancestors(&self) -> impl Iterator<Item = &'a Tree<'s>> + '_ {
+        self.path.iter().rev().map(|ancestor| ancestor.tree)
+    }
+
+    /// Return the children of the current node.
+    pub fn children(&self) -> Vec<item::Ref<'s, 'a>> {
+        match self.node {
+            item::Ref::Tree(tree) => children(tree),
+            item::Ref::Token(_) => default(),
+        }
+    }
+
+    /// Return the children of the parent of the current node, which include the current node at
+    /// [`Self::index`]. If the current node is the root, the result is empty.
+    pub fn siblings(&self) -> &[item::Ref<'s, 'a>] {
+        self.path.last().map(|ancestor| &ancestor.children[..]).unwrap_or_default()
+    }
+
+    /// Return the index of the current node among its siblings, if it is not the root.
+    pub fn index(&self) -> Option<usize> {
+        self.path.last().map(|ancestor| ancestor.index)
+    }
+
+    /// Return the tokens of the current node, in source order, with their locations.
+    pub fn tokens(&self) -> impl Iterator<Item = (Range<Location>, token::Ref<'s, 'a>)> {
+        let mut collector = TokenCollector::default();
+        match self.node {
+            item::Ref::Tree(tree) => tree.visit_item(&mut collector),
+            item::Ref::Token(token) => collector.tokens.push(token),
+        }
+        collector.tokens.into_iter().map(|token| (token.code.range(), token))
+    }
+
+    /// Move to the parent of the current node. Return `false` if the current node is the root.
+    pub fn goto_parent(&mut self) -> bool {
+        match self.path.pop() {
+            Some(Ancestor { tree, .. }) => {
+                self.node = item::Ref::Tree(tree);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move to the first child of the current node. Return `false` if it has no children.
+    pub fn goto_first_child(&mut self) -> bool {
+        self.goto_child(|_| 0)
+    }
+
+    /// Move to the last child of the current node. Return `false` if it has no children.
+    pub fn goto_last_child(&mut self) -> bool {
+        self.goto_child(|len| len - 1)
+    }
+
+    fn goto_child(&mut self, index: impl FnOnce(usize) -> usize) -> bool {
+        let item::Ref::Tree(tree) = self.node else { return false };
+        let children = children(tree);
+        if children.is_empty() {
+            return false;
+        }
+        let index = index(children.len());
+        self.enter(tree, children, index);
+        true
+    }
+
+    fn enter(&mut self, tree: &'a Tree<'s>, children: Vec<item::Ref<'s, 'a>>, index: usize) {
+        self.node = children[index];
+        self.path.push(Ancestor { tree, children, index });
+    }
+
+    /// Move to the next sibling of the current node. Return `false` if there is none.
+    pub fn goto_next_sibling(&mut self) -> bool {
+        self.goto_sibling(|index| index.checked_add(1))
+    }
+
+    /// Move to the previous sibling of the current node. Return `false` if there is none.
+    pub fn goto_prev_sibling(&mut self) -> bool {
+        self.goto_sibling(|index| index.checked_sub(1))
+    }
+
+    fn goto_sibling(&mut self, index: impl FnOnce(usize) -> Option<usize>) -> bool {
+        let Some(ancestor) = self.path.last_mut() else { return false };
+        let Some(index) = index(ancestor.index) else { return false };
+        let Some(&node) = ancestor.children.get(index) else { return false };
+        ancestor.index = index;
+        self.node = node;
+        true
+    }
+}
+
+fn range(node: &item::Ref) -> Range<Location> {
+    match node {
+        item::Ref::Tree(tree) => tree.span.range(),
+        item::Ref::Token(token) => token.code.range(),
+    }
+}
+
+pub(super) fn children<'s, 'a>(tree: &'a Tree<'s>) -> Vec<item::Ref<'s, 'a>> {
+    let mut collector = ChildCollector::default();
+    tree.variant.visit_item(&mut collector);
+    collector.children
+}
+
+
+// === Collectors ===
+
+#[derive(Debug, Default)]
+struct ChildCollector<'s, 'a> {
+    children: Vec<item::Ref<'s, 'a>>,
+}
+
+impl<'s, 'a> Visitor for ChildCollector<'s, 'a> {}
+impl<'s, 'a> ItemVisitor<'s, 'a> for ChildCollector<'s, 'a> {
+    fn visit_item(&mut self, item: item::Ref<'s, 'a>) -> bool {
+        self.children.push(item);
+        false
+    }
+}
+
+#[derive(Debug, Default)]
+struct TokenCollector<'s, 'a> {
+    tokens: Vec<token::Ref<'s, 'a>>,
+}
+
+impl<'s, 'a> Visitor for TokenCollector<'s, 'a> {}
+impl<'s, 'a> ItemVisitor<'s, 'a> for TokenCollector<'s, 'a> {
+    fn visit_item(&mut self, item: item::Ref<'s, 'a>) -> bool {
+        if let item::Ref::Token(token) = item {
+            self.tokens.push(token);
+        }
+        true
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(node: item::Ref) -> String {
+        match node {
+            item::Ref::Tree(tree) => tree.trimmed_code(),
+            item::Ref::Token(token) => token.code.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_node_at_offset() {
+        let tree = crate::Parser::new().run("main =\n    x = foo (bar 1) 'ą' + y");
+        let cursor = Cursor::node_at_offset(&tree, 24);
+        assert_eq!(code(cursor.node()), "1");
+        let ancestors: Vec<_> = cursor.ancestors().map(|tree| tree.trimmed_code()).collect();
+        assert_eq!(ancestors[..4], ["1", "bar 1", "(bar 1)", "foo (bar 1)"]);
+        assert!(std::ptr::eq(cursor.ancestors().last().unwrap(), &tree));
+        assert_eq!(code(Cursor::node_at_offset(&tree, 19).node()), "(");
+        // `ą` is 2 bytes in UTF-8, and 1 code unit in UTF-16.
+        assert_eq!(code(Cursor::node_at_offset(&tree, 32).node()), "+");
+        assert_eq!(code(Cursor::node_at_utf16_offset(&tree, 31).node()), "+");
+        // Whitespace is contained by the nodes enclosing it, but not by its neighbours.
+        assert_eq!(code(Cursor::node_at_offset(&tree, 31).node()), "foo (bar 1) 'ą' + y");
+    }
+
+    #[test]
+    fn test_navigation() {
+        let tree = crate::Parser::new().run("f a b");
+        let mut cursor = Cursor::node_at_offset(&tree, 2);
+        assert_eq!(code(cursor.node()), "a");
+        assert!(cursor.token().is_some());
+        assert!(cursor.goto_parent());
+        assert_eq!(code(cursor.node()), "a");
+        assert!(cursor.goto_parent());
+        assert_eq!(code(cursor.node()), "f a");
+        assert_eq!(cursor.siblings().iter().map(|node| code(*node)).collect_vec(), ["f a", "b"]);
+        assert!(cursor.goto_next_sibling());
+        assert_eq!((code(cursor.node()), cursor.index()), ("b".into(), Some(1)));
+        assert!(!cursor.goto_next_sibling());
+        assert!(cursor.goto_prev_sibling());
+        assert!(cursor.goto_first_child());
+        assert_eq!(code(cursor.node()), "f");
+        assert!(cursor.goto_last_child());
+        assert!(cursor.token().is_some());
+        assert!(!cursor.goto_first_child());
+        while cursor.goto_parent() {}
+        assert_eq!(cursor.depth(), 0);
+        assert!(!cursor.goto_next_sibling());
+    }
+
+    #[test]
+    fn test_tokens() {
+        let tree = crate::Parser::new().run("x = 'ą'\ny");
+        let tokens = Cursor::new(&tree)
+            .tokens()
+            .filter(|(_, token)| !token.code.is_empty())
+            .map(|(range, token)| (token.code.to_string(), range.start.utf8, range.start.utf16))
+            .collect_vec();
+        let expected =
+            [("x", 0, 0), ("=", 2, 2), ("'", 4, 4), ("ą", 5, 5), ("'", 7, 6), ("\n", 8, 7)];
+        let expected = expected.map(|(code, utf8, utf16)| (code.to_string(), utf8, utf16));
+        assert_eq!(tokens[..6], expected);
+        assert_eq!(tokens[6], ("y".into(), 9, 8));
+    }
+}
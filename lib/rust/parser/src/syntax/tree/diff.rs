@@ -0,0 +1,803 @@
+//! Finding the corresponding nodes of two trees, and the edits transforming one into the other.
+//!
+//! After a document is reparsed, the nodes of the new tree have no identity in common with the
+//! nodes of the old tree. [`diff`] computes a [`Diff`]: a one-to-one mapping between the nodes of
+//! the old tree and the nodes of the new tree that represent the same code, and an edit script
+//! transforming the old tree into the new tree. Data associated with the nodes of the old tree,
+//! such as node IDs, visualizations, or widget state, can be carried over to the new tree by
+//! following the mapping.
+//!
+//! # Nodes
+//! The nodes of a tree are its [`Tree`]s and tokens, with the parent-child relation navigated by a
+//! [`Cursor`](super::cursor::Cursor). They are identified by [`NodeId`]s; see [`nodes`].
+//!
+//! # Algorithm
+//! The mapping is computed as by GumTree (Falleri et al., "Fine-grained and accurate source code
+//! differencing", 2014), in three phases:
+//! 1. Top-down: The largest subtrees that are identical in both trees (in the kinds of their nodes
+//!    and the code of their tokens, ignoring whitespace) are matched, with their descendants. When
+//!    a subtree occurs several times, the occurrences nearest each other are matched. If the text
+//!    edits between the trees are known, locations in the old code are mapped to the new code for
+//!    this comparison, so that identical code is matched correctly even when it was shifted.
+//! 2. Bottom-up: Unmatched nodes of the old tree are matched to unmatched nodes of the same kind in
+//!    the new tree that contain enough of the counterparts of their descendants.
+//! 3. Recovery: The unmatched children of matched nodes are matched in order, first to identical
+//!    children, then to children of the same kind.
+//!
+//! The edit script is derived from the mapping. It is the shortest script possible for the
+//! mapping, as computed by Chawathe et al. ("Change detection in hierarchically structured
+//! information", 1996).
+//!
+//! All phases are deterministic: Diffing the same trees always produces the same result.
+
+use crate::syntax::item;
+use crate::syntax::tree::*;
+
+use super::cursor::children;
+
+use enso_text::Byte;
+use enso_text::Change;
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeSet;
+use std::hash::Hasher;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The minimum height of the subtrees matched by the top-down phase. Smaller subtrees, such as
+/// single tokens, occur too often for their content to identify them.
+const MIN_HEIGHT: usize = 2;
+
+/// The minimum ratio of common descendants for the bottom-up phase to match two nodes.
+const MIN_DICE: f64 = 0.5;
+
+/// The maximum number of candidate pairs compared when matching the occurrences of a subtree that
+/// occurs several times; above it, occurrences are matched in order.
+const MAX_CANDIDATE_PAIRS: usize = 1 << 16;
+
+/// The maximum product of the lengths of two sequences of children for which their longest common
+/// subsequence is found; above it, only their common prefix and suffix are matched.
+const MAX_COMMON_SUBSEQUENCE_SIZE: usize = 1 << 20;
+
+
+
+// ============
+// === Diff ===
+// ============
+
+/// Identifies a node of a tree by its index in a pre-order traversal of the tree: The root is node
+/// `0`, and each node is followed by the nodes of its subtrees, in source order.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(pub usize);
+
+/// An operation of an edit script transforming the old tree into the new tree.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Edit {
+    /// A node of the new tree with no counterpart in the old tree is inserted.
+    Insert {
+        /// The node, in the new tree.
+        node:   NodeId,
+        /// The parent of the node, in the new tree.
+        parent: NodeId,
+        /// The position of the node among the children of its parent.
+        index:  usize,
+    },
+    /// A node of the old tree with no counterpart in the new tree is deleted.
+    Delete {
+        /// The node, in the old tree.
+        node: NodeId,
+    },
+    /// The code of a token is changed, e.g. by renaming an identifier. The roots of the trees are
+    /// always counterparts; if they are of different kinds, the root is updated too.
+    Update {
+        /// The node, in the old tree.
+        old: NodeId,
+        /// The node, in the new tree.
+        new: NodeId,
+    },
+    /// A node is moved to a different parent, or to a different position among its siblings.
+    Move {
+        /// The node, in the old tree.
+        old:    NodeId,
+        /// The node, in the new tree.
+        new:    NodeId,
+        /// The new parent of the node, in the new tree.
+        parent: NodeId,
+        /// The position of the node among the children of its new parent.
+        index:  usize,
+    },
+}
+
+/// The correspondence between the nodes of two trees, and the edits transforming one into the
+/// other. See the [module documentation](self).
+#[derive(Clone, Debug, Default)]
+pub struct Diff {
+    old_ranges:   Vec<Range<usize>>,
+    new_ranges:   Vec<Range<usize>>,
+    old_to_new:   Vec<Option<usize>>,
+    new_to_old:   Vec<Option<usize>>,
+    edits:        Vec<Edit>,
+    /// The outermost node of the old tree with a counterpart, for each range of the old code.
+    old_by_range: HashMap<Range<usize>, usize>,
+}
+
+/// Compute the [`Diff`] between two trees.
+pub fn diff<'s>(old: &Tree<'s>, new: &Tree<'s>) -> Diff {
+    diff_with_edits::<&str>(old, new, &[])
+}
+
+/// Compute the [`Diff`] between two trees, given the text edits transforming the code of the old
+/// tree into the code of the new tree; they are applied in order. The edits are used to match
+/// repeated code more accurately; any edits produce a valid result.
+pub fn diff_with_edits<'s, S: AsRef<str>>(
+    old: &Tree<'s>,
+    new: &Tree<'s>,
+    edits: &[Change<Byte, S>],
+) -> Diff {
+    let mut matcher = Matcher::new(Flat::new(old), Flat::new(new), OffsetMap::new(edits));
+    matcher.run();
+    let edits = matcher.edit_script();
+    let Matcher { old, new, old_to_new, new_to_old, .. } = matcher;
+    let mut old_by_range = HashMap::new();
+    for (node, counterpart) in old.nodes.iter().zip(&old_to_new) {
+        if counterpart.is_some() {
+            old_by_range.entry(node.range.clone()).or_insert(node.id);
+        }
+    }
+    let ranges = |flat: Flat| flat.nodes.into_iter().map(|node| node.range).collect();
+    Diff {
+        old_ranges: ranges(old),
+        new_ranges: ranges(new),
+        old_to_new,
+        new_to_old,
+        edits,
+        old_by_range,
+    }
+}
+
+/// Return the nodes of the tree, indexed by their [`NodeId`]s.
+pub fn nodes<'s, 'a>(tree: &'a Tree<'s>) -> Vec<item::Ref<'s, 'a>> {
+    Flat::new(tree).nodes.into_iter().map(|node| node.item).collect()
+}
+
+impl Diff {
+    /// Return the edit script transforming the old tree into the new tree. The insertions, updates
+    /// and moves come first, in the order of the nodes of the new tree; they are followed by the
+    /// deletions, each node being deleted before its parent.
+    pub fn edits(&self) -> &[Edit] {
+        &self.edits
+    }
+
+    /// Return the node of the new tree corresponding to the given node of the old tree, if any.
+    pub fn new_node(&self, old: NodeId) -> Option<NodeId> {
+        self.old_to_new.get(old.0).copied().flatten().map(NodeId)
+    }
+
+    /// Return the node of the old tree corresponding to the given node of the new tree, if any.
+    pub fn old_node(&self, new: NodeId) -> Option<NodeId> {
+        self.new_to_old.get(new.0).copied().flatten().map(NodeId)
+    }
+
+    /// Return the pairs of corresponding nodes, in the order of the nodes of the old tree.
+    pub fn matches(&self) -> impl Iterator<Item = (NodeId, NodeId)> + '_ {
+        let pairs = self.old_to_new.iter().enumerate();
+        pairs.filter_map(|(old, new)| Some((NodeId(old), NodeId((*new)?))))
+    }
+
+    /// Return the range of the code of a node of the old tree, excluding its left whitespace, in
+    /// bytes.
+    pub fn old_range(&self, old: NodeId) -> Range<usize> {
+        self.old_ranges[old.0].clone()
+    }
+
+    /// Return the range of the code of a node of the new tree, excluding its left whitespace, in
+    /// bytes.
+    pub fn new_range(&self, new: NodeId) -> Range<usize> {
+        self.new_ranges[new.0].clone()
+    }
+
+    /// Given the range of the code of a node of the old tree, return the range of its counterpart
+    /// in the new tree. When several nodes have the same range, the outermost node that has a
+    /// counterpart is used. Returns [`None`] if no node with the range has a counterpart.
+    pub fn map_range(&self, old: Range<usize>) -> Option<Range<usize>> {
+        let node = *self.old_by_range.get(&old)?;
+        self.old_to_new[node].map(|new| self.new_ranges[new].clone())
+    }
+}
+
+
+
+// ============
+// === Flat ===
+// ============
+
+/// A tree, flattened to a sequence of nodes in pre-order.
+#[derive(Debug)]
+struct Flat<'s, 'a> {
+    nodes: Vec<Node<'s, 'a>>,
+}
+
+#[derive(Debug)]
+struct Node<'s, 'a> {
+    id:       usize,
+    item:     item::Ref<'s, 'a>,
+    parent:   Option<usize>,
+    children: Vec<usize>,
+    /// The position of the node among the children of its parent.
+    index:    usize,
+    /// Identifies the kind of node; nodes of the same kind are [`Tree`]s of the same variant, or
+    /// tokens of the same variant.
+    kind:     u64,
+    /// A hash of the kinds and code of the nodes of the subtree.
+    hash:     u64,
+    /// The number of nodes in the longest path from this node to a leaf, including both.
+    height:   usize,
+    /// The number of nodes in the subtree. Its nodes are the ones from `id` to `id + size`.
+    size:     usize,
+    /// The range of the code of the node, excluding its left whitespace, in bytes.
+    range:    Range<usize>,
+}
+
+impl<'s, 'a> Flat<'s, 'a> {
+    fn new(root: &'a Tree<'s>) -> Self {
+        let mut nodes: Vec<Node<'s, 'a>> = default();
+        // The tree is traversed without recursion, so that arbitrarily deep trees can be diffed.
+        let mut stack = vec![(item::Ref::Tree(root), None::<usize>)];
+        while let Some((item, parent)) = stack.pop() {
+            let id = nodes.len();
+            let index = match parent {
+                Some(parent) => {
+                    let siblings = &mut nodes[parent].children;
+                    siblings.push(id);
+                    siblings.len() - 1
+                }
+                None => 0,
+            };
+            let (kind, range) = match item {
+                item::Ref::Tree(tree) => {
+                    let children = children(tree).into_iter().rev();
+                    stack.extend(children.map(|child| (child, Some(id))));
+                    (hash_of(&(0, mem::discriminant(&*tree.variant))), tree.span.range())
+                }
+                item::Ref::Token(token) =>
+                    (hash_of(&(1, mem::discriminant(&token.data))), token.code.range()),
+            };
+            let range = range.start.utf8 as usize..range.end.utf8 as usize;
+            let children = default();
+            let (hash, height, size) = default();
+            nodes.push(Node { id, item, parent, children, index, kind, hash, height, size, range });
+        }
+        // Children follow their parents, so a reverse traversal visits the children first.
+        for id in (0..nodes.len()).rev() {
+            let node = &nodes[id];
+            let mut hasher = DefaultHasher::new();
+            hasher.write_u64(node.kind);
+            value(node.item).hash(&mut hasher);
+            let (mut height, mut size) = (0, 1);
+            for &child in &node.children {
+                let child = &nodes[child];
+                hasher.write_u64(child.hash);
+                height = height.max(child.height);
+                size += child.size;
+            }
+            let node = &mut nodes[id];
+            node.hash = hasher.finish();
+            node.height = height + 1;
+            node.size = size;
+        }
+        Self { nodes }
+    }
+
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Return whether the subtrees rooted at the nodes are identical.
+    ///
+    /// Subtrees with different hashes are rejected immediately. As hashes can collide, subtrees
+    /// with equal hashes are then compared node by node.
+    fn is_isomorphic(&self, node: usize, other: &Self, other_node: usize) -> bool {
+        let (root, other_root) = (&self.nodes[node], &other.nodes[other_node]);
+        if (root.hash, root.size) != (other_root.hash, other_root.size) {
+            return false;
+        }
+        // The subtrees are contiguous in pre-order, so they are identical if their nodes are
+        // pairwise identical in kind, code, and number of children.
+        let nodes = &self.nodes[node..node + root.size];
+        let other_nodes = &other.nodes[other_node..other_node + root.size];
+        nodes.iter().zip(other_nodes).all(|(node, other)| {
+            node.kind == other.kind
+                && value(node.item) == value(other.item)
+                && node.children.len() == other.children.len()
+        })
+    }
+}
+
+/// The code of the node, if it is a token.
+fn value<'s>(item: item::Ref<'s, '_>) -> Option<&'s str> {
+    match item {
+        item::Ref::Tree(_) => None,
+        item::Ref::Token(token) => Some(token.code.repr.0),
+    }
+}
+
+fn hash_of(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+
+
+// =================
+// === OffsetMap ===
+// =================
+
+/// Maps offsets in the old code to the corresponding offsets in the new code.
+#[derive(Debug)]
+struct OffsetMap {
+    /// The start and end of the replaced range, and the length of the inserted text, of each edit.
+    edits: Vec<(usize, usize, usize)>,
+}
+
+impl OffsetMap {
+    fn new<S: AsRef<str>>(edits: &[Change<Byte, S>]) -> Self {
+        let edits = edits.iter().map(|edit| {
+            let (start, end) = (edit.range.start.value, edit.range.end.value);
+            (start, end.max(start), edit.text.as_ref().len())
+        });
+        Self { edits: edits.collect() }
+    }
+
+    /// Offsets after an edit are shifted; offsets inside a replaced range are moved to its start.
+    fn map(&self, offset: usize) -> usize {
+        self.edits.iter().fold(offset, |offset, &(start, end, inserted)| {
+            if offset >= end {
+                offset - (end - start) + inserted
+            } else {
+                offset.min(start)
+            }
+        })
+    }
+}
+
+
+
+// ===============
+// === Matcher ===
+// ===============
+
+#[derive(Debug)]
+struct Matcher<'s, 'a> {
+    old:        Flat<'s, 'a>,
+    new:        Flat<'s, 'a>,
+    offsets:    OffsetMap,
+    old_to_new: Vec<Option<usize>>,
+    new_to_old: Vec<Option<usize>>,
+}
+
+impl<'s, 'a> Matcher<'s, 'a> {
+    fn new(old: Flat<'s, 'a>, new: Flat<'s, 'a>, offsets: OffsetMap) -> Self {
+        let old_to_new = vec![None; old.len()];
+        let new_to_old = vec![None; new.len()];
+        Self { old, new, offsets, old_to_new, new_to_old }
+    }
+
+    fn run(&mut self) {
+        if self.old.is_isomorphic(0, &self.new, 0) {
+            self.link_subtrees(0, 0);
+            return;
+        }
+        self.link(0, 0);
+        self.match_identical();
+        self.match_similar();
+        self.recover(0, 0);
+    }
+
+    fn link(&mut self, old: usize, new: usize) {
+        self.old_to_new[old] = Some(new);
+        self.new_to_old[new] = Some(old);
+    }
+
+    /// Match the nodes of identical subtrees.
+    fn link_subtrees(&mut self, old: usize, new: usize) {
+        for i in 0..self.old.nodes[old].size {
+            self.link(old + i, new + i);
+        }
+    }
+
+    fn same_kind(&self, old: usize, new: usize) -> bool {
+        self.old.nodes[old].kind == self.new.nodes[new].kind
+    }
+
+    /// The distance between the locations of the nodes, in the new code.
+    fn distance(&self, old: usize, new: usize) -> usize {
+        let old_start = self.offsets.map(self.old.nodes[old].range.start);
+        old_start.abs_diff(self.new.nodes[new].range.start)
+    }
+
+
+    // === Top-down ===
+
+    fn match_identical(&mut self) {
+        let mut groups: BTreeMap<_, (Vec<usize>, Vec<usize>)> = default();
+        let key = |node: &Node| (Reverse(node.height), node.hash, node.size);
+        for node in self.old.nodes.iter().filter(|node| node.height >= MIN_HEIGHT) {
+            groups.entry(key(node)).or_default().0.push(node.id);
+        }
+        for node in self.new.nodes.iter().filter(|node| node.height >= MIN_HEIGHT) {
+            if let Some(group) = groups.get_mut(&key(node)) {
+                group.1.push(node.id);
+            }
+        }
+        for (old, new) in groups.into_values() {
+            let old = old.into_iter().filter(|&node| self.old_to_new[node].is_none()).collect_vec();
+            let new = new.into_iter().filter(|&node| self.new_to_old[node].is_none()).collect_vec();
+            for (old, new) in self.pair_nearest(&old, &new) {
+                if self.old.is_isomorphic(old, &self.new, new) {
+                    self.link_subtrees(old, new);
+                }
+            }
+        }
+    }
+
+    /// Pair each node with the nearest node of the other sequence, nearest pairs first.
+    fn pair_nearest(&self, old: &[usize], new: &[usize]) -> Vec<(usize, usize)> {
+        if old.len() * new.len() > MAX_CANDIDATE_PAIRS {
+            return old.iter().copied().zip(new.iter().copied()).collect();
+        }
+        let candidates = old.iter().flat_map(|&old| new.iter().map(move |&new| (old, new)));
+        let mut candidates =
+            candidates.map(|(old, new)| (self.distance(old, new), old, new)).collect_vec();
+        candidates.sort_unstable();
+        let (mut old_paired, mut new_paired) = (HashSet::new(), HashSet::new());
+        let candidates = candidates.into_iter().map(|(_, old, new)| (old, new));
+        candidates.filter(|&(old, new)| old_paired.insert(old) && new_paired.insert(new)).collect()
+    }
+
+
+    // === Bottom-up ===
+
+    fn match_similar(&mut self) {
+        for old in (1..self.old.len()).rev() {
+            if self.old_to_new[old].is_none() && !self.old.nodes[old].children.is_empty() {
+                if let Some(new) = self.best_candidate(old) {
+                    self.link(old, new);
+                    self.recover(old, new);
+                }
+            }
+        }
+    }
+
+    /// Find the unmatched node of the new tree most similar to the given node of the old tree.
+    fn best_candidate(&self, old: usize) -> Option<usize> {
+        let node = &self.old.nodes[old];
+        let mut candidates = BTreeSet::new();
+        for descendant in old + 1..old + node.size {
+            // A descendant whose parent is matched yields the same candidates as its parent.
+            let parent = self.old.nodes[descendant].parent;
+            if parent != Some(old) && parent.and_then(|parent| self.old_to_new[parent]).is_some() {
+                continue;
+            }
+            let Some(counterpart) = self.old_to_new[descendant] else { continue };
+            let mut ancestor = self.new.nodes[counterpart].parent;
+            while let Some(new) = ancestor {
+                if self.new_to_old[new].is_none() && self.same_kind(old, new) {
+                    candidates.insert(new);
+                    break;
+                }
+                ancestor = self.new.nodes[new].parent;
+            }
+        }
+        let mut best: Option<(f64, usize, usize)> = None;
+        for new in candidates {
+            let dice = self.dice(old, new);
+            let distance = self.distance(old, new);
+            let is_better = match best {
+                Some((best_dice, best_distance, _)) =>
+                    dice > best_dice || dice == best_dice && distance < best_distance,
+                None => dice >= MIN_DICE,
+            };
+            if is_better {
+                best = Some((dice, distance, new));
+            }
+        }
+        best.map(|(_, _, new)| new)
+    }
+
+    /// The ratio of the descendants of the nodes that are counterparts of each other.
+    fn dice(&self, old: usize, new: usize) -> f64 {
+        let (old_size, new_size) = (self.old.nodes[old].size, self.new.nodes[new].size);
+        let in_new = |node: usize| new < node && node < new + new_size;
+        let descendants = old + 1..old + old_size;
+        let common = descendants.filter_map(|node| self.old_to_new[node]).filter(|&n| in_new(n));
+        2.0 * common.count() as f64 / (old_size + new_size - 2) as f64
+    }
+
+
+    // === Recovery ===
+
+    /// Match the unmatched children of the given nodes, and of any children matched in turn.
+    fn recover(&mut self, old: usize, new: usize) {
+        let mut pending = vec![(old, new)];
+        while let Some((old, new)) = pending.pop() {
+            let old_children = self.unmatched_old_children(old);
+            let new_children = self.unmatched_new_children(new);
+            let identical = |i: usize, j: usize| {
+                self.old.is_isomorphic(old_children[i], &self.new, new_children[j])
+            };
+            let pairs = common_subsequence(old_children.len(), new_children.len(), identical);
+            for (i, j) in pairs {
+                self.link_subtrees(old_children[i], new_children[j]);
+            }
+            let old_children = self.unmatched_old_children(old);
+            let new_children = self.unmatched_new_children(new);
+            let similar = |i: usize, j: usize| self.same_kind(old_children[i], new_children[j]);
+            let pairs = common_subsequence(old_children.len(), new_children.len(), similar);
+            for (i, j) in pairs {
+                let (old, new) = (old_children[i], new_children[j]);
+                self.link(old, new);
+                pending.push((old, new));
+            }
+        }
+    }
+
+    fn unmatched_old_children(&self, old: usize) -> Vec<usize> {
+        let children = self.old.nodes[old].children.iter().copied();
+        children.filter(|&child| self.old_to_new[child].is_none()).collect()
+    }
+
+    fn unmatched_new_children(&self, new: usize) -> Vec<usize> {
+        let children = self.new.nodes[new].children.iter().copied();
+        children.filter(|&child| self.new_to_old[child].is_none()).collect()
+    }
+
+
+    // === Edit script ===
+
+    fn edit_script(&self) -> Vec<Edit> {
+        let mut misaligned = vec![false; self.new.len()];
+        for (new, old) in self.new_to_old.iter().enumerate() {
+            if let Some(old) = *old {
+                self.mark_misaligned(old, new, &mut misaligned);
+            }
+        }
+        let mut edits = vec![];
+        for node in &self.new.nodes {
+            let (new, index) = (NodeId(node.id), node.index);
+            match (self.new_to_old[node.id], node.parent) {
+                (None, Some(parent)) =>
+                    edits.push(Edit::Insert { node: new, parent: NodeId(parent), index }),
+                (None, None) => {}
+                (Some(old_id), parent) => {
+                    let old_node = &self.old.nodes[old_id];
+                    let old = NodeId(old_id);
+                    if old_node.kind != node.kind || value(old_node.item) != value(node.item) {
+                        edits.push(Edit::Update { old, new });
+                    }
+                    if let Some(parent) = parent {
+                        let old_parent = old_node.parent.and_then(|parent| self.old_to_new[parent]);
+                        if old_parent != Some(parent) || misaligned[node.id] {
+                            let parent = NodeId(parent);
+                            edits.push(Edit::Move { old, new, parent, index });
+                        }
+                    }
+                }
+            }
+        }
+        let deleted = (0..self.old.len()).rev().filter(|&node| self.old_to_new[node].is_none());
+        edits.extend(deleted.map(|node| Edit::Delete { node: NodeId(node) }));
+        edits
+    }
+
+    /// Mark the children of the new node that must be moved to be in the same order as their
+    /// counterparts among the children of the old node.
+    fn mark_misaligned(&self, old: usize, new: usize, misaligned: &mut [bool]) {
+        let children = self.new.nodes[new].children.iter().filter_map(|&child| {
+            let counterpart = &self.old.nodes[self.new_to_old[child]?];
+            (counterpart.parent == Some(old)).then_some((child, counterpart.index))
+        });
+        let (children, old_indices): (Vec<_>, Vec<_>) = children.unzip();
+        let in_order = longest_increasing_subsequence(&old_indices);
+        for (child, in_order) in children.into_iter().zip(in_order) {
+            misaligned[child] = !in_order;
+        }
+    }
+}
+
+
+
+// ===================
+// === Subsequences ===
+// ===================
+
+/// Return the pairs of indices of the elements of a longest common subsequence of two sequences of
+/// the given lengths, given the equality of their elements.
+fn common_subsequence(
+    a_len: usize,
+    b_len: usize,
+    eq: impl Fn(usize, usize) -> bool,
+) -> Vec<(usize, usize)> {
+    let prefix = (0..a_len.min(b_len)).take_while(|&i| eq(i, i)).count();
+    let suffix =
+        (0..a_len.min(b_len) - prefix).take_while(|&i| eq(a_len - 1 - i, b_len - 1 - i)).count();
+    let mut pairs = (0..prefix).map(|i| (i, i)).collect_vec();
+    let (a_end, b_end) = (a_len - suffix, b_len - suffix);
+    let (n, m) = (a_end - prefix, b_end - prefix);
+    if n > 0 && m > 0 && n * m <= MAX_COMMON_SUBSEQUENCE_SIZE {
+        // `lengths[i * (m + 1) + j]` is the length of the longest common subsequence of the
+        // remaining elements, starting at `i` and `j`.
+        let mut lengths = vec![0u32; (n + 1) * (m + 1)];
+        let at = |i: usize, j: usize| i * (m + 1) + j;
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lengths[at(i, j)] = if eq(prefix + i, prefix + j) {
+                    lengths[at(i + 1, j + 1)] + 1
+                } else {
+                    lengths[at(i + 1, j)].max(lengths[at(i, j + 1)])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if eq(prefix + i, prefix + j) && lengths[at(i, j)] == lengths[at(i + 1, j + 1)] + 1 {
+                pairs.push((prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if lengths[at(i + 1, j)] >= lengths[at(i, j + 1)] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+    }
+    pairs.extend((0..suffix).rev().map(|i| (a_len - 1 - i, b_len - 1 - i)));
+    pairs
+}
+
+/// Return whether each element is part of a longest strictly increasing subsequence.
+fn longest_increasing_subsequence(values: &[usize]) -> Vec<bool> {
+    // `tails[k]` is the element ending the increasing subsequence of length `k + 1` that has the
+    // smallest last value found so far.
+    let mut tails: Vec<usize> = vec![];
+    let mut previous = vec![None; values.len()];
+    for (i, &value) in values.iter().enumerate() {
+        let position = tails.partition_point(|&tail| values[tail] < value);
+        previous[i] = position.checked_sub(1).map(|position| tails[position]);
+        match tails.get_mut(position) {
+            Some(tail) => *tail = i,
+            None => tails.push(i),
+        }
+    }
+    let mut in_subsequence = vec![false; values.len()];
+    let mut next = tails.last().copied();
+    while let Some(i) = next {
+        in_subsequence[i] = true;
+        next = previous[i];
+    }
+    in_subsequence
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(node: item::Ref) -> String {
+        match node {
+            item::Ref::Tree(tree) => tree.trimmed_code(),
+            item::Ref::Token(token) => token.code.to_string(),
+        }
+    }
+
+    /// Diff the trees of the given code, and describe the edits by the code of the nodes.
+    fn edits<S: AsRef<str>>(old: &str, new: &str, changes: &[Change<Byte, S>]) -> Vec<String> {
+        let parser = crate::Parser::new();
+        let (old_tree, new_tree) = (parser.run(old), parser.run(new));
+        let (old_nodes, new_nodes) = (nodes(&old_tree), nodes(&new_tree));
+        let old = |node: NodeId| code(old_nodes[node.0]);
+        let new = |node: NodeId| code(new_nodes[node.0]);
+        let diff = diff_with_edits(&old_tree, &new_tree, changes);
+        let describe = |edit: &Edit| match *edit {
+            Edit::Insert { node, index, .. } => format!("insert {:?} at {index}", new(node)),
+            Edit::Delete { node } => format!("delete {:?}", old(node)),
+            Edit::Update { old: from, new: to } =>
+                format!("update {:?} to {:?}", old(from), new(to)),
+            Edit::Move { new: node, index, .. } => format!("move {:?} to {index}", new(node)),
+        };
+        diff.edits().iter().map(describe).collect()
+    }
+
+    fn edits_of(old: &str, new: &str) -> Vec<String> {
+        edits::<&str>(old, new, &[])
+    }
+
+    #[test]
+    fn test_identical() {
+        let parser = crate::Parser::new();
+        let code = "main =\n    x = foo (bar 1) 'ą' + y\n    x";
+        let (old, new) = (parser.run(code), parser.run(code));
+        let diff = diff(&old, &new);
+        assert!(diff.edits().is_empty());
+        let len = nodes(&old).len();
+        assert!(diff.matches().eq((0..len).map(|node| (NodeId(node), NodeId(node)))));
+    }
+
+    #[test]
+    fn test_update() {
+        assert_eq!(edits_of("foo = 1", "foo = 2"), [r#"update "1" to "2""#]);
+        let expected = [
+            r#"update "x" to "y""#,
+            r#"insert "bar a b c" at 2"#,
+            r#"move "bar a b" to 0"#,
+            r#"update "foo" to "bar""#,
+            r#"insert "c" at 1"#,
+            r#"insert "c" at 0"#,
+        ];
+        assert_eq!(edits_of("x = foo a b", "y = bar a b c"), expected);
+    }
+
+    #[test]
+    fn test_insert_and_delete() {
+        let old = "a = 1\nb = 2";
+        let new = "a = 1\nc = 3\nb = 2";
+        let inserted = edits_of(old, new);
+        assert_eq!(inserted[0], r#"insert "c = 3" at 3"#);
+        assert!(inserted.iter().all(|edit| edit.starts_with("insert")));
+        let deleted = edits_of(new, old);
+        assert_eq!(deleted.len(), inserted.len());
+        assert!(deleted.iter().all(|edit| edit.starts_with("delete")));
+        assert_eq!(deleted.last().unwrap(), r#"delete "c = 3""#);
+    }
+
+    #[test]
+    fn test_move() {
+        let old = "main =\n    x = 1\n    y = 2";
+        let new = "main =\n    y = 2\n    x = 1";
+        assert_eq!(edits_of(old, new), [r#"move "y = 2" to 1"#, r#"move "\n" to 2"#]);
+        let expected = [
+            r#"move "foo (a b)" to 2"#,
+            r#"insert "(a b)" at 1"#,
+            r#"insert "(" at 0"#,
+            r#"move "a b" to 1"#,
+            r#"move "a" to 0"#,
+            r#"insert ")" at 2"#,
+        ];
+        assert_eq!(edits_of("x = foo a b", "x = foo (a b)"), expected);
+    }
+
+    #[test]
+    fn test_text_edits() {
+        let (old, new) = ("f a\nf a", "long_name\nf a\nf a");
+        // Without the text edit, the nearest occurrences of the repeated line are matched, which
+        // are not the ones that were shifted by the insertion.
+        assert!(edits_of(old, new).iter().any(|edit| edit.starts_with("move")));
+        let change = Change { range: (Byte(0)..Byte(0)).into(), text: "long_name\n" };
+        let expected =
+            [r#"insert "long_name" at 1"#, r#"insert "long_name" at 0"#, r#"insert "\n" at 2"#];
+        assert_eq!(edits(old, new, &[change]), expected);
+    }
+
+    #[test]
+    fn test_map_range() {
+        let parser = crate::Parser::new();
+        let (old, new) = (parser.run("f a\nf a"), parser.run("long_name\nf a\nf a"));
+        let change = Change { range: (Byte(0)..Byte(0)).into(), text: "long_name\n" };
+        let diff = diff_with_edits(&old, &new, &[change]);
+        assert_eq!(diff.map_range(0..3), Some(10..13));
+        assert_eq!(diff.map_range(6..7), Some(16..17));
+        assert_eq!(diff.map_range(1..3), None);
+        let node = diff.matches().find(|&(old, _)| diff.old_range(old) == (4..7)).unwrap().1;
+        assert_eq!(diff.new_range(node), 14..17);
+        assert_eq!(diff.old_node(node).map(|old| diff.old_range(old)), Some(4..7));
+    }
+}
@@ -1,4 +1,7 @@
 //! Definition of [`Visitor`] deriving. It implements the visitor pattern for [`Ast`].
+//!
+//! This crate also defines [`Encode`] deriving, which implements the binary encoder of
+//! `enso_parser::format::encode` for the syntax tree types.
 
 // === Standard Linter Configuration ===
 #![deny(non_ascii_idents)]
@@ -32,6 +35,9 @@ use syn::DataEnum;
 use syn::DataStruct;
 use syn::DeriveInput;
 use syn::Fields;
+use syn::Lit;
+use syn::Meta;
+use syn::NestedMeta;
 use syn::Variant;
 
 
@@ -41,7 +47,8 @@ use syn::Variant;
 /// ======================
 use quote::ToTokens;
 
-/// Implements [`TreeVisitable`], [`TreeVisitableMut`], [`SpanVisitable`], and [`SpanVisitableMut`].
+/// Implements [`TreeVisitable`], [`TreeVisitableMut`], [`SpanVisitable`], [`SpanVisitableMut`],
+/// [`CodeVisitableMut`], and [`ItemVisitable`].
 /// These traits are defined in the [`crate::ast`] module. Macros in this module hardcode the names
 /// of the traits and are not implemented in a generic way because the current Rust implementation
 /// does not understand generic definition. See the [`crate::ast`] module to learn more about the
@@ -55,6 +62,7 @@ pub fn derive_visitor(input: proc_macro::TokenStream) -> proc_macro::TokenStream
     let body_mut = gen_body(quote!(TreeVisitableMut::visit_mut), &decl.data, true);
     let body_span = gen_body(quote!(SpanVisitable::visit_span), &decl.data, false);
     let body_span_mut = gen_body(quote!(SpanVisitableMut::visit_span_mut), &decl.data, true);
+    let body_code_mut = gen_body(quote!(CodeVisitableMut::visit_code_mut), &decl.data, true);
     let body_item = gen_body(quote!(ItemVisitable::visit_item), &decl.data, false);
 
     let impl_generics_vec: Vec<_> = impl_generics.to_token_stream().into_iter().collect();
@@ -104,6 +112,14 @@ pub fn derive_visitor(input: proc_macro::TokenStream) -> proc_macro::TokenStream
             }
         }
 
+        impl #impl_generics CodeVisitableMut #impl_generics for #ident #ty_generics {
+            fn visit_code_mut<T: CodeVisitorMut<'s>>(&'a mut self, visitor:&mut T) {
+                visitor.before_visiting_children();
+                #body_code_mut
+                visitor.after_visiting_children();
+            }
+        }
+
         impl #impl_generics ItemVisitable #impl_generics for #ident #ty_generics {
             fn visit_item<T: ItemVisitor #impl_generics>(&'a self, visitor:&mut T) {
                 visitor.before_visiting_children();
@@ -179,3 +195,207 @@ fn body_for_enum(f: &TokenStream, data: &DataEnum) -> TokenStream {
     let body = quote!(match self { #(#arms)* });
     body
 }
+
+
+
+// =====================
+// === Derive Encode ===
+// =====================
+
+/// Implements `Encode` (defined in `enso_parser::format::encode`) for a struct or enum, writing the
+/// same representation that `format::serialize` produces for the type's derived `Serialize` impl.
+///
+/// Fields marked `#[serde(skip)]` or `#[serde(skip_serializing)]` are not encoded. A field with a
+/// `#[serde(serialize_with = ...)]` function must specify an equivalent encoding function with
+/// `#[encode(with = "path")]`; the function converts a reference to the field to an array of the
+/// 32-bit words representing it.
+///
+/// A struct whose fields are all inline (see `Encode::is_inline`) is inline too. For an enum named
+/// `Variant`, the implementation of `Encode::variant` is also generated, so that a `Variant`
+/// contained by a parent struct can be merged into the parent's representation, as
+/// `format::serialize` does.
+#[proc_macro_derive(Encode, attributes(encode))]
+pub fn derive_encode(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let mut decl = syn::parse_macro_input!(input as DeriveInput);
+    for param in decl.generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(crate::format::Encode));
+    }
+    let ident = &decl.ident;
+    let (impl_generics, ty_generics, where_clause) = decl.generics.split_for_impl();
+    let body = match &decl.data {
+        Data::Struct(t) => encode_struct(t),
+        Data::Enum(t) => encode_enum(t),
+        Data::Union(_) => panic!("Untagged union types not supported."),
+    };
+    let variant = match &decl.data {
+        Data::Enum(t) if ident == "Variant" => encode_variant(t),
+        _ => quote!(),
+    };
+    let output = quote! {
+        impl #impl_generics crate::format::Encode for #ident #ty_generics #where_clause {
+            #body
+            #variant
+        }
+    };
+    output.into()
+}
+
+/// How a field is encoded.
+enum FieldEncoding {
+    /// The field is not part of the representation.
+    Skip,
+    /// The field is encoded by its `Encode` implementation.
+    Encode,
+    /// The field is converted to words by the given function.
+    With(syn::Path),
+}
+
+fn field_encoding(field: &syn::Field) -> FieldEncoding {
+    let mut encoding = FieldEncoding::Encode;
+    let mut serialize_with = false;
+    for attr in &field.attrs {
+        let is_serde = attr.path.is_ident("serde");
+        let is_encode = attr.path.is_ident("encode");
+        if !is_serde && !is_encode {
+            continue;
+        }
+        let Ok(Meta::List(list)) = attr.parse_meta() else { continue };
+        for meta in list.nested {
+            match meta {
+                NestedMeta::Meta(Meta::Path(path))
+                    if is_serde && (path.is_ident("skip") || path.is_ident("skip_serializing")) =>
+                    return FieldEncoding::Skip,
+                NestedMeta::Meta(Meta::NameValue(value))
+                    if is_serde && value.path.is_ident("serialize_with") =>
+                    serialize_with = true,
+                NestedMeta::Meta(Meta::NameValue(value))
+                    if is_encode && value.path.is_ident("with") =>
+                    match value.lit {
+                        Lit::Str(path) => encoding = FieldEncoding::With(path.parse().unwrap()),
+                        _ => panic!("Expected `#[encode(with = \"path\")]`."),
+                    },
+                _ => (),
+            }
+        }
+    }
+    if serialize_with && !matches!(encoding, FieldEncoding::With(_)) {
+        panic!("A field with `#[serde(serialize_with)]` requires `#[encode(with)]`.");
+    }
+    encoding
+}
+
+/// Encode each field of a sequence, given expressions referring to them.
+fn encode_fields<'f>(
+    fields: impl IntoIterator<Item = (&'f syn::Field, TokenStream)>,
+) -> Vec<TokenStream> {
+    let mut body = vec![];
+    for (field, value) in fields {
+        match field_encoding(field) {
+            FieldEncoding::Skip => (),
+            FieldEncoding::Encode => body.push(quote!(encoder.field(#value);)),
+            FieldEncoding::With(path) => body.push(quote!(encoder.words(#path(#value));)),
+        }
+    }
+    body
+}
+
+fn encode_struct(data: &DataStruct) -> TokenStream {
+    let fields: Vec<_> = data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| match &field.ident {
+            Some(name) => (field, quote!(&self.#name)),
+            None => {
+                let index = syn::Index::from(i);
+                (field, quote!(&self.#index))
+            }
+        })
+        .collect();
+    let body = encode_fields(fields.iter().cloned());
+    let mut is_inline = vec![];
+    let mut write_inline = vec![];
+    for (field, value) in &fields {
+        let ty = &field.ty;
+        match field_encoding(field) {
+            FieldEncoding::Skip => (),
+            FieldEncoding::Encode => {
+                is_inline.push(quote!(<#ty as crate::format::Encode>::is_inline()));
+                write_inline.push(quote!(crate::format::Encode::write_inline(#value, out);));
+            }
+            FieldEncoding::With(path) => write_inline.push(quote! {
+                for word in #path(#value) {
+                    out.extend_from_slice(&word.to_le_bytes());
+                }
+            }),
+        }
+    }
+    quote! {
+        fn encode<'a>(&'a self, encoder: &mut crate::format::encode::Encoder<'a>) {
+            if <Self as crate::format::Encode>::is_inline() {
+                encoder.inline(self);
+            } else {
+                #(#body)*
+            }
+        }
+
+        fn is_inline() -> bool {
+            true #(&& #is_inline)*
+        }
+
+        #[allow(unused_variables)]
+        fn write_inline(&self, out: &mut Vec<u8>) {
+            #(#write_inline)*
+        }
+    }
+}
+
+fn encode_enum(data: &DataEnum) -> TokenStream {
+    let arms = data.variants.iter().enumerate().map(|(i, variant)| {
+        let discriminant = i as u32;
+        let variant_ident = &variant.ident;
+        let (pattern, body) = match &variant.fields {
+            Fields::Unit => (quote!(Self::#variant_ident), vec![]),
+            Fields::Named(fields) => {
+                let names = field_names(fields);
+                let body = encode_fields(fields.named.iter().zip(names.iter().map(|n| quote!(#n))));
+                (quote!(Self::#variant_ident { #(#names),* }), body)
+            }
+            Fields::Unnamed(fields) => {
+                let names = identifier_sequence(fields.unnamed.len());
+                let body =
+                    encode_fields(fields.unnamed.iter().zip(names.iter().map(|n| quote!(#n))));
+                (quote!(Self::#variant_ident(#(#names),*)), body)
+            }
+        };
+        quote!(#pattern => {
+            encoder.begin_object();
+            encoder.u32(#discriminant);
+            #(#body)*
+            encoder.end_object();
+        })
+    });
+    quote! {
+        fn encode<'a>(&'a self, encoder: &mut crate::format::encode::Encoder<'a>) {
+            #[allow(unused_variables)]
+            match self { #(#arms)* }
+        }
+    }
+}
+
+fn encode_variant(data: &DataEnum) -> TokenStream {
+    let arms = data.variants.iter().enumerate().map(|(i, variant)| {
+        let discriminant = i as u32;
+        let variant_ident = &variant.ident;
+        match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 =>
+                quote!(Self::#variant_ident(value) => Some((#discriminant, value)),),
+            _ => quote!(Self::#variant_ident { .. } => None,),
+        }
+    });
+    quote! {
+        fn variant(&self) -> Option<(u32, &dyn crate::format::Encode)> {
+            match self { #(#arms)* }
+        }
+    }
+}